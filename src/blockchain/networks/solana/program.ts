@@ -39,7 +39,7 @@ export interface SolanaProgramInterface {
   openDisputeWithBond(params: OpenDisputeParams): Promise<TransactionResult>;
   respondToDisputeWithBond(params: RespondToDisputeParams): Promise<TransactionResult>;
   resolveDisputeWithExplanation(params: ResolveDisputeParams): Promise<TransactionResult>;
-  defaultJudgment(params: AutoCancelParams): Promise<TransactionResult>;
+  defaultJudgment(params: DefaultJudgmentParams): Promise<TransactionResult>;
 
   // Utility operations
   initializeBuyerBondAccount(params: InitializeBondParams): Promise<TransactionResult>;
@@ -48,8 +48,9 @@ export interface SolanaProgramInterface {
   autoCancel(params: AutoCancelParams): Promise<TransactionResult>;
 
   // State queries
-  getEscrowState(escrowId: number, tradeId: number): Promise<EscrowState>;
-  getEscrowBalance(escrowId: number, tradeId: number): Promise<number>;
+  getEscrowState(sellerAddress: string, escrowId: number, tradeId: number): Promise<EscrowState>;
+  getEscrowBalance(sellerAddress: string, escrowId: number, tradeId: number): Promise<number>;
+  getPriceUpdateAddress(currency: string): Promise<string | undefined>;
 
   // Wallet balance queries
   getUsdcBalance(): Promise<number>;
@@ -115,6 +116,63 @@ export class SolanaProgram implements SolanaProgramInterface {
     return { provider, program };
   }
 
+  // Escrows created before the seeds were scoped to the seller still live at the legacy
+  // address, so fall back to it when nothing exists at the seller-scoped one
+  private async findEscrowPDA(
+    seller: PublicKey,
+    escrowId: number,
+    tradeId: number
+  ): Promise<PublicKey> {
+    const [escrowPDA] = PDADerivation.deriveEscrowPDA(this.programId, seller, escrowId, tradeId);
+    if (await this.connection.getAccountInfo(escrowPDA)) {
+      return escrowPDA;
+    }
+
+    const [legacyEscrowPDA] = PDADerivation.deriveLegacyEscrowPDA(
+      this.programId,
+      escrowId,
+      tradeId
+    );
+    const legacyAccount = await this.connection.getAccountInfo(legacyEscrowPDA);
+
+    // Any seller could have used these ids, so only take the legacy escrow if it is this
+    // seller's: the seller key follows the discriminator, escrow_id and trade_id
+    if (legacyAccount && new PublicKey(legacyAccount.data.subarray(24, 56)).equals(seller)) {
+      return legacyEscrowPDA;
+    }
+
+    return escrowPDA;
+  }
+
+  // Settlement closes the escrow, its vault and any bond and deposit accounts, refunding
+  // each one's rent to whoever paid it, as recorded on the escrow
+  private async getSettlementAccounts(
+    program: Program<LocalsolanaContracts>,
+    escrowPDA: PublicKey
+  ) {
+    const escrowAccount = await program.account.escrow.fetch(escrowPDA);
+
+    const [buyerBondPDA] = PDADerivation.deriveBuyerBondPDA(this.programId, escrowPDA);
+    const [sellerBondPDA] = PDADerivation.deriveSellerBondPDA(this.programId, escrowPDA);
+    const [buyerDepositPDA] = PDADerivation.deriveBuyerDepositPDA(this.programId, escrowPDA);
+    const [buyerBond, sellerBond, buyerDeposit] = await this.connection.getMultipleAccountsInfo([
+      buyerBondPDA,
+      sellerBondPDA,
+      buyerDepositPDA,
+    ]);
+
+    return {
+      seller: escrowAccount.escrowRentPayer ?? escrowAccount.seller,
+      escrowTokenRentPayer: escrowAccount.escrowTokenRentPayer ?? escrowAccount.seller,
+      buyerBondAccount: buyerBond ? buyerBondPDA : null,
+      sellerBondAccount: sellerBond ? sellerBondPDA : null,
+      buyerBondRentPayer: escrowAccount.buyerBondRentPayer ?? escrowAccount.seller,
+      sellerBondRentPayer: escrowAccount.sellerBondRentPayer ?? escrowAccount.seller,
+      buyerDepositAccount: buyerDeposit ? buyerDepositPDA : null,
+      buyerDepositRentPayer: escrowAccount.buyerDepositRentPayer ?? escrowAccount.buyer,
+    };
+  }
+
   // Core Escrow Operations
   async createEscrow(params: CreateEscrowParams): Promise<TransactionResult> {
     try {
@@ -124,19 +182,40 @@ export class SolanaProgram implements SolanaProgramInterface {
       // Convert addresses to PublicKeys
       const seller = new PublicKey(params.sellerAddress);
       const buyer = new PublicKey(params.buyerAddress);
+      const priceUpdate = params.priceUpdateAddress
+        ? new PublicKey(params.priceUpdateAddress)
+        : null;
 
-      // Build transaction
+      // Convert payment method hash hex string to byte array
+      const paymentMethodHashBytes = new Uint8Array(32);
+      for (let i = 0; i < 32; i++) {
+        paymentMethodHashBytes[i] = parseInt(
+          params.fiatTerms.paymentMethodHash.substr(i * 2, 2),
+          16
+        );
+      }
+
+      // Build transaction - the price feed PDA is derived from the currency by Anchor
       const tx = await program.methods
         .createEscrow(
           new BN(params.escrowId),
           new BN(params.tradeId),
           new BN(params.amount),
           params.sequential || false,
-          params.sequentialEscrowAddress ? new PublicKey(params.sequentialEscrowAddress) : null
+          params.sequentialEscrowAddress ? new PublicKey(params.sequentialEscrowAddress) : null,
+          {
+            currency: Array.from(Buffer.from(params.fiatTerms.currency, 'ascii')),
+            fiatAmount: new BN(params.fiatTerms.fiatAmount),
+            rate: new BN(params.fiatTerms.rate),
+            paymentMethodHash: Array.from(paymentMethodHashBytes),
+          },
+          params.requiresAcceptance || false,
+          params.requiresBuyerDeposit || false
         )
         .accounts({
           seller: seller,
           buyer: buyer,
+          priceUpdate: priceUpdate,
         })
         .transaction();
 
@@ -170,17 +249,20 @@ export class SolanaProgram implements SolanaProgramInterface {
       const solanaConfig = getSolanaDevnetConfig();
       const usdcMint = new PublicKey(solanaConfig.usdcMint);
 
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
+
       // Build transaction - pass escrowId and tradeId as arguments
       const tx = await program.methods
         .fundEscrow(new BN(params.escrowId), new BN(params.tradeId))
         .accounts({
           seller: seller,
+          escrow: escrowPDA,
           sellerTokenAccount: sellerTokenAccount,
           tokenMint: usdcMint,
           // tokenProgram: TOKEN_PROGRAM_ID,
           // systemProgram: SystemProgram.programId,
           // rent: SYSVAR_RENT_PUBKEY,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -210,19 +292,13 @@ export class SolanaProgram implements SolanaProgramInterface {
 
       // Convert addresses to PublicKeys
       const buyer = new PublicKey(params.buyerAddress);
+      const seller = new PublicKey(params.sellerAddress);
 
       console.log('[DEBUG] Using buyer address:', buyer.toString());
       console.log('[DEBUG] EscrowId:', params.escrowId, 'TradeId:', params.tradeId);
 
       // Derive the escrow PDA using the same seeds as the contract
-      const [escrowPDA] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from('escrow'),
-          new BN(params.escrowId).toArrayLike(Buffer, 'le', 8),
-          new BN(params.tradeId).toArrayLike(Buffer, 'le', 8),
-        ],
-        this.programId
-      );
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
 
       console.log('[DEBUG] Derived escrow PDA:', escrowPDA.toString());
 
@@ -265,6 +341,7 @@ export class SolanaProgram implements SolanaProgramInterface {
 
       // Convert addresses to PublicKeys
       const authority = new PublicKey(params.authorityAddress);
+      const seller = new PublicKey(params.sellerAddress);
       const buyerTokenAccount = new PublicKey(params.buyerTokenAccount);
       const arbitratorTokenAccount = new PublicKey(params.arbitratorTokenAccount);
       const sequentialEscrowTokenAccount = params.sequentialEscrowTokenAccount
@@ -275,30 +352,23 @@ export class SolanaProgram implements SolanaProgramInterface {
       console.log('[DEBUG] EscrowId:', params.escrowId, 'TradeId:', params.tradeId);
 
       // Derive the escrow PDA using the same seeds as the contract
-      const [escrowPDA] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from('escrow'),
-          new BN(params.escrowId).toArrayLike(Buffer, 'le', 8),
-          new BN(params.tradeId).toArrayLike(Buffer, 'le', 8),
-        ],
-        this.programId
-      );
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
 
       console.log('[DEBUG] Derived escrow PDA:', escrowPDA.toString());
 
       // Derive the escrow token account PDA
-      const [escrowTokenPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from('escrow_token'), escrowPDA.toBuffer()],
-        this.programId
-      );
+      const [escrowTokenPDA] = PDADerivation.deriveEscrowTokenPDA(this.programId, escrowPDA);
 
       console.log('[DEBUG] Derived escrow token PDA:', escrowTokenPDA.toString());
 
+      const settlementAccounts = await this.getSettlementAccounts(program, escrowPDA);
+
       // Build transaction - releaseEscrow takes no parameters, but we need to provide the PDAs explicitly
       const tx = await program.methods
         .releaseEscrow()
         .accounts({
           authority: authority,
+          ...settlementAccounts,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -339,15 +409,28 @@ export class SolanaProgram implements SolanaProgramInterface {
       const authority = new PublicKey(params.authorityAddress);
       const sellerTokenAccount = new PublicKey(params.sellerTokenAccount);
 
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
+      const [escrowTokenPDA] = PDADerivation.deriveEscrowTokenPDA(this.programId, escrowPDA);
+      const escrowTokenAccount = await this.connection.getAccountInfo(escrowTokenPDA);
+      const settlementAccounts = await this.getSettlementAccounts(program, escrowPDA);
+
       // Build transaction
       const tx = await program.methods
         .cancelEscrow()
         .accounts({
-          seller: seller,
+          ...settlementAccounts,
           authority: authority,
+          escrow: escrowPDA,
+          escrowTokenAccount: escrowTokenAccount ? escrowTokenPDA : null,
           sellerTokenAccount: sellerTokenAccount,
+          buyerTokenAccount: params.buyerTokenAccount
+            ? new PublicKey(params.buyerTokenAccount)
+            : null,
+          arbitratorTokenAccount: params.arbitratorTokenAccount
+            ? new PublicKey(params.arbitratorTokenAccount)
+            : null,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -375,6 +458,11 @@ export class SolanaProgram implements SolanaProgramInterface {
       // Convert addresses to PublicKeys
       const disputingParty = new PublicKey(params.disputingPartyAddress);
       const disputingPartyTokenAccount = new PublicKey(params.disputingPartyTokenAccount);
+      const escrowPDA = await this.findEscrowPDA(
+        new PublicKey(params.sellerAddress),
+        params.escrowId,
+        params.tradeId
+      );
 
       // Convert evidence hash string to byte array
       const evidenceHashBytes = new Uint8Array(32);
@@ -394,9 +482,10 @@ export class SolanaProgram implements SolanaProgramInterface {
         .openDisputeWithBond(Array.from(evidenceHashBytes))
         .accounts({
           disputingParty: disputingParty,
+          escrow: escrowPDA,
           disputingPartyTokenAccount: disputingPartyTokenAccount,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -423,6 +512,11 @@ export class SolanaProgram implements SolanaProgramInterface {
       // Convert addresses to PublicKeys
       const respondingParty = new PublicKey(params.respondingPartyAddress);
       const respondingPartyTokenAccount = new PublicKey(params.respondingPartyTokenAccount);
+      const escrowPDA = await this.findEscrowPDA(
+        new PublicKey(params.sellerAddress),
+        params.escrowId,
+        params.tradeId
+      );
 
       // Convert evidence hash string to byte array
       const evidenceHashBytes = new Uint8Array(32);
@@ -442,9 +536,10 @@ export class SolanaProgram implements SolanaProgramInterface {
         .respondToDisputeWithBond(Array.from(evidenceHashBytes))
         .accounts({
           respondingParty: respondingParty,
+          escrow: escrowPDA,
           respondingPartyTokenAccount: respondingPartyTokenAccount,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -488,17 +583,21 @@ export class SolanaProgram implements SolanaProgramInterface {
         resolutionHashBytes.set(hashBuffer.slice(0, 32));
       }
 
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
+      const settlementAccounts = await this.getSettlementAccounts(program, escrowPDA);
+
       // Build transaction
       const tx = await program.methods
         .resolveDisputeWithExplanation(params.buyerWins, Array.from(resolutionHashBytes))
         .accounts({
           arbitrator: arbitrator,
-          seller: seller,
+          ...settlementAccounts,
+          escrow: escrowPDA,
           buyerTokenAccount: buyerTokenAccount,
           sellerTokenAccount: sellerTokenAccount,
           arbitratorTokenAccount: arbitratorTokenAccount,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -524,20 +623,24 @@ export class SolanaProgram implements SolanaProgramInterface {
 
       // Convert addresses to PublicKeys
       const seller = new PublicKey(params.sellerAddress);
-      const arbitrator = new PublicKey(params.arbitratorAddress);
+      const caller = new PublicKey(params.callerAddress);
       const buyerTokenAccount = new PublicKey(params.buyerTokenAccount);
       const sellerTokenAccount = new PublicKey(params.sellerTokenAccount);
 
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
+      const settlementAccounts = await this.getSettlementAccounts(program, escrowPDA);
+
       // Build transaction
       const tx = await program.methods
         .defaultJudgment()
         .accounts({
-          seller: seller,
-          arbitrator: arbitrator,
+          ...settlementAccounts,
+          caller: caller,
+          escrow: escrowPDA,
           buyerTokenAccount: buyerTokenAccount,
           sellerTokenAccount: sellerTokenAccount,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -565,17 +668,23 @@ export class SolanaProgram implements SolanaProgramInterface {
       // Convert addresses to PublicKeys
       const payer = new PublicKey(params.payerAddress);
       const tokenMint = new PublicKey(params.tokenMint);
+      const escrowPDA = await this.findEscrowPDA(
+        new PublicKey(params.sellerAddress),
+        params.escrowId,
+        params.tradeId
+      );
 
       // Build transaction
       const tx = await program.methods
         .initializeBuyerBondAccount(new BN(params.escrowId), new BN(params.tradeId))
         .accounts({
           payer: payer,
+          escrow: escrowPDA,
           tokenMint: tokenMint,
           // tokenProgram: TOKEN_PROGRAM_ID,
           // systemProgram: SystemProgram.programId,
           // rent: SYSVAR_RENT_PUBKEY,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -602,17 +711,23 @@ export class SolanaProgram implements SolanaProgramInterface {
       // Convert addresses to PublicKeys
       const payer = new PublicKey(params.payerAddress);
       const tokenMint = new PublicKey(params.tokenMint);
+      const escrowPDA = await this.findEscrowPDA(
+        new PublicKey(params.sellerAddress),
+        params.escrowId,
+        params.tradeId
+      );
 
       // Build transaction
       const tx = await program.methods
         .initializeSellerBondAccount(new BN(params.escrowId), new BN(params.tradeId))
         .accounts({
           payer: payer,
+          escrow: escrowPDA,
           tokenMint: tokenMint,
           // tokenProgram: TOKEN_PROGRAM_ID,
           // systemProgram: SystemProgram.programId,
           // rent: SYSVAR_RENT_PUBKEY,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -638,13 +753,19 @@ export class SolanaProgram implements SolanaProgramInterface {
 
       // Convert addresses to PublicKeys
       const buyer = new PublicKey(params.buyerAddress);
+      const escrowPDA = await this.findEscrowPDA(
+        new PublicKey(params.sellerAddress),
+        params.escrowId,
+        params.tradeId
+      );
 
       // Build transaction
       const tx = await program.methods
         .updateSequentialAddress(new PublicKey(params.newSequentialAddress))
         .accounts({
           buyer: buyer,
-        })
+          escrow: escrowPDA,
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -673,15 +794,25 @@ export class SolanaProgram implements SolanaProgramInterface {
       const seller = new PublicKey(params.sellerAddress);
       const sellerTokenAccount = new PublicKey(params.sellerTokenAccount);
 
+      const escrowPDA = await this.findEscrowPDA(seller, params.escrowId, params.tradeId);
+      const [escrowTokenPDA] = PDADerivation.deriveEscrowTokenPDA(this.programId, escrowPDA);
+      const escrowTokenAccount = await this.connection.getAccountInfo(escrowTokenPDA);
+      const settlementAccounts = await this.getSettlementAccounts(program, escrowPDA);
+
       // Build transaction
       const tx = await program.methods
         .autoCancel()
         .accounts({
           arbitrator: arbitrator,
-          seller: seller,
+          ...settlementAccounts,
+          escrow: escrowPDA,
+          escrowTokenAccount: escrowTokenAccount ? escrowTokenPDA : null,
           sellerTokenAccount: sellerTokenAccount,
+          arbitratorTokenAccount: params.arbitratorTokenAccount
+            ? new PublicKey(params.arbitratorTokenAccount)
+            : null,
           // tokenProgram: TOKEN_PROGRAM_ID,
-        })
+        } as any)
         .transaction();
 
       // Send transaction using Dynamic.xyz wallet
@@ -701,13 +832,17 @@ export class SolanaProgram implements SolanaProgramInterface {
   }
 
   // State Queries
-  async getEscrowState(escrowId: number, tradeId: number): Promise<EscrowState> {
+  async getEscrowState(
+    sellerAddress: string,
+    escrowId: number,
+    tradeId: number
+  ): Promise<EscrowState> {
     try {
       // Get provider and program with Dynamic.xyz wallet
       const { program } = await this.getProviderAndProgram();
 
       // Derive PDA
-      const [escrowPDA] = PDADerivation.deriveEscrowPDA(this.programId, escrowId, tradeId);
+      const escrowPDA = await this.findEscrowPDA(new PublicKey(sellerAddress), escrowId, tradeId);
 
       // Fetch account data
       const escrowAccount = await program.account.escrow.fetch(escrowPDA);
@@ -727,10 +862,10 @@ export class SolanaProgram implements SolanaProgramInterface {
     }
   }
 
-  async getEscrowBalance(escrowId: number, tradeId: number): Promise<number> {
+  async getEscrowBalance(sellerAddress: string, escrowId: number, tradeId: number): Promise<number> {
     try {
       // Derive PDAs
-      const [escrowPDA] = PDADerivation.deriveEscrowPDA(this.programId, escrowId, tradeId);
+      const escrowPDA = await this.findEscrowPDA(new PublicKey(sellerAddress), escrowId, tradeId);
 
       const [escrowTokenPDA] = PDADerivation.deriveEscrowTokenPDA(this.programId, escrowPDA);
 
@@ -742,6 +877,25 @@ export class SolanaProgram implements SolanaProgramInterface {
     }
   }
 
+  // create_escrow checks the committed rate against Pyth once the arbitrator registered a
+  // feed for the currency, so it then needs the feed's price update account
+  async getPriceUpdateAddress(currency: string): Promise<string | undefined> {
+    try {
+      const { program } = await this.getProviderAndProgram();
+
+      const [priceFeedPDA] = PDADerivation.derivePriceFeedPDA(this.programId, currency);
+      const priceFeed = await program.account.priceFeed.fetchNullable(priceFeedPDA);
+      if (!priceFeed) {
+        return undefined;
+      }
+
+      const [priceUpdate] = PDADerivation.derivePythPriceUpdatePDA(priceFeed.feedId);
+      return priceUpdate.toString();
+    } catch (error) {
+      throw new Error(`Failed to fetch price feed: ${this.handleError(error)}`);
+    }
+  }
+
   // New methods that accept escrow address directly
   async getEscrowStateByAddress(escrowAddress: string): Promise<EscrowState> {
     try {
@@ -63,6 +63,14 @@ export interface EscrowEvent {
   timestamp: number;
 }
 
+// What the buyer pays off-chain, committed by the seller when creating the escrow
+export interface FiatTerms {
+  currency: string; // ISO 4217 alphabetic code, e.g. 'EUR'
+  fiatAmount: string; // In the currency's minor units
+  rate: string; // Fiat major units per whole token, 8 decimals
+  paymentMethodHash: string; // Hex-encoded hash of the agreed payment method details
+}
+
 // Parameter interfaces for escrow operations
 export interface CreateEscrowParams {
   escrowId: number;
@@ -75,6 +83,10 @@ export interface CreateEscrowParams {
   fiatDeadline: number;
   sequential?: boolean;
   sequentialEscrowAddress?: string;
+  fiatTerms: FiatTerms;
+  requiresAcceptance?: boolean;
+  requiresBuyerDeposit?: boolean; // buyer must post the deposit before marking fiat paid
+  priceUpdateAddress?: string; // Pyth price update, required if the currency has a price feed
 }
 
 export interface FundEscrowParams {
@@ -88,12 +100,14 @@ export interface FundEscrowParams {
 export interface MarkFiatPaidParams {
   escrowId: number;
   tradeId: number;
+  sellerAddress: string;
   buyerAddress: string;
 }
 
 export interface ReleaseEscrowParams {
   escrowId: number;
   tradeId: number;
+  sellerAddress: string;
   authorityAddress: string;
   buyerTokenAccount: string;
   arbitratorTokenAccount: string;
@@ -106,6 +120,8 @@ export interface CancelEscrowParams {
   sellerAddress: string;
   authorityAddress: string;
   sellerTokenAccount: string;
+  buyerTokenAccount?: string; // Receives the buyer deposit and any cancellation penalty
+  arbitratorTokenAccount?: string; // Receives stray bond tokens
 }
 
 export interface OpenDisputeParams {
@@ -113,6 +129,7 @@ export interface OpenDisputeParams {
   tradeId: number;
   evidenceHash: string;
   bondAmount: string;
+  sellerAddress: string;
   disputingPartyAddress: string;
   disputingPartyTokenAccount: string;
 }
@@ -122,6 +139,7 @@ export interface RespondToDisputeParams {
   tradeId: number;
   evidenceHash: string;
   bondAmount: string;
+  sellerAddress: string;
   respondingPartyAddress: string;
   respondingPartyTokenAccount: string;
 }
@@ -143,7 +161,7 @@ export interface DefaultJudgmentParams {
   escrowId: number;
   tradeId: number;
   sellerAddress: string;
-  arbitratorAddress: string;
+  callerAddress: string; // Anyone may apply the default judgment once the response deadline passed
   buyerTokenAccount: string;
   sellerTokenAccount: string;
 }
@@ -152,6 +170,7 @@ export interface InitializeBondParams {
   escrowId: number;
   tradeId: number;
   bondAmount: string;
+  sellerAddress: string;
   payerAddress: string;
   tokenMint: string;
 }
@@ -160,6 +179,7 @@ export interface UpdateSequentialParams {
   escrowId: number;
   tradeId: number;
   newSequentialAddress: string;
+  sellerAddress: string;
   buyerAddress: string;
 }
 
@@ -169,4 +189,5 @@ export interface AutoCancelParams {
   arbitratorAddress: string;
   sellerAddress: string;
   sellerTokenAccount: string;
+  arbitratorTokenAccount?: string; // Receives stray bond tokens
 }
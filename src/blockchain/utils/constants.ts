@@ -12,6 +12,9 @@ export const BLOCKCHAIN_CONSTANTS = {
     DEFAULT_DISPUTE_DEADLINE_HOURS: 168, // 7 days
     BOND_AMOUNT: 5, // 5 USDC bond for disputes
     FEE_PERCENTAGE: 0.5, // 0.5% fee
+    // Pyth push oracle, whose sponsored price feed accounts are keyed by shard and feed id
+    PYTH_PUSH_ORACLE_PROGRAM_ID: 'pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT',
+    PYTH_SHARD_ID: 0,
   },
 
   // EVM constants (for future use)
@@ -3,34 +3,46 @@
  */
 
 import { PublicKey } from '@solana/web3.js';
+import { BLOCKCHAIN_CONSTANTS } from './constants.js';
 
 export class PDADerivation {
   /**
    * Derive the main escrow PDA
-   * Seeds: ["escrow", escrow_id, trade_id]
+   * Seeds: ["escrow", seller, escrow_id, trade_id]
    */
   static deriveEscrowPDA(
     programId: PublicKey,
+    seller: PublicKey,
     escrowId: number,
     tradeId: number
   ): [PublicKey, number] {
-    // Convert to proper u64 little-endian buffers (8 bytes each)
-    const escrowIdBuffer = Buffer.alloc(8);
-    escrowIdBuffer.writeBigUInt64LE(BigInt(escrowId), 0);
-
-    const tradeIdBuffer = Buffer.alloc(8);
-    tradeIdBuffer.writeBigUInt64LE(BigInt(tradeId), 0);
-
     return PublicKey.findProgramAddressSync(
       [
         Buffer.from('escrow'), // "escrow" as ASCII bytes
-        escrowIdBuffer, // u64 little-endian
-        tradeIdBuffer, // u64 little-endian
+        seller.toBuffer(),
+        this.u64ToBuffer(escrowId), // u64 little-endian
+        this.u64ToBuffer(tradeId), // u64 little-endian
       ],
       programId
     );
   }
 
+  /**
+   * Derive the escrow PDA used by escrows created before seeds were scoped to the seller.
+   * Only needed to address existing escrows during the transition period.
+   * Seeds: ["escrow", escrow_id, trade_id]
+   */
+  static deriveLegacyEscrowPDA(
+    programId: PublicKey,
+    escrowId: number,
+    tradeId: number
+  ): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('escrow'), this.u64ToBuffer(escrowId), this.u64ToBuffer(tradeId)],
+      programId
+    );
+  }
+
   /**
    * Derive the escrow token account PDA
    * Seeds: ["escrow_token", escrow_pda]
@@ -64,11 +76,50 @@ export class PDADerivation {
     );
   }
 
+  /**
+   * Derive the buyer deposit account PDA
+   * Seeds: ["buyer_deposit", escrow_pda]
+   */
+  static deriveBuyerDepositPDA(programId: PublicKey, escrowPDA: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('buyer_deposit'), escrowPDA.toBuffer()],
+      programId
+    );
+  }
+
+  /**
+   * Derive the price feed PDA the arbitrator registers for a fiat currency
+   * Seeds: ["price_feed", currency]
+   */
+  static derivePriceFeedPDA(programId: PublicKey, currency: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('price_feed'), Buffer.from(currency, 'ascii')],
+      programId
+    );
+  }
+
+  /**
+   * Derive the Pyth push oracle price update account for a feed id
+   * Seeds: [shard_id (u16 little-endian), feed_id]
+   */
+  static derivePythPriceUpdatePDA(
+    feedId: Uint8Array | number[],
+    shardId: number = BLOCKCHAIN_CONSTANTS.SOLANA.PYTH_SHARD_ID
+  ): [PublicKey, number] {
+    const shard = Buffer.alloc(2);
+    shard.writeUInt16LE(shardId, 0);
+    return PublicKey.findProgramAddressSync(
+      [shard, Buffer.from(feedId)],
+      new PublicKey(BLOCKCHAIN_CONSTANTS.SOLANA.PYTH_PUSH_ORACLE_PROGRAM_ID)
+    );
+  }
+
   /**
    * Derive all PDAs for an escrow in one call
    */
   static deriveAllEscrowPDAs(
     programId: PublicKey,
+    seller: PublicKey,
     escrowId: number,
     tradeId: number
   ): {
@@ -77,7 +128,7 @@ export class PDADerivation {
     buyerBond: [PublicKey, number];
     sellerBond: [PublicKey, number];
   } {
-    const [escrowPDA] = this.deriveEscrowPDA(programId, escrowId, tradeId);
+    const [escrowPDA] = this.deriveEscrowPDA(programId, seller, escrowId, tradeId);
     const [escrowTokenPDA] = this.deriveEscrowTokenPDA(programId, escrowPDA);
     const [buyerBondPDA] = this.deriveBuyerBondPDA(programId, escrowPDA);
     const [sellerBondPDA] = this.deriveSellerBondPDA(programId, escrowPDA);
@@ -90,6 +141,15 @@ export class PDADerivation {
     };
   }
 
+  /**
+   * Encode a u64 as an 8-byte little-endian buffer
+   */
+  private static u64ToBuffer(value: number): Buffer {
+    const buffer = Buffer.alloc(8);
+    buffer.writeBigUInt64LE(BigInt(value), 0);
+    return buffer;
+  }
+
   /**
    * Validate that a PDA derivation is correct
    */
@@ -50,6 +50,12 @@ export const BlockchainServiceTest: React.FC = () => {
           amount: '10',
           depositDeadline: Date.now() / 1000 + 3600,
           fiatDeadline: Date.now() / 1000 + 7200,
+          fiatTerms: {
+            currency: 'USD',
+            fiatAmount: '1000',
+            rate: '100000000',
+            paymentMethodHash: '00'.repeat(32),
+          },
         });
       } catch (err) {
         addTestResult(`✅ createEscrow() throws expected error: ${(err as Error).message}`);
@@ -0,0 +1,192 @@
+//! RPC access: fetching escrows and submitting (or simulating) instructions.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use localsolana_contracts_client::pda::{
+    find_buyer_bond_address, find_escrow_token_address, find_seller_bond_address,
+};
+use localsolana_contracts_client::token::TokenAccount;
+use localsolana_contracts_client::{Escrow, EscrowError, EscrowState, ID};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub struct Chain {
+    rpc: RpcClient,
+    keypair_path: PathBuf,
+    signer: Option<Keypair>,
+}
+
+/// Balances of the dispute bond accounts, `None` when the account does not exist.
+pub struct BondBalances {
+    pub buyer: Option<u64>,
+    pub seller: Option<u64>,
+}
+
+impl Chain {
+    pub fn new(url: &str, keypair: Option<&Path>) -> Result<Self> {
+        let keypair_path = match keypair {
+            Some(path) => path.to_path_buf(),
+            None => default_keypair_path()?,
+        };
+        // Read-only commands work without a keypair, so a missing one is only an error
+        // once something needs to be signed
+        let signer = read_keypair_file(&keypair_path).ok();
+        Ok(Chain {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            keypair_path,
+            signer,
+        })
+    }
+
+    pub fn signer(&self) -> Result<&Keypair> {
+        self.signer
+            .as_ref()
+            .ok_or_else(|| anyhow!("cannot read keypair {}", self.keypair_path.display()))
+    }
+
+    /// All escrow accounts owned by the program, optionally only those in `state`.
+    pub fn escrows(&self, state: Option<EscrowState>) -> Result<Vec<Escrow>> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            0,
+            Escrow::discriminator().to_vec(),
+        ))];
+        if let Some(state) = state {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                Escrow::STATE_OFFSET,
+                vec![state as u8],
+            )));
+        }
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(
+                &ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .context("fetching program accounts")?;
+
+        let mut escrows = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            match Escrow::try_from_account_data(&account.data) {
+                Ok(escrow) => escrows.push(escrow),
+                Err(err) => eprintln!("warning: skipping {address}: {err}"),
+            }
+        }
+        escrows.sort_by_key(|escrow| (escrow.escrow_id, escrow.trade_id));
+        Ok(escrows)
+    }
+
+    pub fn escrow(&self, address: &Pubkey) -> Result<Escrow> {
+        let account = self
+            .rpc
+            .get_account(address)
+            .with_context(|| format!("fetching escrow {address}"))?;
+        if account.owner != ID {
+            bail!("{address} is not owned by the escrow program");
+        }
+        Ok(Escrow::try_from_account_data(&account.data)?)
+    }
+
+    fn token_account(&self, address: &Pubkey) -> Result<Option<TokenAccount>> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .with_context(|| format!("fetching token account {address}"))?
+            .value;
+        account
+            .map(|account| TokenAccount::try_from_account_data(&account.data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Mint of the escrow vault, `None` if the escrow was never funded.
+    pub fn vault_mint(&self, escrow: &Pubkey) -> Result<Option<Pubkey>> {
+        Ok(self
+            .token_account(&find_escrow_token_address(escrow).0)?
+            .map(|vault| vault.mint))
+    }
+
+    pub fn bond_balances(&self, escrow: &Pubkey) -> Result<BondBalances> {
+        Ok(BondBalances {
+            buyer: self
+                .token_account(&find_buyer_bond_address(escrow).0)?
+                .map(|bond| bond.amount),
+            seller: self
+                .token_account(&find_seller_bond_address(escrow).0)?
+                .map(|bond| bond.amount),
+        })
+    }
+
+    /// Sends `ix` signed by the configured keypair, or simulates it with `dry_run`.
+    pub fn submit(&self, ix: Instruction, dry_run: bool) -> Result<()> {
+        let signer = self.signer()?;
+        let blockhash = self
+            .rpc
+            .get_latest_blockhash()
+            .context("fetching blockhash")?;
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+
+        if dry_run {
+            let result = self
+                .rpc
+                .simulate_transaction(&tx)
+                .context("simulating transaction")?
+                .value;
+            for line in result.logs.unwrap_or_default() {
+                println!("  {line}");
+            }
+            if let Some(units) = result.units_consumed {
+                println!("compute units: {units}");
+            }
+            return match result.err {
+                Some(err) => Err(describe(&err)),
+                None => {
+                    println!("simulation succeeded");
+                    Ok(())
+                }
+            };
+        }
+
+        match self.rpc.send_and_confirm_transaction(&tx) {
+            Ok(signature) => {
+                println!("{signature}");
+                Ok(())
+            }
+            Err(err) => match err.get_transaction_error() {
+                Some(tx_err) => Err(describe(&tx_err)),
+                None => Err(anyhow!(err).context("sending transaction")),
+            },
+        }
+    }
+}
+
+// Maps a custom program error code back to the EscrowError name and message
+fn describe(err: &TransactionError) -> anyhow::Error {
+    if let TransactionError::InstructionError(_, ix_err @ InstructionError::Custom(_)) = err {
+        if let Some(escrow_err) = EscrowError::from_instruction_error(ix_err) {
+            return anyhow!("transaction failed: {escrow_err}");
+        }
+    }
+    anyhow!("transaction failed: {err}")
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
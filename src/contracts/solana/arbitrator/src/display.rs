@@ -0,0 +1,205 @@
+//! Human-readable output.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use localsolana_contracts_client::constants::{
+    ARBITRATION_DEADLINE_HOURS, DISPUTE_RESPONSE_DEADLINE_HOURS, FIAT_RATE_DECIMALS,
+    SECONDS_PER_HOUR,
+};
+use localsolana_contracts_client::Escrow;
+
+use crate::chain::BondBalances;
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+pub fn print_list(escrows: &[Escrow]) {
+    if escrows.is_empty() {
+        println!("no escrows found");
+        return;
+    }
+    println!(
+        "{:<44}  {:>10}  {:>10}  {:<9}  {:>12}  {:>4}",
+        "ESCROW", "ESCROW ID", "TRADE ID", "STATE", "AMOUNT", "FIAT"
+    );
+    for escrow in escrows {
+        println!(
+            "{:<44}  {:>10}  {:>10}  {:<9}  {:>12}  {:>4}",
+            escrow.address().to_string(),
+            escrow.escrow_id,
+            escrow.trade_id,
+            escrow.state.to_string(),
+            format_amount(escrow.amount),
+            if escrow.fiat_paid { "paid" } else { "-" },
+        );
+    }
+}
+
+pub fn print_record(escrow: &Escrow, bonds: &BondBalances, now: i64) {
+    println!("escrow             {}", escrow.address());
+    println!("escrow id          {}", escrow.escrow_id);
+    println!("trade id           {}", escrow.trade_id);
+    println!("state              {}", escrow.state);
+    println!("layout version     {}", escrow.version);
+    println!("update counter     {}", escrow.counter);
+    println!("seller             {}", escrow.seller);
+    println!("buyer              {}", escrow.buyer);
+    println!("arbitrator         {}", escrow.arbitrator);
+    println!("amount             {}", format_amount(escrow.amount));
+    println!("fee                {}", format_amount(escrow.fee));
+    println!(
+        "tracked balance    {}",
+        format_amount(escrow.tracked_balance)
+    );
+    println!("fiat paid          {}", escrow.fiat_paid);
+    println!(
+        "deposit deadline   {}",
+        format_time(escrow.deposit_deadline, now)
+    );
+    if escrow.fiat_deadline != 0 {
+        println!(
+            "fiat deadline      {}",
+            format_time(escrow.fiat_deadline, now)
+        );
+    }
+    if let Some(address) = escrow.sequential_escrow_address {
+        println!("sequential address {address}");
+    }
+    match &escrow.fiat_terms {
+        Some(terms) => {
+            let currency = terms.currency_code();
+            println!(
+                "fiat amount        {} {currency} minor units",
+                terms.fiat_amount
+            );
+            println!(
+                "fiat rate          {} {currency} per token",
+                format_rate(terms.rate)
+            );
+            println!(
+                "payment method     {}",
+                format_hash(Some(terms.payment_method_hash))
+            );
+        }
+        None => println!("fiat terms         not recorded"),
+    }
+
+    println!();
+    println!("dispute");
+    let Some(initiated) = escrow.dispute_initiated_time else {
+        println!("  none");
+        return;
+    };
+    let initiator = escrow
+        .dispute_initiator
+        .map(|initiator| {
+            let role = if initiator == escrow.buyer {
+                "buyer"
+            } else {
+                "seller"
+            };
+            format!("{initiator} ({role})")
+        })
+        .unwrap_or_else(|| "-".to_string());
+    println!("  initiator             {initiator}");
+    println!("  opened                {}", format_time(initiated, now));
+    println!(
+        "  response deadline     {}",
+        format_time(
+            initiated + DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR,
+            now
+        )
+    );
+    println!(
+        "  arbitration deadline  {}",
+        format_time(
+            initiated + ARBITRATION_DEADLINE_HOURS * SECONDS_PER_HOUR,
+            now
+        )
+    );
+    println!(
+        "  buyer evidence        {}",
+        format_hash(escrow.dispute_evidence_hash_buyer)
+    );
+    println!(
+        "  seller evidence       {}",
+        format_hash(escrow.dispute_evidence_hash_seller)
+    );
+    println!(
+        "  resolution            {}",
+        format_hash(escrow.dispute_resolution_hash)
+    );
+    println!("  buyer bond            {}", format_bond(bonds.buyer));
+    println!("  seller bond           {}", format_bond(bonds.seller));
+}
+
+// Amounts are USDC base units (6 decimals)
+fn format_amount(amount: u64) -> String {
+    format!("{}.{:06}", amount / 1_000_000, amount % 1_000_000)
+}
+
+fn format_rate(rate: u64) -> String {
+    let scale = 10u64.pow(FIAT_RATE_DECIMALS);
+    format!(
+        "{}.{:0width$}",
+        rate / scale,
+        rate % scale,
+        width = FIAT_RATE_DECIMALS as usize
+    )
+}
+
+fn format_bond(balance: Option<u64>) -> String {
+    balance
+        .map(format_amount)
+        .unwrap_or_else(|| "no account".to_string())
+}
+
+fn format_hash(hash: Option<[u8; 32]>) -> String {
+    match hash {
+        Some(hash) => hash.iter().map(|b| format!("{b:02x}")).collect(),
+        None => "-".to_string(),
+    }
+}
+
+fn format_time(timestamp: i64, now: i64) -> String {
+    let delta = timestamp - now;
+    let (hours, minutes) = (delta.abs() / 3600, delta.abs() % 3600 / 60);
+    if delta >= 0 {
+        format!("{timestamp} (in {hours}h {minutes:02}m)")
+    } else {
+        format!("{timestamp} ({hours}h {minutes:02}m ago, expired)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_usdc_amounts() {
+        assert_eq!(format_amount(100_000_000), "100.000000");
+        assert_eq!(format_amount(1_010_000), "1.010000");
+    }
+
+    #[test]
+    fn formats_fiat_rates() {
+        assert_eq!(format_rate(92_000_000), "0.92000000");
+        assert_eq!(format_rate(1_550_012_345_678), "15500.12345678");
+    }
+
+    #[test]
+    fn formats_relative_deadlines() {
+        assert_eq!(format_time(7_200, 0), "7200 (in 2h 00m)");
+        assert_eq!(format_time(0, 5_400), "0 (1h 30m ago, expired)");
+    }
+
+    #[test]
+    fn formats_hashes_as_hex() {
+        assert_eq!(format_hash(Some([0xab; 32])), "ab".repeat(32));
+        assert_eq!(format_hash(None), "-");
+    }
+}
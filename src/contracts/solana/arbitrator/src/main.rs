@@ -0,0 +1,283 @@
+//! Arbitrator command-line tool.
+//!
+//! Lists escrows by state, prints the full dispute record of an escrow and settles
+//! disputes with `resolve_dispute_with_explanation`, `default_judgment` or `auto_cancel`.
+//! Every settling command accepts `--dry-run` to simulate the transaction instead of
+//! sending it.
+//!
+//! Defaults target a local test validator (`solana-test-validator`) and the Solana CLI
+//! keypair. The program only accepts the hardcoded arbitrator key for `resolve` and
+//! `auto-cancel`; `default-judgment` is permissionless.
+
+mod chain;
+mod display;
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use localsolana_contracts_client::instructions::{
+    AutoCancel, DefaultJudgment, ResolveDisputeWithExplanation,
+};
+use localsolana_contracts_client::pda::find_associated_token_address;
+use localsolana_contracts_client::EscrowState;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+use crate::chain::Chain;
+
+#[derive(Parser)]
+#[command(
+    name = "arbitrator",
+    version,
+    about = "Inspect and settle escrow disputes"
+)]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        env = "SOLANA_RPC_URL",
+        default_value = "http://127.0.0.1:8899",
+        global = true
+    )]
+    url: String,
+
+    /// Signing keypair (the arbitrator for resolve and auto-cancel)
+    #[arg(long, short = 'k', env = "ARBITRATOR_KEYPAIR", global = true)]
+    keypair: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List escrows, optionally filtered by state
+    List {
+        #[arg(long, short = 's')]
+        state: Option<EscrowState>,
+    },
+    /// Show an escrow with its full dispute record
+    Show { escrow: Pubkey },
+    /// Settle a dispute in favour of the buyer or the seller
+    Resolve {
+        escrow: Pubkey,
+        #[arg(long, value_enum)]
+        winner: Party,
+        /// SHA-256 of the off-chain explanation, 64 hex characters
+        #[arg(long, value_parser = parse_hash)]
+        resolution_hash: [u8; 32],
+        #[command(flatten)]
+        token_accounts: TokenAccounts,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Award a dispute to the initiator after the response deadline
+    DefaultJudgment {
+        escrow: Pubkey,
+        #[command(flatten)]
+        token_accounts: TokenAccounts,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Cancel an escrow whose deposit or fiat deadline has expired
+    AutoCancel {
+        escrow: Pubkey,
+        #[command(flatten)]
+        token_accounts: TokenAccounts,
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Party {
+    Buyer,
+    Seller,
+}
+
+/// Token account overrides. By default the associated token accounts for the mint of
+/// the escrow vault are used.
+#[derive(clap::Args)]
+struct TokenAccounts {
+    #[arg(long)]
+    buyer_token_account: Option<Pubkey>,
+    #[arg(long)]
+    seller_token_account: Option<Pubkey>,
+    #[arg(long)]
+    arbitrator_token_account: Option<Pubkey>,
+}
+
+impl TokenAccounts {
+    fn or_associated(
+        override_: Option<Pubkey>,
+        wallet: &Pubkey,
+        mint: Option<Pubkey>,
+    ) -> Result<Pubkey> {
+        match (override_, mint) {
+            (Some(account), _) => Ok(account),
+            (None, Some(mint)) => Ok(find_associated_token_address(wallet, &mint)),
+            (None, None) => bail!("escrow vault not found; pass token accounts explicitly"),
+        }
+    }
+}
+
+fn parse_hash(s: &str) -> Result<[u8; 32], String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 64 {
+        return Err("expected 64 hex characters".into());
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+            .map_err(|_| format!("invalid hex at position {}", 2 * i))?;
+    }
+    Ok(out)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let chain = Chain::new(&cli.url, cli.keypair.as_deref())?;
+
+    match cli.command {
+        Command::List { state } => {
+            let escrows = chain.escrows(state)?;
+            display::print_list(&escrows);
+        }
+        Command::Show { escrow } => {
+            let escrow = chain.escrow(&escrow)?;
+            let bonds = chain.bond_balances(&escrow.address())?;
+            display::print_record(&escrow, &bonds, display::unix_now());
+        }
+        Command::Resolve {
+            escrow,
+            winner,
+            resolution_hash,
+            token_accounts,
+            dry_run,
+        } => {
+            let escrow = chain.escrow(&escrow)?;
+            require_state(&escrow.state, EscrowState::Disputed)?;
+            let mint = chain.vault_mint(&escrow.address())?;
+            let ix = ResolveDisputeWithExplanation {
+                escrow: &escrow,
+                arbitrator: chain.signer()?.pubkey(),
+                decision: matches!(winner, Party::Buyer),
+                resolution_hash,
+                buyer_token_account: TokenAccounts::or_associated(
+                    token_accounts.buyer_token_account,
+                    &escrow.buyer,
+                    mint,
+                )?,
+                seller_token_account: TokenAccounts::or_associated(
+                    token_accounts.seller_token_account,
+                    &escrow.seller,
+                    mint,
+                )?,
+                arbitrator_token_account: TokenAccounts::or_associated(
+                    token_accounts.arbitrator_token_account,
+                    &escrow.arbitrator,
+                    mint,
+                )?,
+            }
+            .instruction();
+            chain.submit(ix, dry_run)?;
+        }
+        Command::DefaultJudgment {
+            escrow,
+            token_accounts,
+            dry_run,
+        } => {
+            let escrow = chain.escrow(&escrow)?;
+            require_state(&escrow.state, EscrowState::Disputed)?;
+            let mint = chain.vault_mint(&escrow.address())?;
+            let ix = DefaultJudgment {
+                escrow: &escrow,
+                caller: chain.signer()?.pubkey(),
+                buyer_token_account: TokenAccounts::or_associated(
+                    token_accounts.buyer_token_account,
+                    &escrow.buyer,
+                    mint,
+                )?,
+                seller_token_account: TokenAccounts::or_associated(
+                    token_accounts.seller_token_account,
+                    &escrow.seller,
+                    mint,
+                )?,
+            }
+            .instruction();
+            chain.submit(ix, dry_run)?;
+        }
+        Command::AutoCancel {
+            escrow,
+            token_accounts,
+            dry_run,
+        } => {
+            let escrow = chain.escrow(&escrow)?;
+            if !matches!(escrow.state, EscrowState::Created | EscrowState::Funded) {
+                bail!(
+                    "escrow is {}, only Created or Funded escrows can be auto-cancelled",
+                    escrow.state
+                );
+            }
+            let seller_token_account = if escrow.state == EscrowState::Funded {
+                let mint = chain.vault_mint(&escrow.address())?;
+                Some(TokenAccounts::or_associated(
+                    token_accounts.seller_token_account,
+                    &escrow.seller,
+                    mint,
+                )?)
+            } else {
+                None
+            };
+            let ix = AutoCancel {
+                escrow: &escrow,
+                arbitrator: chain.signer()?.pubkey(),
+                seller_token_account,
+                arbitrator_token_account: None,
+            }
+            .instruction();
+            chain.submit(ix, dry_run)?;
+        }
+    }
+    Ok(())
+}
+
+fn require_state(actual: &EscrowState, expected: EscrowState) -> Result<()> {
+    if *actual != expected {
+        bail!("escrow is {actual}, expected {expected}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_resolution_hash() {
+        let hex = "ab".repeat(32);
+        assert_eq!(parse_hash(&hex).unwrap(), [0xab; 32]);
+        assert_eq!(parse_hash(&format!("0x{hex}")).unwrap(), [0xab; 32]);
+        assert!(parse_hash("abcd").is_err());
+        assert!(parse_hash(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_state_filter() {
+        let cli = Cli::try_parse_from(["arbitrator", "list", "--state", "disputed"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::List {
+                state: Some(EscrowState::Disputed)
+            }
+        ));
+    }
+}
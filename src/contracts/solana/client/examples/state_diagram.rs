@@ -0,0 +1,12 @@
+//! Regenerates `state_machine.md` next to the program from the transition table.
+
+use std::path::Path;
+
+use localsolana_contracts_client::state_machine;
+
+fn main() -> std::io::Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../state_machine.md");
+    std::fs::write(&path, state_machine::diagram_page())?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
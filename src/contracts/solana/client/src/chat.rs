@@ -0,0 +1,123 @@
+//! Chat transcript Merkle trees, as checkpointed by `commit_chat_root` and checked by
+//! `prove_chat_message`.
+//!
+//! Trees follow RFC 6962: leaf `i` is `sha256(0x00 || sha256(message_i))` and inner
+//! nodes are `sha256(0x01 || left || right)`, with the left subtree always the largest
+//! power of two. Appending messages never changes the proof shape of earlier leaves
+//! beyond adding siblings, so a proof against the latest checkpoint covers the whole
+//! transcript so far. How a message is serialized before hashing is up to the chat
+//! backend, as long as both parties and the arbitrator agree on it.
+
+use solana_program::hash::{hash, hashv};
+
+use crate::state_machine::{self, Violation, CHAT_LEAF_PREFIX, CHAT_NODE_PREFIX};
+
+/// `message_hash` as passed to `prove_chat_message`.
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    hash(message).to_bytes()
+}
+
+pub fn leaf_hash(message_hash: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[CHAT_LEAF_PREFIX], message_hash]).to_bytes()
+}
+
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[CHAT_NODE_PREFIX], left, right]).to_bytes()
+}
+
+// Largest power of two below `n`, for n > 1
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Root over the leaf hashes of a transcript, or `None` for an empty one.
+pub fn root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    match leaves.len() {
+        0 => None,
+        1 => Some(leaves[0]),
+        n => {
+            let (left, right) = leaves.split_at(split(n));
+            Some(node_hash(&root(left)?, &root(right)?))
+        }
+    }
+}
+
+/// Audit path of leaf `index`, bottom-up, as `prove_chat_message` takes it.
+pub fn proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let (mut leaves, mut index) = (leaves, index);
+    let mut path = Vec::new();
+    while leaves.len() > 1 {
+        let k = split(leaves.len());
+        let (left, right) = leaves.split_at(k);
+        if index < k {
+            path.push(root(right)?);
+            leaves = left;
+        } else {
+            path.push(root(left)?);
+            leaves = right;
+            index -= k;
+        }
+    }
+    // collected top-down
+    path.reverse();
+    Some(path)
+}
+
+/// The check `prove_chat_message` runs, for trying a proof before sending it.
+pub fn verify(
+    leaf_index: u64,
+    message_count: u64,
+    message_hash: &[u8; 32],
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+) -> Result<(), Violation> {
+    state_machine::verify_inclusion(
+        leaf_index,
+        message_count,
+        leaf_hash(message_hash),
+        proof,
+        root,
+        node_hash,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_leaf_proves_against_roots_of_every_size() {
+        let hashes: Vec<[u8; 32]> = (0..13u8).map(|i| message_hash(&[i])).collect();
+        let leaves: Vec<[u8; 32]> = hashes.iter().map(leaf_hash).collect();
+        for size in 1..=leaves.len() {
+            let root = root(&leaves[..size]).unwrap();
+            for index in 0..size {
+                let path = proof(&leaves[..size], index).unwrap();
+                let (i, n) = (index as u64, size as u64);
+                assert_eq!(verify(i, n, &hashes[index], &path, &root), Ok(()));
+                let other = &hashes[(index + 1) % hashes.len()];
+                assert!(verify(i, n, other, &path, &root).is_err());
+                // the path of a smaller tree does not reach the root of a grown one
+                if let Some(grown) = leaves.get(..size + 1).and_then(super::root) {
+                    assert!(verify(i, n + 1, &hashes[index], &path, &grown).is_err());
+                }
+            }
+        }
+        assert_eq!(root(&[]), None);
+        assert_eq!(proof(&leaves[..3], 3), None);
+    }
+
+    #[test]
+    fn matches_rfc_6962_shape() {
+        let leaves: Vec<[u8; 32]> = (0..3u8).map(|i| leaf_hash(&message_hash(&[i]))).collect();
+        let expected = node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2]);
+        assert_eq!(root(&leaves), Some(expected));
+        assert_eq!(
+            proof(&leaves, 2),
+            Some(vec![node_hash(&leaves[0], &leaves[1])])
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Program constants that matter to off-chain callers. Keep in sync with `constants` in
+//! the program's lib.rs; the lifecycle rules come from the shared `state_machine`.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state_machine::FeePolicy;
+
+pub use crate::state_machine::{
+    BUYER_DEPOSIT_BASIS_POINTS, DEPOSIT_DEADLINE_MINUTES, DISPUTE_BOND_BASIS_POINTS,
+    DISPUTE_RESPONSE_DEADLINE_HOURS, FEE_BASIS_POINTS, FIAT_DEADLINE_MINUTES, FIAT_RATE_DECIMALS,
+    MAX_AMOUNT, MIN_BUYER_DEPOSIT, MIN_DISPUTE_BOND, MIN_FEE, ROUNDING, SECONDS_PER_HOUR,
+    SECONDS_PER_MINUTE, SELLER_CANCEL_PENALTY_BASIS_POINTS,
+};
+
+pub const ARBITRATION_DEADLINE_HOURS: i64 = 168;
+
+/// Hardcoded arbitrator (`ARBITRATOR_BYTES` in the program).
+pub const ARBITRATOR: Pubkey =
+    solana_program::pubkey!("GGrXhNVxUZXaA2uMopsa5q23aPmoNvQF14uxqo8qENUr");
+
+/// Pyth Solana receiver program, owner of `PriceUpdateV2` accounts
+/// (`PYTH_RECEIVER_BYTES` in the program).
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+pub const ESCROW_VERSION: u8 = 7;
+pub const EVENT_SCHEMA_VERSION: u8 = 6;
+
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const ESCROW_TOKEN_SEED: &[u8] = b"escrow_token";
+pub const BUYER_BOND_SEED: &[u8] = b"buyer_bond";
+pub const SELLER_BOND_SEED: &[u8] = b"seller_bond";
+pub const BUYER_DEPOSIT_SEED: &[u8] = b"buyer_deposit";
+pub const PRICE_FEED_SEED: &[u8] = b"price_feed";
+pub const PROFILE_SEED: &[u8] = b"profile";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Fee charged on top of `amount`, as computed by `create_escrow` under `policy` (the
+/// `Config` account's, or `FeePolicy::DEFAULT` while it is uninitialized).
+pub fn fee_for(amount: u64, policy: FeePolicy) -> Option<u64> {
+    crate::state_machine::fee(amount, policy).ok()
+}
+
+/// Bond each party posts to dispute, as computed by `open_dispute_with_bond` under the
+/// escrow's `policy`.
+pub fn dispute_bond_for(amount: u64, policy: FeePolicy) -> Option<u64> {
+    crate::state_machine::dispute_bond(amount, policy).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arbitrator_matches_program_bytes() {
+        let bytes: [u8; 32] = [
+            0xe2, 0xef, 0x04, 0xd8, 0x35, 0x5b, 0x03, 0xd1, 0xdb, 0x14, 0x87, 0x9e, 0x38, 0x84,
+            0x4d, 0x64, 0x74, 0xc7, 0x8b, 0xe4, 0xbe, 0x4e, 0x31, 0xb4, 0xae, 0xfe, 0x13, 0xc8,
+            0x2f, 0xdb, 0xdb, 0x2b,
+        ];
+        assert_eq!(ARBITRATOR, Pubkey::new_from_array(bytes));
+    }
+
+    #[test]
+    fn pyth_receiver_matches_program_bytes() {
+        let bytes: [u8; 32] = [
+            0x0c, 0xb7, 0xfa, 0xbb, 0x52, 0xf7, 0xa6, 0x48, 0xbb, 0x5b, 0x31, 0x7d, 0x9a, 0x01,
+            0x8b, 0x90, 0x57, 0xcb, 0x02, 0x47, 0x74, 0xfa, 0xfe, 0x01, 0xe6, 0xc4, 0xdf, 0x98,
+            0xcc, 0x38, 0x58, 0x81,
+        ];
+        assert_eq!(PYTH_RECEIVER_PROGRAM_ID, Pubkey::new_from_array(bytes));
+    }
+
+    #[test]
+    fn fee_and_bond_use_basis_points() {
+        let policy = FeePolicy::DEFAULT;
+        assert_eq!(fee_for(MAX_AMOUNT, policy), Some(1_000_000));
+        assert_eq!(dispute_bond_for(MAX_AMOUNT, policy), Some(5_000_000));
+        assert_eq!(fee_for(u64::MAX, policy), None);
+        assert_eq!(fee_for(1, policy), Some(MIN_FEE));
+        assert_eq!(dispute_bond_for(1, policy), Some(MIN_DISPUTE_BOND));
+    }
+}
@@ -0,0 +1,264 @@
+//! Program error codes and client-side decode errors.
+
+use std::fmt;
+
+use solana_program::instruction::InstructionError;
+
+/// Anchor numbers `#[error_code]` variants from 6000 in declaration order.
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+/// Mirror of the program's `EscrowError`. Order must match the program exactly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum EscrowError {
+    InvalidAmount = ERROR_CODE_OFFSET,
+    ExceedsMaximum,
+    Unauthorized,
+    DepositDeadlineExpired,
+    FiatDeadlineExpired,
+    InvalidState,
+    MissingSequentialAddress,
+    TerminalState,
+    FeeCalculationError,
+    InsufficientFunds,
+    IncorrectBondAmount,
+    ResponseDeadlineExpired,
+    InvalidEvidenceHash,
+    DuplicateEvidence,
+    ArbitrationDeadlineExpired,
+    MissingDisputeBond,
+    InvalidResolutionExplanation,
+    BumpNotFound,
+    InvalidEscrowAccount,
+    AlreadyMigrated,
+    InvalidRentPayer,
+    MissingBondAccount,
+    NotTerminalState,
+    CounterOverflow,
+    NoSurplus,
+    InvalidFiatTerms,
+    InvalidOraclePrice,
+    StaleOraclePrice,
+    OraclePriceUncertain,
+    RateOutOfBand,
+    InvalidRateBand,
+    InvalidEncryptionKey,
+    RecipientProfileMismatch,
+    InvalidChatRoot,
+    InvalidInclusionProof,
+    AcceptanceRequired,
+    MissingBuyerDeposit,
+    MissingArbitratorTokenAccount,
+    InvalidFeePolicy,
+    BuyerDepositRequired,
+}
+
+impl EscrowError {
+    pub const ALL: [EscrowError; 40] = [
+        EscrowError::InvalidAmount,
+        EscrowError::ExceedsMaximum,
+        EscrowError::Unauthorized,
+        EscrowError::DepositDeadlineExpired,
+        EscrowError::FiatDeadlineExpired,
+        EscrowError::InvalidState,
+        EscrowError::MissingSequentialAddress,
+        EscrowError::TerminalState,
+        EscrowError::FeeCalculationError,
+        EscrowError::InsufficientFunds,
+        EscrowError::IncorrectBondAmount,
+        EscrowError::ResponseDeadlineExpired,
+        EscrowError::InvalidEvidenceHash,
+        EscrowError::DuplicateEvidence,
+        EscrowError::ArbitrationDeadlineExpired,
+        EscrowError::MissingDisputeBond,
+        EscrowError::InvalidResolutionExplanation,
+        EscrowError::BumpNotFound,
+        EscrowError::InvalidEscrowAccount,
+        EscrowError::AlreadyMigrated,
+        EscrowError::InvalidRentPayer,
+        EscrowError::MissingBondAccount,
+        EscrowError::NotTerminalState,
+        EscrowError::CounterOverflow,
+        EscrowError::NoSurplus,
+        EscrowError::InvalidFiatTerms,
+        EscrowError::InvalidOraclePrice,
+        EscrowError::StaleOraclePrice,
+        EscrowError::OraclePriceUncertain,
+        EscrowError::RateOutOfBand,
+        EscrowError::InvalidRateBand,
+        EscrowError::InvalidEncryptionKey,
+        EscrowError::RecipientProfileMismatch,
+        EscrowError::InvalidChatRoot,
+        EscrowError::InvalidInclusionProof,
+        EscrowError::AcceptanceRequired,
+        EscrowError::MissingBuyerDeposit,
+        EscrowError::MissingArbitratorTokenAccount,
+        EscrowError::InvalidFeePolicy,
+        EscrowError::BuyerDepositRequired,
+    ];
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(ERROR_CODE_OFFSET)? as usize;
+        Self::ALL.get(index).copied()
+    }
+
+    /// Maps `InstructionError::Custom` from a failed transaction back to the program error.
+    pub fn from_instruction_error(err: &InstructionError) -> Option<Self> {
+        match err {
+            InstructionError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    /// The program's `#[msg]` text.
+    pub fn message(self) -> &'static str {
+        match self {
+            EscrowError::InvalidAmount => "Invalid amount: Zero or negative",
+            EscrowError::ExceedsMaximum => "Amount exceeds maximum (100 USDC)",
+            EscrowError::Unauthorized => "Unauthorized caller",
+            EscrowError::DepositDeadlineExpired => "Deposit deadline expired",
+            EscrowError::FiatDeadlineExpired => "Fiat payment deadline expired",
+            EscrowError::InvalidState => "Invalid state transition",
+            EscrowError::MissingSequentialAddress => "Missing sequential escrow address",
+            EscrowError::TerminalState => "Already in terminal state",
+            EscrowError::FeeCalculationError => "Fee calculation error",
+            EscrowError::InsufficientFunds => "Insufficient funds to cover principal and fee",
+            EscrowError::IncorrectBondAmount => "Dispute bond amount incorrect",
+            EscrowError::ResponseDeadlineExpired => "Dispute response deadline expired",
+            EscrowError::InvalidEvidenceHash => "Evidence hash missing or invalid",
+            EscrowError::DuplicateEvidence => "Duplicate evidence submission",
+            EscrowError::ArbitrationDeadlineExpired => "Arbitration deadline expired",
+            EscrowError::MissingDisputeBond => "Missing dispute bond",
+            EscrowError::InvalidResolutionExplanation => "Invalid resolution explanation",
+            EscrowError::BumpNotFound => "Required bump seed not found",
+            EscrowError::InvalidEscrowAccount => "Escrow account data is not a valid Escrow",
+            EscrowError::AlreadyMigrated => "Escrow already at current layout version",
+            EscrowError::InvalidRentPayer => {
+                "Rent refund account does not match the recorded payer"
+            }
+            EscrowError::MissingBondAccount => {
+                "Initialized bond account must be supplied so it can be closed"
+            }
+            EscrowError::NotTerminalState => "Escrow is not in a terminal state",
+            EscrowError::CounterOverflow => "Escrow update counter overflow",
+            EscrowError::NoSurplus => "Vault holds no surplus over the tracked balance",
+            EscrowError::InvalidFiatTerms => {
+                "Fiat terms need an ISO 4217 currency code and a non-zero amount and rate"
+            }
+            EscrowError::InvalidOraclePrice => {
+                "Not a fully verified Pyth price update for the registered feed, or a non-positive price"
+            }
+            EscrowError::StaleOraclePrice => "Oracle price is older than the feed allows",
+            EscrowError::OraclePriceUncertain => {
+                "Oracle price confidence interval is wider than the feed allows"
+            }
+            EscrowError::RateOutOfBand => {
+                "Committed exchange rate deviates from the oracle price by more than the feed allows"
+            }
+            EscrowError::InvalidRateBand => {
+                "Price feed limits must be non-zero and the confidence limit at most 100%"
+            }
+            EscrowError::InvalidEncryptionKey => {
+                "Encryption key must be a non-zero x25519 public key"
+            }
+            EscrowError::RecipientProfileMismatch => {
+                "Recipient profile does not belong to the other party"
+            }
+            EscrowError::InvalidChatRoot => {
+                "Chat root must be non-zero and cover more messages than the last checkpoint"
+            }
+            EscrowError::InvalidInclusionProof => {
+                "Message is not included in the party's committed chat root"
+            }
+            EscrowError::AcceptanceRequired => {
+                "Escrow must be accepted by the buyer before it is funded"
+            }
+            EscrowError::MissingBuyerDeposit => {
+                "Posted buyer deposit account must be supplied so it can be settled"
+            }
+            EscrowError::MissingArbitratorTokenAccount => {
+                "Arbitrator token account must be supplied to sweep stray bond tokens"
+            }
+            EscrowError::InvalidFeePolicy => {
+                "Fee and bond floors may not exceed the maximum trade amount"
+            }
+            EscrowError::BuyerDepositRequired => {
+                "Buyer deposit must be posted before fiat is marked paid"
+            }
+        }
+    }
+}
+
+impl fmt::Display for EscrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({}): {}", self, self.code(), self.message())
+    }
+}
+
+impl std::error::Error for EscrowError {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account discriminator does not match")]
+    InvalidDiscriminator,
+    #[error("unknown event discriminator {0:?}")]
+    UnknownEvent([u8; 8]),
+    #[error("instruction data is not an emit_cpi event")]
+    NotAnEvent,
+    #[error("account is not an SPL token account")]
+    InvalidTokenAccount,
+    #[error("unknown escrow state {0:?}")]
+    UnknownState(String),
+    #[error("failed to decode: {0}")]
+    Decode(#[from] std::io::Error),
+    #[error("invalid base64 in program log: {0}")]
+    Base64(#[from] base64::DecodeError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_follow_declaration_order() {
+        for (index, err) in EscrowError::ALL.iter().enumerate() {
+            assert_eq!(err.code(), ERROR_CODE_OFFSET + index as u32);
+            assert_eq!(EscrowError::from_code(err.code()), Some(*err));
+        }
+        assert_eq!(EscrowError::InvalidState.code(), 6005);
+        assert_eq!(EscrowError::CounterOverflow.code(), 6023);
+        assert_eq!(EscrowError::NoSurplus.code(), 6024);
+        assert_eq!(EscrowError::InvalidFiatTerms.code(), 6025);
+        assert_eq!(EscrowError::InvalidRateBand.code(), 6030);
+        assert_eq!(EscrowError::RecipientProfileMismatch.code(), 6032);
+        assert_eq!(EscrowError::InvalidInclusionProof.code(), 6034);
+        assert_eq!(EscrowError::AcceptanceRequired.code(), 6035);
+        assert_eq!(EscrowError::MissingBuyerDeposit.code(), 6036);
+        assert_eq!(EscrowError::MissingArbitratorTokenAccount.code(), 6037);
+        assert_eq!(EscrowError::InvalidFeePolicy.code(), 6038);
+        assert_eq!(EscrowError::BuyerDepositRequired.code(), 6039);
+    }
+
+    #[test]
+    fn unknown_codes_map_to_none() {
+        assert_eq!(EscrowError::from_code(0), None);
+        assert_eq!(EscrowError::from_code(5999), None);
+        assert_eq!(EscrowError::from_code(6040), None);
+    }
+
+    #[test]
+    fn maps_custom_instruction_errors() {
+        assert_eq!(
+            EscrowError::from_instruction_error(&InstructionError::Custom(6002)),
+            Some(EscrowError::Unauthorized)
+        );
+        assert_eq!(
+            EscrowError::from_instruction_error(&InstructionError::InvalidArgument),
+            None
+        );
+    }
+}
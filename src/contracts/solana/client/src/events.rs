@@ -0,0 +1,681 @@
+//! Event decoding.
+//!
+//! The program delivers events with `emit_cpi!`: a self-invocation signed by the event
+//! authority PDA whose instruction data is `EVENT_IX_TAG || discriminator || borsh(event)`.
+//! Indexers should read them from inner instructions; `Event::from_log` covers the
+//! `Program data:` log form used by `emit!` for completeness.
+
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::error::ClientError;
+use crate::state::{BalanceChangeReason, EscrowState, FiatTerms, Rounding};
+
+/// `anchor_lang::event::EVENT_IX_TAG_LE`, the prefix of every `emit_cpi!` instruction.
+pub const EVENT_IX_TAG: [u8; 8] = 0x1d9acb512ea545e4u64.to_le_bytes();
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+// Most bytes appended to one event since schema version 1, as zero values: rounding,
+// min_fee_applied, the fiat_terms tag, requires_acceptance, min_fee and
+// requires_buyer_deposit of EscrowCreated
+const APPENDED_FIELDS_LEN: usize = 13;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCreated {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub arbitrator: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub timestamp: i64,
+    pub rounding: Rounding,
+    /// `min_fee` was charged instead of the rounded 1%.
+    pub min_fee_applied: bool,
+    /// Set by every program version that records fiat terms.
+    pub fiat_terms: Option<FiatTerms>,
+    /// `fund_escrow` waits for `accept_escrow`.
+    pub requires_acceptance: bool,
+    /// Fee floor of the policy the escrow was created under. 0 before schema version 5,
+    /// when it was always `MIN_FEE`.
+    pub min_fee: u64,
+    /// `mark_fiat_paid` waits for `post_buyer_deposit`.
+    pub requires_buyer_deposit: bool,
+}
+
+/// Emitted by `accept_escrow`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowAccepted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted by `post_buyer_deposit`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuyerDepositPosted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub rounding: Rounding,
+    /// `MIN_BUYER_DEPOSIT` was posted instead of the rounded 2%.
+    pub min_deposit_applied: bool,
+    pub counter: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by the settlement that returned the buyer deposit to the buyer or, when
+/// `forfeited`, paid it to the seller.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuyerDepositSettled {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub forfeited: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted by `cancel_escrow` when the seller pays the buyer for cancelling a funded escrow
+/// the buyer accepted.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CancellationPenaltyPaid {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by `post_payment_details`. `ciphertext_hash` is the hash of the payment details
+/// encrypted from `sender_key` to `recipient_key`, the keys registered at the time.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PaymentDetailsPosted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub sender_key: [u8; 32],
+    pub recipient_key: [u8; 32],
+    pub ciphertext_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted by `create_escrow` after the committed rate passed the oracle check.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FiatRateChecked {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub currency: [u8; 3],
+    pub feed_id: [u8; 32],
+    pub committed_rate: u64,
+    /// The oracle price with `FIAT_RATE_DECIMALS` decimals, like `committed_rate`.
+    pub oracle_rate: u64,
+    pub deviation_bps: u64,
+    /// Publish time of the oracle price.
+    pub publish_time: i64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FundsDeposited {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub counter: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FiatMarkedPaid {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowReleased {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub counter: u64,
+    pub timestamp: i64,
+    pub destination: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCancelled {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub counter: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisputeOpened {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub disputing_party: Pubkey,
+    pub timestamp: i64,
+    pub evidence_hash: [u8; 32],
+    pub bond_amount: u64,
+    pub rounding: Rounding,
+    /// `min_bond` was posted instead of the rounded 5%.
+    pub min_bond_applied: bool,
+    /// Bond floor of the escrow's fee policy. 0 before schema version 5, when it was
+    /// always `MIN_DISPUTE_BOND`.
+    pub min_bond: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisputeResponseSubmitted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub responding_party: Pubkey,
+    pub timestamp: i64,
+    pub evidence_hash: [u8; 32],
+    pub bond_amount: u64,
+    pub rounding: Rounding,
+    /// `min_bond` was posted instead of the rounded 5%.
+    pub min_bond_applied: bool,
+    /// Bond floor of the escrow's fee policy. 0 before schema version 5, when it was
+    /// always `MIN_DISPUTE_BOND`.
+    pub min_bond: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisputeResolved {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub decision: bool,
+    pub fee: u64,
+    pub counter: u64,
+    pub timestamp: i64,
+    pub resolution_hash: [u8; 32],
+    pub winner: Pubkey,
+    pub explanation_reference: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisputeDefaultJudgment {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub defaulting_party: Pubkey,
+    pub decision: bool,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowStateChanged {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub from: Option<EscrowState>,
+    pub to: EscrowState,
+    pub counter: u64,
+    pub actor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowBalanceChanged {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub new_balance: u64,
+    pub reason: BalanceChangeReason,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SequentialAddressUpdated {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub old_address: Option<Pubkey>,
+    pub new_address: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChatRootCommitted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub party: Pubkey,
+    pub root: [u8; 32],
+    pub message_count: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by `prove_chat_message`: message `leaf_index` of `party`'s checkpoint hashes to
+/// `message_hash`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChatMessageProven {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub party: Pubkey,
+    pub root: [u8; 32],
+    pub message_count: u64,
+    pub leaf_index: u64,
+    pub message_hash: [u8; 32],
+    pub prover: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowMigrated {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BondAccountReclaimed {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub bond_account: Pubkey,
+    pub rent_destination: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted by `reconcile` when the vault does not hold exactly `tracked_balance`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BalanceDiscrepancy {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub tracked_balance: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SurplusSwept {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits.
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum Event {
+            $($name($name),)*
+        }
+
+        impl Event {
+            /// Decodes `discriminator || borsh(event)`.
+            ///
+            /// Fields are only ever appended to events, so ones emitted under an older
+            /// `schema_version` are zero-padded and read the newer fields as zero
+            /// values: `Floor` rounding, `false` flags and `None` options.
+            pub fn try_from_bytes(data: &[u8]) -> Result<Self, ClientError> {
+                if data.len() < 8 {
+                    return Err(ClientError::NotAnEvent);
+                }
+                let (disc, body) = data.split_at(8);
+                let mut padded = body.to_vec();
+                padded.resize(body.len() + APPENDED_FIELDS_LEN, 0);
+                let mut body = padded.as_slice();
+                $(
+                    if disc == $name::discriminator() {
+                        return Ok(Event::$name($name::deserialize(&mut body)?));
+                    }
+                )*
+                let mut unknown = [0u8; 8];
+                unknown.copy_from_slice(disc);
+                Err(ClientError::UnknownEvent(unknown))
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$name(_) => stringify!($name),)*
+                }
+            }
+
+            /// Encodes the event as `discriminator || borsh(event)`.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                match self {
+                    $(Event::$name(event) => {
+                        let mut data = $name::discriminator().to_vec();
+                        data.extend(borsh::to_vec(event).expect("borsh encoding to Vec"));
+                        data
+                    })*
+                }
+            }
+        }
+
+        $(
+            impl $name {
+                pub fn discriminator() -> [u8; 8] {
+                    crate::discriminator("event", stringify!($name))
+                }
+            }
+
+            impl From<$name> for Event {
+                fn from(event: $name) -> Self {
+                    Event::$name(event)
+                }
+            }
+        )*
+    };
+}
+
+events!(
+    EscrowCreated,
+    EscrowAccepted,
+    FiatRateChecked,
+    FundsDeposited,
+    FiatMarkedPaid,
+    EscrowReleased,
+    EscrowCancelled,
+    DisputeOpened,
+    DisputeResponseSubmitted,
+    DisputeResolved,
+    DisputeDefaultJudgment,
+    EscrowStateChanged,
+    EscrowBalanceChanged,
+    SequentialAddressUpdated,
+    EscrowMigrated,
+    BondAccountReclaimed,
+    BalanceDiscrepancy,
+    SurplusSwept,
+    PaymentDetailsPosted,
+    ChatRootCommitted,
+    ChatMessageProven,
+    BuyerDepositPosted,
+    BuyerDepositSettled,
+    CancellationPenaltyPaid,
+);
+
+impl Event {
+    /// Decodes the data of an `emit_cpi!` inner instruction.
+    pub fn from_cpi_instruction_data(data: &[u8]) -> Result<Self, ClientError> {
+        match data.strip_prefix(&EVENT_IX_TAG[..]) {
+            Some(event) => Self::try_from_bytes(event),
+            None => Err(ClientError::NotAnEvent),
+        }
+    }
+
+    /// Decodes a `Program data: <base64>` log line; returns `None` for any other line.
+    pub fn from_log(line: &str) -> Option<Result<Self, ClientError>> {
+        let encoded = line.strip_prefix(PROGRAM_DATA_PREFIX)?;
+        Some(
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(ClientError::from)
+                .and_then(|data| Self::try_from_bytes(&data)),
+        )
+    }
+
+    /// Escrow state account the event refers to.
+    pub fn object_id(&self) -> Pubkey {
+        match self {
+            Event::EscrowCreated(e) => e.object_id,
+            Event::EscrowAccepted(e) => e.object_id,
+            Event::FiatRateChecked(e) => e.object_id,
+            Event::FundsDeposited(e) => e.object_id,
+            Event::FiatMarkedPaid(e) => e.object_id,
+            Event::EscrowReleased(e) => e.object_id,
+            Event::EscrowCancelled(e) => e.object_id,
+            Event::DisputeOpened(e) => e.object_id,
+            Event::DisputeResponseSubmitted(e) => e.object_id,
+            Event::DisputeResolved(e) => e.object_id,
+            Event::DisputeDefaultJudgment(e) => e.object_id,
+            Event::EscrowStateChanged(e) => e.object_id,
+            Event::EscrowBalanceChanged(e) => e.object_id,
+            Event::SequentialAddressUpdated(e) => e.object_id,
+            Event::EscrowMigrated(e) => e.object_id,
+            Event::BondAccountReclaimed(e) => e.object_id,
+            Event::BalanceDiscrepancy(e) => e.object_id,
+            Event::SurplusSwept(e) => e.object_id,
+            Event::PaymentDetailsPosted(e) => e.object_id,
+            Event::ChatRootCommitted(e) => e.object_id,
+            Event::ChatMessageProven(e) => e.object_id,
+            Event::BuyerDepositPosted(e) => e.object_id,
+            Event::BuyerDepositSettled(e) => e.object_id,
+            Event::CancellationPenaltyPaid(e) => e.object_id,
+        }
+    }
+
+    pub fn schema_version(&self) -> u8 {
+        match self {
+            Event::EscrowCreated(e) => e.schema_version,
+            Event::EscrowAccepted(e) => e.schema_version,
+            Event::FiatRateChecked(e) => e.schema_version,
+            Event::FundsDeposited(e) => e.schema_version,
+            Event::FiatMarkedPaid(e) => e.schema_version,
+            Event::EscrowReleased(e) => e.schema_version,
+            Event::EscrowCancelled(e) => e.schema_version,
+            Event::DisputeOpened(e) => e.schema_version,
+            Event::DisputeResponseSubmitted(e) => e.schema_version,
+            Event::DisputeResolved(e) => e.schema_version,
+            Event::DisputeDefaultJudgment(e) => e.schema_version,
+            Event::EscrowStateChanged(e) => e.schema_version,
+            Event::EscrowBalanceChanged(e) => e.schema_version,
+            Event::SequentialAddressUpdated(e) => e.schema_version,
+            Event::EscrowMigrated(e) => e.schema_version,
+            Event::BondAccountReclaimed(e) => e.schema_version,
+            Event::BalanceDiscrepancy(e) => e.schema_version,
+            Event::SurplusSwept(e) => e.schema_version,
+            Event::PaymentDetailsPosted(e) => e.schema_version,
+            Event::ChatRootCommitted(e) => e.schema_version,
+            Event::ChatMessageProven(e) => e.schema_version,
+            Event::BuyerDepositPosted(e) => e.schema_version,
+            Event::BuyerDepositSettled(e) => e.schema_version,
+            Event::CancellationPenaltyPaid(e) => e.schema_version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EVENT_SCHEMA_VERSION;
+
+    fn state_changed() -> EscrowStateChanged {
+        EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            from: Some(EscrowState::Funded),
+            to: EscrowState::Released,
+            counter: 3,
+            actor: Pubkey::new_unique(),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn event_ix_tag_matches_wire_bytes() {
+        assert_eq!(
+            EVENT_IX_TAG,
+            [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d]
+        );
+    }
+
+    #[test]
+    fn decodes_cpi_instruction_data() {
+        let event = Event::from(state_changed());
+        let mut data = EVENT_IX_TAG.to_vec();
+        data.extend(event.to_bytes());
+        assert_eq!(Event::from_cpi_instruction_data(&data).unwrap(), event);
+        assert_eq!(event.name(), "EscrowStateChanged");
+    }
+
+    #[test]
+    fn rejects_non_event_instruction_data() {
+        let data = Event::from(state_changed()).to_bytes();
+        assert!(matches!(
+            Event::from_cpi_instruction_data(&data),
+            Err(ClientError::NotAnEvent)
+        ));
+    }
+
+    #[test]
+    fn decodes_program_data_logs() {
+        let event = Event::from(EscrowBalanceChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: Pubkey::new_unique(),
+            escrow_id: 5,
+            trade_id: 6,
+            new_balance: 1_010_000,
+            reason: BalanceChangeReason::Funded,
+            timestamp: 1,
+        });
+        let line = format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(event.to_bytes())
+        );
+        assert_eq!(Event::from_log(&line).unwrap().unwrap(), event);
+        assert!(Event::from_log("Program log: Instruction: ReleaseEscrow").is_none());
+    }
+
+    #[test]
+    fn events_from_older_schema_versions_decode_with_defaults() {
+        let created = EscrowCreated {
+            schema_version: 1,
+            object_id: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            arbitrator: Pubkey::new_unique(),
+            amount: 1_000_000,
+            fee: 10_000,
+            deposit_deadline: 900,
+            fiat_deadline: 0,
+            sequential: false,
+            sequential_escrow_address: None,
+            timestamp: 0,
+            rounding: Rounding::Floor,
+            min_fee_applied: false,
+            fiat_terms: None,
+            requires_acceptance: false,
+            min_fee: 0,
+            requires_buyer_deposit: false,
+        };
+        let current = Event::from(created.clone()).to_bytes();
+        // v1 ends after timestamp: no rounding, minimum flag, fiat terms, acceptance flag,
+        // fee floor or deposit flag
+        let v1 = &current[..current.len() - (1 + 1 + 1 + 1 + 8 + 1)];
+        assert_eq!(
+            Event::try_from_bytes(v1).unwrap(),
+            Event::EscrowCreated(created.clone())
+        );
+        // v3 ends after fiat terms
+        let v3 = &current[..current.len() - (1 + 8 + 1)];
+        assert_eq!(
+            Event::try_from_bytes(v3).unwrap(),
+            Event::EscrowCreated(created)
+        );
+    }
+
+    #[test]
+    fn unknown_discriminator_is_reported() {
+        let data = [0u8; 16];
+        assert!(matches!(
+            Event::try_from_bytes(&data),
+            Err(ClientError::UnknownEvent(disc)) if disc == [0; 8]
+        ));
+    }
+
+    #[test]
+    fn discriminators_are_unique() {
+        let discriminators = [
+            EscrowCreated::discriminator(),
+            EscrowAccepted::discriminator(),
+            FiatRateChecked::discriminator(),
+            FundsDeposited::discriminator(),
+            FiatMarkedPaid::discriminator(),
+            EscrowReleased::discriminator(),
+            EscrowCancelled::discriminator(),
+            DisputeOpened::discriminator(),
+            DisputeResponseSubmitted::discriminator(),
+            DisputeResolved::discriminator(),
+            DisputeDefaultJudgment::discriminator(),
+            EscrowStateChanged::discriminator(),
+            EscrowBalanceChanged::discriminator(),
+            SequentialAddressUpdated::discriminator(),
+            EscrowMigrated::discriminator(),
+            BondAccountReclaimed::discriminator(),
+            BalanceDiscrepancy::discriminator(),
+            SurplusSwept::discriminator(),
+            PaymentDetailsPosted::discriminator(),
+            ChatRootCommitted::discriminator(),
+            ChatMessageProven::discriminator(),
+            BuyerDepositPosted::discriminator(),
+            BuyerDepositSettled::discriminator(),
+            CancellationPenaltyPaid::discriminator(),
+        ];
+        for (i, a) in discriminators.iter().enumerate() {
+            for b in &discriminators[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
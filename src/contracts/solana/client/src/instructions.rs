@@ -0,0 +1,1399 @@
+//! Instruction builders, one per program handler.
+//!
+//! Account lists follow the program's `#[derive(Accounts)]` structs field by field, with
+//! the two `#[event_cpi]` accounts (event authority and program) appended at the end,
+//! except for the read-only views, which emit no events.
+//! Absent optional accounts are passed as the program id, which is how Anchor encodes
+//! `None`. Builders for existing escrows take the decoded `Escrow` so PDAs and rent
+//! refund destinations come from the same data the program checks them against.
+
+use borsh::BorshSerialize;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar;
+use solana_sdk_ids::system_program;
+
+use crate::pda;
+use crate::state::{Escrow, EscrowState, FeePolicy, FiatTerms};
+use crate::{discriminator, ID, TOKEN_PROGRAM_ID};
+
+fn build<A: BorshSerialize>(name: &str, args: &A, mut accounts: Vec<AccountMeta>) -> Instruction {
+    accounts.push(AccountMeta::new_readonly(
+        pda::find_event_authority_address().0,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(ID, false));
+    view(name, args, accounts)
+}
+
+// Instructions without `#[event_cpi]`: the views and the profile, price feed and config
+// registries
+fn view<A: BorshSerialize>(name: &str, args: &A, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = discriminator("global", name).to_vec();
+    data.extend(borsh::to_vec(args).expect("borsh encoding to Vec"));
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
+
+fn optional(account: Option<Pubkey>) -> AccountMeta {
+    AccountMeta::new_readonly(account.unwrap_or(ID), false)
+}
+
+fn optional_mut(account: Option<Pubkey>) -> AccountMeta {
+    match account {
+        Some(key) => AccountMeta::new(key, false),
+        None => AccountMeta::new_readonly(ID, false),
+    }
+}
+
+// Bond accounts the program will insist on closing: any whose rent payer was recorded
+fn bond_accounts(escrow: &Escrow, escrow_key: &Pubkey) -> [AccountMeta; 4] {
+    let buyer = escrow.buyer_bond_rent_payer.is_some();
+    let seller = escrow.seller_bond_rent_payer.is_some();
+    [
+        optional_mut(buyer.then(|| pda::find_buyer_bond_address(escrow_key).0)),
+        optional_mut(seller.then(|| pda::find_seller_bond_address(escrow_key).0)),
+        optional_mut(buyer.then(|| escrow.buyer_bond_rent_payer())),
+        optional_mut(seller.then(|| escrow.seller_bond_rent_payer())),
+    ]
+}
+
+// The buyer deposit account and its rent payer, which settlement needs once one was posted
+fn deposit_accounts(escrow: &Escrow, escrow_key: &Pubkey) -> [AccountMeta; 2] {
+    let posted = escrow.buyer_deposit > 0;
+    [
+        optional_mut(posted.then(|| pda::find_buyer_deposit_address(escrow_key).0)),
+        optional_mut(posted.then(|| escrow.buyer_deposit_rent_payer())),
+    ]
+}
+
+#[derive(BorshSerialize)]
+struct NoArgs {}
+
+#[derive(Clone, Debug)]
+pub struct CreateEscrow {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub fiat_terms: FiatTerms,
+    /// Funding waits until the buyer signs `AcceptEscrow`.
+    pub requires_acceptance: bool,
+    /// `MarkFiatPaid` waits until the buyer signs `PostBuyerDeposit`.
+    pub requires_buyer_deposit: bool,
+    /// Pyth price update to check `fiat_terms.rate` against. Required exactly when the
+    /// currency's price feed is registered.
+    pub price_update: Option<Pubkey>,
+}
+
+impl CreateEscrow {
+    pub fn escrow_address(&self) -> Pubkey {
+        pda::find_escrow_address(&self.seller, self.escrow_id, self.trade_id).0
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            escrow_id: u64,
+            trade_id: u64,
+            amount: u64,
+            sequential: bool,
+            sequential_escrow_address: Option<Pubkey>,
+            fiat_terms: FiatTerms,
+            requires_acceptance: bool,
+            requires_buyer_deposit: bool,
+        }
+        build(
+            "create_escrow",
+            &Args {
+                escrow_id: self.escrow_id,
+                trade_id: self.trade_id,
+                amount: self.amount,
+                sequential: self.sequential,
+                sequential_escrow_address: self.sequential_escrow_address,
+                fiat_terms: self.fiat_terms.clone(),
+                requires_acceptance: self.requires_acceptance,
+                requires_buyer_deposit: self.requires_buyer_deposit,
+            },
+            vec![
+                AccountMeta::new(self.seller, true),
+                AccountMeta::new_readonly(self.buyer, false),
+                AccountMeta::new(self.escrow_address(), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(
+                    pda::find_price_feed_address(&self.fiat_terms.currency).0,
+                    false,
+                ),
+                optional(self.price_update),
+                AccountMeta::new_readonly(pda::find_config_address().0, false),
+            ],
+        )
+    }
+}
+
+/// `CreateEscrow` and `FundEscrow` in one instruction, with the same events. The escrow
+/// never requires acceptance.
+#[derive(Clone, Debug)]
+pub struct CreateAndFundEscrow {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub fiat_terms: FiatTerms,
+    /// `MarkFiatPaid` waits until the buyer signs `PostBuyerDeposit`.
+    pub requires_buyer_deposit: bool,
+    pub price_update: Option<Pubkey>,
+    pub seller_token_account: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl CreateAndFundEscrow {
+    pub fn escrow_address(&self) -> Pubkey {
+        pda::find_escrow_address(&self.seller, self.escrow_id, self.trade_id).0
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            escrow_id: u64,
+            trade_id: u64,
+            amount: u64,
+            sequential: bool,
+            sequential_escrow_address: Option<Pubkey>,
+            fiat_terms: FiatTerms,
+            requires_buyer_deposit: bool,
+        }
+        let escrow = self.escrow_address();
+        build(
+            "create_and_fund_escrow",
+            &Args {
+                escrow_id: self.escrow_id,
+                trade_id: self.trade_id,
+                amount: self.amount,
+                sequential: self.sequential,
+                sequential_escrow_address: self.sequential_escrow_address,
+                fiat_terms: self.fiat_terms.clone(),
+                requires_buyer_deposit: self.requires_buyer_deposit,
+            },
+            vec![
+                AccountMeta::new(self.seller, true),
+                AccountMeta::new_readonly(self.buyer, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(self.seller_token_account, false),
+                AccountMeta::new(pda::find_escrow_token_address(&escrow).0, false),
+                AccountMeta::new_readonly(self.token_mint, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+                AccountMeta::new_readonly(
+                    pda::find_price_feed_address(&self.fiat_terms.currency).0,
+                    false,
+                ),
+                optional(self.price_update),
+                AccountMeta::new_readonly(pda::find_config_address().0, false),
+            ],
+        )
+    }
+}
+
+/// Signed by the buyer of an escrow created with `requires_acceptance`.
+#[derive(Clone, Debug)]
+pub struct AcceptEscrow<'a> {
+    pub escrow: &'a Escrow,
+}
+
+impl AcceptEscrow<'_> {
+    pub fn instruction(&self) -> Instruction {
+        build(
+            "accept_escrow",
+            &NoArgs {},
+            vec![
+                AccountMeta::new_readonly(self.escrow.buyer, true),
+                AccountMeta::new(self.escrow.address(), false),
+            ],
+        )
+    }
+}
+
+/// Funds an escrow created by `CreateEscrow`; it is paid for by the seller, who also
+/// becomes the recorded vault rent payer.
+#[derive(Clone, Debug)]
+pub struct FundEscrow {
+    pub seller: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller_token_account: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl FundEscrow {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            escrow_id: u64,
+            trade_id: u64,
+        }
+        let escrow = pda::find_escrow_address(&self.seller, self.escrow_id, self.trade_id).0;
+        build(
+            "fund_escrow",
+            &Args {
+                escrow_id: self.escrow_id,
+                trade_id: self.trade_id,
+            },
+            vec![
+                AccountMeta::new(self.seller, true),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(self.seller_token_account, false),
+                AccountMeta::new(pda::find_escrow_token_address(&escrow).0, false),
+                AccountMeta::new_readonly(self.token_mint, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+            ],
+        )
+    }
+}
+
+/// Signed by the buyer of a funded escrow, who pays the deposit and its account's rent.
+#[derive(Clone, Debug)]
+pub struct PostBuyerDeposit<'a> {
+    pub escrow: &'a Escrow,
+    pub buyer_token_account: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl PostBuyerDeposit<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "post_buyer_deposit",
+            &NoArgs {},
+            vec![
+                AccountMeta::new(self.escrow.buyer, true),
+                AccountMeta::new(escrow_key, false),
+                AccountMeta::new_readonly(pda::find_escrow_token_address(&escrow_key).0, false),
+                AccountMeta::new(self.buyer_token_account, false),
+                AccountMeta::new(pda::find_buyer_deposit_address(&escrow_key).0, false),
+                AccountMeta::new_readonly(self.token_mint, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MarkFiatPaid<'a> {
+    pub escrow: &'a Escrow,
+}
+
+impl MarkFiatPaid<'_> {
+    pub fn instruction(&self) -> Instruction {
+        build(
+            "mark_fiat_paid",
+            &NoArgs {},
+            vec![
+                AccountMeta::new(self.escrow.buyer, true),
+                AccountMeta::new(self.escrow.address(), false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateSequentialAddress<'a> {
+    pub escrow: &'a Escrow,
+    pub new_address: Pubkey,
+}
+
+impl UpdateSequentialAddress<'_> {
+    pub fn instruction(&self) -> Instruction {
+        build(
+            "update_sequential_address",
+            &self.new_address,
+            vec![
+                AccountMeta::new(self.escrow.buyer, true),
+                AccountMeta::new(self.escrow.address(), false),
+            ],
+        )
+    }
+}
+
+/// Both parties must have registered an encryption key, see `RegisterEncryptionKey`.
+#[derive(Clone, Debug)]
+pub struct PostPaymentDetails<'a> {
+    pub escrow: &'a Escrow,
+    /// The buyer or the seller; the other party is the recipient.
+    pub sender: Pubkey,
+    /// Hash of the payment details encrypted to the recipient's key.
+    pub ciphertext_hash: [u8; 32],
+}
+
+impl PostPaymentDetails<'_> {
+    pub fn recipient(&self) -> Pubkey {
+        if self.sender == self.escrow.seller {
+            self.escrow.buyer
+        } else {
+            self.escrow.seller
+        }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        build(
+            "post_payment_details",
+            &self.ciphertext_hash,
+            vec![
+                AccountMeta::new_readonly(self.sender, true),
+                AccountMeta::new_readonly(self.escrow.address(), false),
+                AccountMeta::new_readonly(pda::find_profile_address(&self.sender).0, false),
+                AccountMeta::new_readonly(pda::find_profile_address(&self.recipient()).0, false),
+            ],
+        )
+    }
+}
+
+/// Checkpoints `party`'s transcript; see `chat::root`.
+#[derive(Clone, Debug)]
+pub struct CommitChatRoot<'a> {
+    pub escrow: &'a Escrow,
+    /// The buyer or the seller.
+    pub party: Pubkey,
+    pub root: [u8; 32],
+    pub message_count: u64,
+}
+
+impl CommitChatRoot<'_> {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            root: [u8; 32],
+            message_count: u64,
+        }
+        build(
+            "commit_chat_root",
+            &Args {
+                root: self.root,
+                message_count: self.message_count,
+            },
+            vec![
+                AccountMeta::new_readonly(self.party, true),
+                AccountMeta::new(self.escrow.address(), false),
+            ],
+        )
+    }
+}
+
+/// Proves a message against `party`'s last checkpoint while the escrow is disputed; see
+/// `chat::proof`.
+#[derive(Clone, Debug)]
+pub struct ProveChatMessage<'a> {
+    pub escrow: &'a Escrow,
+    /// A party or the arbitrator.
+    pub prover: Pubkey,
+    /// Whose checkpoint the proof is against.
+    pub party: Pubkey,
+    pub leaf_index: u64,
+    pub message_hash: [u8; 32],
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl ProveChatMessage<'_> {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args<'a> {
+            party: Pubkey,
+            leaf_index: u64,
+            message_hash: [u8; 32],
+            proof: &'a Vec<[u8; 32]>,
+        }
+        build(
+            "prove_chat_message",
+            &Args {
+                party: self.party,
+                leaf_index: self.leaf_index,
+                message_hash: self.message_hash,
+                proof: &self.proof,
+            },
+            vec![
+                AccountMeta::new_readonly(self.prover, true),
+                AccountMeta::new_readonly(self.escrow.address(), false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReleaseEscrow<'a> {
+    pub escrow: &'a Escrow,
+    /// Seller or arbitrator.
+    pub authority: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub arbitrator_token_account: Pubkey,
+    /// Required for sequential escrows.
+    pub sequential_escrow_token_account: Option<Pubkey>,
+}
+
+impl ReleaseEscrow<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        let mut accounts = vec![
+            AccountMeta::new(self.authority, true),
+            AccountMeta::new(self.escrow.escrow_rent_payer(), false),
+            AccountMeta::new(self.escrow.escrow_token_rent_payer(), false),
+            AccountMeta::new(escrow_key, false),
+            AccountMeta::new(pda::find_escrow_token_address(&escrow_key).0, false),
+            AccountMeta::new(self.buyer_token_account, false),
+            AccountMeta::new(self.arbitrator_token_account, false),
+            optional_mut(self.sequential_escrow_token_account),
+        ];
+        accounts.extend(bond_accounts(self.escrow, &escrow_key));
+        accounts.push(AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false));
+        accounts.extend(deposit_accounts(self.escrow, &escrow_key));
+        build("release_escrow", &NoArgs {}, accounts)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CancelEscrow<'a> {
+    pub escrow: &'a Escrow,
+    /// Seller or arbitrator.
+    pub authority: Pubkey,
+    /// Receives principal and fee back; required when the escrow is funded.
+    pub seller_token_account: Option<Pubkey>,
+    /// Receives the buyer deposit back and the cancellation penalty; required when a deposit
+    /// was posted or the seller cancels a funded escrow the buyer accepted.
+    pub buyer_token_account: Option<Pubkey>,
+    /// Receives stray tokens swept out of unused bond accounts; required when there are any.
+    pub arbitrator_token_account: Option<Pubkey>,
+}
+
+impl CancelEscrow<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        let funded = self.escrow.state == EscrowState::Funded;
+        let mut accounts = vec![
+            AccountMeta::new(self.escrow.escrow_rent_payer(), false),
+            AccountMeta::new(self.authority, true),
+            AccountMeta::new(self.escrow.escrow_token_rent_payer(), false),
+            AccountMeta::new(escrow_key, false),
+            optional_mut(funded.then(|| pda::find_escrow_token_address(&escrow_key).0)),
+            optional_mut(self.seller_token_account),
+        ];
+        accounts.extend(bond_accounts(self.escrow, &escrow_key));
+        accounts.push(AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false));
+        accounts.push(optional_mut(self.buyer_token_account));
+        accounts.extend(deposit_accounts(self.escrow, &escrow_key));
+        accounts.push(optional_mut(self.arbitrator_token_account));
+        build("cancel_escrow", &NoArgs {}, accounts)
+    }
+}
+
+#[derive(BorshSerialize)]
+struct EscrowIds {
+    escrow_id: u64,
+    trade_id: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct InitializeBuyerBondAccount<'a> {
+    pub escrow: &'a Escrow,
+    /// Pays rent for the bond account and is refunded when it is closed.
+    pub payer: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl InitializeBuyerBondAccount<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "initialize_buyer_bond_account",
+            &EscrowIds {
+                escrow_id: self.escrow.escrow_id,
+                trade_id: self.escrow.trade_id,
+            },
+            vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(escrow_key, false),
+                AccountMeta::new(pda::find_buyer_bond_address(&escrow_key).0, false),
+                AccountMeta::new_readonly(self.token_mint, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InitializeSellerBondAccount<'a> {
+    pub escrow: &'a Escrow,
+    /// Pays rent for the bond account and is refunded when it is closed.
+    pub payer: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl InitializeSellerBondAccount<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "initialize_seller_bond_account",
+            &EscrowIds {
+                escrow_id: self.escrow.escrow_id,
+                trade_id: self.escrow.trade_id,
+            },
+            vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(escrow_key, false),
+                AccountMeta::new(pda::find_seller_bond_address(&escrow_key).0, false),
+                AccountMeta::new_readonly(self.token_mint, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OpenDisputeWithBond<'a> {
+    pub escrow: &'a Escrow,
+    /// Buyer or seller.
+    pub disputing_party: Pubkey,
+    pub disputing_party_token_account: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+impl OpenDisputeWithBond<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "open_dispute_with_bond",
+            &self.evidence_hash,
+            vec![
+                AccountMeta::new(self.disputing_party, true),
+                AccountMeta::new(escrow_key, false),
+                AccountMeta::new(self.disputing_party_token_account, false),
+                AccountMeta::new(pda::find_buyer_bond_address(&escrow_key).0, false),
+                AccountMeta::new(pda::find_seller_bond_address(&escrow_key).0, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RespondToDisputeWithBond<'a> {
+    pub escrow: &'a Escrow,
+    /// The party that did not open the dispute.
+    pub responding_party: Pubkey,
+    pub responding_party_token_account: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+impl RespondToDisputeWithBond<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "respond_to_dispute_with_bond",
+            &self.evidence_hash,
+            vec![
+                AccountMeta::new(self.responding_party, true),
+                AccountMeta::new(escrow_key, false),
+                AccountMeta::new(self.responding_party_token_account, false),
+                AccountMeta::new(pda::find_buyer_bond_address(&escrow_key).0, false),
+                AccountMeta::new(pda::find_seller_bond_address(&escrow_key).0, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+}
+
+/// Permissionless once the dispute response deadline has passed.
+#[derive(Clone, Debug)]
+pub struct DefaultJudgment<'a> {
+    pub escrow: &'a Escrow,
+    pub caller: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub seller_token_account: Pubkey,
+}
+
+impl DefaultJudgment<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        let mut accounts = vec![
+            AccountMeta::new(self.escrow.escrow_rent_payer(), false),
+            AccountMeta::new(self.caller, true),
+            AccountMeta::new(self.escrow.escrow_token_rent_payer(), false),
+            AccountMeta::new(self.escrow.buyer_bond_rent_payer(), false),
+            AccountMeta::new(self.escrow.seller_bond_rent_payer(), false),
+            AccountMeta::new(escrow_key, false),
+            AccountMeta::new(pda::find_escrow_token_address(&escrow_key).0, false),
+            AccountMeta::new(self.buyer_token_account, false),
+            AccountMeta::new(self.seller_token_account, false),
+            AccountMeta::new(pda::find_buyer_bond_address(&escrow_key).0, false),
+            AccountMeta::new(pda::find_seller_bond_address(&escrow_key).0, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        accounts.extend(deposit_accounts(self.escrow, &escrow_key));
+        build("default_judgment", &NoArgs {}, accounts)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolveDisputeWithExplanation<'a> {
+    pub escrow: &'a Escrow,
+    pub arbitrator: Pubkey,
+    /// true releases to the buyer, false returns to the seller.
+    pub decision: bool,
+    pub resolution_hash: [u8; 32],
+    pub buyer_token_account: Pubkey,
+    pub seller_token_account: Pubkey,
+    pub arbitrator_token_account: Pubkey,
+}
+
+impl ResolveDisputeWithExplanation<'_> {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            decision: bool,
+            resolution_hash: [u8; 32],
+        }
+        let escrow_key = self.escrow.address();
+        let mut accounts = vec![
+            AccountMeta::new(self.arbitrator, true),
+            AccountMeta::new(self.escrow.escrow_rent_payer(), false),
+            AccountMeta::new(self.escrow.escrow_token_rent_payer(), false),
+            AccountMeta::new(self.escrow.buyer_bond_rent_payer(), false),
+            AccountMeta::new(self.escrow.seller_bond_rent_payer(), false),
+            AccountMeta::new(escrow_key, false),
+            AccountMeta::new(pda::find_escrow_token_address(&escrow_key).0, false),
+            AccountMeta::new(self.buyer_token_account, false),
+            AccountMeta::new(self.seller_token_account, false),
+            AccountMeta::new(self.arbitrator_token_account, false),
+            AccountMeta::new(pda::find_buyer_bond_address(&escrow_key).0, false),
+            AccountMeta::new(pda::find_seller_bond_address(&escrow_key).0, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        accounts.extend(deposit_accounts(self.escrow, &escrow_key));
+        build(
+            "resolve_dispute_with_explanation",
+            &Args {
+                decision: self.decision,
+                resolution_hash: self.resolution_hash,
+            },
+            accounts,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AutoCancel<'a> {
+    pub escrow: &'a Escrow,
+    pub arbitrator: Pubkey,
+    /// Receives principal and fee back; required when the escrow is funded.
+    pub seller_token_account: Option<Pubkey>,
+    /// Receives stray tokens swept out of unused bond accounts; required when there are any.
+    pub arbitrator_token_account: Option<Pubkey>,
+}
+
+impl AutoCancel<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        let funded = self.escrow.state == EscrowState::Funded;
+        let mut accounts = vec![
+            AccountMeta::new(self.arbitrator, true),
+            AccountMeta::new(self.escrow.escrow_rent_payer(), false),
+            AccountMeta::new(self.escrow.escrow_token_rent_payer(), false),
+            AccountMeta::new(escrow_key, false),
+            optional_mut(funded.then(|| pda::find_escrow_token_address(&escrow_key).0)),
+            optional_mut(self.seller_token_account),
+        ];
+        accounts.extend(bond_accounts(self.escrow, &escrow_key));
+        accounts.push(AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false));
+        accounts.extend(deposit_accounts(self.escrow, &escrow_key));
+        accounts.push(optional_mut(self.arbitrator_token_account));
+        build("auto_cancel", &NoArgs {}, accounts)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MigrateEscrow {
+    pub escrow: Pubkey,
+    /// Pays any extra rent for the larger layout.
+    pub payer: Pubkey,
+}
+
+impl MigrateEscrow {
+    pub fn instruction(&self) -> Instruction {
+        build(
+            "migrate_escrow",
+            &NoArgs {},
+            vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(self.escrow, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+}
+
+/// Only for terminal escrows that are still open; the bond accounts of a closed escrow
+/// cannot be reclaimed because their rent payers were recorded on it.
+#[derive(Clone, Debug)]
+pub struct ReclaimBondAccounts<'a> {
+    pub caller: Pubkey,
+    pub escrow: &'a Escrow,
+    pub buyer_bond: bool,
+    pub seller_bond: bool,
+}
+
+impl ReclaimBondAccounts<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "reclaim_bond_accounts",
+            &NoArgs {},
+            vec![
+                AccountMeta::new_readonly(self.caller, true),
+                AccountMeta::new_readonly(escrow_key, false),
+                optional_mut(
+                    self.buyer_bond
+                        .then(|| pda::find_buyer_bond_address(&escrow_key).0),
+                ),
+                optional_mut(
+                    self.seller_bond
+                        .then(|| pda::find_seller_bond_address(&escrow_key).0),
+                ),
+                optional_mut(
+                    self.buyer_bond
+                        .then(|| self.escrow.buyer_bond_rent_payer()),
+                ),
+                optional_mut(
+                    self.seller_bond
+                        .then(|| self.escrow.seller_bond_rent_payer()),
+                ),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Reconcile<'a> {
+    pub escrow: &'a Escrow,
+    pub caller: Pubkey,
+}
+
+impl Reconcile<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "reconcile",
+            &NoArgs {},
+            vec![
+                AccountMeta::new_readonly(self.caller, true),
+                AccountMeta::new_readonly(escrow_key, false),
+                AccountMeta::new_readonly(pda::find_escrow_token_address(&escrow_key).0, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SweepSurplus<'a> {
+    pub escrow: &'a Escrow,
+    pub arbitrator: Pubkey,
+    /// Receives the surplus; must belong to the escrow's arbitrator.
+    pub arbitrator_token_account: Pubkey,
+}
+
+impl SweepSurplus<'_> {
+    pub fn instruction(&self) -> Instruction {
+        let escrow_key = self.escrow.address();
+        build(
+            "sweep_surplus",
+            &NoArgs {},
+            vec![
+                AccountMeta::new_readonly(self.arbitrator, true),
+                AccountMeta::new_readonly(escrow_key, false),
+                AccountMeta::new(pda::find_escrow_token_address(&escrow_key).0, false),
+                AccountMeta::new(self.arbitrator_token_account, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+}
+
+/// Creates the owner's profile; later changes go through `UpdateEncryptionKey`.
+#[derive(Clone, Debug)]
+pub struct RegisterEncryptionKey {
+    pub owner: Pubkey,
+    /// x25519 public key.
+    pub encryption_key: [u8; 32],
+}
+
+impl RegisterEncryptionKey {
+    pub fn instruction(&self) -> Instruction {
+        view(
+            "register_encryption_key",
+            &self.encryption_key,
+            vec![
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new(pda::find_profile_address(&self.owner).0, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateEncryptionKey {
+    pub owner: Pubkey,
+    pub encryption_key: [u8; 32],
+}
+
+impl UpdateEncryptionKey {
+    pub fn instruction(&self) -> Instruction {
+        view(
+            "update_encryption_key",
+            &self.encryption_key,
+            vec![
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(pda::find_profile_address(&self.owner).0, false),
+            ],
+        )
+    }
+}
+
+/// Oracle limits of a price feed, see `state::PriceFeed`.
+#[derive(BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceFeedSettings {
+    pub feed_id: [u8; 32],
+    pub max_deviation_bps: u16,
+    pub max_age_seconds: u32,
+    pub max_confidence_bps: u16,
+}
+
+/// Arbitrator only; one feed per currency.
+#[derive(Clone, Debug)]
+pub struct RegisterPriceFeed {
+    pub arbitrator: Pubkey,
+    pub currency: [u8; 3],
+    pub settings: PriceFeedSettings,
+}
+
+impl RegisterPriceFeed {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            currency: [u8; 3],
+            settings: PriceFeedSettings,
+        }
+        view(
+            "register_price_feed",
+            &Args {
+                currency: self.currency,
+                settings: self.settings,
+            },
+            vec![
+                AccountMeta::new(self.arbitrator, true),
+                AccountMeta::new(pda::find_price_feed_address(&self.currency).0, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+}
+
+/// Arbitrator only; replaces the feed id and all limits.
+#[derive(Clone, Debug)]
+pub struct UpdatePriceFeed {
+    pub arbitrator: Pubkey,
+    pub currency: [u8; 3],
+    pub settings: PriceFeedSettings,
+}
+
+impl UpdatePriceFeed {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            settings: PriceFeedSettings,
+        }
+        view(
+            "update_price_feed",
+            &Args {
+                settings: self.settings,
+            },
+            vec![
+                AccountMeta::new_readonly(self.arbitrator, true),
+                AccountMeta::new(pda::find_price_feed_address(&self.currency).0, false),
+            ],
+        )
+    }
+}
+
+/// Arbitrator only; sets the fee policy escrows created from then on are charged by.
+#[derive(Clone, Debug)]
+pub struct InitializeConfig {
+    pub arbitrator: Pubkey,
+    pub fee_policy: FeePolicy,
+}
+
+impl InitializeConfig {
+    pub fn instruction(&self) -> Instruction {
+        view(
+            "initialize_config",
+            &self.fee_policy,
+            vec![
+                AccountMeta::new(self.arbitrator, true),
+                AccountMeta::new(pda::find_config_address().0, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+}
+
+/// Arbitrator only; existing escrows keep the policy they were created under.
+#[derive(Clone, Debug)]
+pub struct UpdateConfig {
+    pub arbitrator: Pubkey,
+    pub fee_policy: FeePolicy,
+}
+
+impl UpdateConfig {
+    pub fn instruction(&self) -> Instruction {
+        view(
+            "update_config",
+            &self.fee_policy,
+            vec![
+                AccountMeta::new_readonly(self.arbitrator, true),
+                AccountMeta::new(pda::find_config_address().0, false),
+            ],
+        )
+    }
+}
+
+/// Simulate and decode the return data with `views::EscrowQuote`.
+#[derive(Clone, Debug)]
+pub struct QuoteEscrow {
+    pub amount: u64,
+}
+
+impl QuoteEscrow {
+    pub fn instruction(&self) -> Instruction {
+        #[derive(BorshSerialize)]
+        struct Args {
+            amount: u64,
+        }
+        view(
+            "quote_escrow",
+            &Args {
+                amount: self.amount,
+            },
+            vec![AccountMeta::new_readonly(
+                pda::find_config_address().0,
+                false,
+            )],
+        )
+    }
+}
+
+/// Simulate and decode the return data with `views::EscrowStatusReport`.
+#[derive(Clone, Debug)]
+pub struct EscrowStatus {
+    pub escrow: Pubkey,
+}
+
+impl EscrowStatus {
+    pub fn instruction(&self) -> Instruction {
+        view(
+            "escrow_status",
+            &NoArgs {},
+            vec![AccountMeta::new_readonly(self.escrow, false)],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::tests::sample_escrow;
+
+    fn keys(ix: &Instruction) -> Vec<Pubkey> {
+        ix.accounts.iter().map(|meta| meta.pubkey).collect()
+    }
+
+    fn assert_event_cpi_tail(ix: &Instruction) {
+        let n = ix.accounts.len();
+        assert_eq!(
+            ix.accounts[n - 2].pubkey,
+            pda::find_event_authority_address().0
+        );
+        assert_eq!(ix.accounts[n - 1].pubkey, ID);
+        assert!(ix.accounts[n - 2..]
+            .iter()
+            .all(|m| !m.is_signer && !m.is_writable));
+    }
+
+    #[test]
+    fn create_escrow_encodes_args_after_discriminator() {
+        let builder = CreateEscrow {
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            amount: 3,
+            sequential: true,
+            sequential_escrow_address: None,
+            fiat_terms: FiatTerms {
+                currency: *b"USD",
+                fiat_amount: 4,
+                rate: 5,
+                payment_method_hash: [6; 32],
+            },
+            requires_acceptance: true,
+            requires_buyer_deposit: false,
+            price_update: None,
+        };
+        let ix = builder.instruction();
+        assert_eq!(ix.program_id, ID);
+        assert_eq!(&ix.data[..8], &discriminator("global", "create_escrow"));
+        let mut expected = Vec::new();
+        expected.extend(1u64.to_le_bytes());
+        expected.extend(2u64.to_le_bytes());
+        expected.extend(3u64.to_le_bytes());
+        expected.extend([1u8, 0u8]);
+        expected.extend(b"USD");
+        expected.extend(4u64.to_le_bytes());
+        expected.extend(5u64.to_le_bytes());
+        expected.extend([6u8; 32]);
+        expected.extend([1u8, 0u8]);
+        assert_eq!(&ix.data[8..], expected.as_slice());
+        assert_eq!(ix.accounts.len(), 9);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[2].pubkey, builder.escrow_address());
+        // the feed address goes in even without a price update
+        assert_eq!(
+            keys(&ix)[4..7],
+            [
+                pda::find_price_feed_address(b"USD").0,
+                ID,
+                pda::find_config_address().0
+            ]
+        );
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn create_and_fund_escrow_takes_create_then_fund_accounts() {
+        let builder = CreateAndFundEscrow {
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            amount: 3,
+            sequential: false,
+            sequential_escrow_address: None,
+            fiat_terms: FiatTerms {
+                currency: *b"USD",
+                fiat_amount: 4,
+                rate: 5,
+                payment_method_hash: [6; 32],
+            },
+            requires_buyer_deposit: true,
+            price_update: None,
+            seller_token_account: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+        };
+        let ix = builder.instruction();
+        assert_eq!(
+            &ix.data[..8],
+            &discriminator("global", "create_and_fund_escrow")
+        );
+        // same arguments as create_escrow, without the acceptance flag
+        assert_eq!(ix.data.len(), 8 + 8 + 8 + 8 + 1 + 1 + 3 + 8 + 8 + 32 + 1);
+        assert_eq!(ix.data.last(), Some(&1));
+        let escrow = builder.escrow_address();
+        assert_eq!(ix.accounts.len(), 14);
+        assert_eq!(ix.accounts[2].pubkey, escrow);
+        assert_eq!(
+            ix.accounts[4].pubkey,
+            pda::find_escrow_token_address(&escrow).0
+        );
+        assert_eq!(
+            keys(&ix)[9..12],
+            [
+                pda::find_price_feed_address(b"USD").0,
+                ID,
+                pda::find_config_address().0
+            ]
+        );
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn create_escrow_with_price_update_passes_the_currency_feed() {
+        let price_update = Pubkey::new_unique();
+        let ix = CreateEscrow {
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            amount: 3,
+            sequential: false,
+            sequential_escrow_address: None,
+            fiat_terms: FiatTerms {
+                currency: *b"EUR",
+                fiat_amount: 4,
+                rate: 5,
+                payment_method_hash: [6; 32],
+            },
+            requires_acceptance: false,
+            requires_buyer_deposit: false,
+            price_update: Some(price_update),
+        }
+        .instruction();
+        let k = keys(&ix);
+        assert_eq!(k[4], pda::find_price_feed_address(b"EUR").0);
+        assert_eq!(k[5], price_update);
+        assert!(ix.accounts[4..6].iter().all(|m| !m.is_writable));
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn payment_details_go_to_the_other_partys_profile() {
+        let escrow = sample_escrow();
+        let ix = PostPaymentDetails {
+            escrow: &escrow,
+            sender: escrow.buyer,
+            ciphertext_hash: [9; 32],
+        }
+        .instruction();
+        assert_eq!(&ix.data[8..], &[9; 32]);
+        let k = keys(&ix);
+        assert_eq!(k[0], escrow.buyer);
+        assert_eq!(k[2], pda::find_profile_address(&escrow.buyer).0);
+        assert_eq!(k[3], pda::find_profile_address(&escrow.seller).0);
+        assert!(ix.accounts[..4].iter().all(|m| !m.is_writable));
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn release_leaves_unrecorded_bonds_out() {
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Funded;
+        let ix = ReleaseEscrow {
+            escrow: &escrow,
+            authority: escrow.seller,
+            buyer_token_account: Pubkey::new_unique(),
+            arbitrator_token_account: Pubkey::new_unique(),
+            sequential_escrow_token_account: None,
+        }
+        .instruction();
+        assert_eq!(ix.accounts.len(), 17);
+        // sequential account, all four bond slots and both deposit slots are placeholders
+        for meta in ix.accounts[7..12].iter().chain(&ix.accounts[13..15]) {
+            assert_eq!(meta.pubkey, ID);
+            assert!(!meta.is_writable);
+        }
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn cancel_includes_recorded_bond_and_its_payer() {
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Funded;
+        let bond_payer = Pubkey::new_unique();
+        escrow.buyer_bond_rent_payer = Some(bond_payer);
+        let seller_token_account = Pubkey::new_unique();
+        let ix = CancelEscrow {
+            escrow: &escrow,
+            authority: escrow.seller,
+            seller_token_account: Some(seller_token_account),
+            buyer_token_account: None,
+            arbitrator_token_account: None,
+        }
+        .instruction();
+        let escrow_key = escrow.address();
+        let k = keys(&ix);
+        assert_eq!(k[0], escrow.seller);
+        assert_eq!(k[4], pda::find_escrow_token_address(&escrow_key).0);
+        assert_eq!(k[5], seller_token_account);
+        assert_eq!(k[6], pda::find_buyer_bond_address(&escrow_key).0);
+        assert_eq!(k[7], ID);
+        assert_eq!(k[8], bond_payer);
+        assert_eq!(k[9], ID);
+    }
+
+    #[test]
+    fn auto_cancel_of_unfunded_escrow_skips_vault() {
+        let escrow = sample_escrow();
+        let ix = AutoCancel {
+            escrow: &escrow,
+            arbitrator: crate::constants::ARBITRATOR,
+            seller_token_account: None,
+            arbitrator_token_account: None,
+        }
+        .instruction();
+        assert_eq!(ix.accounts[4].pubkey, ID);
+        assert_eq!(ix.accounts[5].pubkey, ID);
+    }
+
+    #[test]
+    fn resolve_refunds_rent_to_recorded_payers() {
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Disputed;
+        let vault_payer = Pubkey::new_unique();
+        escrow.escrow_token_rent_payer = Some(vault_payer);
+        let ix = ResolveDisputeWithExplanation {
+            escrow: &escrow,
+            arbitrator: crate::constants::ARBITRATOR,
+            decision: true,
+            resolution_hash: [7; 32],
+            buyer_token_account: Pubkey::new_unique(),
+            seller_token_account: Pubkey::new_unique(),
+            arbitrator_token_account: Pubkey::new_unique(),
+        }
+        .instruction();
+        let k = keys(&ix);
+        assert_eq!(k[2], vault_payer);
+        // unrecorded bond payers fall back to the seller
+        assert_eq!(k[3], escrow.seller);
+        assert_eq!(ix.data.len(), 8 + 1 + 32);
+        assert_eq!(ix.accounts.len(), 17);
+    }
+
+    #[test]
+    fn cancel_returns_a_posted_buyer_deposit() {
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Funded;
+        escrow.buyer_deposit = 20_000;
+        escrow.buyer_deposit_rent_payer = Some(escrow.buyer);
+        let buyer_token_account = Pubkey::new_unique();
+        let ix = CancelEscrow {
+            escrow: &escrow,
+            authority: escrow.seller,
+            seller_token_account: Some(Pubkey::new_unique()),
+            buyer_token_account: Some(buyer_token_account),
+            arbitrator_token_account: None,
+        }
+        .instruction();
+        let k = keys(&ix);
+        assert_eq!(k[11], buyer_token_account);
+        assert_eq!(k[12], pda::find_buyer_deposit_address(&escrow.address()).0);
+        assert_eq!(k[13], escrow.buyer);
+        assert!(ix.accounts[11..14].iter().all(|m| m.is_writable));
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn legacy_escrow_uses_legacy_address() {
+        let mut escrow = sample_escrow();
+        escrow.seller_scoped_seeds = false;
+        let ix = MarkFiatPaid { escrow: &escrow }.instruction();
+        assert_eq!(
+            ix.accounts[1].pubkey,
+            pda::find_legacy_escrow_address(escrow.escrow_id, escrow.trade_id).0
+        );
+        assert_eq!(ix.accounts[0].pubkey, escrow.buyer);
+    }
+
+    #[test]
+    fn reclaim_refunds_the_recorded_payers() {
+        let mut escrow = sample_escrow();
+        let payer = Pubkey::new_unique();
+        escrow.buyer_bond_rent_payer = Some(payer);
+        let ix = ReclaimBondAccounts {
+            caller: Pubkey::new_unique(),
+            escrow: &escrow,
+            buyer_bond: true,
+            seller_bond: false,
+        }
+        .instruction();
+        let k = keys(&ix);
+        assert_eq!(k[1], escrow.address());
+        assert_eq!(k[2], pda::find_buyer_bond_address(&escrow.address()).0);
+        assert_eq!(k[3], ID);
+        assert_eq!(k[4], payer);
+        assert_eq!(k[5], ID);
+    }
+
+    #[test]
+    fn sweep_writes_only_the_vault_and_the_destination() {
+        let escrow = sample_escrow();
+        let destination = Pubkey::new_unique();
+        let ix = SweepSurplus {
+            escrow: &escrow,
+            arbitrator: crate::constants::ARBITRATOR,
+            arbitrator_token_account: destination,
+        }
+        .instruction();
+        let vault = pda::find_escrow_token_address(&escrow.address()).0;
+        let writable: Vec<Pubkey> = ix
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(writable, [vault, destination]);
+        assert!(ix.accounts[0].is_signer);
+        assert_event_cpi_tail(&ix);
+    }
+
+    #[test]
+    fn config_instructions_carry_the_policy_without_event_accounts() {
+        let arbitrator = crate::constants::ARBITRATOR;
+        let fee_policy = FeePolicy {
+            min_fee: 1,
+            min_dispute_bond: 2,
+            rounding: crate::state::Rounding::Ceil,
+        };
+        let init = InitializeConfig {
+            arbitrator,
+            fee_policy,
+        }
+        .instruction();
+        assert_eq!(
+            keys(&init),
+            [arbitrator, pda::find_config_address().0, system_program::ID]
+        );
+        let mut expected = discriminator("global", "initialize_config").to_vec();
+        expected.extend(1u64.to_le_bytes());
+        expected.extend(2u64.to_le_bytes());
+        expected.push(1);
+        assert_eq!(init.data, expected);
+
+        let update = UpdateConfig {
+            arbitrator,
+            fee_policy,
+        }
+        .instruction();
+        assert_eq!(keys(&update), [arbitrator, pda::find_config_address().0]);
+        assert!(update.accounts[0].is_signer && !update.accounts[0].is_writable);
+        assert_eq!(&update.data[8..], &expected[8..]);
+    }
+
+    #[test]
+    fn views_take_no_signers_and_no_event_accounts() {
+        let quote = QuoteEscrow { amount: 1_000_000 }.instruction();
+        assert_eq!(keys(&quote), [pda::find_config_address().0]);
+        assert!(!quote.accounts[0].is_signer && !quote.accounts[0].is_writable);
+        assert_eq!(&quote.data[..8], &discriminator("global", "quote_escrow"));
+        assert_eq!(&quote.data[8..], &1_000_000u64.to_le_bytes());
+
+        let escrow = Pubkey::new_unique();
+        let status = EscrowStatus { escrow }.instruction();
+        assert_eq!(keys(&status), [escrow]);
+        assert!(!status.accounts[0].is_signer && !status.accounts[0].is_writable);
+        assert_eq!(status.data, discriminator("global", "escrow_status"));
+    }
+}
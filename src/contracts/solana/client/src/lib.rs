@@ -0,0 +1,94 @@
+//! Rust client for the `localsolana_contracts` escrow program.
+//!
+//! Builds instructions with the exact account lists the program expects, derives its
+//! PDAs, decodes `Escrow`, `PriceFeed`, `Config` and `UserProfile` accounts (including escrows created under
+//! v0.1.2) and every event the program emits, decodes the return data of the read-only
+//! views and the Pyth price updates the program reads, builds chat transcript Merkle
+//! proofs, and maps custom error codes back
+//! to `EscrowError`.
+//!
+//! Everything here is pure: nothing talks to an RPC node, so callers are free to use
+//! whichever client and transaction pipeline they already have.
+
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+
+pub mod chat;
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod oracle;
+pub mod pda;
+pub mod state;
+#[path = "../../state_machine.rs"]
+pub mod state_machine;
+pub mod token;
+pub mod views;
+
+pub use error::{ClientError, EscrowError};
+pub use events::Event;
+pub use state::{
+    BalanceChangeReason, ChatCheckpoint, Config, Escrow, EscrowState, FeePolicy, FiatTerms,
+    PriceFeed, Rounding, UserProfile,
+};
+
+/// Program id of the deployed `localsolana_contracts` program.
+pub const ID: Pubkey = solana_program::pubkey!("4PonUp1nPEzDPnRMPjTqufLT3f37QuBJGk1CVnsTXx7x");
+
+/// SPL Token program id.
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL Associated Token Account program id.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub fn id() -> Pubkey {
+    ID
+}
+
+/// Anchor discriminator: the first 8 bytes of `sha256("<namespace>:<name>")`.
+///
+/// Instructions use the `global` namespace with the snake_case handler name, accounts
+/// use `account` and events use `event`, both with the type name.
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = hashv(&[namespace.as_bytes(), b":", name.as_bytes()]);
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash.to_bytes()[..8]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discriminator_matches_known_instruction_sighash() {
+        // sha256("global:initialize")[..8], the canonical Anchor example
+        assert_eq!(
+            discriminator("global", "initialize"),
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+    }
+
+    #[test]
+    fn discriminators_match_program_idl() {
+        assert_eq!(
+            discriminator("account", "Escrow"),
+            [31, 213, 123, 187, 186, 22, 218, 155]
+        );
+        assert_eq!(
+            discriminator("global", "auto_cancel"),
+            [83, 161, 99, 154, 167, 3, 133, 159]
+        );
+    }
+
+    #[test]
+    fn discriminators_are_distinct_per_namespace() {
+        assert_ne!(
+            discriminator("global", "escrow"),
+            discriminator("account", "Escrow")
+        );
+    }
+}
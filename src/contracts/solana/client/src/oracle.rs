@@ -0,0 +1,102 @@
+//! Pyth `PriceUpdateV2` accounts, as read by `create_escrow` when a committed rate is
+//! checked against the oracle.
+//!
+//! Only the layout is mirrored here. Posting an update is done with Pyth's own tooling,
+//! and the account address is then passed to `instructions::CreateEscrow`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::error::ClientError;
+use crate::state_machine::OraclePrice;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// A Pyth receiver price update account. Only `Full` verification is accepted by the
+/// program.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub fn discriminator() -> [u8; 8] {
+        crate::discriminator("account", "PriceUpdateV2")
+    }
+
+    /// Decodes raw account data, discriminator included.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(ClientError::InvalidDiscriminator);
+        }
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    /// Account data, discriminator included.
+    pub fn to_account_data(&self) -> Vec<u8> {
+        let mut data = Self::discriminator().to_vec();
+        self.serialize(&mut data).expect("writing to a Vec");
+        data
+    }
+
+    /// The price as `state_machine::check_rate` takes it.
+    pub fn oracle_price(&self) -> OraclePrice {
+        OraclePrice {
+            price: self.price_message.price,
+            confidence: self.price_message.conf,
+            exponent: self.price_message.exponent,
+            publish_time: self.price_message.publish_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_matches_program_discriminator() {
+        assert_eq!(
+            PriceUpdateV2::discriminator(),
+            [34, 241, 35, 99, 157, 126, 244, 205]
+        );
+        let update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: [7; 32],
+                price: 92_000_000,
+                conf: 10_000,
+                exponent: -8,
+                publish_time: 1_700_000_000,
+                prev_publish_time: 1_699_999_999,
+                ema_price: 92_000_000,
+                ema_conf: 10_000,
+            },
+            posted_slot: 42,
+        };
+        let data = update.to_account_data();
+        assert_eq!(PriceUpdateV2::try_from_account_data(&data).unwrap(), update);
+        assert_eq!(update.oracle_price().price, 92_000_000);
+        assert!(PriceUpdateV2::try_from_account_data(&data[8..]).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! PDA derivation, mirroring the `seeds` constraints in the program.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::{
+    BUYER_BOND_SEED, BUYER_DEPOSIT_SEED, CONFIG_SEED, ESCROW_SEED, ESCROW_TOKEN_SEED,
+    EVENT_AUTHORITY_SEED, PRICE_FEED_SEED, PROFILE_SEED, SELLER_BOND_SEED,
+};
+use crate::{ASSOCIATED_TOKEN_PROGRAM_ID, ID, TOKEN_PROGRAM_ID};
+
+/// Escrow state account: `[b"escrow", seller, escrow_id, trade_id]`.
+pub fn find_escrow_address(seller: &Pubkey, escrow_id: u64, trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ESCROW_SEED,
+            seller.as_ref(),
+            &escrow_id.to_le_bytes(),
+            &trade_id.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// Escrow state account created before seeds were scoped to the seller:
+/// `[b"escrow", escrow_id, trade_id]`.
+pub fn find_legacy_escrow_address(escrow_id: u64, trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ESCROW_SEED,
+            &escrow_id.to_le_bytes(),
+            &trade_id.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// Token vault holding principal and fee: `[b"escrow_token", escrow]`.
+pub fn find_escrow_token_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_TOKEN_SEED, escrow.as_ref()], &ID)
+}
+
+/// Buyer dispute bond account: `[b"buyer_bond", escrow]`.
+pub fn find_buyer_bond_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUYER_BOND_SEED, escrow.as_ref()], &ID)
+}
+
+/// Seller dispute bond account: `[b"seller_bond", escrow]`.
+pub fn find_seller_bond_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SELLER_BOND_SEED, escrow.as_ref()], &ID)
+}
+
+/// Buyer deposit account: `[b"buyer_deposit", escrow]`.
+pub fn find_buyer_deposit_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUYER_DEPOSIT_SEED, escrow.as_ref()], &ID)
+}
+
+/// Oracle feed registered for a fiat currency: `[b"price_feed", currency]`.
+pub fn find_price_feed_address(currency: &[u8; 3]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRICE_FEED_SEED, currency.as_ref()], &ID)
+}
+
+/// The arbitrator's program-wide settings: `[b"config"]`.
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+}
+
+/// A user's profile, holding their encryption key: `[b"profile", owner]`.
+pub fn find_profile_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROFILE_SEED, owner.as_ref()], &ID)
+}
+
+/// Signer the program uses for `emit_cpi!` self-invocations.
+pub fn find_event_authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &ID)
+}
+
+/// Associated token account of `wallet` for `mint`.
+pub fn find_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escrow_address_is_scoped_to_seller() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        assert_ne!(
+            find_escrow_address(&a, 1, 2).0,
+            find_escrow_address(&b, 1, 2).0
+        );
+        assert_ne!(
+            find_escrow_address(&a, 1, 2).0,
+            find_legacy_escrow_address(1, 2).0
+        );
+    }
+
+    #[test]
+    fn legacy_address_matches_empty_seller_seed() {
+        // The program derives legacy escrows with an empty seller seed
+        let (expected, _) = Pubkey::find_program_address(
+            &[ESCROW_SEED, &[], &7u64.to_le_bytes(), &9u64.to_le_bytes()],
+            &ID,
+        );
+        assert_eq!(find_legacy_escrow_address(7, 9).0, expected);
+    }
+
+    #[test]
+    fn child_accounts_are_distinct() {
+        let (escrow, _) = find_escrow_address(&Pubkey::new_unique(), 1, 1);
+        let token = find_escrow_token_address(&escrow).0;
+        let buyer_bond = find_buyer_bond_address(&escrow).0;
+        let seller_bond = find_seller_bond_address(&escrow).0;
+        assert_ne!(token, buyer_bond);
+        assert_ne!(token, seller_bond);
+        assert_ne!(buyer_bond, seller_bond);
+    }
+
+    #[test]
+    fn associated_token_address_is_per_mint_pda() {
+        let wallet = Pubkey::new_unique();
+        let a = find_associated_token_address(&wallet, &Pubkey::new_unique());
+        let b = find_associated_token_address(&wallet, &Pubkey::new_unique());
+        assert_ne!(a, b);
+        assert!(!a.is_on_curve());
+    }
+}
@@ -0,0 +1,638 @@
+//! Account state decoding.
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::error::ClientError;
+use crate::pda;
+use crate::state_machine::{self, Caller, Facts, Party};
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EscrowState {
+    Created,
+    Funded,
+    Released,
+    Cancelled,
+    Disputed,
+    Resolved,
+}
+
+impl EscrowState {
+    pub const ALL: [EscrowState; 6] = [
+        EscrowState::Created,
+        EscrowState::Funded,
+        EscrowState::Released,
+        EscrowState::Cancelled,
+        EscrowState::Disputed,
+        EscrowState::Resolved,
+    ];
+
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            EscrowState::Released | EscrowState::Cancelled | EscrowState::Resolved
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EscrowState::Created => "Created",
+            EscrowState::Funded => "Funded",
+            EscrowState::Released => "Released",
+            EscrowState::Cancelled => "Cancelled",
+            EscrowState::Disputed => "Disputed",
+            EscrowState::Resolved => "Resolved",
+        }
+    }
+}
+
+impl fmt::Display for EscrowState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for EscrowState {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EscrowState::ALL
+            .into_iter()
+            .find(|state| state.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ClientError::UnknownState(s.to_string()))
+    }
+}
+
+impl From<EscrowState> for state_machine::State {
+    fn from(state: EscrowState) -> Self {
+        match state {
+            EscrowState::Created => state_machine::State::Created,
+            EscrowState::Funded => state_machine::State::Funded,
+            EscrowState::Released => state_machine::State::Released,
+            EscrowState::Cancelled => state_machine::State::Cancelled,
+            EscrowState::Disputed => state_machine::State::Disputed,
+            EscrowState::Resolved => state_machine::State::Resolved,
+        }
+    }
+}
+
+/// Rounding applied to a fee or bond, as reported in events.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    HalfEven,
+}
+
+impl From<state_machine::Rounding> for Rounding {
+    fn from(rounding: state_machine::Rounding) -> Self {
+        match rounding {
+            state_machine::Rounding::Floor => Rounding::Floor,
+            state_machine::Rounding::Ceil => Rounding::Ceil,
+            state_machine::Rounding::HalfEven => Rounding::HalfEven,
+        }
+    }
+}
+
+impl From<Rounding> for state_machine::Rounding {
+    fn from(rounding: Rounding) -> Self {
+        match rounding {
+            Rounding::Floor => state_machine::Rounding::Floor,
+            Rounding::Ceil => state_machine::Rounding::Ceil,
+            Rounding::HalfEven => state_machine::Rounding::HalfEven,
+        }
+    }
+}
+
+/// Floors and rounding of fees and bonds, held by `Config` and snapshotted onto each
+/// escrow at creation.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FeePolicy {
+    /// In base units.
+    pub min_fee: u64,
+    /// In base units.
+    pub min_dispute_bond: u64,
+    pub rounding: Rounding,
+}
+
+impl From<FeePolicy> for state_machine::FeePolicy {
+    fn from(policy: FeePolicy) -> Self {
+        state_machine::FeePolicy {
+            min_fee: policy.min_fee,
+            min_dispute_bond: policy.min_dispute_bond,
+            rounding: policy.rounding.into(),
+        }
+    }
+}
+
+impl From<state_machine::FeePolicy> for FeePolicy {
+    fn from(policy: state_machine::FeePolicy) -> Self {
+        FeePolicy {
+            min_fee: policy.min_fee,
+            min_dispute_bond: policy.min_dispute_bond,
+            rounding: policy.rounding.into(),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BalanceChangeReason {
+    Funded,
+    Released,
+    Cancelled,
+    AutoCancelled,
+    DefaultJudgment,
+    ResolvedToBuyer,
+    ResolvedToSeller,
+}
+
+impl BalanceChangeReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BalanceChangeReason::Funded => "Funded",
+            BalanceChangeReason::Released => "Released",
+            BalanceChangeReason::Cancelled => "Cancelled",
+            BalanceChangeReason::AutoCancelled => "AutoCancelled",
+            BalanceChangeReason::DefaultJudgment => "DefaultJudgment",
+            BalanceChangeReason::ResolvedToBuyer => "ResolvedToBuyer",
+            BalanceChangeReason::ResolvedToSeller => "ResolvedToSeller",
+        }
+    }
+}
+
+/// The program's `Escrow` account, current layout.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Escrow {
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub arbitrator: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub state: EscrowState,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub fiat_paid: bool,
+    pub counter: u64,
+    pub dispute_initiator: Option<Pubkey>,
+    pub dispute_initiated_time: Option<i64>,
+    pub dispute_evidence_hash_buyer: Option<[u8; 32]>,
+    pub dispute_evidence_hash_seller: Option<[u8; 32]>,
+    pub dispute_resolution_hash: Option<[u8; 32]>,
+    pub tracked_balance: u64,
+    // Fields below were added after v0.1.2; legacy accounts decode them as zero values
+    pub version: u8,
+    pub seller_scoped_seeds: bool,
+    pub escrow_rent_payer: Option<Pubkey>,
+    pub escrow_token_rent_payer: Option<Pubkey>,
+    pub buyer_bond_rent_payer: Option<Pubkey>,
+    pub seller_bond_rent_payer: Option<Pubkey>,
+    // Added in layout version 2
+    pub fiat_terms: Option<FiatTerms>,
+    // Added in layout version 3
+    pub chat_checkpoint_buyer: Option<ChatCheckpoint>,
+    pub chat_checkpoint_seller: Option<ChatCheckpoint>,
+    // Added in layout version 4
+    pub requires_acceptance: bool,
+    pub accepted_at: Option<i64>,
+    // Added in layout version 5; 0 when the buyer posted no deposit
+    pub buyer_deposit: u64,
+    pub buyer_deposit_rent_payer: Option<Pubkey>,
+    // Added in layout version 6; no policy means the defaults, and the bond is 0 until a
+    // dispute is opened
+    pub fee_policy: Option<FeePolicy>,
+    pub dispute_bond: u64,
+    // Added in layout version 7
+    pub requires_buyer_deposit: bool,
+}
+
+/// What the buyer pays off-chain, committed by the seller in `create_escrow`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FiatTerms {
+    /// ISO 4217 alphabetic code, e.g. `*b"EUR"`.
+    pub currency: [u8; 3],
+    /// In the currency's minor units.
+    pub fiat_amount: u64,
+    /// Fiat major units per whole token, with `FIAT_RATE_DECIMALS` decimals.
+    pub rate: u64,
+    /// Hash of the agreed payment method details.
+    pub payment_method_hash: [u8; 32],
+}
+
+/// A party's latest chat transcript checkpoint, see `chat` for how the root is built.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ChatCheckpoint {
+    pub root: [u8; 32],
+    /// Number of messages (leaves) the root covers.
+    pub message_count: u64,
+    pub committed_at: i64,
+}
+
+impl FiatTerms {
+    pub fn currency_code(&self) -> &str {
+        std::str::from_utf8(&self.currency).unwrap_or("???")
+    }
+}
+
+impl Escrow {
+    /// Borsh size of the current layout with every Option set (`Escrow::INIT_SPACE`).
+    pub const SPACE: usize = 699;
+
+    /// Offset of `state` in account data. Only fixed-size fields precede it, so it can be
+    /// used in `getProgramAccounts` memcmp filters.
+    pub const STATE_OFFSET: usize = 8 + 144;
+
+    pub fn discriminator() -> [u8; 8] {
+        crate::discriminator("account", "Escrow")
+    }
+
+    /// Decodes raw account data, discriminator included.
+    ///
+    /// Accounts of older layouts were allocated larger than their Borsh encoding and
+    /// zero-padded, and zero bytes decode to the defaults of the fields added since
+    /// (version 0, no seller-scoped seeds, no recorded rent payers, no fiat terms, chat
+    /// checkpoints or fee policy). Short
+    /// buffers are padded the same way so those accounts decode whether or not they have
+    /// been migrated.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(ClientError::InvalidDiscriminator);
+        }
+        let mut body = data[8..].to_vec();
+        if body.len() < Self::SPACE {
+            body.resize(Self::SPACE, 0);
+        }
+        Ok(Self::deserialize(&mut body.as_slice())?)
+    }
+
+    /// Address of this escrow's state account.
+    pub fn address(&self) -> Pubkey {
+        if self.seller_scoped_seeds {
+            pda::find_escrow_address(&self.seller, self.escrow_id, self.trade_id).0
+        } else {
+            pda::find_legacy_escrow_address(self.escrow_id, self.trade_id).0
+        }
+    }
+
+    // Rent refund destinations, falling back to the seller like the program does
+    pub fn escrow_rent_payer(&self) -> Pubkey {
+        self.escrow_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn escrow_token_rent_payer(&self) -> Pubkey {
+        self.escrow_token_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn buyer_bond_rent_payer(&self) -> Pubkey {
+        self.buyer_bond_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn seller_bond_rent_payer(&self) -> Pubkey {
+        self.seller_bond_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn buyer_deposit_rent_payer(&self) -> Pubkey {
+        self.buyer_deposit_rent_payer.unwrap_or(self.buyer)
+    }
+
+    /// Fee policy the escrow was created under, the defaults for escrows created before
+    /// it was recorded.
+    pub fn fee_policy(&self) -> state_machine::FeePolicy {
+        self.fee_policy
+            .map(Into::into)
+            .unwrap_or(state_machine::FeePolicy::DEFAULT)
+    }
+
+    /// Bond each party posted to the dispute. Disputes opened before it was recorded
+    /// posted what the initiator's bond account holds, passed as `initiator_bond_balance`.
+    pub fn posted_dispute_bond(&self, initiator_bond_balance: u64) -> u64 {
+        if self.dispute_bond > 0 {
+            self.dispute_bond
+        } else {
+            initiator_bond_balance
+        }
+    }
+
+    pub fn needs_migration(&self) -> bool {
+        self.version < crate::constants::ESCROW_VERSION
+    }
+
+    /// What the lifecycle guards read from this escrow, see `state_machine::check`.
+    pub fn facts(&self) -> Facts {
+        Facts {
+            state: self.state.into(),
+            fiat_paid: self.fiat_paid,
+            requires_acceptance: self.requires_acceptance,
+            accepted: self.accepted_at.is_some(),
+            buyer_deposit_posted: self.buyer_deposit > 0,
+            requires_buyer_deposit: self.requires_buyer_deposit,
+            sequential: self.sequential,
+            has_sequential_address: self.sequential_escrow_address.is_some(),
+            deposit_deadline: self.deposit_deadline,
+            fiat_deadline: self.fiat_deadline,
+            dispute_initiator: self.dispute_initiator.map(|initiator| {
+                if initiator == self.buyer {
+                    Party::Buyer
+                } else {
+                    Party::Seller
+                }
+            }),
+            dispute_initiated_time: self.dispute_initiated_time,
+            buyer_evidence: self.dispute_evidence_hash_buyer.is_some(),
+            seller_evidence: self.dispute_evidence_hash_seller.is_some(),
+        }
+    }
+
+    /// Roles `signer` holds in this escrow.
+    pub fn caller(&self, signer: &Pubkey) -> Caller {
+        Caller::of(signer, &self.seller, &self.buyer, &self.arbitrator)
+    }
+}
+
+/// The program's `UserProfile` account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserProfile {
+    pub owner: Pubkey,
+    /// x25519 public key payment details for this user are encrypted to.
+    pub encryption_key: [u8; 32],
+    pub updated_at: i64,
+}
+
+impl UserProfile {
+    pub const SPACE: usize = 32 + 32 + 8;
+
+    pub fn discriminator() -> [u8; 8] {
+        crate::discriminator("account", "UserProfile")
+    }
+
+    /// Decodes raw account data, discriminator included.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(ClientError::InvalidDiscriminator);
+        }
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn address(&self) -> Pubkey {
+        pda::find_profile_address(&self.owner).0
+    }
+}
+
+/// The program's `PriceFeed` account: the Pyth feed and limits `create_escrow` checks
+/// committed rates in `currency` against.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PriceFeed {
+    pub currency: [u8; 3],
+    pub feed_id: [u8; 32],
+    pub max_deviation_bps: u16,
+    pub max_age_seconds: u32,
+    pub max_confidence_bps: u16,
+}
+
+impl PriceFeed {
+    pub const SPACE: usize = 3 + 32 + 2 + 4 + 2;
+
+    pub fn discriminator() -> [u8; 8] {
+        crate::discriminator("account", "PriceFeed")
+    }
+
+    /// Decodes raw account data, discriminator included.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(ClientError::InvalidDiscriminator);
+        }
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn address(&self) -> Pubkey {
+        pda::find_price_feed_address(&self.currency).0
+    }
+
+    pub fn band(&self) -> state_machine::RateBand {
+        state_machine::RateBand {
+            max_deviation_bps: self.max_deviation_bps,
+            max_age_seconds: self.max_age_seconds,
+            max_confidence_bps: self.max_confidence_bps,
+        }
+    }
+}
+
+/// The program's `Config` account: the fee policy `create_escrow` snapshots onto new
+/// escrows. Until the arbitrator initializes it the program applies
+/// `state_machine::FeePolicy::DEFAULT`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub fee_policy: FeePolicy,
+}
+
+impl Config {
+    pub const SPACE: usize = 8 + 8 + 1;
+
+    pub fn discriminator() -> [u8; 8] {
+        crate::discriminator("account", "Config")
+    }
+
+    /// Decodes raw account data, discriminator included.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return Err(ClientError::InvalidDiscriminator);
+        }
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn address() -> Pubkey {
+        pda::find_config_address().0
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn sample_escrow() -> Escrow {
+        let seller = Pubkey::new_unique();
+        Escrow {
+            escrow_id: 11,
+            trade_id: 22,
+            seller,
+            buyer: Pubkey::new_unique(),
+            arbitrator: crate::constants::ARBITRATOR,
+            amount: 1_000_000,
+            fee: 10_000,
+            deposit_deadline: 1_700_000_900,
+            fiat_deadline: 0,
+            state: EscrowState::Created,
+            sequential: false,
+            sequential_escrow_address: None,
+            fiat_paid: false,
+            counter: 0,
+            dispute_initiator: None,
+            dispute_initiated_time: None,
+            dispute_evidence_hash_buyer: None,
+            dispute_evidence_hash_seller: None,
+            dispute_resolution_hash: None,
+            tracked_balance: 0,
+            version: 7,
+            seller_scoped_seeds: true,
+            escrow_rent_payer: Some(seller),
+            escrow_token_rent_payer: None,
+            buyer_bond_rent_payer: None,
+            seller_bond_rent_payer: None,
+            fiat_terms: Some(FiatTerms {
+                currency: *b"EUR",
+                fiat_amount: 92,
+                rate: 92_000_000,
+                payment_method_hash: [7; 32],
+            }),
+            chat_checkpoint_buyer: None,
+            chat_checkpoint_seller: None,
+            requires_acceptance: false,
+            accepted_at: None,
+            buyer_deposit: 0,
+            buyer_deposit_rent_payer: None,
+            fee_policy: Some(state_machine::FeePolicy::DEFAULT.into()),
+            dispute_bond: 0,
+            requires_buyer_deposit: false,
+        }
+    }
+
+    fn account_data(escrow: &Escrow) -> Vec<u8> {
+        let mut data = Escrow::discriminator().to_vec();
+        data.extend(borsh::to_vec(escrow).unwrap());
+        data.resize(8 + Escrow::SPACE, 0);
+        data
+    }
+
+    #[test]
+    fn space_matches_fully_populated_encoding() {
+        let mut escrow = sample_escrow();
+        escrow.sequential_escrow_address = Some(Pubkey::new_unique());
+        escrow.dispute_initiator = Some(Pubkey::new_unique());
+        escrow.dispute_initiated_time = Some(1);
+        escrow.dispute_evidence_hash_buyer = Some([1; 32]);
+        escrow.dispute_evidence_hash_seller = Some([2; 32]);
+        escrow.dispute_resolution_hash = Some([3; 32]);
+        escrow.escrow_token_rent_payer = Some(Pubkey::new_unique());
+        escrow.buyer_bond_rent_payer = Some(Pubkey::new_unique());
+        escrow.seller_bond_rent_payer = Some(Pubkey::new_unique());
+        let checkpoint = ChatCheckpoint {
+            root: [4; 32],
+            message_count: 5,
+            committed_at: 6,
+        };
+        escrow.chat_checkpoint_buyer = Some(checkpoint.clone());
+        escrow.chat_checkpoint_seller = Some(checkpoint);
+        escrow.accepted_at = Some(7);
+        escrow.buyer_deposit_rent_payer = Some(Pubkey::new_unique());
+        assert_eq!(borsh::to_vec(&escrow).unwrap().len(), Escrow::SPACE);
+    }
+
+    #[test]
+    fn decodes_current_layout() {
+        let escrow = sample_escrow();
+        let decoded = Escrow::try_from_account_data(&account_data(&escrow)).unwrap();
+        assert_eq!(decoded, escrow);
+        assert!(!decoded.needs_migration());
+    }
+
+    #[test]
+    fn decodes_legacy_layout_with_defaults() {
+        let mut escrow = sample_escrow();
+        escrow.version = 0;
+        escrow.seller_scoped_seeds = false;
+        escrow.escrow_rent_payer = None;
+        escrow.fiat_terms = None;
+        escrow.fee_policy = None;
+
+        // v0.1.2 data: the same prefix, no trailing fields, zero padding
+        let mut data = Escrow::discriminator().to_vec();
+        let full = borsh::to_vec(&escrow).unwrap();
+        data.extend(&full[..full.len() - (1 + 1 + 4 + 1 + 2 + 1 + 1 + 8 + 1 + 1 + 8 + 1)]);
+        data.extend([0u8; 64]);
+
+        let decoded = Escrow::try_from_account_data(&data).unwrap();
+        assert_eq!(decoded, escrow);
+        assert!(decoded.needs_migration());
+        assert_eq!(decoded.address(), pda::find_legacy_escrow_address(11, 22).0);
+        assert_eq!(decoded.escrow_rent_payer(), escrow.seller);
+        assert_eq!(decoded.fee_policy(), state_machine::FeePolicy::DEFAULT);
+    }
+
+    #[test]
+    fn posted_bond_falls_back_to_the_initiator_bond_balance() {
+        let mut escrow = sample_escrow();
+        assert_eq!(escrow.posted_dispute_bond(70_000), 70_000);
+        escrow.dispute_bond = 50_000;
+        assert_eq!(escrow.posted_dispute_bond(70_000), 50_000);
+    }
+
+    #[test]
+    fn config_space_matches_encoding() {
+        let config = Config {
+            fee_policy: state_machine::FeePolicy::DEFAULT.into(),
+        };
+        let mut data = Config::discriminator().to_vec();
+        data.extend(borsh::to_vec(&config).unwrap());
+        assert_eq!(data.len(), 8 + Config::SPACE);
+        assert_eq!(Config::try_from_account_data(&data).unwrap(), config);
+    }
+
+    #[test]
+    fn state_offset_points_at_state_byte() {
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Disputed;
+        let data = account_data(&escrow);
+        assert_eq!(data[Escrow::STATE_OFFSET], EscrowState::Disputed as u8);
+    }
+
+    #[test]
+    fn rejects_wrong_discriminator() {
+        let mut data = account_data(&sample_escrow());
+        data[0] ^= 0xff;
+        assert!(matches!(
+            Escrow::try_from_account_data(&data),
+            Err(ClientError::InvalidDiscriminator)
+        ));
+    }
+
+    #[test]
+    fn state_parses_case_insensitively() {
+        assert_eq!(
+            "disputed".parse::<EscrowState>().unwrap(),
+            EscrowState::Disputed
+        );
+        assert!("open".parse::<EscrowState>().is_err());
+    }
+
+    #[test]
+    fn facts_feed_the_shared_guards() {
+        use crate::state_machine::{check, Action, State, Violation};
+
+        let mut escrow = sample_escrow();
+        escrow.state = EscrowState::Disputed;
+        escrow.dispute_initiator = Some(escrow.seller);
+        escrow.dispute_initiated_time = Some(1_700_000_000);
+        escrow.dispute_evidence_hash_seller = Some([1; 32]);
+        for state in EscrowState::ALL {
+            assert_eq!(State::from(state).name(), state.as_str());
+        }
+
+        let facts = escrow.facts();
+        assert_eq!(facts.dispute_initiator, Some(Party::Seller));
+        let buyer = escrow.caller(&escrow.buyer);
+        assert_eq!(
+            check(Action::RespondToDispute, buyer, &facts, 1_700_000_000),
+            Ok(State::Disputed)
+        );
+        let seller = escrow.caller(&escrow.seller);
+        assert_eq!(
+            check(Action::RespondToDispute, seller, &facts, 1_700_000_000),
+            Err(Violation::Unauthorized)
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Minimal SPL token account decoding, enough to read vault and bond balances.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::error::ClientError;
+
+/// Size of an SPL token account.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl TokenAccount {
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() != TOKEN_ACCOUNT_LEN {
+            return Err(ClientError::InvalidTokenAccount);
+        }
+        let pubkey = |range: std::ops::Range<usize>| {
+            Pubkey::new_from_array(data[range].try_into().expect("32-byte slice"))
+        };
+        Ok(TokenAccount {
+            mint: pubkey(0..32),
+            owner: pubkey(32..64),
+            amount: u64::from_le_bytes(data[64..72].try_into().expect("8-byte slice")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_mint_owner_and_amount() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&1_010_000u64.to_le_bytes());
+        assert_eq!(
+            TokenAccount::try_from_account_data(&data).unwrap(),
+            TokenAccount {
+                mint,
+                owner,
+                amount: 1_010_000
+            }
+        );
+        assert!(TokenAccount::try_from_account_data(&data[..64]).is_err());
+    }
+}
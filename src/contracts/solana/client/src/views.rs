@@ -0,0 +1,136 @@
+//! Return data of the read-only `quote_escrow` and `escrow_status` instructions.
+//!
+//! Both are meant to be simulated. Anchor writes the handler's return value with
+//! `set_return_data` as plain Borsh, without a discriminator, so the bytes in the
+//! simulation's `returnData` decode directly into these types.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::ClientError;
+use crate::state::EscrowState;
+use crate::state_machine::{self, Action, Role};
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EscrowPhase {
+    Created,
+    AwaitingFiatPayment,
+    PendingCryptoRelease,
+    Completed,
+    Cancelled,
+    Disputed,
+    Resolved,
+    /// Created and accepted by the buyer. Last so the other phases keep their encoding.
+    Accepted,
+}
+
+impl From<EscrowPhase> for state_machine::Phase {
+    fn from(phase: EscrowPhase) -> Self {
+        match phase {
+            EscrowPhase::Created => state_machine::Phase::Created,
+            EscrowPhase::AwaitingFiatPayment => state_machine::Phase::AwaitingFiatPayment,
+            EscrowPhase::PendingCryptoRelease => state_machine::Phase::PendingCryptoRelease,
+            EscrowPhase::Completed => state_machine::Phase::Completed,
+            EscrowPhase::Cancelled => state_machine::Phase::Cancelled,
+            EscrowPhase::Disputed => state_machine::Phase::Disputed,
+            EscrowPhase::Resolved => state_machine::Phase::Resolved,
+            EscrowPhase::Accepted => state_machine::Phase::Accepted,
+        }
+    }
+}
+
+impl EscrowPhase {
+    /// The frontend's name for this phase, e.g. `AWAITING_FIAT_PAYMENT`.
+    pub fn name(self) -> &'static str {
+        state_machine::Phase::from(self).name()
+    }
+}
+
+/// What an escrow of `amount` created at the time of the call would cost. Deadlines are
+/// those of an escrow created, funded and disputed at that time.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowQuote {
+    pub amount: u64,
+    pub fee: u64,
+    /// `amount + fee`, what `fund_escrow` takes from the seller.
+    pub total_deposit: u64,
+    /// Posted by each party to a dispute.
+    pub dispute_bond: u64,
+    /// What `post_buyer_deposit` takes from the buyer, if the buyer posts one.
+    pub buyer_deposit: u64,
+    /// Paid to the buyer out of the fee by a seller cancelling after the buyer accepted.
+    pub cancellation_penalty: u64,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub response_deadline: i64,
+}
+
+impl EscrowQuote {
+    pub fn try_from_return_data(data: &[u8]) -> Result<Self, ClientError> {
+        Ok(Self::try_from_slice(data)?)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowStatusReport {
+    pub state: EscrowState,
+    pub phase: EscrowPhase,
+    /// Bit masks over `Action::ALL`, see `Action::bit`.
+    pub seller_actions: u32,
+    pub buyer_actions: u32,
+    pub arbitrator_actions: u32,
+    pub anyone_actions: u32,
+    /// Seconds until each deadline, present only while it is running and 0 once passed.
+    pub deposit_seconds_left: Option<i64>,
+    pub fiat_seconds_left: Option<i64>,
+    pub response_seconds_left: Option<i64>,
+    /// Cluster time the report was computed at.
+    pub timestamp: i64,
+}
+
+impl EscrowStatusReport {
+    pub fn try_from_return_data(data: &[u8]) -> Result<Self, ClientError> {
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    /// Actions a signer holding only `role` could take next. Bond account setup is not
+    /// included since it depends on accounts the escrow does not record.
+    pub fn actions(&self, role: Role) -> Vec<Action> {
+        Action::from_mask(match role {
+            Role::Seller => self.seller_actions,
+            Role::Buyer => self.buyer_actions,
+            Role::Arbitrator => self.arbitrator_actions,
+            Role::Other => self.anyone_actions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_report_round_trips_and_expands_masks() {
+        let report = EscrowStatusReport {
+            state: EscrowState::Funded,
+            phase: EscrowPhase::AwaitingFiatPayment,
+            seller_actions: Action::Cancel.bit(),
+            buyer_actions: Action::MarkFiatPaid.bit() | Action::UpdateSequentialAddress.bit(),
+            arbitrator_actions: Action::Cancel.bit(),
+            anyone_actions: 0,
+            deposit_seconds_left: None,
+            fiat_seconds_left: Some(1_200),
+            response_seconds_left: None,
+            timestamp: 1_700_000_000,
+        };
+        let data = borsh::to_vec(&report).unwrap();
+        let decoded = EscrowStatusReport::try_from_return_data(&data).unwrap();
+        assert_eq!(decoded, report);
+        assert_eq!(decoded.phase.name(), "AWAITING_FIAT_PAYMENT");
+        assert_eq!(
+            decoded.actions(Role::Buyer),
+            vec![Action::MarkFiatPaid, Action::UpdateSequentialAddress]
+        );
+        assert!(decoded.actions(Role::Other).is_empty());
+        assert!(EscrowStatusReport::try_from_return_data(&data[..data.len() - 1]).is_err());
+    }
+}
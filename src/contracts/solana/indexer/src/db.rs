@@ -0,0 +1,269 @@
+//! Minimal database abstraction over SQLite and Postgres.
+//!
+//! Statements are written once with `$1, $2, ...` placeholders, which both engines
+//! accept as long as each placeholder first appears in ascending order. Only column
+//! types differ between the two, see `Dialect`.
+
+use anyhow::{anyhow, bail, Result};
+use postgres::types::{ToSql, Type};
+
+/// A bound parameter or a returned column. `None` is SQL `NULL`; the variant still
+/// carries the type because Postgres needs it to bind a `NULL`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Int(Option<i64>),
+    Text(Option<String>),
+    Bool(Option<bool>),
+    Bytes(Option<Vec<u8>>),
+}
+
+impl Value {
+    pub fn int(&self) -> Result<Option<i64>> {
+        match self {
+            Value::Int(value) => Ok(*value),
+            // SQLite has no boolean type
+            Value::Bool(value) => Ok(value.map(i64::from)),
+            other => null_or_mismatch(other, "integer"),
+        }
+    }
+
+    pub fn text(&self) -> Result<Option<&str>> {
+        match self {
+            Value::Text(value) => Ok(value.as_deref()),
+            other => null_or_mismatch(other, "text"),
+        }
+    }
+
+    pub fn bytes(&self) -> Result<Option<&[u8]>> {
+        match self {
+            Value::Bytes(value) => Ok(value.as_deref()),
+            other => null_or_mismatch(other, "bytes"),
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Value::Int(value) => value.is_none(),
+            Value::Text(value) => value.is_none(),
+            Value::Bool(value) => value.is_none(),
+            Value::Bytes(value) => value.is_none(),
+        }
+    }
+}
+
+fn null_or_mismatch<T>(value: &Value, expected: &str) -> Result<Option<T>> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        bail!("expected {expected} column, found {value:?}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    Sqlite,
+    Postgres,
+}
+
+impl Dialect {
+    pub fn blob(self) -> &'static str {
+        match self {
+            Dialect::Sqlite => "BLOB",
+            Dialect::Postgres => "BYTEA",
+        }
+    }
+
+    /// Auto-assigned primary key that increases with insertion order.
+    pub fn serial_key(self) -> &'static str {
+        match self {
+            Dialect::Sqlite => "INTEGER PRIMARY KEY",
+            Dialect::Postgres => "BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY",
+        }
+    }
+}
+
+pub trait Database {
+    fn dialect(&self) -> Dialect;
+
+    /// Runs one or more statements without parameters.
+    fn batch(&mut self, sql: &str) -> Result<()>;
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64>;
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>>;
+}
+
+/// Opens `url`: a `postgres://` or `postgresql://` URL, otherwise a SQLite file path
+/// (`:memory:` for an in-memory database).
+pub fn open(url: &str) -> Result<Box<dyn Database>> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        let client = postgres::Client::connect(url, postgres::NoTls)?;
+        Ok(Box::new(Postgres(client)))
+    } else {
+        let path = url.strip_prefix("sqlite://").unwrap_or(url);
+        Ok(Box::new(Sqlite(rusqlite::Connection::open(path)?)))
+    }
+}
+
+impl<D: Database + ?Sized> Database for Box<D> {
+    fn dialect(&self) -> Dialect {
+        (**self).dialect()
+    }
+
+    fn batch(&mut self, sql: &str) -> Result<()> {
+        (**self).batch(sql)
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64> {
+        (**self).execute(sql, params)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>> {
+        (**self).query(sql, params)
+    }
+}
+
+pub struct Sqlite(pub rusqlite::Connection);
+
+impl Sqlite {
+    pub fn in_memory() -> Result<Self> {
+        Ok(Sqlite(rusqlite::Connection::open_in_memory()?))
+    }
+}
+
+impl rusqlite::ToSql for Value {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        match self {
+            Value::Int(value) => rusqlite::ToSql::to_sql(value),
+            Value::Text(value) => rusqlite::ToSql::to_sql(value),
+            Value::Bool(value) => rusqlite::ToSql::to_sql(value),
+            Value::Bytes(value) => rusqlite::ToSql::to_sql(value),
+        }
+    }
+}
+
+impl Database for Sqlite {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    fn batch(&mut self, sql: &str) -> Result<()> {
+        Ok(self.0.execute_batch(sql)?)
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64> {
+        let changed = self
+            .0
+            .prepare_cached(sql)?
+            .execute(rusqlite::params_from_iter(params))?;
+        Ok(changed as u64)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>> {
+        use rusqlite::types::ValueRef;
+
+        let mut statement = self.0.prepare_cached(sql)?;
+        let columns = statement.column_count();
+        let mut rows = statement.query(rusqlite::params_from_iter(params))?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut values = Vec::with_capacity(columns);
+            for i in 0..columns {
+                values.push(match row.get_ref(i)? {
+                    ValueRef::Null => Value::Int(None),
+                    ValueRef::Integer(value) => Value::Int(Some(value)),
+                    ValueRef::Text(value) => Value::Text(Some(String::from_utf8(value.to_vec())?)),
+                    ValueRef::Blob(value) => Value::Bytes(Some(value.to_vec())),
+                    ValueRef::Real(_) => bail!("unexpected REAL column"),
+                });
+            }
+            out.push(values);
+        }
+        Ok(out)
+    }
+}
+
+pub struct Postgres(pub postgres::Client);
+
+fn pg_params(params: &[Value]) -> Vec<&(dyn ToSql + Sync)> {
+    params
+        .iter()
+        .map(|value| match value {
+            Value::Int(value) => value as &(dyn ToSql + Sync),
+            Value::Text(value) => value as &(dyn ToSql + Sync),
+            Value::Bool(value) => value as &(dyn ToSql + Sync),
+            Value::Bytes(value) => value as &(dyn ToSql + Sync),
+        })
+        .collect()
+}
+
+impl Database for Postgres {
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
+    }
+
+    fn batch(&mut self, sql: &str) -> Result<()> {
+        Ok(self.0.batch_execute(sql)?)
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64> {
+        Ok(self.0.execute(sql, &pg_params(params))?)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>> {
+        let rows = self.0.query(sql, &pg_params(params))?;
+        rows.iter()
+            .map(|row| {
+                (0..row.len())
+                    .map(|i| {
+                        let ty = row.columns()[i].type_();
+                        Ok(match *ty {
+                            Type::INT8 => Value::Int(row.try_get(i)?),
+                            Type::INT4 => {
+                                Value::Int(row.try_get::<_, Option<i32>>(i)?.map(i64::from))
+                            }
+                            Type::TEXT | Type::VARCHAR => Value::Text(row.try_get(i)?),
+                            Type::BOOL => Value::Bool(row.try_get(i)?),
+                            Type::BYTEA => Value::Bytes(row.try_get(i)?),
+                            _ => return Err(anyhow!("unsupported column type {ty}")),
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_binds_dollar_placeholders_in_order() {
+        let mut db = Sqlite::in_memory().unwrap();
+        db.batch("CREATE TABLE t (a BIGINT, b TEXT, c BOOLEAN, d BLOB)")
+            .unwrap();
+        db.execute(
+            "INSERT INTO t (a, b, c, d) VALUES ($1, $2, $3, $4)",
+            &[
+                Value::Int(Some(-5)),
+                Value::Text(None),
+                Value::Bool(Some(true)),
+                Value::Bytes(Some(vec![1, 2])),
+            ],
+        )
+        .unwrap();
+        let rows = db
+            .query(
+                "SELECT a, b, c, d FROM t WHERE a = $1",
+                &[Value::Int(Some(-5))],
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0].int().unwrap(), Some(-5));
+        assert_eq!(rows[0][1].text().unwrap(), None);
+        assert_eq!(rows[0][2].int().unwrap(), Some(1));
+        assert_eq!(rows[0][3].bytes().unwrap(), Some(&[1u8, 2][..]));
+        assert!(rows[0][0].text().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Extracts program events from a confirmed transaction.
+//!
+//! `emit_cpi!` events are self-invocations of the program carrying
+//! `EVENT_IX_TAG || discriminator || borsh(event)`; only the program can sign for its
+//! event authority, so any such inner instruction of a successful transaction is genuine.
+//! Transactions from before the switch to `emit_cpi!` carry events as `Program data:`
+//! logs instead; those are only accepted while the program itself is executing, because
+//! any program can write a `Program data:` line.
+
+use localsolana_contracts_client::{ClientError, Event, ID};
+use solana_sdk::pubkey::Pubkey;
+
+/// The parts of a transaction the decoder needs, independent of the RPC encoding.
+#[derive(Clone, Debug, Default)]
+pub struct RawTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Static keys followed by writable then readonly lookup-table addresses
+    pub account_keys: Vec<Pubkey>,
+    /// In execution order
+    pub inner_instructions: Vec<InnerInstruction>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct InnerInstruction {
+    pub program_id_index: u8,
+    pub data: Vec<u8>,
+}
+
+/// Every event the program emitted in `tx`, in emission order. Events that fail to
+/// decode (for example from a newer program version) are returned as errors in place
+/// so the caller can decide whether to skip them.
+pub fn events(tx: &RawTransaction) -> Vec<Result<Event, ClientError>> {
+    let mut events: Vec<_> = tx
+        .inner_instructions
+        .iter()
+        .filter(|ix| tx.account_keys.get(ix.program_id_index as usize) == Some(&ID))
+        .filter_map(|ix| match Event::from_cpi_instruction_data(&ix.data) {
+            Err(ClientError::NotAnEvent) => None,
+            result => Some(result),
+        })
+        .collect();
+    events.extend(log_events(&tx.logs));
+    events
+}
+
+fn log_events(logs: &[String]) -> Vec<Result<Event, ClientError>> {
+    let program = ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(id);
+                continue;
+            }
+            if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+                continue;
+            }
+        }
+        if stack.last() == Some(&program.as_str()) {
+            if let Some(event) = Event::from_log(line) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use localsolana_contracts_client::events::{FiatMarkedPaid, EVENT_IX_TAG};
+
+    fn fiat_paid() -> Event {
+        Event::from(FiatMarkedPaid {
+            schema_version: 1,
+            object_id: Pubkey::new_unique(),
+            escrow_id: 1,
+            trade_id: 2,
+            timestamp: 3,
+        })
+    }
+
+    fn program_data(event: &Event) -> String {
+        use base64::Engine;
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(event.to_bytes())
+        )
+    }
+
+    #[test]
+    fn decodes_events_from_self_cpi() {
+        let event = fiat_paid();
+        let mut data = EVENT_IX_TAG.to_vec();
+        data.extend(event.to_bytes());
+        let other = Pubkey::new_unique();
+        let tx = RawTransaction {
+            account_keys: vec![other, ID],
+            inner_instructions: vec![
+                // Same bytes from another program are ignored
+                InnerInstruction {
+                    program_id_index: 0,
+                    data: data.clone(),
+                },
+                // Token transfers and other CPIs of the program are not events
+                InnerInstruction {
+                    program_id_index: 1,
+                    data: vec![3, 0, 0, 0],
+                },
+                InnerInstruction {
+                    program_id_index: 1,
+                    data,
+                },
+            ],
+            ..RawTransaction::default()
+        };
+        let decoded = events(&tx);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_ref().unwrap(), &event);
+    }
+
+    #[test]
+    fn decodes_program_data_only_inside_the_program() {
+        let event = fiat_paid();
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {other} invoke [1]"),
+            program_data(&event),
+            format!("Program {ID} invoke [2]"),
+            "Program log: Instruction: MarkFiatPaid".to_string(),
+            program_data(&event),
+            format!("Program {ID} success"),
+            program_data(&event),
+            format!("Program {other} success"),
+        ];
+        let tx = RawTransaction {
+            logs,
+            ..RawTransaction::default()
+        };
+        let decoded = events(&tx);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_ref().unwrap(), &event);
+    }
+
+    #[test]
+    fn reports_unknown_events() {
+        let mut data = EVENT_IX_TAG.to_vec();
+        data.extend([0xff; 16]);
+        let tx = RawTransaction {
+            account_keys: vec![ID],
+            inner_instructions: vec![InnerInstruction {
+                program_id_index: 0,
+                data,
+            }],
+            ..RawTransaction::default()
+        };
+        assert!(matches!(
+            events(&tx).as_slice(),
+            [Err(ClientError::UnknownEvent(_))]
+        ));
+    }
+}
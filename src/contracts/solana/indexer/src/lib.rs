@@ -0,0 +1,81 @@
+//! Event indexer for the localsolana_contracts escrow program.
+//!
+//! Decodes every event the program emits from confirmed transactions (`decode`),
+//! keeps them as an append-only log and materializes escrow, dispute and
+//! balance-history rows from it in SQLite or Postgres (`store`, `projection`).
+//! Transactions are indexed at `confirmed` commitment and re-checked once their slot is
+//! finalized; ones dropped on a fork are removed and their escrows rolled back.
+
+pub mod db;
+pub mod decode;
+pub mod projection;
+pub mod source;
+pub mod store;
+
+use anyhow::Result;
+
+use crate::db::Database;
+use crate::source::{Source, Status};
+use crate::store::{IndexedTransaction, Store};
+
+/// Counts from one `poll`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PollSummary {
+    pub indexed: usize,
+    pub finalized: usize,
+    pub dropped: usize,
+}
+
+/// Settles finality of earlier transactions, then indexes new ones at or after
+/// `min_slot`.
+pub fn poll<D: Database>(
+    source: &Source,
+    store: &mut Store<D>,
+    min_slot: u64,
+) -> Result<PollSummary> {
+    let mut summary = PollSummary::default();
+
+    let finalized_slot = source.finalized_slot()?;
+    let settled: Vec<_> = store
+        .unfinalized()?
+        .into_iter()
+        .filter(|(_, slot)| *slot <= finalized_slot)
+        .collect();
+    let mut dropped = Vec::new();
+    for ((signature, _), status) in settled.iter().zip(source.statuses(&settled)?) {
+        match status {
+            Status::Finalized => {
+                store.mark_finalized(signature)?;
+                summary.finalized += 1;
+            }
+            Status::Dropped => dropped.push(signature.clone()),
+            Status::Pending => {}
+        }
+    }
+    summary.dropped = dropped.len();
+    store.remove(&dropped)?;
+
+    let resume = store.resume_point()?;
+    for listed in source.list(resume.as_deref(), min_slot)? {
+        if store.contains(&listed.signature)? {
+            continue;
+        }
+        let raw = source.transaction(&listed.signature)?;
+        let mut events = Vec::new();
+        for event in decode::events(&raw) {
+            match event {
+                Ok(event) => events.push(event),
+                Err(err) => eprintln!("warning: {}: {err}", raw.signature),
+            }
+        }
+        store.insert(&IndexedTransaction {
+            signature: raw.signature,
+            slot: raw.slot,
+            block_time: raw.block_time,
+            finalized: listed.finalized,
+            events,
+        })?;
+        summary.indexed += 1;
+    }
+    Ok(summary)
+}
@@ -0,0 +1,98 @@
+//! Indexer service.
+//!
+//! Polls the cluster for transactions of the program and materializes their events into
+//! `--database`, a SQLite file or a Postgres URL. `--replay-from <slot>` discards
+//! everything indexed at or after the slot and fetches it again; `--once` runs a single
+//! poll and exits.
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use localsolana_indexer::db;
+use localsolana_indexer::source::Source;
+use localsolana_indexer::store::Store;
+
+#[derive(Parser)]
+#[command(
+    name = "indexer",
+    version,
+    about = "Materialize escrow events into SQL"
+)]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        env = "SOLANA_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// SQLite path or postgres:// URL
+    #[arg(
+        long,
+        short = 'd',
+        env = "INDEXER_DATABASE",
+        default_value = "indexer.db"
+    )]
+    database: String,
+
+    /// Seconds between polls
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+
+    /// Re-index everything from this slot on
+    #[arg(long)]
+    replay_from: Option<u64>,
+
+    /// Run a single poll and exit
+    #[arg(long)]
+    once: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let source = Source::new(&cli.url);
+    let mut store = Store::new(db::open(&cli.database)?)?;
+
+    let min_slot = match cli.replay_from {
+        Some(slot) => {
+            let removed = store.truncate_from(slot)?;
+            println!("replaying from slot {slot}, removed {removed} transactions");
+            slot
+        }
+        None => 0,
+    };
+
+    loop {
+        match localsolana_indexer::poll(&source, &mut store, min_slot) {
+            Ok(summary) => {
+                if summary != Default::default() {
+                    println!(
+                        "indexed {}, finalized {}, dropped {}",
+                        summary.indexed, summary.finalized, summary.dropped
+                    );
+                }
+            }
+            Err(err) if cli.once => return Err(err),
+            Err(err) => eprintln!("error: poll failed: {err:#}"),
+        }
+        if cli.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(cli.interval));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,525 @@
+//! Folds the event history of one escrow into the rows the indexer materializes.
+//!
+//! Rows are always rebuilt from the full event log of the escrow rather than patched in
+//! place, so dropping a transaction after a fork or replaying from a slot only needs the
+//! affected escrows to be projected again.
+//!
+//! State names follow the escrow, trade leg and dispute tables in
+//! `docs/state-ref-escrows-trades.md`.
+
+use localsolana_contracts_client::{BalanceChangeReason, EscrowState, Event};
+use solana_sdk::pubkey::Pubkey;
+
+/// An event together with where it was emitted.
+#[derive(Clone, Debug)]
+pub struct LoggedEvent {
+    pub signature: String,
+    pub slot: u64,
+    /// Position among the program's events in the transaction
+    pub seq: u32,
+    pub event: Event,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRow {
+    pub address: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    // Creation terms; unknown when the history starts after `EscrowCreated`
+    pub seller: Option<Pubkey>,
+    pub buyer: Option<Pubkey>,
+    pub arbitrator: Option<Pubkey>,
+    pub amount: Option<u64>,
+    pub fee: Option<u64>,
+    pub deposit_deadline: Option<i64>,
+    pub fiat_deadline: Option<i64>,
+    pub sequential: Option<bool>,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub state: Option<EscrowState>,
+    pub fiat_paid: bool,
+    pub fiat_paid_at: Option<i64>,
+    pub counter: u64,
+    pub tracked_balance: u64,
+    pub version: Option<u8>,
+    pub created_at: Option<i64>,
+    pub updated_at: i64,
+    pub last_signature: String,
+    pub last_slot: u64,
+}
+
+impl EscrowRow {
+    /// `CREATED`, `FUNDED`, `RELEASED`, `CANCELLED`, `DISPUTED` or `RESOLVED`.
+    pub fn state_name(&self) -> Option<String> {
+        self.state.map(|state| state.as_str().to_uppercase())
+    }
+
+    /// The escrow state with `FIAT_PAID` split out of `FUNDED`, as trade legs track it.
+    pub fn leg_state_name(&self) -> Option<String> {
+        match self.state {
+            Some(EscrowState::Funded) if self.fiat_paid => Some("FIAT_PAID".to_string()),
+            _ => self.state_name(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Opened,
+    Responded,
+    Resolved,
+    Defaulted,
+}
+
+impl DisputeStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DisputeStatus::Opened => "OPENED",
+            DisputeStatus::Responded => "RESPONDED",
+            DisputeStatus::Resolved => "RESOLVED",
+            DisputeStatus::Defaulted => "DEFAULTED",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeRow {
+    pub escrow: Pubkey,
+    pub status: DisputeStatus,
+    pub initiator: Pubkey,
+    pub opened_at: i64,
+    pub initiator_evidence_hash: [u8; 32],
+    pub initiator_bond: u64,
+    pub responder: Option<Pubkey>,
+    pub responded_at: Option<i64>,
+    pub responder_evidence_hash: Option<[u8; 32]>,
+    pub responder_bond: Option<u64>,
+    /// true when the escrow went to the buyer
+    pub decision: Option<bool>,
+    pub winner: Option<Pubkey>,
+    pub defaulting_party: Option<Pubkey>,
+    pub resolution_hash: Option<[u8; 32]>,
+    pub explanation_reference: Option<String>,
+    pub resolved_at: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BalanceRow {
+    pub escrow: Pubkey,
+    pub signature: String,
+    pub slot: u64,
+    pub seq: u32,
+    pub new_balance: u64,
+    pub reason: BalanceChangeReason,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Projection {
+    pub escrow: Option<EscrowRow>,
+    pub dispute: Option<DisputeRow>,
+    pub balances: Vec<BalanceRow>,
+}
+
+/// Projects `events`, which must be the complete history of `address` in chain order.
+pub fn project(address: Pubkey, events: &[LoggedEvent]) -> Projection {
+    let mut projection = Projection::default();
+    for logged in events {
+        apply(&mut projection, address, logged);
+    }
+    projection
+}
+
+fn apply(projection: &mut Projection, address: Pubkey, logged: &LoggedEvent) {
+    let Some((escrow_id, trade_id, timestamp)) = ids(&logged.event) else {
+        return;
+    };
+    let escrow = projection.escrow.get_or_insert_with(|| EscrowRow {
+        address,
+        escrow_id,
+        trade_id,
+        seller: None,
+        buyer: None,
+        arbitrator: None,
+        amount: None,
+        fee: None,
+        deposit_deadline: None,
+        fiat_deadline: None,
+        sequential: None,
+        sequential_escrow_address: None,
+        state: None,
+        fiat_paid: false,
+        fiat_paid_at: None,
+        counter: 0,
+        tracked_balance: 0,
+        version: None,
+        created_at: None,
+        updated_at: timestamp,
+        last_signature: String::new(),
+        last_slot: 0,
+    });
+    escrow.updated_at = timestamp;
+    escrow.last_signature.clone_from(&logged.signature);
+    escrow.last_slot = logged.slot;
+
+    match &logged.event {
+        Event::EscrowCreated(e) => {
+            escrow.seller = Some(e.seller);
+            escrow.buyer = Some(e.buyer);
+            escrow.arbitrator = Some(e.arbitrator);
+            escrow.amount = Some(e.amount);
+            escrow.fee = Some(e.fee);
+            escrow.deposit_deadline = Some(e.deposit_deadline);
+            escrow.fiat_deadline = Some(e.fiat_deadline);
+            escrow.sequential = Some(e.sequential);
+            escrow.sequential_escrow_address = e.sequential_escrow_address;
+            escrow.state = Some(EscrowState::Created);
+            escrow.created_at = Some(e.timestamp);
+        }
+        Event::FundsDeposited(e) => {
+            escrow.state = Some(EscrowState::Funded);
+            escrow.counter = e.counter;
+        }
+        Event::FiatMarkedPaid(e) => {
+            escrow.fiat_paid = true;
+            escrow.fiat_paid_at = Some(e.timestamp);
+        }
+        Event::EscrowReleased(e) => {
+            escrow.state = Some(EscrowState::Released);
+            escrow.counter = e.counter;
+        }
+        Event::EscrowCancelled(e) => {
+            escrow.state = Some(EscrowState::Cancelled);
+            escrow.counter = e.counter;
+        }
+        Event::DisputeOpened(e) => {
+            escrow.state = Some(EscrowState::Disputed);
+            projection.dispute = Some(DisputeRow {
+                escrow: address,
+                status: DisputeStatus::Opened,
+                initiator: e.disputing_party,
+                opened_at: e.timestamp,
+                initiator_evidence_hash: e.evidence_hash,
+                initiator_bond: e.bond_amount,
+                responder: None,
+                responded_at: None,
+                responder_evidence_hash: None,
+                responder_bond: None,
+                decision: None,
+                winner: None,
+                defaulting_party: None,
+                resolution_hash: None,
+                explanation_reference: None,
+                resolved_at: None,
+            });
+        }
+        Event::DisputeResponseSubmitted(e) => {
+            if let Some(dispute) = &mut projection.dispute {
+                dispute.status = DisputeStatus::Responded;
+                dispute.responder = Some(e.responding_party);
+                dispute.responded_at = Some(e.timestamp);
+                dispute.responder_evidence_hash = Some(e.evidence_hash);
+                dispute.responder_bond = Some(e.bond_amount);
+            }
+        }
+        Event::DisputeResolved(e) => {
+            escrow.state = Some(EscrowState::Resolved);
+            escrow.counter = e.counter;
+            if let Some(dispute) = &mut projection.dispute {
+                dispute.status = DisputeStatus::Resolved;
+                dispute.decision = Some(e.decision);
+                dispute.winner = Some(e.winner);
+                dispute.resolution_hash = Some(e.resolution_hash);
+                dispute.explanation_reference = Some(e.explanation_reference.clone());
+                dispute.resolved_at = Some(e.timestamp);
+            }
+        }
+        Event::DisputeDefaultJudgment(e) => {
+            escrow.state = Some(EscrowState::Resolved);
+            if let Some(dispute) = &mut projection.dispute {
+                dispute.status = DisputeStatus::Defaulted;
+                dispute.decision = Some(e.decision);
+                dispute.defaulting_party = Some(e.defaulting_party);
+                dispute.resolved_at = Some(e.timestamp);
+            }
+        }
+        // Emitted after every mutation since schema version 1; authoritative for the
+        // state and counter
+        Event::EscrowStateChanged(e) => {
+            escrow.state = Some(e.to);
+            escrow.counter = e.counter;
+        }
+        Event::EscrowBalanceChanged(e) => {
+            escrow.tracked_balance = e.new_balance;
+            projection.balances.push(BalanceRow {
+                escrow: address,
+                signature: logged.signature.clone(),
+                slot: logged.slot,
+                seq: logged.seq,
+                new_balance: e.new_balance,
+                reason: e.reason,
+                timestamp: e.timestamp,
+            });
+        }
+        Event::SequentialAddressUpdated(e) => {
+            escrow.sequential_escrow_address = Some(e.new_address);
+        }
+        Event::EscrowMigrated(e) => {
+            escrow.version = Some(e.to_version);
+        }
+        Event::EscrowAccepted(_)
+        | Event::BuyerDepositPosted(_)
+        | Event::BuyerDepositSettled(_)
+        | Event::CancellationPenaltyPaid(_)
+        | Event::FiatRateChecked(_)
+        | Event::PaymentDetailsPosted(_)
+        | Event::ChatRootCommitted(_)
+        | Event::ChatMessageProven(_)
+        | Event::BondAccountReclaimed(_)
+        | Event::BalanceDiscrepancy(_)
+        | Event::SurplusSwept(_) => {}
+    }
+}
+
+// Identity and timestamp of an event. `BondAccountReclaimed` carries no escrow ids, and
+// neither it, the acceptance, buyer deposit, cancellation penalty, oracle, payment details
+// and chat records nor the vault housekeeping events change anything the indexer
+// materializes.
+fn ids(event: &Event) -> Option<(u64, u64, i64)> {
+    Some(match event {
+        Event::EscrowCreated(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::FundsDeposited(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::FiatMarkedPaid(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowReleased(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowCancelled(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::DisputeOpened(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::DisputeResponseSubmitted(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::DisputeResolved(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::DisputeDefaultJudgment(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowStateChanged(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowBalanceChanged(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::SequentialAddressUpdated(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowMigrated(e) => (e.escrow_id, e.trade_id, e.timestamp),
+        Event::EscrowAccepted(_)
+        | Event::BuyerDepositPosted(_)
+        | Event::BuyerDepositSettled(_)
+        | Event::CancellationPenaltyPaid(_)
+        | Event::FiatRateChecked(_)
+        | Event::PaymentDetailsPosted(_)
+        | Event::ChatRootCommitted(_)
+        | Event::ChatMessageProven(_)
+        | Event::BondAccountReclaimed(_)
+        | Event::BalanceDiscrepancy(_)
+        | Event::SurplusSwept(_) => return None,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use localsolana_contracts_client::events::{
+        DisputeDefaultJudgment, DisputeOpened, EscrowBalanceChanged, EscrowCreated, FiatMarkedPaid,
+        FundsDeposited,
+    };
+    use localsolana_contracts_client::Rounding;
+
+    pub(crate) struct History {
+        pub escrow: Pubkey,
+        pub seller: Pubkey,
+        pub buyer: Pubkey,
+        slot: u64,
+    }
+
+    impl History {
+        pub(crate) fn new() -> Self {
+            History {
+                escrow: Pubkey::new_unique(),
+                seller: Pubkey::new_unique(),
+                buyer: Pubkey::new_unique(),
+                slot: 100,
+            }
+        }
+
+        pub(crate) fn created(&self) -> Event {
+            Event::from(EscrowCreated {
+                schema_version: 1,
+                object_id: self.escrow,
+                escrow_id: 7,
+                trade_id: 8,
+                seller: self.seller,
+                buyer: self.buyer,
+                arbitrator: Pubkey::new_unique(),
+                amount: 1_000_000,
+                fee: 10_000,
+                deposit_deadline: 1_900,
+                fiat_deadline: 0,
+                sequential: false,
+                sequential_escrow_address: None,
+                timestamp: 1_000,
+                rounding: Rounding::Floor,
+                min_fee_applied: false,
+                fiat_terms: None,
+                requires_acceptance: false,
+                min_fee: 0,
+                requires_buyer_deposit: false,
+            })
+        }
+
+        pub(crate) fn funded(&self) -> Vec<Event> {
+            vec![
+                Event::from(FundsDeposited {
+                    schema_version: 1,
+                    object_id: self.escrow,
+                    escrow_id: 7,
+                    trade_id: 8,
+                    amount: 1_000_000,
+                    fee: 10_000,
+                    counter: 1,
+                    timestamp: 1_100,
+                }),
+                Event::from(EscrowBalanceChanged {
+                    schema_version: 1,
+                    object_id: self.escrow,
+                    escrow_id: 7,
+                    trade_id: 8,
+                    new_balance: 1_010_000,
+                    reason: BalanceChangeReason::Funded,
+                    timestamp: 1_100,
+                }),
+            ]
+        }
+
+        pub(crate) fn fiat_paid(&self) -> Event {
+            Event::from(FiatMarkedPaid {
+                schema_version: 1,
+                object_id: self.escrow,
+                escrow_id: 7,
+                trade_id: 8,
+                timestamp: 1_200,
+            })
+        }
+
+        pub(crate) fn disputed(&self) -> Event {
+            Event::from(DisputeOpened {
+                schema_version: 1,
+                object_id: self.escrow,
+                escrow_id: 7,
+                trade_id: 8,
+                disputing_party: self.buyer,
+                timestamp: 1_300,
+                evidence_hash: [1; 32],
+                bond_amount: 50_000,
+                rounding: Rounding::Floor,
+                min_bond_applied: false,
+                min_bond: 0,
+            })
+        }
+
+        pub(crate) fn defaulted(&self) -> Vec<Event> {
+            vec![
+                Event::from(DisputeDefaultJudgment {
+                    schema_version: 1,
+                    object_id: self.escrow,
+                    escrow_id: 7,
+                    trade_id: 8,
+                    defaulting_party: self.seller,
+                    decision: true,
+                    timestamp: 300_000,
+                }),
+                Event::from(EscrowBalanceChanged {
+                    schema_version: 1,
+                    object_id: self.escrow,
+                    escrow_id: 7,
+                    trade_id: 8,
+                    new_balance: 0,
+                    reason: BalanceChangeReason::DefaultJudgment,
+                    timestamp: 300_000,
+                }),
+            ]
+        }
+
+        /// Wraps the events of one transaction.
+        pub(crate) fn tx(&mut self, events: Vec<Event>) -> Vec<LoggedEvent> {
+            self.slot += 1;
+            let signature = format!("sig{}", self.slot);
+            events
+                .into_iter()
+                .enumerate()
+                .map(|(seq, event)| LoggedEvent {
+                    signature: signature.clone(),
+                    slot: self.slot,
+                    seq: seq as u32,
+                    event,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn follows_the_trade_leg_states() {
+        let mut h = History::new();
+        let mut events = h.tx(vec![h.created()]);
+        let projection = project(h.escrow, &events);
+        let escrow = projection.escrow.unwrap();
+        assert_eq!(escrow.state_name().as_deref(), Some("CREATED"));
+        assert_eq!(escrow.seller, Some(h.seller));
+
+        events.extend(h.tx(h.funded()));
+        let escrow = project(h.escrow, &events).escrow.unwrap();
+        assert_eq!(escrow.leg_state_name().as_deref(), Some("FUNDED"));
+        assert_eq!(escrow.tracked_balance, 1_010_000);
+        assert_eq!(escrow.counter, 1);
+
+        events.extend(h.tx(vec![h.fiat_paid()]));
+        let escrow = project(h.escrow, &events).escrow.unwrap();
+        assert_eq!(escrow.state_name().as_deref(), Some("FUNDED"));
+        assert_eq!(escrow.leg_state_name().as_deref(), Some("FIAT_PAID"));
+        assert_eq!(escrow.fiat_paid_at, Some(1_200));
+    }
+
+    #[test]
+    fn tracks_dispute_through_default_judgment() {
+        let mut h = History::new();
+        let mut events = h.tx(vec![h.created()]);
+        events.extend(h.tx(h.funded()));
+        events.extend(h.tx(vec![h.disputed()]));
+        let projection = project(h.escrow, &events);
+        assert_eq!(
+            projection.dispute.as_ref().unwrap().status,
+            DisputeStatus::Opened
+        );
+        assert_eq!(
+            projection.escrow.unwrap().state,
+            Some(EscrowState::Disputed)
+        );
+
+        events.extend(h.tx(h.defaulted()));
+        let projection = project(h.escrow, &events);
+        let dispute = projection.dispute.unwrap();
+        assert_eq!(dispute.status, DisputeStatus::Defaulted);
+        assert_eq!(dispute.defaulting_party, Some(h.seller));
+        assert_eq!(dispute.decision, Some(true));
+        assert_eq!(
+            projection.escrow.unwrap().state_name().as_deref(),
+            Some("RESOLVED")
+        );
+        let reasons: Vec<_> = projection.balances.iter().map(|b| b.reason).collect();
+        assert_eq!(
+            reasons,
+            [
+                BalanceChangeReason::Funded,
+                BalanceChangeReason::DefaultJudgment
+            ]
+        );
+    }
+
+    #[test]
+    fn history_starting_mid_trade_leaves_terms_unknown() {
+        let mut h = History::new();
+        let events = h.tx(vec![h.fiat_paid()]);
+        let escrow = project(h.escrow, &events).escrow.unwrap();
+        assert_eq!((escrow.escrow_id, escrow.trade_id), (7, 8));
+        assert_eq!(escrow.seller, None);
+        assert_eq!(escrow.state, None);
+        assert!(escrow.fiat_paid);
+    }
+}
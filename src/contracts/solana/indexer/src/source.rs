@@ -0,0 +1,185 @@
+//! RPC access: listing the program's transactions and fetching them in a form the
+//! decoder understands.
+
+use anyhow::{anyhow, Context, Result};
+use localsolana_contracts_client::ID;
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::{
+    TransactionConfirmationStatus, UiInstruction, UiLoadedAddresses, UiTransactionEncoding,
+};
+
+use crate::decode::{InnerInstruction, RawTransaction};
+
+// Maximum page size of getSignaturesForAddress
+const PAGE_SIZE: usize = 1_000;
+
+pub struct Source {
+    rpc: RpcClient,
+}
+
+/// A successful program transaction as listed by `getSignaturesForAddress`.
+#[derive(Clone, Debug)]
+pub struct Listed {
+    pub signature: String,
+    pub slot: u64,
+    pub finalized: bool,
+}
+
+/// Finality of a previously indexed transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Finalized,
+    Pending,
+    /// Unknown to the cluster or failed: it was on a fork that did not survive
+    Dropped,
+}
+
+impl Source {
+    pub fn new(url: &str) -> Self {
+        Source {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+        }
+    }
+
+    /// Successful transactions of the program newer than `until` and at or after
+    /// `min_slot`, oldest first.
+    pub fn list(&self, until: Option<&str>, min_slot: u64) -> Result<Vec<Listed>> {
+        let until = until.map(str::parse::<Signature>).transpose()?;
+        let mut before = None;
+        let mut listed = Vec::new();
+        'pages: loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &ID,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(PAGE_SIZE),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .context("listing program signatures")?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(last.signature.parse()?);
+            let full = page.len() == PAGE_SIZE;
+            for entry in page {
+                if entry.slot < min_slot {
+                    break 'pages;
+                }
+                // Failed transactions changed nothing; their events were rolled back
+                if entry.err.is_some() {
+                    continue;
+                }
+                listed.push(Listed {
+                    signature: entry.signature,
+                    slot: entry.slot,
+                    finalized: entry.confirmation_status
+                        == Some(TransactionConfirmationStatus::Finalized),
+                });
+            }
+            if !full {
+                break;
+            }
+        }
+        listed.reverse();
+        Ok(listed)
+    }
+
+    pub fn transaction(&self, signature: &str) -> Result<RawTransaction> {
+        let parsed: Signature = signature.parse()?;
+        let confirmed = self
+            .rpc
+            .get_transaction_with_config(
+                &parsed,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .with_context(|| format!("fetching transaction {signature}"))?;
+        let transaction = confirmed
+            .transaction
+            .transaction
+            .decode()
+            .ok_or_else(|| anyhow!("cannot decode transaction {signature}"))?;
+        let meta = confirmed
+            .transaction
+            .meta
+            .ok_or_else(|| anyhow!("transaction {signature} has no status meta"))?;
+
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+            for address in loaded.writable.iter().chain(&loaded.readonly) {
+                account_keys.push(address.parse::<Pubkey>()?);
+            }
+        }
+
+        let mut inner_instructions = Vec::new();
+        let mut groups: Vec<_> = Option::from(meta.inner_instructions).unwrap_or_default();
+        groups.sort_by_key(|group| group.index);
+        for group in groups {
+            for ix in group.instructions {
+                if let UiInstruction::Compiled(ix) = ix {
+                    inner_instructions.push(InnerInstruction {
+                        program_id_index: ix.program_id_index,
+                        data: bs58::decode(&ix.data).into_vec()?,
+                    });
+                }
+            }
+        }
+
+        Ok(RawTransaction {
+            signature: signature.to_string(),
+            slot: confirmed.slot,
+            block_time: confirmed.block_time,
+            account_keys,
+            inner_instructions,
+            logs: Option::from(meta.log_messages).unwrap_or_default(),
+        })
+    }
+
+    pub fn finalized_slot(&self) -> Result<u64> {
+        self.rpc
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .context("fetching finalized slot")
+    }
+
+    /// Finality of `signatures`, which were indexed at `slot`s the cluster has since
+    /// finalized.
+    pub fn statuses(&self, signatures: &[(String, u64)]) -> Result<Vec<Status>> {
+        let mut out = Vec::with_capacity(signatures.len());
+        // getSignatureStatuses takes at most 256 signatures
+        for chunk in signatures.chunks(256) {
+            let parsed = chunk
+                .iter()
+                .map(|(signature, _)| signature.parse())
+                .collect::<Result<Vec<Signature>, _>>()?;
+            let statuses = self
+                .rpc
+                .get_signature_statuses_with_history(&parsed)
+                .context("fetching signature statuses")?
+                .value;
+            for ((_, slot), status) in chunk.iter().zip(statuses) {
+                out.push(match status {
+                    None => Status::Dropped,
+                    Some(status) if status.err.is_some() => Status::Dropped,
+                    // Landed again in another block after its fork was abandoned
+                    Some(status) if status.slot != *slot => Status::Dropped,
+                    Some(status) if status.satisfies_commitment(CommitmentConfig::finalized()) => {
+                        Status::Finalized
+                    }
+                    Some(_) => Status::Pending,
+                });
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,581 @@
+//! Persistence: the raw event log plus the escrow, dispute and balance-history rows
+//! projected from it.
+//!
+//! Every indexed transaction and its decoded events are kept in `indexed_transactions`
+//! and `indexed_events`. The materialized tables are derived data: whenever the event
+//! log of an escrow changes, its rows are deleted and projected again from the log.
+//! Transactions stay unfinalized until the cluster finalizes their slot; one that was
+//! dropped on a fork is removed with `remove`, which rolls its escrows back.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use localsolana_contracts_client::Event;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::db::{Database, Value};
+use crate::projection::{self, LoggedEvent, Projection};
+
+/// A transaction with the events decoded from it, ready to store.
+#[derive(Clone, Debug)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub finalized: bool,
+    pub events: Vec<Event>,
+}
+
+pub struct Store<D> {
+    db: D,
+}
+
+impl<D: Database> Store<D> {
+    /// Wraps `db`, creating the tables if needed.
+    pub fn new(mut db: D) -> Result<Self> {
+        let dialect = db.dialect();
+        db.batch(
+            &SCHEMA
+                .replace("{SERIAL_KEY}", dialect.serial_key())
+                .replace("{BLOB}", dialect.blob()),
+        )
+        .context("creating tables")?;
+        Ok(Store { db })
+    }
+
+    pub fn database(&mut self) -> &mut D {
+        &mut self.db
+    }
+
+    pub fn contains(&mut self, signature: &str) -> Result<bool> {
+        let rows = self.db.query(
+            "SELECT 1 FROM indexed_transactions WHERE signature = $1",
+            &[text(signature)],
+        )?;
+        Ok(!rows.is_empty())
+    }
+
+    /// Stores `tx` and updates the escrows it touched. Returns `false` if it was
+    /// already indexed.
+    pub fn insert(&mut self, tx: &IndexedTransaction) -> Result<bool> {
+        self.atomically(|store| {
+            if store.contains(&tx.signature)? {
+                return Ok(false);
+            }
+            store.db.execute(
+                "INSERT INTO indexed_transactions (signature, slot, block_time, finalized) \
+                 VALUES ($1, $2, $3, $4)",
+                &[
+                    text(&tx.signature),
+                    int(tx.slot),
+                    Value::Int(tx.block_time),
+                    Value::Bool(Some(tx.finalized)),
+                ],
+            )?;
+            let id = store.transaction_id(&tx.signature)?;
+            let mut touched = BTreeSet::new();
+            for (seq, event) in tx.events.iter().enumerate() {
+                store.db.execute(
+                    "INSERT INTO indexed_events (transaction_id, seq, escrow, name, data) \
+                     VALUES ($1, $2, $3, $4, $5)",
+                    &[
+                        Value::Int(Some(id)),
+                        int(seq as u64),
+                        key(&event.object_id()),
+                        text(event.name()),
+                        Value::Bytes(Some(event.to_bytes())),
+                    ],
+                )?;
+                touched.insert(event.object_id());
+            }
+            for escrow in touched {
+                store.rebuild(&escrow)?;
+            }
+            Ok(true)
+        })
+    }
+
+    /// Forgets transactions, typically ones dropped on a fork, and rolls back the
+    /// escrows they touched.
+    pub fn remove(&mut self, signatures: &[String]) -> Result<()> {
+        self.atomically(|store| {
+            let mut touched = BTreeSet::new();
+            for signature in signatures {
+                let Some(id) = store.find_transaction_id(signature)? else {
+                    continue;
+                };
+                for row in store.db.query(
+                    "SELECT DISTINCT escrow FROM indexed_events WHERE transaction_id = $1",
+                    &[Value::Int(Some(id))],
+                )? {
+                    touched.insert(parse_key(&row[0])?);
+                }
+                store.db.execute(
+                    "DELETE FROM indexed_events WHERE transaction_id = $1",
+                    &[Value::Int(Some(id))],
+                )?;
+                store.db.execute(
+                    "DELETE FROM indexed_transactions WHERE id = $1",
+                    &[Value::Int(Some(id))],
+                )?;
+            }
+            for escrow in touched {
+                store.rebuild(&escrow)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes everything indexed at or after `slot` so it can be fetched again.
+    /// Returns the number of transactions removed.
+    pub fn truncate_from(&mut self, slot: u64) -> Result<usize> {
+        let signatures = self
+            .db
+            .query(
+                "SELECT signature FROM indexed_transactions WHERE slot >= $1",
+                &[int(slot)],
+            )?
+            .iter()
+            .map(|row| Ok(row[0].text()?.unwrap_or_default().to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        self.remove(&signatures)?;
+        Ok(signatures.len())
+    }
+
+    /// Signatures and slots of transactions not yet known to be finalized.
+    pub fn unfinalized(&mut self) -> Result<Vec<(String, u64)>> {
+        self.db
+            .query(
+                "SELECT signature, slot FROM indexed_transactions WHERE NOT finalized \
+                 ORDER BY slot, id",
+                &[],
+            )?
+            .iter()
+            .map(|row| {
+                Ok((
+                    row[0].text()?.unwrap_or_default().to_string(),
+                    row[1].int()?.unwrap_or_default() as u64,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn mark_finalized(&mut self, signature: &str) -> Result<()> {
+        self.db.execute(
+            "UPDATE indexed_transactions SET finalized = $1 WHERE signature = $2",
+            &[Value::Bool(Some(true)), text(signature)],
+        )?;
+        Ok(())
+    }
+
+    /// Newest finalized transaction; fetching resumes after it. Unfinalized ones after
+    /// it are listed again and skipped if still present.
+    pub fn resume_point(&mut self) -> Result<Option<String>> {
+        let rows = self.db.query(
+            "SELECT signature FROM indexed_transactions WHERE finalized \
+             ORDER BY slot DESC, id DESC LIMIT 1",
+            &[],
+        )?;
+        Ok(match rows.first() {
+            Some(row) => row[0].text()?.map(str::to_string),
+            None => None,
+        })
+    }
+
+    fn atomically<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.db.batch("BEGIN")?;
+        match f(self) {
+            Ok(value) => {
+                self.db.batch("COMMIT")?;
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = self.db.batch("ROLLBACK");
+                Err(err)
+            }
+        }
+    }
+
+    fn find_transaction_id(&mut self, signature: &str) -> Result<Option<i64>> {
+        let rows = self.db.query(
+            "SELECT id FROM indexed_transactions WHERE signature = $1",
+            &[text(signature)],
+        )?;
+        match rows.first() {
+            Some(row) => row[0].int(),
+            None => Ok(None),
+        }
+    }
+
+    fn transaction_id(&mut self, signature: &str) -> Result<i64> {
+        self.find_transaction_id(signature)?
+            .with_context(|| format!("transaction {signature} not stored"))
+    }
+
+    fn rebuild(&mut self, escrow: &Pubkey) -> Result<()> {
+        // Transaction ids follow insertion order, which is chain order within a slot
+        let rows = self.db.query(
+            "SELECT t.signature, t.slot, e.seq, e.data \
+             FROM indexed_events e JOIN indexed_transactions t ON t.id = e.transaction_id \
+             WHERE e.escrow = $1 ORDER BY t.slot, t.id, e.seq",
+            &[key(escrow)],
+        )?;
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            events.push(LoggedEvent {
+                signature: row[0].text()?.unwrap_or_default().to_string(),
+                slot: row[1].int()?.unwrap_or_default() as u64,
+                seq: row[2].int()?.unwrap_or_default() as u32,
+                event: Event::try_from_bytes(row[3].bytes()?.unwrap_or_default())?,
+            });
+        }
+        let projection = projection::project(*escrow, &events);
+
+        for table in [
+            "indexed_escrows",
+            "indexed_disputes",
+            "indexed_balance_history",
+        ] {
+            let column = if table == "indexed_escrows" {
+                "address"
+            } else {
+                "escrow"
+            };
+            self.db.execute(
+                &format!("DELETE FROM {table} WHERE {column} = $1"),
+                &[key(escrow)],
+            )?;
+        }
+        self.write(&projection)
+    }
+
+    fn write(&mut self, projection: &Projection) -> Result<()> {
+        if let Some(e) = &projection.escrow {
+            self.db.execute(
+                "INSERT INTO indexed_escrows (address, escrow_id, trade_id, seller, buyer, \
+                 arbitrator, amount, fee, deposit_deadline, fiat_deadline, sequential, \
+                 sequential_escrow_address, state, leg_state, fiat_paid, fiat_paid_at, counter, \
+                 tracked_balance, version, created_at, updated_at, last_signature, last_slot) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+                 $17, $18, $19, $20, $21, $22, $23)",
+                &[
+                    key(&e.address),
+                    int(e.escrow_id),
+                    int(e.trade_id),
+                    opt_key(e.seller),
+                    opt_key(e.buyer),
+                    opt_key(e.arbitrator),
+                    opt_int(e.amount),
+                    opt_int(e.fee),
+                    Value::Int(e.deposit_deadline),
+                    Value::Int(e.fiat_deadline),
+                    Value::Bool(e.sequential),
+                    opt_key(e.sequential_escrow_address),
+                    Value::Text(e.state_name()),
+                    Value::Text(e.leg_state_name()),
+                    Value::Bool(Some(e.fiat_paid)),
+                    Value::Int(e.fiat_paid_at),
+                    int(e.counter),
+                    int(e.tracked_balance),
+                    Value::Int(e.version.map(i64::from)),
+                    Value::Int(e.created_at),
+                    Value::Int(Some(e.updated_at)),
+                    text(&e.last_signature),
+                    int(e.last_slot),
+                ],
+            )?;
+        }
+        if let Some(d) = &projection.dispute {
+            self.db.execute(
+                "INSERT INTO indexed_disputes (escrow, status, initiator, opened_at, \
+                 initiator_evidence_hash, initiator_bond, responder, responded_at, \
+                 responder_evidence_hash, responder_bond, decision, winner, defaulting_party, \
+                 resolution_hash, explanation_reference, resolved_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
+                &[
+                    key(&d.escrow),
+                    text(d.status.as_str()),
+                    key(&d.initiator),
+                    Value::Int(Some(d.opened_at)),
+                    Value::Text(Some(hex(&d.initiator_evidence_hash))),
+                    int(d.initiator_bond),
+                    opt_key(d.responder),
+                    Value::Int(d.responded_at),
+                    Value::Text(d.responder_evidence_hash.as_ref().map(|h| hex(h))),
+                    opt_int(d.responder_bond),
+                    Value::Bool(d.decision),
+                    opt_key(d.winner),
+                    opt_key(d.defaulting_party),
+                    Value::Text(d.resolution_hash.as_ref().map(|h| hex(h))),
+                    Value::Text(d.explanation_reference.clone()),
+                    Value::Int(d.resolved_at),
+                ],
+            )?;
+        }
+        for b in &projection.balances {
+            self.db.execute(
+                "INSERT INTO indexed_balance_history (escrow, signature, seq, slot, \
+                 new_balance, reason, timestamp) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    key(&b.escrow),
+                    text(&b.signature),
+                    int(u64::from(b.seq)),
+                    int(b.slot),
+                    int(b.new_balance),
+                    text(b.reason.as_str()),
+                    Value::Int(Some(b.timestamp)),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS indexed_transactions (
+    id {SERIAL_KEY},
+    signature TEXT NOT NULL UNIQUE,
+    slot BIGINT NOT NULL,
+    block_time BIGINT,
+    finalized BOOLEAN NOT NULL
+);
+CREATE INDEX IF NOT EXISTS indexed_transactions_slot ON indexed_transactions (slot);
+
+CREATE TABLE IF NOT EXISTS indexed_events (
+    transaction_id BIGINT NOT NULL,
+    seq BIGINT NOT NULL,
+    escrow TEXT NOT NULL,
+    name TEXT NOT NULL,
+    data {BLOB} NOT NULL,
+    PRIMARY KEY (transaction_id, seq)
+);
+CREATE INDEX IF NOT EXISTS indexed_events_escrow ON indexed_events (escrow);
+
+CREATE TABLE IF NOT EXISTS indexed_escrows (
+    address TEXT PRIMARY KEY,
+    escrow_id BIGINT NOT NULL,
+    trade_id BIGINT NOT NULL,
+    seller TEXT,
+    buyer TEXT,
+    arbitrator TEXT,
+    amount BIGINT,
+    fee BIGINT,
+    deposit_deadline BIGINT,
+    fiat_deadline BIGINT,
+    sequential BOOLEAN,
+    sequential_escrow_address TEXT,
+    state TEXT,
+    leg_state TEXT,
+    fiat_paid BOOLEAN NOT NULL,
+    fiat_paid_at BIGINT,
+    counter BIGINT NOT NULL,
+    tracked_balance BIGINT NOT NULL,
+    version BIGINT,
+    created_at BIGINT,
+    updated_at BIGINT NOT NULL,
+    last_signature TEXT NOT NULL,
+    last_slot BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS indexed_escrows_ids ON indexed_escrows (escrow_id, trade_id);
+
+CREATE TABLE IF NOT EXISTS indexed_disputes (
+    escrow TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    initiator TEXT NOT NULL,
+    opened_at BIGINT NOT NULL,
+    initiator_evidence_hash TEXT NOT NULL,
+    initiator_bond BIGINT NOT NULL,
+    responder TEXT,
+    responded_at BIGINT,
+    responder_evidence_hash TEXT,
+    responder_bond BIGINT,
+    decision BOOLEAN,
+    winner TEXT,
+    defaulting_party TEXT,
+    resolution_hash TEXT,
+    explanation_reference TEXT,
+    resolved_at BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS indexed_balance_history (
+    escrow TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    seq BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    new_balance BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY (escrow, signature, seq)
+);
+";
+
+fn text(value: &str) -> Value {
+    Value::Text(Some(value.to_string()))
+}
+
+// Token amounts, slots and counters stay far below i64::MAX, so BIGINT holds them
+fn int(value: u64) -> Value {
+    Value::Int(Some(value as i64))
+}
+
+fn opt_int(value: Option<u64>) -> Value {
+    Value::Int(value.map(|value| value as i64))
+}
+
+fn key(value: &Pubkey) -> Value {
+    Value::Text(Some(value.to_string()))
+}
+
+fn opt_key(value: Option<Pubkey>) -> Value {
+    Value::Text(value.map(|value| value.to_string()))
+}
+
+fn parse_key(value: &Value) -> Result<Pubkey> {
+    let text = value.text()?.context("missing escrow address")?;
+    text.parse()
+        .with_context(|| format!("invalid escrow address {text}"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Sqlite;
+    use crate::projection::tests::History;
+
+    fn store() -> Store<Sqlite> {
+        Store::new(Sqlite::in_memory().unwrap()).unwrap()
+    }
+
+    fn tx(signature: &str, slot: u64, events: Vec<Event>) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot,
+            block_time: Some(1_700_000_000),
+            finalized: false,
+            events,
+        }
+    }
+
+    fn escrow_state(store: &mut Store<Sqlite>, escrow: &Pubkey) -> Option<(String, i64)> {
+        let rows = store
+            .database()
+            .query(
+                "SELECT leg_state, tracked_balance FROM indexed_escrows WHERE address = $1",
+                &[key(escrow)],
+            )
+            .unwrap();
+        rows.first().map(|row| {
+            (
+                row[0].text().unwrap().unwrap().to_string(),
+                row[1].int().unwrap().unwrap(),
+            )
+        })
+    }
+
+    fn count(store: &mut Store<Sqlite>, table: &str) -> i64 {
+        store
+            .database()
+            .query(&format!("SELECT COUNT(*) FROM {table}"), &[])
+            .unwrap()[0][0]
+            .int()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn materializes_escrow_dispute_and_balances() {
+        let h = History::new();
+        let mut store = store();
+        assert!(store.insert(&tx("a", 10, vec![h.created()])).unwrap());
+        assert!(store.insert(&tx("b", 11, h.funded())).unwrap());
+        assert_eq!(
+            escrow_state(&mut store, &h.escrow),
+            Some(("FUNDED".to_string(), 1_010_000))
+        );
+        store.insert(&tx("c", 12, vec![h.fiat_paid()])).unwrap();
+        store.insert(&tx("d", 13, vec![h.disputed()])).unwrap();
+        store.insert(&tx("e", 14, h.defaulted())).unwrap();
+
+        assert_eq!(
+            escrow_state(&mut store, &h.escrow),
+            Some(("RESOLVED".to_string(), 0))
+        );
+        let dispute = store
+            .database()
+            .query(
+                "SELECT status, defaulting_party, decision FROM indexed_disputes",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(dispute[0][0].text().unwrap(), Some("DEFAULTED"));
+        assert_eq!(
+            dispute[0][1].text().unwrap(),
+            Some(h.seller.to_string().as_str())
+        );
+        assert_eq!(dispute[0][2].int().unwrap(), Some(1));
+        assert_eq!(count(&mut store, "indexed_balance_history"), 2);
+    }
+
+    #[test]
+    fn inserting_twice_is_a_no_op() {
+        let h = History::new();
+        let mut store = store();
+        let created = tx("a", 10, vec![h.created()]);
+        assert!(store.insert(&created).unwrap());
+        assert!(!store.insert(&created).unwrap());
+        assert_eq!(count(&mut store, "indexed_events"), 1);
+    }
+
+    #[test]
+    fn removing_a_forked_transaction_rolls_the_escrow_back() {
+        let h = History::new();
+        let mut store = store();
+        store.insert(&tx("a", 10, vec![h.created()])).unwrap();
+        store.insert(&tx("b", 11, h.funded())).unwrap();
+        store.insert(&tx("c", 12, vec![h.fiat_paid()])).unwrap();
+
+        store.remove(&["c".to_string()]).unwrap();
+        assert_eq!(
+            escrow_state(&mut store, &h.escrow),
+            Some(("FUNDED".to_string(), 1_010_000))
+        );
+        store.remove(&["a".to_string(), "b".to_string()]).unwrap();
+        assert_eq!(escrow_state(&mut store, &h.escrow), None);
+        assert_eq!(count(&mut store, "indexed_balance_history"), 0);
+    }
+
+    #[test]
+    fn truncating_from_a_slot_allows_replay() {
+        let h = History::new();
+        let mut store = store();
+        store.insert(&tx("a", 10, vec![h.created()])).unwrap();
+        store.insert(&tx("b", 11, h.funded())).unwrap();
+        assert_eq!(store.truncate_from(11).unwrap(), 1);
+        assert_eq!(
+            escrow_state(&mut store, &h.escrow),
+            Some(("CREATED".to_string(), 0))
+        );
+        assert!(store.insert(&tx("b", 11, h.funded())).unwrap());
+        assert_eq!(
+            escrow_state(&mut store, &h.escrow),
+            Some(("FUNDED".to_string(), 1_010_000))
+        );
+    }
+
+    #[test]
+    fn resumes_after_newest_finalized_transaction() {
+        let h = History::new();
+        let mut store = store();
+        assert_eq!(store.resume_point().unwrap(), None);
+        store.insert(&tx("a", 10, vec![h.created()])).unwrap();
+        store.insert(&tx("b", 11, h.funded())).unwrap();
+        assert_eq!(store.unfinalized().unwrap().len(), 2);
+        store.mark_finalized("a").unwrap();
+        assert_eq!(store.resume_point().unwrap().as_deref(), Some("a"));
+        assert_eq!(store.unfinalized().unwrap(), vec![("b".to_string(), 11)]);
+    }
+}
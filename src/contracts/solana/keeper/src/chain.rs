@@ -0,0 +1,216 @@
+//! RPC access with retries: scanning escrows, reading the cluster clock and sending
+//! transactions.
+
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use localsolana_contracts_client::pda::find_escrow_token_address;
+use localsolana_contracts_client::token::TokenAccount;
+use localsolana_contracts_client::{Escrow, EscrowError, ID};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as ClientError;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::clock::Clock;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use crate::metrics::Metrics;
+
+pub struct Chain {
+    rpc: RpcClient,
+    signer: Keypair,
+    max_retries: u32,
+    metrics: Arc<Metrics>,
+}
+
+/// Result of a transaction the cluster processed.
+pub enum Submission {
+    Confirmed(Signature),
+    /// The program or runtime rejected it; resending the same transaction will not help
+    Rejected(String),
+}
+
+// ClientError is large, but it is what the RPC client returns
+#[allow(clippy::result_large_err)]
+impl Chain {
+    pub fn new(url: &str, keypair: &Path, max_retries: u32, metrics: Arc<Metrics>) -> Result<Self> {
+        let signer = read_keypair_file(keypair)
+            .map_err(|err| anyhow!("cannot read keypair {}: {err}", keypair.display()))?;
+        Ok(Chain {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            signer,
+            max_retries,
+            metrics,
+        })
+    }
+
+    pub fn signer(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    // Retries RPC failures with exponential backoff. Errors carrying a transaction error
+    // are final and returned straight away.
+    fn retry<T>(&self, what: &str, mut call: impl FnMut() -> Result<T, ClientError>) -> Result<T> {
+        let mut delay = Duration::from_millis(500);
+        let mut attempt = 0;
+        loop {
+            match call() {
+                Ok(value) => return Ok(value),
+                Err(err) if err.get_transaction_error().is_none() && attempt < self.max_retries => {
+                    attempt += 1;
+                    self.metrics.rpc_retries.fetch_add(1, Ordering::Relaxed);
+                    eprintln!(
+                        "warning: {what} failed ({err}), retry {attempt}/{} in {delay:?}",
+                        self.max_retries
+                    );
+                    thread::sleep(delay);
+                    delay = (delay * 2).min(Duration::from_secs(30));
+                }
+                Err(err) => return Err(anyhow!(err).context(what.to_string())),
+            }
+        }
+    }
+
+    /// Every escrow account owned by the program.
+    pub fn escrows(&self) -> Result<Vec<Escrow>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                Escrow::discriminator().to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self.retry("fetching program accounts", || {
+            self.rpc
+                .get_program_accounts_with_config(&ID, config.clone())
+        })?;
+
+        let mut escrows = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            match Escrow::try_from_account_data(&account.data) {
+                Ok(escrow) => escrows.push(escrow),
+                Err(err) => eprintln!("warning: skipping {address}: {err}"),
+            }
+        }
+        Ok(escrows)
+    }
+
+    /// Unix time of the cluster, which is what the program compares deadlines against.
+    /// Wall-clock time on this host can be ahead of it.
+    pub fn cluster_time(&self) -> Result<i64> {
+        let account = self.retry("fetching clock sysvar", || {
+            self.rpc.get_account(&sysvar::clock::ID)
+        })?;
+        let clock: Clock = bincode_clock(&account.data)?;
+        Ok(clock.unix_timestamp)
+    }
+
+    pub fn account_exists(&self, address: &Pubkey) -> Result<bool> {
+        let account = self.retry("fetching account", || {
+            self.rpc
+                .get_account_with_commitment(address, self.rpc.commitment())
+        })?;
+        Ok(account.value.is_some())
+    }
+
+    /// Mint of the escrow vault, `None` if the escrow was never funded.
+    pub fn vault_mint(&self, escrow: &Pubkey) -> Result<Option<Pubkey>> {
+        let vault = find_escrow_token_address(escrow).0;
+        let account = self.retry("fetching escrow vault", || {
+            self.rpc
+                .get_account_with_commitment(&vault, self.rpc.commitment())
+        })?;
+        account
+            .value
+            .map(|account| {
+                TokenAccount::try_from_account_data(&account.data).map(|vault| vault.mint)
+            })
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Sends `ixs` in one transaction signed and paid for by the keeper.
+    ///
+    /// A fresh blockhash is fetched on every attempt, so a retry after an expired
+    /// blockhash sends a new transaction rather than replaying a stale one.
+    pub fn submit(&self, ixs: &[Instruction]) -> Result<Submission> {
+        let result = self.retry("sending transaction", || {
+            let blockhash = self.rpc.get_latest_blockhash()?;
+            let tx = Transaction::new_signed_with_payer(
+                ixs,
+                Some(&self.signer.pubkey()),
+                &[&self.signer],
+                blockhash,
+            );
+            self.rpc.send_and_confirm_transaction(&tx)
+        });
+        match result {
+            Ok(signature) => Ok(Submission::Confirmed(signature)),
+            Err(err) => match err
+                .downcast_ref::<ClientError>()
+                .and_then(ClientError::get_transaction_error)
+            {
+                Some(tx_err) => Ok(Submission::Rejected(describe(&tx_err))),
+                None => Err(err),
+            },
+        }
+    }
+}
+
+fn bincode_clock(data: &[u8]) -> Result<Clock> {
+    // Clock is five little-endian 8-byte fields
+    let field = |i: usize| -> Result<[u8; 8]> {
+        data.get(i * 8..i * 8 + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("clock sysvar too short")
+    };
+    Ok(Clock {
+        slot: u64::from_le_bytes(field(0)?),
+        epoch_start_timestamp: i64::from_le_bytes(field(1)?),
+        epoch: u64::from_le_bytes(field(2)?),
+        leader_schedule_epoch: u64::from_le_bytes(field(3)?),
+        unix_timestamp: i64::from_le_bytes(field(4)?),
+    })
+}
+
+// Maps a custom program error code back to the EscrowError name and message
+fn describe(err: &TransactionError) -> String {
+    if let TransactionError::InstructionError(_, ix_err @ InstructionError::Custom(_)) = err {
+        if let Some(escrow_err) = EscrowError::from_instruction_error(ix_err) {
+            return escrow_err.to_string();
+        }
+    }
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_clock_sysvar() {
+        let mut data = Vec::new();
+        for value in [42u64, 1_000, 3, 4, 1_700_000_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        let clock = bincode_clock(&data).unwrap();
+        assert_eq!(clock.slot, 42);
+        assert_eq!(clock.unix_timestamp, 1_700_000_000);
+        assert!(bincode_clock(&data[..32]).is_err());
+    }
+}
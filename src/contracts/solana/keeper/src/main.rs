@@ -0,0 +1,332 @@
+//! Keeper service.
+//!
+//! Periodically scans every escrow account, works out from the cluster clock which
+//! escrows are past a deadline (see `planner`) and submits `auto_cancel` or
+//! `default_judgment` for them. RPC failures are retried with backoff; attempts are
+//! tracked per escrow update counter so a scan never resubmits while an earlier
+//! transaction may still land (see `tracker`). Prometheus metrics are served on
+//! `--metrics-addr`.
+//!
+//! `auto_cancel` only accepts the hardcoded arbitrator key, so with any other keypair
+//! the keeper only applies default judgments. For a missing bond account the keeper
+//! initializes it in the same transaction; it pays the rent and is refunded when
+//! `default_judgment` closes the account.
+//!
+//! Defaults target a local test validator; `--once` runs a single scan and exits, which
+//! is how the keeper is exercised against `solana-test-validator`.
+
+mod chain;
+mod metrics;
+mod planner;
+mod tracker;
+
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use localsolana_contracts_client::constants::ARBITRATOR;
+use localsolana_contracts_client::instructions::{
+    AutoCancel, DefaultJudgment, InitializeBuyerBondAccount, InitializeSellerBondAccount,
+};
+use localsolana_contracts_client::pda::{
+    find_associated_token_address, find_buyer_bond_address, find_seller_bond_address,
+};
+use localsolana_contracts_client::{Escrow, EscrowState};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::chain::{Chain, Submission};
+use crate::metrics::Metrics;
+use crate::planner::{Action, Plan};
+use crate::tracker::{Outcome, Tracker};
+
+#[derive(Parser)]
+#[command(
+    name = "keeper",
+    version,
+    about = "Auto-cancel expired escrows and apply default judgments"
+)]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        env = "SOLANA_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Fee payer and signer; must be the arbitrator for auto_cancel
+    #[arg(long, short = 'k', env = "KEEPER_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Seconds between scans
+    #[arg(long, default_value_t = 30)]
+    interval: u64,
+
+    /// Address for the Prometheus endpoint
+    #[arg(long, env = "KEEPER_METRICS_ADDR", default_value = "127.0.0.1:9464")]
+    metrics_addr: SocketAddr,
+
+    /// Retries for a failed RPC call before giving up on it for this scan
+    #[arg(long, default_value_t = 5)]
+    max_retries: u32,
+
+    /// Seconds before resubmitting for an escrow whose counter has not changed
+    #[arg(long, default_value_t = 120)]
+    resubmit_after: u64,
+
+    /// Seconds before retrying an escrow after the program rejected the transaction
+    #[arg(long, default_value_t = 300)]
+    rejected_cooldown: u64,
+
+    /// Log what would be submitted without sending anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Run a single scan and exit
+    #[arg(long)]
+    once: bool,
+}
+
+struct Keeper {
+    chain: Chain,
+    tracker: Tracker,
+    metrics: Arc<Metrics>,
+    can_auto_cancel: bool,
+    dry_run: bool,
+}
+
+impl Keeper {
+    fn scan(&mut self) -> Result<()> {
+        let now = self.chain.cluster_time()?;
+        let escrows = self.chain.escrows()?;
+        self.metrics
+            .escrows_scanned
+            .store(escrows.len() as u64, Ordering::Relaxed);
+
+        let mut actionable = BTreeMap::new();
+        let mut waiting = BTreeMap::new();
+        let mut due = Vec::new();
+        for escrow in escrows {
+            match planner::plan(&escrow, now) {
+                Plan::Act(action) => {
+                    *actionable.entry(action.as_str()).or_default() += 1;
+                    due.push((escrow, action));
+                }
+                Plan::Wait(reason) => *waiting.entry(reason.as_str()).or_default() += 1,
+                Plan::Terminal => {}
+            }
+        }
+        self.metrics.set_actionable(actionable);
+        self.metrics.set_waiting(waiting);
+
+        let due_keys: HashSet<Pubkey> = due.iter().map(|(escrow, _)| escrow.address()).collect();
+        self.tracker.retain(|escrow| due_keys.contains(escrow));
+
+        for (escrow, action) in due {
+            if action == Action::AutoCancel && !self.can_auto_cancel {
+                continue;
+            }
+            let address = escrow.address();
+            if !self
+                .tracker
+                .should_submit(&address, escrow.counter, action, Instant::now())
+            {
+                continue;
+            }
+            // One failing escrow must not hold up the rest of the scan
+            if let Err(err) = self.execute(&escrow, action) {
+                self.metrics.record_submission(action.as_str(), "error");
+                eprintln!("error: {} {address}: {err:#}", action.as_str());
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, escrow: &Escrow, action: Action) -> Result<()> {
+        let address = escrow.address();
+        let ixs = match action {
+            Action::AutoCancel => self.auto_cancel(escrow)?,
+            Action::DefaultJudgment => self.default_judgment(escrow)?,
+        };
+        if self.dry_run {
+            println!(
+                "would submit {} for {address} (counter {})",
+                action.as_str(),
+                escrow.counter
+            );
+            return Ok(());
+        }
+
+        let outcome = match self.chain.submit(&ixs)? {
+            Submission::Confirmed(signature) => {
+                println!("{} {address}: {signature}", action.as_str());
+                self.metrics.record_submission(action.as_str(), "confirmed");
+                Outcome::Submitted
+            }
+            Submission::Rejected(reason) => {
+                eprintln!("warning: {} {address} rejected: {reason}", action.as_str());
+                self.metrics.record_submission(action.as_str(), "rejected");
+                Outcome::Rejected
+            }
+        };
+        self.tracker
+            .record(address, escrow.counter, action, outcome, Instant::now());
+        Ok(())
+    }
+
+    fn auto_cancel(&self, escrow: &Escrow) -> Result<Vec<Instruction>> {
+        // Only a funded escrow has a vault to refund to the seller
+        let seller_token_account = if escrow.state == EscrowState::Funded {
+            let mint = self.vault_mint(escrow)?;
+            Some(find_associated_token_address(&escrow.seller, &mint))
+        } else {
+            None
+        };
+        Ok(vec![AutoCancel {
+            escrow,
+            arbitrator: self.chain.signer(),
+            seller_token_account,
+            arbitrator_token_account: None,
+        }
+        .instruction()])
+    }
+
+    fn default_judgment(&self, escrow: &Escrow) -> Result<Vec<Instruction>> {
+        let address = escrow.address();
+        let mint = self.vault_mint(escrow)?;
+        let keeper = self.chain.signer();
+
+        // default_judgment needs both bond accounts; the party that never responded
+        // usually has none
+        let mut escrow = escrow.clone();
+        let mut ixs = Vec::new();
+        if !self
+            .chain
+            .account_exists(&find_buyer_bond_address(&address).0)?
+        {
+            ixs.push(
+                InitializeBuyerBondAccount {
+                    escrow: &escrow,
+                    payer: keeper,
+                    token_mint: mint,
+                }
+                .instruction(),
+            );
+            escrow.buyer_bond_rent_payer = Some(keeper);
+        }
+        if !self
+            .chain
+            .account_exists(&find_seller_bond_address(&address).0)?
+        {
+            ixs.push(
+                InitializeSellerBondAccount {
+                    escrow: &escrow,
+                    payer: keeper,
+                    token_mint: mint,
+                }
+                .instruction(),
+            );
+            escrow.seller_bond_rent_payer = Some(keeper);
+        }
+        ixs.push(
+            DefaultJudgment {
+                escrow: &escrow,
+                caller: keeper,
+                buyer_token_account: find_associated_token_address(&escrow.buyer, &mint),
+                seller_token_account: find_associated_token_address(&escrow.seller, &mint),
+            }
+            .instruction(),
+        );
+        Ok(ixs)
+    }
+
+    fn vault_mint(&self, escrow: &Escrow) -> Result<Pubkey> {
+        self.chain
+            .vault_mint(&escrow.address())?
+            .ok_or_else(|| anyhow!("escrow vault not found"))
+    }
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let metrics = Arc::new(Metrics::default());
+    let keypair = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair_path()?,
+    };
+    let chain = Chain::new(&cli.url, &keypair, cli.max_retries, metrics.clone())?;
+
+    let can_auto_cancel = chain.signer() == ARBITRATOR;
+    if !can_auto_cancel {
+        eprintln!(
+            "warning: {} is not the arbitrator; expired escrows will not be auto-cancelled",
+            chain.signer()
+        );
+    }
+    if !cli.once {
+        metrics::serve(cli.metrics_addr, metrics.clone())?;
+        println!("metrics on http://{}/metrics", cli.metrics_addr);
+    }
+
+    let mut keeper = Keeper {
+        chain,
+        tracker: Tracker::new(
+            Duration::from_secs(cli.resubmit_after),
+            Duration::from_secs(cli.rejected_cooldown),
+        ),
+        metrics,
+        can_auto_cancel,
+        dry_run: cli.dry_run,
+    };
+    loop {
+        match keeper.scan() {
+            Ok(()) => {
+                keeper.metrics.scans.fetch_add(1, Ordering::Relaxed);
+                keeper
+                    .metrics
+                    .last_scan_unixtime
+                    .store(unix_now(), Ordering::Relaxed);
+            }
+            Err(err) if cli.once => bail!(err),
+            Err(err) => {
+                keeper.metrics.scan_errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("error: scan failed: {err:#}");
+            }
+        }
+        if cli.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(cli.interval));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
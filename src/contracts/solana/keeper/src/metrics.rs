@@ -0,0 +1,168 @@
+//! Prometheus metrics, served as plain text on `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Default)]
+pub struct Metrics {
+    pub scans: AtomicU64,
+    pub scan_errors: AtomicU64,
+    pub escrows_scanned: AtomicU64,
+    pub last_scan_unixtime: AtomicU64,
+    pub rpc_retries: AtomicU64,
+    // label set -> value
+    actionable: Mutex<BTreeMap<&'static str, u64>>,
+    waiting: Mutex<BTreeMap<&'static str, u64>>,
+    submissions: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+}
+
+impl Metrics {
+    pub fn set_actionable(&self, counts: BTreeMap<&'static str, u64>) {
+        *self.actionable.lock().unwrap() = counts;
+    }
+
+    pub fn set_waiting(&self, counts: BTreeMap<&'static str, u64>) {
+        *self.waiting.lock().unwrap() = counts;
+    }
+
+    pub fn record_submission(&self, action: &'static str, result: &'static str) {
+        *self
+            .submissions
+            .lock()
+            .unwrap()
+            .entry((action, result))
+            .or_default() += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counter = |out: &mut String, name: &str, help: &str, value: u64| {
+            let _ = writeln!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}"
+            );
+        };
+        counter(
+            &mut out,
+            "keeper_scans_total",
+            "Completed account scans",
+            self.scans.load(Ordering::Relaxed),
+        );
+        counter(
+            &mut out,
+            "keeper_scan_errors_total",
+            "Scans that failed",
+            self.scan_errors.load(Ordering::Relaxed),
+        );
+        counter(
+            &mut out,
+            "keeper_rpc_retries_total",
+            "RPC calls retried after a transient error",
+            self.rpc_retries.load(Ordering::Relaxed),
+        );
+        let _ = writeln!(
+            out,
+            "# HELP keeper_escrows_scanned Escrow accounts seen in the last scan\n\
+             # TYPE keeper_escrows_scanned gauge\nkeeper_escrows_scanned {}",
+            self.escrows_scanned.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP keeper_last_scan_timestamp_seconds Unix time of the last completed scan\n\
+             # TYPE keeper_last_scan_timestamp_seconds gauge\nkeeper_last_scan_timestamp_seconds {}",
+            self.last_scan_unixtime.load(Ordering::Relaxed)
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP keeper_escrows_actionable Escrows the keeper can act on, by action\n\
+             # TYPE keeper_escrows_actionable gauge"
+        );
+        for (action, value) in self.actionable.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "keeper_escrows_actionable{{action=\"{action}\"}} {value}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP keeper_escrows_waiting Open escrows not yet actionable, by what they wait on\n\
+             # TYPE keeper_escrows_waiting gauge"
+        );
+        for (reason, value) in self.waiting.lock().unwrap().iter() {
+            let _ = writeln!(out, "keeper_escrows_waiting{{reason=\"{reason}\"}} {value}");
+        }
+        let _ = writeln!(
+            out,
+            "# HELP keeper_submissions_total Transactions submitted, by action and result\n\
+             # TYPE keeper_submissions_total counter"
+        );
+        for ((action, result), value) in self.submissions.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "keeper_submissions_total{{action=\"{action}\",result=\"{result}\"}} {value}"
+            );
+        }
+        out
+    }
+}
+
+/// Serves `metrics` on `addr` from a background thread.
+pub fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            // The request itself does not matter; every path returns the metrics
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let body = metrics.render();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_labelled_series() {
+        let metrics = Metrics::default();
+        metrics.scans.fetch_add(2, Ordering::Relaxed);
+        metrics.set_actionable(BTreeMap::from([("auto_cancel", 3)]));
+        metrics.record_submission("auto_cancel", "confirmed");
+        metrics.record_submission("auto_cancel", "confirmed");
+        let text = metrics.render();
+        assert!(text.contains("keeper_scans_total 2\n"));
+        assert!(text.contains("keeper_escrows_actionable{action=\"auto_cancel\"} 3\n"));
+        assert!(text
+            .contains("keeper_submissions_total{action=\"auto_cancel\",result=\"confirmed\"} 2\n"));
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let metrics = Arc::new(Metrics::default());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        serve(addr, metrics).unwrap();
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("keeper_scans_total 0"));
+    }
+}
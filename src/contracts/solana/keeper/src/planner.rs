@@ -0,0 +1,197 @@
+//! Decides which escrows are actionable. Pure functions over decoded state so the rules
+//! can be tested without a validator; they mirror the checks in `auto_cancel` and
+//! `default_judgment`.
+
+use localsolana_contracts_client::constants::{DISPUTE_RESPONSE_DEADLINE_HOURS, SECONDS_PER_HOUR};
+use localsolana_contracts_client::{Escrow, EscrowState};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Created past its deposit deadline, or Funded past its fiat deadline without payment
+    AutoCancel,
+    /// Disputed past the response deadline with no response from the other party
+    DefaultJudgment,
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::AutoCancel => "auto_cancel",
+            Action::DefaultJudgment => "default_judgment",
+        }
+    }
+}
+
+/// Why an escrow is not actionable yet, for the waiting gauges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Waiting {
+    DepositDeadline,
+    FiatDeadline,
+    /// Fiat marked paid; only the seller or arbitrator can move it on
+    Release,
+    ResponseDeadline,
+    /// Both parties posted evidence; only the arbitrator can resolve it
+    Arbitration,
+}
+
+impl Waiting {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Waiting::DepositDeadline => "deposit_deadline",
+            Waiting::FiatDeadline => "fiat_deadline",
+            Waiting::Release => "release",
+            Waiting::ResponseDeadline => "response_deadline",
+            Waiting::Arbitration => "arbitration",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plan {
+    Act(Action),
+    Wait(Waiting),
+    Terminal,
+}
+
+pub fn response_deadline(escrow: &Escrow) -> Option<i64> {
+    escrow
+        .dispute_initiated_time?
+        .checked_add(DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR)
+}
+
+/// `now` is the cluster clock; the program requires deadlines to be strictly in the past.
+pub fn plan(escrow: &Escrow, now: i64) -> Plan {
+    match escrow.state {
+        EscrowState::Created if now > escrow.deposit_deadline => Plan::Act(Action::AutoCancel),
+        EscrowState::Created => Plan::Wait(Waiting::DepositDeadline),
+        EscrowState::Funded if escrow.fiat_paid => Plan::Wait(Waiting::Release),
+        EscrowState::Funded if now > escrow.fiat_deadline => Plan::Act(Action::AutoCancel),
+        EscrowState::Funded => Plan::Wait(Waiting::FiatDeadline),
+        EscrowState::Disputed => {
+            let Some(initiator) = escrow.dispute_initiator else {
+                return Plan::Wait(Waiting::Arbitration);
+            };
+            let responded = if initiator == escrow.buyer {
+                escrow.dispute_evidence_hash_seller.is_some()
+            } else {
+                escrow.dispute_evidence_hash_buyer.is_some()
+            };
+            match response_deadline(escrow) {
+                _ if responded => Plan::Wait(Waiting::Arbitration),
+                Some(deadline) if now > deadline => Plan::Act(Action::DefaultJudgment),
+                Some(_) => Plan::Wait(Waiting::ResponseDeadline),
+                None => Plan::Wait(Waiting::Arbitration),
+            }
+        }
+        EscrowState::Released | EscrowState::Cancelled | EscrowState::Resolved => Plan::Terminal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn escrow(state: EscrowState) -> Escrow {
+        let seller = Pubkey::new_unique();
+        Escrow {
+            escrow_id: 1,
+            trade_id: 1,
+            seller,
+            buyer: Pubkey::new_unique(),
+            arbitrator: Pubkey::new_unique(),
+            amount: 1_000_000,
+            fee: 10_000,
+            deposit_deadline: 1_000,
+            fiat_deadline: 2_000,
+            state,
+            sequential: false,
+            sequential_escrow_address: None,
+            fiat_paid: false,
+            counter: 1,
+            dispute_initiator: None,
+            dispute_initiated_time: None,
+            dispute_evidence_hash_buyer: None,
+            dispute_evidence_hash_seller: None,
+            dispute_resolution_hash: None,
+            tracked_balance: 0,
+            version: 2,
+            seller_scoped_seeds: true,
+            escrow_rent_payer: Some(seller),
+            escrow_token_rent_payer: None,
+            buyer_bond_rent_payer: None,
+            seller_bond_rent_payer: None,
+            fiat_terms: None,
+            chat_checkpoint_buyer: None,
+            chat_checkpoint_seller: None,
+            requires_acceptance: false,
+            accepted_at: None,
+            buyer_deposit: 0,
+            buyer_deposit_rent_payer: None,
+            fee_policy: None,
+            dispute_bond: 0,
+            requires_buyer_deposit: false,
+        }
+    }
+
+    fn disputed_by_buyer() -> Escrow {
+        let mut e = escrow(EscrowState::Disputed);
+        e.fiat_paid = true;
+        e.dispute_initiator = Some(e.buyer);
+        e.dispute_initiated_time = Some(10_000);
+        e.dispute_evidence_hash_buyer = Some([1; 32]);
+        e
+    }
+
+    #[test]
+    fn created_escrow_expires_after_deposit_deadline() {
+        let e = escrow(EscrowState::Created);
+        assert_eq!(plan(&e, 1_000), Plan::Wait(Waiting::DepositDeadline));
+        assert_eq!(plan(&e, 1_001), Plan::Act(Action::AutoCancel));
+    }
+
+    #[test]
+    fn funded_escrow_expires_unless_fiat_paid() {
+        let mut e = escrow(EscrowState::Funded);
+        assert_eq!(plan(&e, 2_000), Plan::Wait(Waiting::FiatDeadline));
+        assert_eq!(plan(&e, 2_001), Plan::Act(Action::AutoCancel));
+        e.fiat_paid = true;
+        assert_eq!(plan(&e, 2_001), Plan::Wait(Waiting::Release));
+    }
+
+    #[test]
+    fn unanswered_dispute_gets_default_judgment_after_deadline() {
+        let e = disputed_by_buyer();
+        let deadline = response_deadline(&e).unwrap();
+        assert_eq!(deadline, 10_000 + 72 * 3600);
+        assert_eq!(plan(&e, deadline), Plan::Wait(Waiting::ResponseDeadline));
+        assert_eq!(plan(&e, deadline + 1), Plan::Act(Action::DefaultJudgment));
+    }
+
+    #[test]
+    fn answered_dispute_waits_for_arbitrator() {
+        let mut e = disputed_by_buyer();
+        e.dispute_evidence_hash_seller = Some([2; 32]);
+        assert_eq!(plan(&e, i64::MAX), Plan::Wait(Waiting::Arbitration));
+    }
+
+    #[test]
+    fn seller_initiated_dispute_checks_buyer_response() {
+        let mut e = disputed_by_buyer();
+        e.dispute_initiator = Some(e.seller);
+        e.dispute_evidence_hash_seller = Some([2; 32]);
+        e.dispute_evidence_hash_buyer = None;
+        assert_eq!(plan(&e, i64::MAX), Plan::Act(Action::DefaultJudgment));
+    }
+
+    #[test]
+    fn terminal_states_are_ignored() {
+        for state in [
+            EscrowState::Released,
+            EscrowState::Cancelled,
+            EscrowState::Resolved,
+        ] {
+            assert_eq!(plan(&escrow(state), i64::MAX), Plan::Terminal);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Idempotency: remembers what was submitted for each escrow so repeated scans do not
+//! resubmit while a transaction may still land.
+//!
+//! Attempts are keyed by escrow address and the escrow's update counter. Every mutation
+//! bumps the counter, so a new counter means the escrow moved on and any earlier attempt
+//! is stale. For the same counter we wait out `resubmit_after` (long enough for the
+//! blockhash of an in-flight transaction to expire) or, after the program rejected the
+//! transaction, `rejected_cooldown` (covers cluster clock lag around deadlines).
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::planner::Action;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Submitted,
+    Rejected,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Attempt {
+    counter: u64,
+    action: Action,
+    outcome: Outcome,
+    at: Instant,
+}
+
+pub struct Tracker {
+    attempts: HashMap<Pubkey, Attempt>,
+    resubmit_after: Duration,
+    rejected_cooldown: Duration,
+}
+
+impl Tracker {
+    pub fn new(resubmit_after: Duration, rejected_cooldown: Duration) -> Self {
+        Tracker {
+            attempts: HashMap::new(),
+            resubmit_after,
+            rejected_cooldown,
+        }
+    }
+
+    pub fn should_submit(
+        &self,
+        escrow: &Pubkey,
+        counter: u64,
+        action: Action,
+        now: Instant,
+    ) -> bool {
+        let Some(attempt) = self.attempts.get(escrow) else {
+            return true;
+        };
+        if attempt.counter != counter || attempt.action != action {
+            return true;
+        }
+        let wait = match attempt.outcome {
+            Outcome::Submitted => self.resubmit_after,
+            Outcome::Rejected => self.rejected_cooldown,
+        };
+        now.duration_since(attempt.at) >= wait
+    }
+
+    pub fn record(
+        &mut self,
+        escrow: Pubkey,
+        counter: u64,
+        action: Action,
+        outcome: Outcome,
+        now: Instant,
+    ) {
+        self.attempts.insert(
+            escrow,
+            Attempt {
+                counter,
+                action,
+                outcome,
+                at: now,
+            },
+        );
+    }
+
+    /// Drops escrows that no longer exist or are no longer actionable.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Pubkey) -> bool) {
+        self.attempts.retain(|escrow, _| keep(escrow));
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.attempts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> Tracker {
+        Tracker::new(Duration::from_secs(90), Duration::from_secs(300))
+    }
+
+    #[test]
+    fn skips_same_counter_until_resubmit_window_passes() {
+        let mut t = tracker();
+        let escrow = Pubkey::new_unique();
+        let start = Instant::now();
+        assert!(t.should_submit(&escrow, 3, Action::AutoCancel, start));
+        t.record(escrow, 3, Action::AutoCancel, Outcome::Submitted, start);
+        assert!(!t.should_submit(
+            &escrow,
+            3,
+            Action::AutoCancel,
+            start + Duration::from_secs(89)
+        ));
+        assert!(t.should_submit(
+            &escrow,
+            3,
+            Action::AutoCancel,
+            start + Duration::from_secs(90)
+        ));
+    }
+
+    #[test]
+    fn new_counter_invalidates_previous_attempt() {
+        let mut t = tracker();
+        let escrow = Pubkey::new_unique();
+        let start = Instant::now();
+        t.record(escrow, 3, Action::DefaultJudgment, Outcome::Rejected, start);
+        assert!(!t.should_submit(&escrow, 3, Action::DefaultJudgment, start));
+        assert!(t.should_submit(&escrow, 4, Action::DefaultJudgment, start));
+    }
+
+    #[test]
+    fn rejected_attempts_cool_down_longer() {
+        let mut t = tracker();
+        let escrow = Pubkey::new_unique();
+        let start = Instant::now();
+        t.record(escrow, 1, Action::AutoCancel, Outcome::Rejected, start);
+        assert!(!t.should_submit(
+            &escrow,
+            1,
+            Action::AutoCancel,
+            start + Duration::from_secs(120)
+        ));
+        assert!(t.should_submit(
+            &escrow,
+            1,
+            Action::AutoCancel,
+            start + Duration::from_secs(300)
+        ));
+    }
+
+    #[test]
+    fn retain_forgets_finished_escrows() {
+        let mut t = tracker();
+        let kept = Pubkey::new_unique();
+        let dropped = Pubkey::new_unique();
+        let now = Instant::now();
+        t.record(kept, 1, Action::AutoCancel, Outcome::Submitted, now);
+        t.record(dropped, 1, Action::AutoCancel, Outcome::Submitted, now);
+        t.retain(|escrow| *escrow == kept);
+        assert_eq!(t.len(), 1);
+        assert!(t.should_submit(&dropped, 1, Action::AutoCancel, now));
+    }
+}
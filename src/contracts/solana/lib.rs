@@ -9,10 +9,25 @@
  *
  * 1. ESCROW CREATION & FUNDING:
  *    - Seller creates an escrow with trade details (amount, deadlines, sequential trade support)
- *    - Seller funds the escrow with principal + 1% fee
+ *    - Seller commits the fiat terms: currency, fiat amount, exchange rate and payment method hash
+ *    - If the arbitrator registered a Pyth feed for the currency, with limits on deviation,
+ *      price age and confidence, the rate must pass the check against it
+ *    - Seller may require the buyer to accept the escrow first, so a mistyped or
+ *      unreachable buyer address cannot receive a funded trade. Once the buyer accepted,
+ *      a seller who cancels the funded escrow pays the buyer 0.5% (at most the fee) out of
+ *      the escrowed fee; arbitrator cancellations are exempt
+ *    - Seller funds the escrow with principal + 1% fee, or creates and funds it in one
+ *      instruction (create_and_fund_escrow) so a failed deposit leaves nothing behind
  *    - 15-minute deposit deadline for funding, 30-minute fiat payment deadline
  *
  * 2. TRADE EXECUTION:
+ *    - Each party registers an x25519 public key in its profile once; either party can then
+ *      post the hash of payment details encrypted to the other's key on the escrow
+ *    - Either party can checkpoint the Merkle root of its chat transcript on the escrow
+ *    - Buyer may post a 2% deposit (at least 0.02 USDC) once the escrow is funded, and must
+ *      before marking fiat paid when the seller required it at creation. It is returned on
+ *      release, cancellation and every dispute outcome, and goes to the seller only if the
+ *      fiat deadline passes unpaid (auto_cancel)
  *    - Buyer marks fiat payment as completed
  *    - Seller can then release funds to buyer (or next sequential escrow)
  *    - Supports sequential trades where funds flow to another escrow
@@ -20,26 +35,35 @@
  * 3. DISPUTE RESOLUTION:
  *    - Either party can open a dispute by posting a 5% bond
  *    - Both parties submit evidence hashes and bonds
+ *    - Single chat messages can be proven against a party's committed transcript root
  *    - 72-hour response deadline for non-initiating party
  *    - Arbitrator makes final decision within 7 days
  *    - Winner gets their bond back, loser's bond goes to platform
  *
  * 4. AUTOMATED SAFEGUARDS:
  *    - Auto-cancellation if deadlines expire
- *    - Default judgment if one party doesn't respond to dispute
+ *    - Default judgment if one party doesn't respond to dispute (callable by anyone)
+ *    - Vault reconciliation against the tracked balance (callable by anyone), and an
+ *      arbitrator sweep of stray tokens so vaults can always be closed
  *    - Secure PDA-based token accounts with proper authority controls
  *
  * KEY FEATURES:
  * - Maximum trade size: 100 USDC
- * - 1% platform fee on all trades
- * - 5% dispute bond requirement
+ * - 1% platform fee on all trades, at least 0.01 USDC by default
+ * - 5% dispute bond requirement, at least 0.05 USDC by default
+ * - Fee and bond floors and rounding set by the arbitrator in a config account, snapshotted
+ *   onto each escrow at creation and reported in events; payouts return the bond posted
  * - Sequential trade support for complex trading flows
- * - Comprehensive event logging for off-chain indexing
- * - Rent refunds to reduce user costs
+ * - Versioned events delivered via CPI for reliable off-chain indexing
+ * - Every mutation bumps the escrow counter and emits a uniform EscrowStateChanged event
+ * - Rent refunds to reduce user costs (always to the account that paid the rent)
+ * - Read-only quote_escrow and escrow_status views that return fees, phase, allowed
+ *   actions and deadline countdowns through return data
  *
  * SECURITY:
  * - Only authorized parties can perform actions
  * - PDA-derived addresses prevent address spoofing
+ * - Escrow PDAs are scoped to the seller, so IDs cannot be squatted by third parties
  * - Proper state machine prevents invalid transitions
  * - Bond system discourages frivolous disputes
  *
@@ -49,40 +73,50 @@
  */
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
 use anchor_spl::token::{self, Token, Transfer, CloseAccount};
 
 declare_id!("4PonUp1nPEzDPnRMPjTqufLT3f37QuBJGk1CVnsTXx7x");
 
-mod constants {
-    // Maximum amount allowed (100 USDC)
-    pub const MAX_AMOUNT: u64 = 100_000_000; // 6 decimals for USDC
-
-    // Fee percentage (1%)
-    pub const FEE_BASIS_POINTS: u64 = 100; // 1% = 100 basis points
+// Transition table, guards and payout arithmetic, kept free of Anchor so the client
+// crate compiles the same rules
+pub mod state_machine;
 
-    // Dispute bond percentage (5%)
-    pub const DISPUTE_BOND_BASIS_POINTS: u64 = 500; // 5% = 500 basis points
+use state_machine::{Action, Party, Role, Settlement};
 
-    // Production Deadlines
-    pub const DEPOSIT_DEADLINE_MINUTES: i64 = 15; // 15 minutes from order initiation
-    pub const FIAT_DEADLINE_MINUTES: i64 = 30;    // 30 minutes after funding
-
-    // Shorten deadlines for localnet testing
-    // pub const DEPOSIT_DEADLINE_MINUTES: i64 = 1;
-    // pub const FIAT_DEADLINE_MINUTES: i64 = 1;
+mod constants {
+    // Amount limit, fee and bond rates and the deadlines live in state_machine
 
-    pub const DISPUTE_RESPONSE_DEADLINE_HOURS: i64 = 72; // 72 hours to respond to dispute
     pub const ARBITRATION_DEADLINE_HOURS: i64 = 168;     // 7 days for arbitrator to make decision
 
     // Hardcoded arbitrator Pubkey (base58: GGrXhNVxUZXaA2uMopsa5q23aPmoNvQF14uxqo8qENUr)
     pub const ARBITRATOR_BYTES: [u8; 32] =
     [0xe2, 0xef, 0x04, 0xd8, 0x35, 0x5b, 0x03, 0xd1, 0xdb, 0x14, 0x87, 0x9e, 0x38, 0x84, 0x4d, 0x64, 0x74, 0xc7, 0x8b, 0xe4, 0xbe, 0x4e, 0x31, 0xb4, 0xae, 0xfe, 0x13, 0xc8, 0x2f, 0xdb, 0xdb, 0x2b];
 
-    // Other constants
-    pub const SECONDS_PER_MINUTE: i64 = 60;
-    pub const SECONDS_PER_HOUR: i64 = 3600;
-
-    pub const MAX_U64: u64 = u64::MAX; // 18,446,744,073,709,551,615
+    pub use crate::state_machine::SECONDS_PER_HOUR;
+
+    // Pyth Solana receiver program, owner of PriceUpdateV2 accounts
+    // (base58: rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ)
+    pub const PYTH_RECEIVER_BYTES: [u8; 32] =
+    [0x0c, 0xb7, 0xfa, 0xbb, 0x52, 0xf7, 0xa6, 0x48, 0xbb, 0x5b, 0x31, 0x7d, 0x9a, 0x01, 0x8b, 0x90, 0x57, 0xcb, 0x02, 0x47, 0x74, 0xfa, 0xfe, 0x01, 0xe6, 0xc4, 0xdf, 0x98, 0xcc, 0x38, 0x58, 0x81];
+
+    // sha256("account:PriceUpdateV2")[..8]
+    pub const PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    // Escrow account layout version written by create_escrow and migrate_escrow.
+    // Escrows created under v0.1.2 have no version byte and read back as 0.
+    // 2: fiat terms
+    // 3: chat transcript checkpoints
+    // 4: buyer acceptance
+    // 5: buyer deposit
+    // 6: fee policy snapshot and posted dispute bond
+    // 7: required buyer deposit
+    pub const ESCROW_VERSION: u8 = 7;
+
+    // Version of the event payload layout, carried in every event as schema_version.
+    // Bump whenever a field is added, removed or reordered in any event.
+    pub const EVENT_SCHEMA_VERSION: u8 = 6;
 }
 
 // Custom error codes
@@ -124,6 +158,87 @@ pub enum EscrowError {
     InvalidResolutionExplanation,
     #[msg("Required bump seed not found")]
     BumpNotFound,
+    #[msg("Escrow account data is not a valid Escrow")]
+    InvalidEscrowAccount,
+    #[msg("Escrow already at current layout version")]
+    AlreadyMigrated,
+    #[msg("Rent refund account does not match the recorded payer")]
+    InvalidRentPayer,
+    #[msg("Initialized bond account must be supplied so it can be closed")]
+    MissingBondAccount,
+    #[msg("Escrow is not in a terminal state")]
+    NotTerminalState,
+    #[msg("Escrow update counter overflow")]
+    CounterOverflow,
+    #[msg("Vault holds no surplus over the tracked balance")]
+    NoSurplus,
+    #[msg("Fiat terms need an ISO 4217 currency code and a non-zero amount and rate")]
+    InvalidFiatTerms,
+    #[msg("Not a fully verified Pyth price update for the registered feed, or a non-positive price")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is older than the feed allows")]
+    StaleOraclePrice,
+    #[msg("Oracle price confidence interval is wider than the feed allows")]
+    OraclePriceUncertain,
+    #[msg("Committed exchange rate deviates from the oracle price by more than the feed allows")]
+    RateOutOfBand,
+    #[msg("Price feed limits must be non-zero and the confidence limit at most 100%")]
+    InvalidRateBand,
+    #[msg("Encryption key must be a non-zero x25519 public key")]
+    InvalidEncryptionKey,
+    #[msg("Recipient profile does not belong to the other party")]
+    RecipientProfileMismatch,
+    #[msg("Chat root must be non-zero and cover more messages than the last checkpoint")]
+    InvalidChatRoot,
+    #[msg("Message is not included in the party's committed chat root")]
+    InvalidInclusionProof,
+    #[msg("Escrow must be accepted by the buyer before it is funded")]
+    AcceptanceRequired,
+    #[msg("Posted buyer deposit account must be supplied so it can be settled")]
+    MissingBuyerDeposit,
+    #[msg("Arbitrator token account must be supplied to sweep stray bond tokens")]
+    MissingArbitratorTokenAccount,
+    #[msg("Fee and bond floors may not exceed the maximum trade amount")]
+    InvalidFeePolicy,
+    #[msg("Buyer deposit must be posted before fiat is marked paid")]
+    BuyerDepositRequired,
+}
+
+impl From<state_machine::Violation> for EscrowError {
+    fn from(violation: state_machine::Violation) -> Self {
+        use state_machine::Violation;
+        match violation {
+            Violation::InvalidAmount => EscrowError::InvalidAmount,
+            Violation::ExceedsMaximum => EscrowError::ExceedsMaximum,
+            Violation::Unauthorized => EscrowError::Unauthorized,
+            Violation::DepositDeadlineExpired => EscrowError::DepositDeadlineExpired,
+            Violation::FiatDeadlineExpired => EscrowError::FiatDeadlineExpired,
+            Violation::InvalidState => EscrowError::InvalidState,
+            Violation::MissingSequentialAddress => EscrowError::MissingSequentialAddress,
+            Violation::TerminalState => EscrowError::TerminalState,
+            Violation::ResponseDeadlineExpired => EscrowError::ResponseDeadlineExpired,
+            Violation::InvalidEvidenceHash => EscrowError::InvalidEvidenceHash,
+            Violation::DuplicateEvidence => EscrowError::DuplicateEvidence,
+            Violation::InvalidFiatTerms => EscrowError::InvalidFiatTerms,
+            Violation::InvalidOraclePrice => EscrowError::InvalidOraclePrice,
+            Violation::StaleOraclePrice => EscrowError::StaleOraclePrice,
+            Violation::OraclePriceUncertain => EscrowError::OraclePriceUncertain,
+            Violation::RateOutOfBand => EscrowError::RateOutOfBand,
+            Violation::InvalidRateBand => EscrowError::InvalidRateBand,
+            Violation::InvalidChatRoot => EscrowError::InvalidChatRoot,
+            Violation::InvalidInclusionProof => EscrowError::InvalidInclusionProof,
+            Violation::AcceptanceRequired => EscrowError::AcceptanceRequired,
+            Violation::InvalidFeePolicy => EscrowError::InvalidFeePolicy,
+            Violation::BuyerDepositRequired => EscrowError::BuyerDepositRequired,
+            Violation::Overflow => EscrowError::FeeCalculationError,
+        }
+    }
+}
+
+impl From<state_machine::Violation> for anchor_lang::error::Error {
+    fn from(violation: state_machine::Violation) -> Self {
+        EscrowError::from(violation).into()
+    }
 }
 
 #[program]
@@ -138,6 +253,9 @@ pub mod localsolana_contracts {
         amount: u64,
         sequential: bool,
         sequential_escrow_address: Option<Pubkey>,
+        fiat_terms: FiatTerms,
+        requires_acceptance: bool,
+        requires_buyer_deposit: bool,
     ) -> Result<()> {
 
         // debugging borsch
@@ -145,27 +263,30 @@ pub mod localsolana_contracts {
         // msg!("Allocated space: {}", ctx.accounts.escrow.to_account_info().data_len()); // Should be 337
         // msg!("Starting initialization");
 
-        // Validate amount
-        require!(amount > 0, EscrowError::InvalidAmount);
-        require!(amount <= MAX_AMOUNT, EscrowError::ExceedsMaximum);
+        // Validate amount, and the sequential address if sequential is true
+        state_machine::check_terms(amount, sequential, sequential_escrow_address.is_some())?;
 
-        // Validate sequential address if sequential is true
-        if sequential {
-            require!(sequential_escrow_address.is_some(), EscrowError::MissingSequentialAddress);
-        }
+        // Validate the fiat side the arbitrator will judge evidence against
+        state_machine::check_fiat_terms(fiat_terms.currency, fiat_terms.fiat_amount, fiat_terms.rate)?;
 
-        // Calculate fee (1% of principal)
-        let fee = amount
-            .checked_mul(FEE_BASIS_POINTS)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .checked_div(10000)
-            .ok_or(EscrowError::FeeCalculationError)?;
+        // Fee floors and rounding in force now; the escrow keeps them for its later charges
+        let fee_policy = read_fee_policy(&ctx.accounts.config)?;
+
+        // Calculate fee (1% of principal, rounded, at least the configured minimum)
+        let fee_charge = state_machine::fee_charge(amount, fee_policy.into())?;
+        let fee = fee_charge.value;
 
         // Calculate deposit deadline (current time + 15 minutes)
         let current_time = Clock::get()?.unix_timestamp;
-        let deposit_deadline = current_time
-            .checked_add(DEPOSIT_DEADLINE_MINUTES * SECONDS_PER_MINUTE)
-            .ok_or(EscrowError::FeeCalculationError)?;
+        let deposit_deadline = state_machine::deposit_deadline(current_time)?;
+
+        // Oracle sanity check of the committed rate, mandatory once the currency has a feed
+        let rate_check = check_committed_rate(
+            &ctx.accounts.price_feed,
+            ctx.accounts.price_update.as_ref(),
+            fiat_terms.rate,
+            current_time,
+        )?;
 
         // Store seller/buyer/arbitrator info
         let seller_key = ctx.accounts.seller.key();
@@ -206,7 +327,29 @@ pub mod localsolana_contracts {
         // initiatialize tracked balance
         escrow.tracked_balance = 0;
 
-        emit!(EscrowCreated {
+        // stamp the account layout version
+        escrow.version = ESCROW_VERSION;
+        escrow.seller_scoped_seeds = true;
+
+        // seller pays rent for the escrow state account
+        escrow.escrow_rent_payer = Some(seller_key);
+
+        escrow.fiat_terms = Some(fiat_terms.clone());
+        escrow.chat_checkpoint_buyer = None;
+        escrow.chat_checkpoint_seller = None;
+
+        // when set, fund_escrow waits for accept_escrow
+        escrow.requires_acceptance = requires_acceptance;
+        escrow.accepted_at = None;
+        // when set, mark_fiat_paid waits for post_buyer_deposit
+        escrow.requires_buyer_deposit = requires_buyer_deposit;
+        escrow.buyer_deposit = 0;
+        escrow.buyer_deposit_rent_payer = None;
+        escrow.fee_policy = Some(fee_policy);
+        escrow.dispute_bond = 0;
+
+        emit_cpi!(EscrowCreated {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -220,14 +363,155 @@ pub mod localsolana_contracts {
             sequential,
             sequential_escrow_address,
             timestamp: current_time,
+            rounding: fee_charge.rounding.into(),
+            min_fee_applied: fee_charge.minimum_applied,
+            fiat_terms: Some(fiat_terms.clone()),
+            requires_acceptance,
+            min_fee: fee_policy.min_fee,
+            requires_buyer_deposit,
+        });
+
+        if let Some((feed_id, publish_time, check)) = rate_check {
+            emit_cpi!(FiatRateChecked {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                currency: fiat_terms.currency,
+                feed_id,
+                committed_rate: fiat_terms.rate,
+                oracle_rate: check.oracle_rate,
+                deviation_bps: check.deviation_bps,
+                publish_time,
+                timestamp: current_time,
+            });
+        }
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: None,
+            to: EscrowState::Created,
+            counter: 0,
+            actor: seller_key,
+            timestamp: current_time,
         });
 
         Ok(())
     }
 
-    pub fn fund_escrow(ctx: Context<FundEscrow>, _escrow_id: u64, _trade_id: u64) -> Result<()> {
+    // The buyer confirms an escrow that names it, so the seller can fund it when
+    // acceptance was required at creation
+    pub fn accept_escrow(ctx: Context<AcceptEscrow>) -> Result<()> {
+        // Verify the escrow is Created and awaiting acceptance, the caller is the buyer and
+        // the deposit deadline
+        let current_time = Clock::get()?.unix_timestamp;
+        let buyer = ctx.accounts.buyer.key();
+        let escrow = &mut ctx.accounts.escrow;
+        let state = escrow.state;
+        let new_state = escrow.check(Action::Accept, &buyer, current_time)?;
+
+        escrow.accepted_at = Some(current_time);
+        let counter = escrow.bump_counter()?;
+
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+
+        emit_cpi!(EscrowAccepted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            buyer,
+            timestamp: current_time,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: buyer,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Buyer stakes a deposit against walking away from a funded trade, see settle_buyer_deposit
+    pub fn post_buyer_deposit(ctx: Context<PostBuyerDeposit>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let buyer = ctx.accounts.buyer.key();
+        let state = ctx.accounts.escrow.state;
+        let new_state = ctx.accounts.escrow.check(Action::PostBuyerDeposit, &buyer, current_time)?;
+
+        // 2% of the principal, rounded as the escrow's fees are, at least MIN_BUYER_DEPOSIT
+        let deposit_charge = state_machine::buyer_deposit_charge(
+            ctx.accounts.escrow.amount,
+            ctx.accounts.escrow.fee_policy(),
+        )?;
+        let deposit = deposit_charge.value;
+        require!(
+            ctx.accounts.buyer_token_account.amount >= deposit,
+            EscrowError::InsufficientFunds
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    to: ctx.accounts.buyer_deposit_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.buyer_deposit = deposit;
+        escrow.buyer_deposit_rent_payer = Some(buyer);
+        let counter = escrow.bump_counter()?;
+
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+
+        emit_cpi!(BuyerDepositPosted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            buyer,
+            amount: deposit,
+            rounding: deposit_charge.rounding.into(),
+            min_deposit_applied: deposit_charge.minimum_applied,
+            counter,
+            timestamp: current_time,
+        });
 
-        use constants::MAX_U64;
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: buyer,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn fund_escrow(ctx: Context<FundEscrow>, _escrow_id: u64, _trade_id: u64) -> Result<()> {
 
         // pull up vars
         let escrow = &mut ctx.accounts.escrow;
@@ -237,23 +521,13 @@ pub mod localsolana_contracts {
         let amount = escrow.amount;
         let fee = escrow.fee;
         let state = escrow.state;
-        let deposit_deadline = escrow.deposit_deadline;
         let seller_key = escrow.seller;
 
-        // Verify escrow state
-        require!(state == EscrowState::Created, EscrowError::InvalidState);
-
-        // Verify deposit deadline
+        // Verify escrow state, deposit deadline and that the caller is the seller
         let current_time = Clock::get()?.unix_timestamp;
-        require!(current_time <= deposit_deadline, EscrowError::DepositDeadlineExpired);
+        let new_state = escrow.check(Action::Fund, &ctx.accounts.seller.key(), current_time)?;
 
-        // Verify caller is seller
-        require!(ctx.accounts.seller.key() == seller_key, EscrowError::Unauthorized);
-
-        let total_amount = amount
-            .checked_add(fee)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .min(MAX_U64);
+        let total_amount = state_machine::vault_total(amount, fee)?;
 
         // Ensure provided token account has sufficient funds
         require!(
@@ -275,31 +549,34 @@ pub mod localsolana_contracts {
         token::transfer(transfer_context, total_amount)?;
 
         // Set fiat deadline (current time + 30 minutes)
-        let fiat_deadline = current_time
-            .checked_add(FIAT_DEADLINE_MINUTES * SECONDS_PER_MINUTE)
-            .ok_or(EscrowError::FeeCalculationError)?;
+        let fiat_deadline = state_machine::fiat_deadline(current_time)?;
 
         // Update escrow state
-        escrow.state = EscrowState::Funded;
-        escrow.counter = escrow.counter.checked_add(1).unwrap();
+        escrow.state = new_state;
+        escrow.bump_counter()?;
         escrow.fiat_deadline = fiat_deadline;
 
         // Update tracked balance
         escrow.tracked_balance = total_amount;
 
+        // seller paid rent for the vault
+        escrow.escrow_token_rent_payer = Some(seller_key);
+
         // Emit balance change event
-        emit!(EscrowBalanceChanged {
+        emit_cpi!(EscrowBalanceChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
             new_balance: total_amount,
-            reason: "Escrow funded".to_string(),
+            reason: BalanceChangeReason::Funded,
             timestamp: current_time,
         });
 
         let counter = escrow.counter;
 
-        emit!(FundsDeposited {
+        emit_cpi!(FundsDeposited {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -309,19 +586,213 @@ pub mod localsolana_contracts {
             timestamp: current_time,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: seller_key,
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
-    pub fn mark_fiat_paid(ctx: Context<MarkFiatPaid>) -> Result<()> {
-        // Verify escrow state
-        require!(ctx.accounts.escrow.state == EscrowState::Funded, EscrowError::InvalidState);
+    // create_escrow and fund_escrow in one instruction, so a failed deposit leaves no
+    // unfunded escrow behind. Emits the same events as the two-step flow, in order
+    pub fn create_and_fund_escrow(
+        ctx: Context<CreateAndFundEscrow>,
+        escrow_id: u64,
+        trade_id: u64,
+        amount: u64,
+        sequential: bool,
+        sequential_escrow_address: Option<Pubkey>,
+        fiat_terms: FiatTerms,
+        requires_buyer_deposit: bool,
+    ) -> Result<()> {
+        // Same validation as create_escrow
+        state_machine::check_terms(amount, sequential, sequential_escrow_address.is_some())?;
+        state_machine::check_fiat_terms(fiat_terms.currency, fiat_terms.fiat_amount, fiat_terms.rate)?;
+
+        let fee_policy = read_fee_policy(&ctx.accounts.config)?;
+        let fee_charge = state_machine::fee_charge(amount, fee_policy.into())?;
+        let fee = fee_charge.value;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let deposit_deadline = state_machine::deposit_deadline(current_time)?;
+
+        let rate_check = check_committed_rate(
+            &ctx.accounts.price_feed,
+            ctx.accounts.price_update.as_ref(),
+            fiat_terms.rate,
+            current_time,
+        )?;
+
+        let seller_key = ctx.accounts.seller.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        let arbitrator = Pubkey::new_from_array(ARBITRATOR_BYTES);
+
+        // Initialize escrow as create_escrow does; there is no buyer acceptance to wait for
+        let escrow = &mut ctx.accounts.escrow;
+        let escrow_key = escrow.key();
+        escrow.escrow_id = escrow_id;
+        escrow.trade_id = trade_id;
+        escrow.seller = seller_key;
+        escrow.buyer = buyer_key;
+        escrow.arbitrator = arbitrator;
+        escrow.amount = amount;
+        escrow.fee = fee;
+        escrow.deposit_deadline = deposit_deadline;
+        escrow.fiat_deadline = 0;
+        escrow.state = EscrowState::Created;
+        escrow.sequential = sequential;
+        escrow.sequential_escrow_address = sequential_escrow_address;
+        escrow.fiat_paid = false;
+        escrow.counter = 0;
+        escrow.dispute_initiator = None;
+        escrow.dispute_initiated_time = None;
+        escrow.dispute_evidence_hash_buyer = None;
+        escrow.dispute_evidence_hash_seller = None;
+        escrow.dispute_resolution_hash = None;
+        escrow.tracked_balance = 0;
+        escrow.version = ESCROW_VERSION;
+        escrow.seller_scoped_seeds = true;
+        escrow.escrow_rent_payer = Some(seller_key);
+        escrow.fiat_terms = Some(fiat_terms.clone());
+        escrow.chat_checkpoint_buyer = None;
+        escrow.chat_checkpoint_seller = None;
+        escrow.requires_acceptance = false;
+        escrow.accepted_at = None;
+        escrow.requires_buyer_deposit = requires_buyer_deposit;
+        escrow.buyer_deposit = 0;
+        escrow.buyer_deposit_rent_payer = None;
+        escrow.fee_policy = Some(fee_policy);
+        escrow.dispute_bond = 0;
+
+        // Then run the fund_escrow guards against it
+        let new_state = escrow.check(Action::Fund, &seller_key, current_time)?;
+
+        let total_amount = state_machine::vault_total(amount, fee)?;
+        require!(
+            ctx.accounts.seller_token_account.amount >= total_amount,
+            EscrowError::InsufficientFunds
+        );
+
+        let transfer_context = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.seller_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        );
+        token::transfer(transfer_context, total_amount)?;
+
+        let fiat_deadline = state_machine::fiat_deadline(current_time)?;
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
+        escrow.fiat_deadline = fiat_deadline;
+        escrow.tracked_balance = total_amount;
+        escrow.escrow_token_rent_payer = Some(seller_key);
+
+        // create_escrow's events
+        emit_cpi!(EscrowCreated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            seller: seller_key,
+            buyer: buyer_key,
+            arbitrator,
+            amount,
+            fee,
+            deposit_deadline,
+            fiat_deadline: 0,
+            sequential,
+            sequential_escrow_address,
+            timestamp: current_time,
+            rounding: fee_charge.rounding.into(),
+            min_fee_applied: fee_charge.minimum_applied,
+            fiat_terms: Some(fiat_terms.clone()),
+            requires_acceptance: false,
+            min_fee: fee_policy.min_fee,
+            requires_buyer_deposit,
+        });
+
+        if let Some((feed_id, publish_time, check)) = rate_check {
+            emit_cpi!(FiatRateChecked {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                currency: fiat_terms.currency,
+                feed_id,
+                committed_rate: fiat_terms.rate,
+                oracle_rate: check.oracle_rate,
+                deviation_bps: check.deviation_bps,
+                publish_time,
+                timestamp: current_time,
+            });
+        }
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: None,
+            to: EscrowState::Created,
+            counter: 0,
+            actor: seller_key,
+            timestamp: current_time,
+        });
+
+        // fund_escrow's events
+        emit_cpi!(EscrowBalanceChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            new_balance: total_amount,
+            reason: BalanceChangeReason::Funded,
+            timestamp: current_time,
+        });
+
+        emit_cpi!(FundsDeposited {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            amount,
+            fee,
+            counter,
+            timestamp: current_time,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(EscrowState::Created),
+            to: new_state,
+            counter,
+            actor: seller_key,
+            timestamp: current_time,
+        });
 
-        // Verify caller is buyer
-        require!(ctx.accounts.buyer.key() == ctx.accounts.escrow.buyer, EscrowError::Unauthorized);
+        Ok(())
+    }
 
-        // Verify fiat deadline
+    pub fn mark_fiat_paid(ctx: Context<MarkFiatPaid>) -> Result<()> {
+        // Verify escrow state, that the caller is the buyer and the fiat deadline
         let current_time = Clock::get()?.unix_timestamp;
-        require!(current_time <= ctx.accounts.escrow.fiat_deadline, EscrowError::FiatDeadlineExpired);
+        let state = ctx.accounts.escrow.state;
+        let new_state = ctx.accounts.escrow
+            .check(Action::MarkFiatPaid, &ctx.accounts.buyer.key(), current_time)?;
 
         // Get escrow key and ids for event
         let escrow_key = ctx.accounts.escrow.key();
@@ -330,11 +801,25 @@ pub mod localsolana_contracts {
 
         // Update fiat_paid flag
         ctx.accounts.escrow.fiat_paid = true;
+        let counter = ctx.accounts.escrow.bump_counter()?;
+
+        emit_cpi!(FiatMarkedPaid {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            timestamp: current_time,
+        });
 
-        emit!(FiatMarkedPaid {
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.buyer.key(),
             timestamp: current_time,
         });
 
@@ -345,33 +830,26 @@ pub mod localsolana_contracts {
         ctx: Context<UpdateSequentialAddress>,
         new_address: Pubkey,
     ) -> Result<()> {
-        // Verify escrow is sequential
-        require!(ctx.accounts.escrow.sequential, EscrowError::InvalidState);
-
-        // Verify caller is buyer
-        require!(ctx.accounts.buyer.key() == ctx.accounts.escrow.buyer, EscrowError::Unauthorized);
-
-        // Verify escrow is not in terminal state
+        // Verify escrow is sequential, the caller is the buyer and the escrow is not in a
+        // terminal state
+        let current_time = Clock::get()?.unix_timestamp;
         let state = ctx.accounts.escrow.state;
-        require!(
-            state != EscrowState::Released &&
-            state != EscrowState::Cancelled &&
-            state != EscrowState::Resolved,
-            EscrowError::TerminalState
-        );
+        let new_state = ctx.accounts.escrow
+            .check(Action::UpdateSequentialAddress, &ctx.accounts.buyer.key(), current_time)?;
 
         let old_address = ctx.accounts.escrow.sequential_escrow_address;
 
         // Update sequential escrow address
         ctx.accounts.escrow.sequential_escrow_address = Some(new_address);
+        let counter = ctx.accounts.escrow.bump_counter()?;
 
         let escrow_id = ctx.accounts.escrow.escrow_id;
-        let current_time = Clock::get()?.unix_timestamp;
         let escrow_key = ctx.accounts.escrow.key();
         let trade_id = ctx.accounts.escrow.trade_id;
 
         // Emit event for sequential escrow address change
-        emit!(SequentialAddressUpdated {
+        emit_cpi!(SequentialAddressUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -380,46 +858,183 @@ pub mod localsolana_contracts {
             timestamp: current_time,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.buyer.key(),
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
-    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
-
-        // pull up vars
-        let escrow = &mut ctx.accounts.escrow;
-        let escrow_key = escrow.key();
-        let escrow_id = escrow.escrow_id;
-        let trade_id = escrow.trade_id;
-        let amount = escrow.amount;
-        let fee = escrow.fee;
-        let state = escrow.state;
-        let seller = escrow.seller;
-        let arbitrator = escrow.arbitrator;
-        let fiat_paid = escrow.fiat_paid;
-        let is_sequential = escrow.sequential;
-        let sequential_escrow_address = escrow.sequential_escrow_address;
-        let buyer = escrow.buyer;
-
-        // Verify escrow state
-        require!(state == EscrowState::Funded, EscrowError::InvalidState);
+    // Either party records the hash of payment details encrypted to the other party's
+    // registered key; the event keeps both keys so the ciphertext can serve as evidence
+    pub fn post_payment_details(ctx: Context<PostPaymentDetails>, ciphertext_hash: [u8; 32]) -> Result<()> {
+        // Verify the escrow is created or funded and the caller is a party
+        let current_time = Clock::get()?.unix_timestamp;
+        let sender = ctx.accounts.sender.key();
+        let escrow = &ctx.accounts.escrow;
+        escrow.check(Action::PostPaymentDetails, &sender, current_time)?;
+        require!(ciphertext_hash != [0u8; 32], EscrowError::InvalidEvidenceHash);
 
-        // Verify caller is seller or arbitrator
-        let caller = ctx.accounts.authority.key();
+        let recipient = if sender == escrow.seller { escrow.buyer } else { escrow.seller };
         require!(
-            caller == seller || caller == arbitrator,
-            EscrowError::Unauthorized
+            ctx.accounts.recipient_profile.owner == recipient,
+            EscrowError::RecipientProfileMismatch
         );
 
-        // Verify fiat is marked as paid
-        require!(fiat_paid, EscrowError::InvalidState);
+        emit_cpi!(PaymentDetailsPosted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow.key(),
+            escrow_id: escrow.escrow_id,
+            trade_id: escrow.trade_id,
+            sender,
+            recipient,
+            sender_key: ctx.accounts.sender_profile.encryption_key,
+            recipient_key: ctx.accounts.recipient_profile.encryption_key,
+            ciphertext_hash,
+            timestamp: current_time,
+        });
 
-        // For sequential trades, verify sequential_escrow_address exists
-        if is_sequential {
-            require!(sequential_escrow_address.is_some(), EscrowError::MissingSequentialAddress);
-            require!(ctx.accounts.sequential_escrow_token_account.is_some(), EscrowError::MissingSequentialAddress);
-        }
+        Ok(())
+    }
 
+    // Either party checkpoints the RFC 6962 Merkle root of its chat transcript so far
+    pub fn commit_chat_root(ctx: Context<CommitChatRoot>, root: [u8; 32], message_count: u64) -> Result<()> {
+        // Verify the escrow is not terminal, the caller is a party and the transcript grew
         let current_time = Clock::get()?.unix_timestamp;
+        let party = ctx.accounts.party.key();
+        let escrow = &mut ctx.accounts.escrow;
+        let state = escrow.state;
+        let new_state = escrow.check(Action::CommitChatRoot, &party, current_time)?;
+
+        let checkpoint = ChatCheckpoint { root, message_count, committed_at: current_time };
+        let slot = if party == escrow.buyer {
+            &mut escrow.chat_checkpoint_buyer
+        } else {
+            &mut escrow.chat_checkpoint_seller
+        };
+        let previous_count = slot.as_ref().map_or(0, |previous| previous.message_count);
+        state_machine::check_chat_checkpoint(root, message_count, previous_count)?;
+        *slot = Some(checkpoint);
+        let counter = escrow.bump_counter()?;
+
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+
+        emit_cpi!(ChatRootCommitted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            party,
+            root,
+            message_count,
+            timestamp: current_time,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: party,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // During a dispute, proves that message `leaf_index` of `party`'s last checkpoint hashes
+    // to `message_hash`, and records it for the arbitrator
+    pub fn prove_chat_message(
+        ctx: Context<ProveChatMessage>,
+        party: Pubkey,
+        leaf_index: u64,
+        message_hash: [u8; 32],
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        // Verify the escrow is disputed and the caller is a party or the arbitrator
+        let current_time = Clock::get()?.unix_timestamp;
+        let prover = ctx.accounts.prover.key();
+        let escrow = &ctx.accounts.escrow;
+        escrow.check(Action::ProveChatMessage, &prover, current_time)?;
+
+        let checkpoint = if party == escrow.buyer {
+            escrow.chat_checkpoint_buyer.as_ref()
+        } else if party == escrow.seller {
+            escrow.chat_checkpoint_seller.as_ref()
+        } else {
+            None
+        };
+        let checkpoint = checkpoint.ok_or(EscrowError::InvalidInclusionProof)?;
+
+        let leaf = hashv(&[&[state_machine::CHAT_LEAF_PREFIX], &message_hash]).to_bytes();
+        state_machine::verify_inclusion(
+            leaf_index,
+            checkpoint.message_count,
+            leaf,
+            &proof,
+            &checkpoint.root,
+            |left, right| hashv(&[&[state_machine::CHAT_NODE_PREFIX], left, right]).to_bytes(),
+        )?;
+
+        emit_cpi!(ChatMessageProven {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow.key(),
+            escrow_id: escrow.escrow_id,
+            trade_id: escrow.trade_id,
+            party,
+            root: checkpoint.root,
+            message_count: checkpoint.message_count,
+            leaf_index,
+            message_hash,
+            prover,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
+
+        // pull up vars
+        let escrow = &mut ctx.accounts.escrow;
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+        let amount = escrow.amount;
+        let fee = escrow.fee;
+        let state = escrow.state;
+        let is_sequential = escrow.sequential;
+        let buyer = escrow.buyer;
+
+        // Verify escrow state, that the caller is the seller or arbitrator, that fiat is
+        // marked as paid and, for sequential trades, that sequential_escrow_address exists
+        let current_time = Clock::get()?.unix_timestamp;
+        let caller = ctx.accounts.authority.key();
+        let new_state = escrow.check(Action::Release, &caller, current_time)?;
+        if is_sequential {
+            require!(ctx.accounts.sequential_escrow_token_account.is_some(), EscrowError::MissingSequentialAddress);
+        }
+
+        let payout = state_machine::payout(
+            Settlement::Release,
+            amount,
+            fee,
+            escrow.dispute_bond,
+            escrow.fee_policy(),
+        )?;
 
         // Create PDA signer seeds
         let escrow_token_bump = ctx.bumps.escrow_token_account;
@@ -445,7 +1060,7 @@ pub mod localsolana_contracts {
             signer_seeds,
         );
 
-        token::transfer(fee_transfer_context, fee)?;
+        token::transfer(fee_transfer_context, payout.to_arbitrator)?;
 
         // Transfer principal to buyer or sequential escrow
         let destination_account = if is_sequential {
@@ -466,27 +1081,28 @@ pub mod localsolana_contracts {
             signer_seeds,
         );
 
-        token::transfer(principal_transfer_context, amount)?;
+        token::transfer(principal_transfer_context, payout.to_buyer)?;
 
         // Update tracked balance to zero
         escrow.tracked_balance = 0;
 
         // Emit balance change event
-        emit!(EscrowBalanceChanged {
+        emit_cpi!(EscrowBalanceChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
             new_balance: 0,
-            reason: "Escrow released".to_string(),
+            reason: BalanceChangeReason::Released,
             timestamp: current_time,
         });
 
-        // Updated: Close escrow_token_account, refund rent to seller
+        // Updated: Close escrow_token_account, refund rent to whoever paid for it
         let close_token_context = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: ctx.accounts.escrow_token_account.to_account_info(),
-                destination: ctx.accounts.authority.to_account_info(), // refund to authority
+                destination: ctx.accounts.escrow_token_rent_payer.to_account_info(),
                 authority: ctx.accounts.escrow_token_account.to_account_info(),
             },
             signer_seeds,
@@ -494,10 +1110,65 @@ pub mod localsolana_contracts {
 
         token::close_account(close_token_context)?;
 
-        // Updated: Close escrow state account, refund rent to seller
-        escrow.state = EscrowState::Released;
-        escrow.counter = escrow.counter.checked_add(1).unwrap();
-        let counter = escrow.counter;
+
+        // Close any bond accounts that were initialized but never used
+        let stray_bond_tokens = close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_bond_account,
+            ctx.bumps.buyer_bond_account,
+            &ctx.accounts.buyer_bond_rent_payer,
+            escrow.buyer_bond_rent_payer.is_some(),
+            Some(&ctx.accounts.arbitrator_token_account),
+            b"buyer_bond",
+            &escrow_key,
+        )? + close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.seller_bond_account,
+            ctx.bumps.seller_bond_account,
+            &ctx.accounts.seller_bond_rent_payer,
+            escrow.seller_bond_rent_payer.is_some(),
+            Some(&ctx.accounts.arbitrator_token_account),
+            b"seller_bond",
+            &escrow_key,
+        )?;
+        if stray_bond_tokens > 0 {
+            emit_cpi!(SurplusSwept {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                amount: stray_bond_tokens,
+                destination: ctx.accounts.arbitrator_token_account.key(),
+                timestamp: current_time,
+            });
+        }
+
+        // A buyer who saw the trade through gets its deposit back
+        if let Some(deposit) = settle_buyer_deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_deposit_account,
+            ctx.bumps.buyer_deposit_account,
+            &ctx.accounts.buyer_deposit_rent_payer,
+            escrow.buyer_deposit > 0,
+            Some(&ctx.accounts.buyer_token_account),
+            &buyer,
+            &escrow_key,
+        )? {
+            emit_cpi!(BuyerDepositSettled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                recipient: buyer,
+                amount: deposit,
+                forfeited: false,
+                timestamp: current_time,
+            });
+        }
+
+        // Escrow state account is closed to its rent payer by the close constraint
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
 
         // Figure out destination for event
         let destination = if is_sequential {
@@ -506,7 +1177,8 @@ pub mod localsolana_contracts {
             buyer
         };
 
-        emit!(EscrowReleased {
+        emit_cpi!(EscrowReleased {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -518,13 +1190,23 @@ pub mod localsolana_contracts {
             destination,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: caller,
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
     pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
 
-        use constants::MAX_U64;
-
         // pull up vars
         let current_time = Clock::get()?.unix_timestamp;
         let escrow = &mut ctx.accounts.escrow;
@@ -535,22 +1217,11 @@ pub mod localsolana_contracts {
         let fee = escrow.fee;
         let seller = escrow.seller;
 
-        // Verify escrow state
+        // Verify escrow state, that the caller is the seller or arbitrator and that fiat is
+        // not marked as paid
         let current_state = escrow.state;
-        require!(
-            current_state == EscrowState::Created || current_state == EscrowState::Funded,
-            EscrowError::InvalidState
-        );
-
-        // Verify caller is seller or arbitrator
         let caller = ctx.accounts.authority.key();
-        require!(
-            caller == escrow.seller || caller == escrow.arbitrator,
-            EscrowError::Unauthorized
-        );
-
-        // Verify fiat is not marked as paid
-        require!(!escrow.fiat_paid, EscrowError::InvalidState);
+        let new_state = escrow.check(Action::Cancel, &caller, current_time)?;
 
         // If escrow is funded, return funds to seller
         if current_state == EscrowState::Funded {
@@ -561,10 +1232,16 @@ pub mod localsolana_contracts {
             let seller_token_account = ctx.accounts.seller_token_account.as_ref()
                 .ok_or(EscrowError::InvalidState)?;
 
-            let total_amount = amount
-                .checked_add(fee)
-                .ok_or(EscrowError::FeeCalculationError)?
-                .min(MAX_U64);
+            // A seller backing out after the buyer accepted pays the buyer a penalty out of
+            // the fee; arbitrator cancellations refund everything
+            let settlement = state_machine::cancellation(&escrow.facts(), escrow.caller(&caller));
+            let payout = state_machine::payout(
+                settlement,
+                amount,
+                fee,
+                escrow.dispute_bond,
+                escrow.fee_policy(),
+            )?;
 
             let escrow_token_bump = ctx.bumps.escrow_token_account;
             let seeds = &[
@@ -586,27 +1263,58 @@ pub mod localsolana_contracts {
                 signer_seeds,
             );
 
-            token::transfer(transfer_context, total_amount)?;
+            token::transfer(transfer_context, payout.to_seller)?;
+
+            if payout.to_buyer > 0 {
+                let buyer_token_account = ctx.accounts.buyer_token_account.as_ref()
+                    .ok_or(EscrowError::InvalidState)?;
+                require_keys_eq!(buyer_token_account.owner, escrow.buyer, EscrowError::Unauthorized);
+
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: escrow_token_account.to_account_info(),
+                            to: buyer_token_account.to_account_info(),
+                            authority: escrow_token_account.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    payout.to_buyer,
+                )?;
+
+                emit_cpi!(CancellationPenaltyPaid {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    object_id: escrow_key,
+                    escrow_id,
+                    trade_id,
+                    seller,
+                    buyer: escrow.buyer,
+                    amount: payout.to_buyer,
+                    timestamp: current_time,
+                });
+            }
 
             // Set tracked balance to Zero
             escrow.tracked_balance = 0;
 
             // Emit escrow balance changed event
-            emit!(EscrowBalanceChanged {
+            emit_cpi!(EscrowBalanceChanged {
+                schema_version: EVENT_SCHEMA_VERSION,
                 object_id: escrow_key,
                 escrow_id,
                 trade_id,
                 new_balance: 0,
-                reason: "Escrow cancelled".to_string(),
+                reason: BalanceChangeReason::Cancelled,
                 timestamp: current_time,
             });
 
-            // Updated: Close escrow_token_account
+            // Updated: Close escrow_token_account, refund rent to whoever paid for it
             let close_token_context = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 CloseAccount {
                     account: escrow_token_account.to_account_info(),
-                    destination: ctx.accounts.seller.to_account_info(),
+                    destination: ctx.accounts.escrow_token_rent_payer.to_account_info(),
                     authority: escrow_token_account.to_account_info(),
                 },
                 signer_seeds,
@@ -615,12 +1323,68 @@ pub mod localsolana_contracts {
             token::close_account(close_token_context)?;
         }
 
+        // Close any bond accounts that were initialized but never used
+        let stray_bond_tokens = close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_bond_account,
+            ctx.bumps.buyer_bond_account,
+            &ctx.accounts.buyer_bond_rent_payer,
+            escrow.buyer_bond_rent_payer.is_some(),
+            ctx.accounts.arbitrator_token_account.as_ref(),
+            b"buyer_bond",
+            &escrow_key,
+        )? + close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.seller_bond_account,
+            ctx.bumps.seller_bond_account,
+            &ctx.accounts.seller_bond_rent_payer,
+            escrow.seller_bond_rent_payer.is_some(),
+            ctx.accounts.arbitrator_token_account.as_ref(),
+            b"seller_bond",
+            &escrow_key,
+        )?;
+        if stray_bond_tokens > 0 {
+            emit_cpi!(SurplusSwept {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                amount: stray_bond_tokens,
+                destination: ctx.accounts.arbitrator_token_account.as_ref().map(|account| account.key()).unwrap_or_default(),
+                timestamp: current_time,
+            });
+        }
+
+        // Cancelling from the seller side is no fault of the buyer, so its deposit comes back
+        let buyer = escrow.buyer;
+        if let Some(deposit) = settle_buyer_deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_deposit_account,
+            ctx.bumps.buyer_deposit_account,
+            &ctx.accounts.buyer_deposit_rent_payer,
+            escrow.buyer_deposit > 0,
+            ctx.accounts.buyer_token_account.as_ref(),
+            &buyer,
+            &escrow_key,
+        )? {
+            emit_cpi!(BuyerDepositSettled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                recipient: buyer,
+                amount: deposit,
+                forfeited: false,
+                timestamp: current_time,
+            });
+        }
+
         // Update escrow state
-        escrow.state = EscrowState::Cancelled;
-        escrow.counter = escrow.counter.checked_add(1).unwrap();
-        let counter = escrow.counter;
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
 
-        emit!(EscrowCancelled {
+        emit_cpi!(EscrowCancelled {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -631,25 +1395,85 @@ pub mod localsolana_contracts {
             timestamp: current_time,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(current_state),
+            to: new_state,
+            counter,
+            actor: caller,
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
     // new, re stack overflow error
     pub fn initialize_buyer_bond_account(
-        _ctx: Context<InitializeBuyerBondAccount>,
+        ctx: Context<InitializeBuyerBondAccount>,
         _escrow_id: u64,
         _trade_id: u64,
     ) -> Result<()> {
-        // The account is initialized automatically through Anchor's account constraints
+        // The account is initialized automatically through Anchor's account constraints;
+        // record who paid its rent so it can be refunded when the account is closed
+        let current_time = Clock::get()?.unix_timestamp;
+        let escrow = &mut ctx.accounts.escrow;
+        let state = escrow.state;
+        let new_state = escrow.check(Action::InitializeBuyerBond, &ctx.accounts.payer.key(), current_time)?;
+        escrow.buyer_bond_rent_payer = Some(ctx.accounts.payer.key());
+        let counter = escrow.bump_counter()?;
+
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.payer.key(),
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
     pub fn initialize_seller_bond_account(
-        _ctx: Context<InitializeSellerBondAccount>,
+        ctx: Context<InitializeSellerBondAccount>,
         _escrow_id: u64,
         _trade_id: u64,
     ) -> Result<()> {
-        // The account is initialized automatically through Anchor's account constraints
+        // The account is initialized automatically through Anchor's account constraints;
+        // record who paid its rent so it can be refunded when the account is closed
+        let current_time = Clock::get()?.unix_timestamp;
+        let escrow = &mut ctx.accounts.escrow;
+        let state = escrow.state;
+        let new_state = escrow.check(Action::InitializeSellerBond, &ctx.accounts.payer.key(), current_time)?;
+        escrow.seller_bond_rent_payer = Some(ctx.accounts.payer.key());
+        let counter = escrow.bump_counter()?;
+
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.payer.key(),
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
@@ -657,28 +1481,19 @@ pub mod localsolana_contracts {
         ctx: Context<OpenDispute>,
         evidence_hash: [u8; 32],
     ) -> Result<()> {
-        // Verify escrow state
-        require!(ctx.accounts.escrow.state == EscrowState::Funded, EscrowError::InvalidState);
-
-        // Verify fiat is marked as paid
-        require!(ctx.accounts.escrow.fiat_paid, EscrowError::InvalidState);
-
-        // Verify caller is buyer or seller
+        // Verify escrow state, that fiat is marked as paid and that the caller is the buyer
+        // or seller
+        let current_time = Clock::get()?.unix_timestamp;
         let caller = ctx.accounts.disputing_party.key();
         let escrow_buyer = ctx.accounts.escrow.buyer;
-        let escrow_seller = ctx.accounts.escrow.seller;
-        require!(
-            caller == escrow_buyer || caller == escrow_seller,
-            EscrowError::Unauthorized
-        );
+        let state = ctx.accounts.escrow.state;
+        let new_state = ctx.accounts.escrow.check(Action::OpenDispute, &caller, current_time)?;
 
-        // Calculate bond amount (5% of transaction value)
-        let amount = ctx.accounts.escrow.amount;
-        let bond_amount = amount
-            .checked_mul(DISPUTE_BOND_BASIS_POINTS)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .checked_div(10000)
-            .ok_or(EscrowError::FeeCalculationError)?;
+        // Calculate bond amount (5% of transaction value, rounded, at least the minimum of the
+        // policy the escrow was created under)
+        let fee_policy = ctx.accounts.escrow.fee_policy();
+        let bond_charge = state_machine::dispute_bond_charge(ctx.accounts.escrow.amount, fee_policy)?;
+        let bond_amount = bond_charge.value;
 
         // Verify bond amount in token account
         require!(
@@ -686,16 +1501,18 @@ pub mod localsolana_contracts {
             EscrowError::IncorrectBondAmount
         );
 
-        let current_time = Clock::get()?.unix_timestamp;
         let escrow_key = ctx.accounts.escrow.key();
         let escrow_id = ctx.accounts.escrow.escrow_id;
         let trade_id = ctx.accounts.escrow.trade_id;
 
         // Update escrow state (dispute details)
         let escrow = &mut ctx.accounts.escrow;
-        escrow.state = EscrowState::Disputed;
+        escrow.state = new_state;
         escrow.dispute_initiator = Some(caller);
         escrow.dispute_initiated_time = Some(current_time);
+        // the respondent posts the same bond, and settlement pays out exactly this much
+        escrow.dispute_bond = bond_amount;
+        let counter = escrow.bump_counter()?;
 
         // Store evidence hash in appropriate field
         if caller == escrow_buyer {
@@ -722,7 +1539,8 @@ pub mod localsolana_contracts {
 
         token::transfer(transfer_context, bond_amount)?;
 
-        emit!(DisputeOpened {
+        emit_cpi!(DisputeOpened {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -730,6 +1548,21 @@ pub mod localsolana_contracts {
             timestamp: current_time,
             evidence_hash,
             bond_amount,
+            rounding: bond_charge.rounding.into(),
+            min_bond_applied: bond_charge.minimum_applied,
+            min_bond: fee_policy.min_dispute_bond,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: caller,
+            timestamp: current_time,
         });
 
         Ok(())
@@ -739,40 +1572,24 @@ pub mod localsolana_contracts {
         ctx: Context<RespondToDispute>,
         evidence_hash: [u8; 32],
     ) -> Result<()> {
-        // Verify escrow state
-        require!(ctx.accounts.escrow.state == EscrowState::Disputed, EscrowError::InvalidState);
-
-        // Verify dispute initiator exists
-        let dispute_initiator_opt = ctx.accounts.escrow.dispute_initiator;
-        require!(dispute_initiator_opt.is_some(), EscrowError::InvalidState);
-        let dispute_initiator = dispute_initiator_opt.unwrap();
-
-        // Verify caller is the non-initiating party
+        // Verify escrow state, that the caller is the non-initiating party, the response
+        // deadline and that the caller has not answered yet
+        let current_time = Clock::get()?.unix_timestamp;
         let caller = ctx.accounts.responding_party.key();
         let escrow_buyer = ctx.accounts.escrow.buyer;
-        let escrow_seller = ctx.accounts.escrow.seller;
-
-        require!(
-            caller != dispute_initiator && (caller == escrow_buyer || caller == escrow_seller),
-            EscrowError::Unauthorized
-        );
-
-        // Verify response deadline
-        let current_time = Clock::get()?.unix_timestamp;
-        let dispute_initiated_time = ctx.accounts.escrow.dispute_initiated_time.unwrap();
-        let response_deadline = dispute_initiated_time
-            .checked_add(DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR)
-            .ok_or(EscrowError::FeeCalculationError)?;
-
-        require!(current_time <= response_deadline, EscrowError::ResponseDeadlineExpired);
-
-        // Calculate bond amount (5% of transaction value)
-        let amount = ctx.accounts.escrow.amount;
-        let bond_amount = amount
-            .checked_mul(DISPUTE_BOND_BASIS_POINTS)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .checked_div(10000)
-            .ok_or(EscrowError::FeeCalculationError)?;
+        let state = ctx.accounts.escrow.state;
+        let new_state = ctx.accounts.escrow.check(Action::RespondToDispute, &caller, current_time)?;
+
+        // Match the initiator's bond, as recorded when the dispute was opened, or as held by
+        // its bond account for disputes opened before bonds were recorded
+        let fee_policy = ctx.accounts.escrow.fee_policy();
+        let bond_charge = state_machine::dispute_bond_charge(ctx.accounts.escrow.amount, fee_policy)?;
+        let initiator_bond = if caller == escrow_buyer {
+            ctx.accounts.seller_bond_account.amount
+        } else {
+            ctx.accounts.buyer_bond_account.amount
+        };
+        let bond_amount = ctx.accounts.escrow.posted_dispute_bond(initiator_bond);
 
         // Verify bond amount in token account
         require!(
@@ -788,14 +1605,12 @@ pub mod localsolana_contracts {
 
         // Store evidence hash in appropriate field
         if caller == escrow_buyer {
-            // Verify no duplicate submission
-            require!(escrow.dispute_evidence_hash_buyer.is_none(), EscrowError::DuplicateEvidence);
             escrow.dispute_evidence_hash_buyer = Some(evidence_hash);
         } else {
-            // Verify no duplicate submission
-            require!(escrow.dispute_evidence_hash_seller.is_none(), EscrowError::DuplicateEvidence);
             escrow.dispute_evidence_hash_seller = Some(evidence_hash);
         }
+        escrow.dispute_bond = bond_amount;
+        let counter = escrow.bump_counter()?;
 
         // Transfer bond to dispute bond account
         let bond_account = if caller == escrow_buyer {
@@ -820,7 +1635,8 @@ pub mod localsolana_contracts {
             .checked_add(ARBITRATION_DEADLINE_HOURS * SECONDS_PER_HOUR)
             .ok_or(EscrowError::FeeCalculationError)?;
 
-        emit!(DisputeResponseSubmitted {
+        emit_cpi!(DisputeResponseSubmitted {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -828,15 +1644,27 @@ pub mod localsolana_contracts {
             timestamp: current_time,
             evidence_hash,
             bond_amount,
+            rounding: bond_charge.rounding.into(),
+            min_bond_applied: bond_charge.minimum_applied && bond_amount == bond_charge.value,
+            min_bond: fee_policy.min_dispute_bond,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: caller,
+            timestamp: current_time,
         });
 
         Ok(())
     }
 
     pub fn default_judgment(ctx: Context<DefaultJudgment>) -> Result<()> {
-
-        use constants::MAX_U64;
-
         // pull up vars
         let current_time = Clock::get()?.unix_timestamp;
         let escrow = &mut ctx.accounts.escrow;
@@ -847,63 +1675,33 @@ pub mod localsolana_contracts {
         let fee = escrow.fee;
         let escrow_buyer = escrow.buyer;
         let escrow_seller = escrow.seller;
-        let dispute_initiator_opt = escrow.dispute_initiator;
-        let dispute_initiated_time = escrow.dispute_initiated_time;
-
-        // Verify escrow state
-        require!(escrow.state == EscrowState::Disputed, EscrowError::InvalidState);
-
-        // Verify caller is the arbitrator
-        require!(
-            ctx.accounts.arbitrator.key() == escrow.arbitrator,
-            EscrowError::Unauthorized
-        );
-
-        // Verify dispute initiator exists
-        require!(dispute_initiator_opt.is_some(), EscrowError::InvalidState);
-        let dispute_initiator = dispute_initiator_opt.unwrap();
-
-        // Verify response deadline has passed
-        let dispute_initiated_time = dispute_initiated_time.unwrap();
-        let response_deadline = dispute_initiated_time
-            .checked_add(DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR)
-            .ok_or(EscrowError::FeeCalculationError)?;
-
-        require!(current_time > response_deadline, EscrowError::InvalidState);
+        let state = escrow.state;
 
-        // Determine winning party (the one who submitted evidence/bond)
-        let evidence_hash_buyer = escrow.dispute_evidence_hash_buyer;
-        let evidence_hash_seller = escrow.dispute_evidence_hash_seller;
+        // No caller check: once the response deadline has passed without a response the
+        // outcome is fully determined, so anyone (typically the initiating party) may trigger it
+        let new_state = escrow.check(Action::DefaultJudgment, &ctx.accounts.caller.key(), current_time)?;
 
-        // Determine winner
-        let winner = if dispute_initiator == escrow_buyer {
-            // Buyer initiated dispute, check if seller responded
-            if evidence_hash_seller.is_none() {
-                escrow_buyer // Seller didn't respond, buyer wins
-            } else {
-                return Err(EscrowError::InvalidState.into()); // Both parties responded, cannot use default judgment
-            }
-        } else {
-            // Seller initiated dispute, check if buyer responded
-            if evidence_hash_buyer.is_none() {
-                escrow_seller // Buyer didn't respond, seller wins
-            } else {
-                return Err(EscrowError::InvalidState.into()); // Both parties responded, cannot use default judgment
-            }
+        // The initiator wins when the other party never answered
+        let party = state_machine::default_winner(&escrow.facts(), current_time)?;
+        let winner = match party {
+            Party::Buyer => escrow_buyer,
+            Party::Seller => escrow_seller,
         };
 
-        // Calculate bond amount and total amount
-        let total_amount = amount
-            .checked_add(fee)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .min(MAX_U64);
-
-        let bond_amount = amount
-            .checked_mul(DISPUTE_BOND_BASIS_POINTS)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .checked_div(10000)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .min(MAX_U64);
+        // Everything in the vault goes to the winner, who also gets back the bond they posted
+        let winning_bond = match party {
+            Party::Buyer => ctx.accounts.buyer_bond_account.amount,
+            Party::Seller => ctx.accounts.seller_bond_account.amount,
+        };
+        let payout = state_machine::payout(
+            Settlement::DefaultJudgment { winner: party },
+            amount,
+            fee,
+            escrow.posted_dispute_bond(winning_bond),
+            escrow.fee_policy(),
+        )?;
+        let total_amount = payout.from_vault();
+        let bond_amount = payout.bond_returned;
 
         // Determine winning account
         let winning_token_account = if winner == escrow_buyer {
@@ -939,12 +1737,13 @@ pub mod localsolana_contracts {
         escrow.tracked_balance = 0;
 
         // emit escrow balance change event
-        emit!(EscrowBalanceChanged {
+        emit_cpi!(EscrowBalanceChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
             new_balance: 0,
-            reason: "Dispute resolved by default judgment".to_string(),
+            reason: BalanceChangeReason::DefaultJudgment,
             timestamp: current_time,
         });
 
@@ -966,10 +1765,18 @@ pub mod localsolana_contracts {
         ];
 
         // Return bond to winning party
-        let (winning_bond_account, winning_bond_seeds) = if winner == escrow_buyer {
-            (&ctx.accounts.buyer_bond_account, &buyer_bond_seeds_array[..])
+        let (winning_bond_account, winning_bond_seeds, winning_bond_rent_payer) = if winner == escrow_buyer {
+            (
+                &ctx.accounts.buyer_bond_account,
+                &buyer_bond_seeds_array[..],
+                ctx.accounts.buyer_bond_rent_payer.to_account_info(),
+            )
         } else {
-            (&ctx.accounts.seller_bond_account, &seller_bond_seeds_array[..])
+            (
+                &ctx.accounts.seller_bond_account,
+                &seller_bond_seeds_array[..],
+                ctx.accounts.seller_bond_rent_payer.to_account_info(),
+            )
         };
 
         // Create a slice of references to the conditionally assigned seeds
@@ -992,7 +1799,7 @@ pub mod localsolana_contracts {
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: ctx.accounts.escrow_token_account.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(), // Refund to seller
+                destination: ctx.accounts.escrow_token_rent_payer.to_account_info(), // Refund to vault rent payer
                 authority: ctx.accounts.escrow_token_account.to_account_info(),
             },
             &[&escrow_token_seeds[..]],
@@ -1003,14 +1810,59 @@ pub mod localsolana_contracts {
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: winning_bond_account.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(), // Refund to seller
+                destination: winning_bond_rent_payer, // Refund to bond rent payer
                 authority: winning_bond_account.to_account_info(),
             },
             &[&winning_bond_seeds[..]],
         ))?;
 
+        // Close the defaulting party's bond account (never funded) so its rent isn't leaked
+        if winner == escrow_buyer {
+            close_bond_account(
+                &ctx.accounts.token_program,
+                &ctx.accounts.seller_bond_account,
+                ctx.accounts.seller_bond_rent_payer.to_account_info(),
+                b"seller_bond",
+                &escrow_key,
+                seller_bond_bump,
+            )?;
+        } else {
+            close_bond_account(
+                &ctx.accounts.token_program,
+                &ctx.accounts.buyer_bond_account,
+                ctx.accounts.buyer_bond_rent_payer.to_account_info(),
+                b"buyer_bond",
+                &escrow_key,
+                buyer_bond_bump,
+            )?;
+        }
+
+        // The buyer deposit only covers an unpaid expiry, so it goes back whatever the judgment
+        if let Some(deposit) = settle_buyer_deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_deposit_account,
+            ctx.bumps.buyer_deposit_account,
+            &ctx.accounts.buyer_deposit_rent_payer,
+            escrow.buyer_deposit > 0,
+            Some(&ctx.accounts.buyer_token_account),
+            &escrow_buyer,
+            &escrow_key,
+        )? {
+            emit_cpi!(BuyerDepositSettled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                recipient: escrow_buyer,
+                amount: deposit,
+                forfeited: false,
+                timestamp: current_time,
+            });
+        }
+
         // Update escrow state
-        escrow.state = EscrowState::Resolved;
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
 
         // Determine defaulting party
         let defaulting_party = if winner == escrow_buyer {
@@ -1019,7 +1871,8 @@ pub mod localsolana_contracts {
             escrow_buyer
         };
 
-        emit!(DisputeDefaultJudgment {
+        emit_cpi!(DisputeDefaultJudgment {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -1028,6 +1881,18 @@ pub mod localsolana_contracts {
             timestamp: current_time,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.caller.key(),
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
@@ -1036,8 +1901,6 @@ pub mod localsolana_contracts {
         decision: bool, // true = release to buyer, false = return to seller
         resolution_hash: [u8; 32],
     ) -> Result<()> {
-        use constants::MAX_U64;
-
         // pull up common vars
         let current_time = Clock::get()?.unix_timestamp;
         let escrow = &mut ctx.accounts.escrow;
@@ -1050,34 +1913,29 @@ pub mod localsolana_contracts {
         let escrow_seller = escrow.seller;
         let is_sequential = escrow.sequential;
         let sequential_escrow_address = escrow.sequential_escrow_address;
-        let evidence_hash_buyer = escrow.dispute_evidence_hash_buyer;
-        let evidence_hash_seller = escrow.dispute_evidence_hash_seller;
-
-        // Verify escrow state
-        require!(escrow.state == EscrowState::Disputed, EscrowError::InvalidState);
-
-        // Verify caller is the arbitrator
-        require!(
-            ctx.accounts.arbitrator.key() == escrow.arbitrator,
-            EscrowError::Unauthorized
-        );
+        let state = escrow.state;
 
-        // Verify both evidence hashes are present
-        require!(
-            evidence_hash_buyer.is_some() && evidence_hash_seller.is_some(),
-            EscrowError::InvalidEvidenceHash
-        );
+        // Verify escrow state, that the caller is the arbitrator and that both evidence
+        // hashes are present
+        let new_state = escrow.check(Action::ResolveDispute, &ctx.accounts.arbitrator.key(), current_time)?;
 
         // Store resolution hash
         escrow.dispute_resolution_hash = Some(resolution_hash);
 
-        // Calculate bond amount (5% of transaction value)
-        let bond_amount = amount
-            .checked_mul(DISPUTE_BOND_BASIS_POINTS)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .checked_div(10000)
-            .ok_or(EscrowError::FeeCalculationError)?
-            .min(MAX_U64);
+        // Work out who gets what; each party posted the bond the initiator did
+        let initiator_bond = if escrow.dispute_initiator == Some(escrow_buyer) {
+            ctx.accounts.buyer_bond_account.amount
+        } else {
+            ctx.accounts.seller_bond_account.amount
+        };
+        let payout = state_machine::payout(
+            Settlement::Resolution { for_buyer: decision },
+            amount,
+            fee,
+            escrow.posted_dispute_bond(initiator_bond),
+            escrow.fee_policy(),
+        )?;
+        let bond_amount = payout.bond_returned;
 
         // Verify both bonds are present by checking account balances
         require!(
@@ -1106,6 +1964,19 @@ pub mod localsolana_contracts {
                 )
             };
 
+        // Bond rent goes back to whoever paid for each bond account
+        let (winning_bond_rent_payer, losing_bond_rent_payer) = if decision {
+            (
+                ctx.accounts.buyer_bond_rent_payer.to_account_info(),
+                ctx.accounts.seller_bond_rent_payer.to_account_info(),
+            )
+        } else {
+            (
+                ctx.accounts.seller_bond_rent_payer.to_account_info(),
+                ctx.accounts.buyer_bond_rent_payer.to_account_info(),
+            )
+        };
+
         // Get and prepare seeds
         let escrow_token_bump = ctx.bumps.escrow_token_account;
         let escrow_token_seeds = &[
@@ -1130,7 +2001,7 @@ pub mod localsolana_contracts {
                 signer_seeds,
             );
 
-            token::transfer(fee_transfer_context, fee)?;
+            token::transfer(fee_transfer_context, payout.to_arbitrator)?;
 
             // Transfer principal to buyer
             let principal_transfer_context = CpiContext::new_with_signer(
@@ -1143,28 +2014,24 @@ pub mod localsolana_contracts {
                 signer_seeds,
             );
 
-            token::transfer(principal_transfer_context, amount)?;
+            token::transfer(principal_transfer_context, payout.to_buyer)?;
 
             // set tracked balance to zero
             escrow.tracked_balance = 0;
 
             // emit escrow balance change event
-            emit!(EscrowBalanceChanged {
+            emit_cpi!(EscrowBalanceChanged {
+                schema_version: EVENT_SCHEMA_VERSION,
                 object_id: escrow_key,
                 escrow_id,
                 trade_id,
                 new_balance: 0,
-                reason: "Dispute resolved to buyer".to_string(),
+                reason: BalanceChangeReason::ResolvedToBuyer,
                 timestamp: current_time,
             });
 
         } else {
             // Transfer all funds to seller
-            let total_amount = amount
-                .checked_add(fee)
-                .ok_or(EscrowError::FeeCalculationError)?
-                .min(MAX_U64);
-
             let signer_seeds = &[&escrow_token_seeds[..]];
 
             let transfer_context = CpiContext::new_with_signer(
@@ -1177,18 +2044,19 @@ pub mod localsolana_contracts {
                 signer_seeds,
             );
 
-            token::transfer(transfer_context, total_amount)?;
+            token::transfer(transfer_context, payout.to_seller)?;
 
             // set tracked balance to zero
             escrow.tracked_balance = 0;
 
             // emit escrow balance change event
-            emit!(EscrowBalanceChanged {
+            emit_cpi!(EscrowBalanceChanged {
+                schema_version: EVENT_SCHEMA_VERSION,
                 object_id: escrow_key,
                 escrow_id,
                 trade_id,
                 new_balance: 0,
-                reason: "Dispute resolved to seller".to_string(),
+                reason: BalanceChangeReason::ResolvedToSeller,
                 timestamp: current_time,
             });
         }
@@ -1249,14 +2117,14 @@ pub mod localsolana_contracts {
             losing_signer_seeds,
         );
 
-        token::transfer(loser_bond_transfer_context, bond_amount)?;
+        token::transfer(loser_bond_transfer_context, payout.bond_forfeited)?;
 
         // Close escrow_token_account
         token::close_account(CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: ctx.accounts.escrow_token_account.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(),
+                destination: ctx.accounts.escrow_token_rent_payer.to_account_info(),
                 authority: ctx.accounts.escrow_token_account.to_account_info(),
             },
             &[&escrow_token_seeds[..]],
@@ -1267,7 +2135,7 @@ pub mod localsolana_contracts {
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: winning_bond_account.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(),
+                destination: winning_bond_rent_payer,
                 authority: winning_bond_account.to_account_info(),
             },
             winning_signer_seeds,
@@ -1278,16 +2146,38 @@ pub mod localsolana_contracts {
             ctx.accounts.token_program.to_account_info(),
             CloseAccount {
                 account: losing_bond_account.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(),
+                destination: losing_bond_rent_payer,
                 authority: losing_bond_account.to_account_info(),
             },
             losing_signer_seeds,
         ))?;
 
+        // The buyer deposit only covers an unpaid expiry, so it goes back whatever the decision
+        if let Some(deposit) = settle_buyer_deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_deposit_account,
+            ctx.bumps.buyer_deposit_account,
+            &ctx.accounts.buyer_deposit_rent_payer,
+            escrow.buyer_deposit > 0,
+            Some(&ctx.accounts.buyer_token_account),
+            &escrow_buyer,
+            &escrow_key,
+        )? {
+            emit_cpi!(BuyerDepositSettled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                recipient: escrow_buyer,
+                amount: deposit,
+                forfeited: false,
+                timestamp: current_time,
+            });
+        }
+
         // Update escrow state
-        escrow.state = EscrowState::Resolved;
-        escrow.counter = escrow.counter.checked_add(1).unwrap();
-        let counter = escrow.counter;
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
 
         // Destination for funds (used only in event)
         let _funds_destination = if decision && is_sequential && sequential_escrow_address.is_some() {
@@ -1298,7 +2188,8 @@ pub mod localsolana_contracts {
             escrow_seller
         };
 
-        emit!(DisputeResolved {
+        emit_cpi!(DisputeResolved {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -1311,12 +2202,22 @@ pub mod localsolana_contracts {
             explanation_reference: "".to_string(), // Reference to off-chain explanation
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.arbitrator.key(),
+            timestamp: current_time,
+        });
+
         Ok(())
     }
 
     pub fn auto_cancel(ctx: Context<AutoCancel>) -> Result<()> {
-        use constants::MAX_U64;
-
         // pull up vars
         let current_time = Clock::get()?.unix_timestamp;
         let escrow = &mut ctx.accounts.escrow;
@@ -1327,39 +2228,12 @@ pub mod localsolana_contracts {
         let fee = escrow.fee;
         let seller = escrow.seller;
         let current_state = escrow.state;
-        let deposit_deadline = escrow.deposit_deadline;
-        let fiat_deadline = escrow.fiat_deadline;
-        let fiat_paid = escrow.fiat_paid;
-
 
-        // Verify caller is the arbitrator
-        require!(
-            ctx.accounts.arbitrator.key() == escrow.arbitrator,
-            EscrowError::Unauthorized
-        );
-
-        // Verify escrow is not in a terminal state
-        require!(
-            current_state != EscrowState::Released &&
-            current_state != EscrowState::Cancelled &&
-            current_state != EscrowState::Resolved,
-            EscrowError::TerminalState
-        );
-
-        // Check for deposit deadline expiry in Created state
-        if current_state == EscrowState::Created {
-            require!(current_time > deposit_deadline, EscrowError::InvalidState);
-        }
+        // Verify caller is the arbitrator, that the escrow is still Created or Funded (disputes
+        // settle through resolution or default judgment) and that its deadline has expired
+        let new_state = escrow.check(Action::AutoCancel, &ctx.accounts.arbitrator.key(), current_time)?;
 
-        // Check for fiat deadline expiry in Funded state
-        if current_state == EscrowState::Funded {
-            require!(
-                current_time > fiat_deadline && !fiat_paid,
-                EscrowError::InvalidState
-            );
-        }
-
-        // If funds are present, return them to seller
+        // If funds are present, return them to seller
         if current_state == EscrowState::Funded {
             // Ensure escrow token account and seller token account are present
             let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
@@ -1368,10 +2242,14 @@ pub mod localsolana_contracts {
             let seller_token_account = ctx.accounts.seller_token_account.as_ref()
                 .ok_or(EscrowError::InvalidState)?;
 
-            let total_amount = amount
-                .checked_add(fee)
-                .ok_or(EscrowError::FeeCalculationError)?
-                .min(MAX_U64);
+            let total_amount = state_machine::payout(
+                Settlement::Refund,
+                amount,
+                fee,
+                escrow.dispute_bond,
+                escrow.fee_policy(),
+            )?
+            .to_seller;
 
             let escrow_token_bump = ctx.bumps.escrow_token_account;
             let seeds = &[
@@ -1399,12 +2277,13 @@ pub mod localsolana_contracts {
             escrow.tracked_balance = 0;
 
             // emit escrow balance change event
-            emit!(EscrowBalanceChanged {
+            emit_cpi!(EscrowBalanceChanged {
+                schema_version: EVENT_SCHEMA_VERSION,
                 object_id: escrow_key,
                 escrow_id,
                 trade_id,
                 new_balance: 0,
-                reason: "Escrow auto-cancelled".to_string(),
+                reason: BalanceChangeReason::AutoCancelled,
                 timestamp: current_time,
             });
 
@@ -1412,19 +2291,82 @@ pub mod localsolana_contracts {
                 ctx.accounts.token_program.to_account_info(),
                 CloseAccount {
                     account: escrow_token_account.to_account_info(),
-                    destination: ctx.accounts.seller.to_account_info(), // Refund to seller (matches close = seller)
+                    destination: ctx.accounts.escrow_token_rent_payer.to_account_info(), // Refund to vault rent payer
                     authority: escrow_token_account.to_account_info(),
                 },
                 signer_seeds,
             ))?;
         }
 
+        // Close any bond accounts that were initialized but never used
+        let stray_bond_tokens = close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_bond_account,
+            ctx.bumps.buyer_bond_account,
+            &ctx.accounts.buyer_bond_rent_payer,
+            escrow.buyer_bond_rent_payer.is_some(),
+            ctx.accounts.arbitrator_token_account.as_ref(),
+            b"buyer_bond",
+            &escrow_key,
+        )? + close_unused_bond_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.seller_bond_account,
+            ctx.bumps.seller_bond_account,
+            &ctx.accounts.seller_bond_rent_payer,
+            escrow.seller_bond_rent_payer.is_some(),
+            ctx.accounts.arbitrator_token_account.as_ref(),
+            b"seller_bond",
+            &escrow_key,
+        )?;
+        if stray_bond_tokens > 0 {
+            emit_cpi!(SurplusSwept {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                amount: stray_bond_tokens,
+                destination: ctx.accounts.arbitrator_token_account.as_ref().map(|account| account.key()).unwrap_or_default(),
+                timestamp: current_time,
+            });
+        }
+
+        // A funded escrow only gets here once the fiat deadline passed without fiat_paid,
+        // so the buyer's deposit compensates the seller for the locked liquidity
+        let forfeited = Settlement::Refund.forfeits_buyer_deposit(current_state == EscrowState::Funded);
+        let deposit_recipient = if forfeited { seller } else { escrow.buyer };
+        let deposit_token_account = if forfeited {
+            ctx.accounts.seller_token_account.as_ref()
+        } else {
+            None
+        };
+        if let Some(deposit) = settle_buyer_deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_deposit_account,
+            ctx.bumps.buyer_deposit_account,
+            &ctx.accounts.buyer_deposit_rent_payer,
+            escrow.buyer_deposit > 0,
+            deposit_token_account,
+            &deposit_recipient,
+            &escrow_key,
+        )? {
+            emit_cpi!(BuyerDepositSettled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                recipient: deposit_recipient,
+                amount: deposit,
+                forfeited,
+                timestamp: current_time,
+            });
+        }
+
         // Update escrow state
-        escrow.state = EscrowState::Cancelled;
-        escrow.counter = escrow.counter.checked_add(1).unwrap();
-        let counter = escrow.counter;
+        escrow.state = new_state;
+        let counter = escrow.bump_counter()?;
 
-        emit!(EscrowCancelled {
+        emit_cpi!(EscrowCancelled {
+            schema_version: EVENT_SCHEMA_VERSION,
             object_id: escrow_key,
             escrow_id,
             trade_id,
@@ -1435,13 +2377,470 @@ pub mod localsolana_contracts {
             timestamp: current_time,
         });
 
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(current_state),
+            to: new_state,
+            counter,
+            actor: ctx.accounts.arbitrator.key(),
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Upgrades an escrow account created under an older layout in place: grows the account
+    // to the current size (payer covers the extra rent) and stamps the current version
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let current_len = escrow_info.data_len();
+        let target_len = 8 + Escrow::INIT_SPACE;
+
+        // Verify discriminator and read the current version
+        let (escrow_id, trade_id, from_version, version_offset) = {
+            let data = escrow_info.try_borrow_data()?;
+            require!(
+                data.len() > 8 && data[..8] == Escrow::DISCRIMINATOR[..],
+                EscrowError::InvalidEscrowAccount
+            );
+            let version_offset = escrow_version_offset(&data)?;
+            let escrow_id = u64::from_le_bytes(data[8..16].try_into().unwrap());
+            let trade_id = u64::from_le_bytes(data[16..24].try_into().unwrap());
+            // v0.1.2 accounts end at (or are zero-padded past) the version byte
+            let from_version = data.get(version_offset).copied().unwrap_or(0);
+            (escrow_id, trade_id, from_version, version_offset)
+        };
+
+        require!(from_version < ESCROW_VERSION, EscrowError::AlreadyMigrated);
+
+        // Grow the account if the current layout needs more space
+        if current_len < target_len {
+            let rent = Rent::get()?;
+            let lamports_needed = rent
+                .minimum_balance(target_len)
+                .saturating_sub(escrow_info.lamports());
+
+            if lamports_needed > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: escrow_info.clone(),
+                        },
+                    ),
+                    lamports_needed,
+                )?;
+            }
+
+            // grown bytes are zeroed, so new fields start from their default values
+            escrow_info.resize(target_len)?;
+        }
+
+        // Stamp the new version
+        escrow_info.try_borrow_mut_data()?[version_offset] = ESCROW_VERSION;
+
+        // Make sure the upgraded account decodes under the current layout, and count the
+        // migration as an update so indexers see the rewritten account
+        let (state, counter) = {
+            let mut data = escrow_info.try_borrow_mut_data()?;
+            let mut escrow = Escrow::try_deserialize(&mut &data[..])?;
+            let counter = escrow.bump_counter()?;
+            escrow.try_serialize(&mut &mut data[..])?;
+            (escrow.state, counter)
+        };
+
+        let escrow_key = escrow_info.key();
+        let current_time = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(EscrowMigrated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from_version,
+            to_version: ESCROW_VERSION,
+            timestamp: current_time,
+        });
+
+        emit_cpi!(EscrowStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            from: Some(state),
+            to: state,
+            counter,
+            actor: ctx.accounts.payer.key(),
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless cleanup of bond accounts left behind by terminal escrows that were kept
+    // open, e.g. settled before bond accounts were closed on settlement. The escrow holds the
+    // rent payer record, so once it is closed its bond accounts can no longer be reclaimed
+    pub fn reclaim_bond_accounts(ctx: Context<ReclaimBondAccounts>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow_key = escrow_info.key();
+
+        // Work out where each bond's rent goes
+        let (buyer_bond_refund, seller_bond_refund) = {
+            require_keys_eq!(*escrow_info.owner, crate::ID, EscrowError::InvalidEscrowAccount);
+            let data = escrow_info.try_borrow_data()?;
+            let escrow = Escrow::try_deserialize(&mut &data[..])?;
+            require!(
+                state_machine::State::from(escrow.state).is_terminal(),
+                EscrowError::NotTerminalState
+            );
+            (escrow.buyer_bond_rent_payer(), escrow.seller_bond_rent_payer())
+        };
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        if let Some(buyer_bond_account) = ctx.accounts.buyer_bond_account.as_ref() {
+            let rent_payer = ctx.accounts.buyer_bond_rent_payer.as_ref()
+                .ok_or(EscrowError::InvalidRentPayer)?;
+            require_keys_eq!(rent_payer.key(), buyer_bond_refund, EscrowError::InvalidRentPayer);
+
+            close_bond_account(
+                &ctx.accounts.token_program,
+                buyer_bond_account,
+                rent_payer.to_account_info(),
+                b"buyer_bond",
+                &escrow_key,
+                ctx.bumps.buyer_bond_account.ok_or(EscrowError::BumpNotFound)?,
+            )?;
+
+            emit_cpi!(BondAccountReclaimed {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                bond_account: buyer_bond_account.key(),
+                rent_destination: rent_payer.key(),
+                timestamp: current_time,
+            });
+        }
+
+        if let Some(seller_bond_account) = ctx.accounts.seller_bond_account.as_ref() {
+            let rent_payer = ctx.accounts.seller_bond_rent_payer.as_ref()
+                .ok_or(EscrowError::InvalidRentPayer)?;
+            require_keys_eq!(rent_payer.key(), seller_bond_refund, EscrowError::InvalidRentPayer);
+
+            close_bond_account(
+                &ctx.accounts.token_program,
+                seller_bond_account,
+                rent_payer.to_account_info(),
+                b"seller_bond",
+                &escrow_key,
+                ctx.bumps.seller_bond_account.ok_or(EscrowError::BumpNotFound)?,
+            )?;
+
+            emit_cpi!(BondAccountReclaimed {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                bond_account: seller_bond_account.key(),
+                rent_destination: rent_payer.key(),
+                timestamp: current_time,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Permissionless check of the vault against tracked_balance. Tokens sent to the vault
+    // directly are not tracked and would make closing it on settlement fail
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+        let tracked_balance = escrow.tracked_balance;
+        let vault_balance = ctx.accounts.escrow_token_account.amount;
+
+        if vault_balance != tracked_balance {
+            emit_cpi!(BalanceDiscrepancy {
+                schema_version: EVENT_SCHEMA_VERSION,
+                object_id: escrow_key,
+                escrow_id,
+                trade_id,
+                tracked_balance,
+                vault_balance,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Moves whatever the vault holds beyond tracked_balance to the platform (the arbitrator
+    // for now). Only the surplus moves, so settlement still pays out exactly what was funded
+    pub fn sweep_surplus(ctx: Context<SweepSurplus>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let escrow_key = escrow.key();
+        let escrow_id = escrow.escrow_id;
+        let trade_id = escrow.trade_id;
+        let surplus = ctx.accounts.escrow_token_account.amount
+            .saturating_sub(escrow.tracked_balance);
+
+        require!(surplus > 0, EscrowError::NoSurplus);
+
+        let escrow_token_bump = ctx.bumps.escrow_token_account;
+        let escrow_token_seeds = &[
+            b"escrow_token".as_ref(),
+            escrow_key.as_ref(),
+            &[escrow_token_bump]
+        ];
+
+        let signer_seeds = &[&escrow_token_seeds[..]];
+
+        let transfer_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.arbitrator_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_token_account.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        token::transfer(transfer_context, surplus)?;
+
+        emit_cpi!(SurplusSwept {
+            schema_version: EVENT_SCHEMA_VERSION,
+            object_id: escrow_key,
+            escrow_id,
+            trade_id,
+            amount: surplus,
+            destination: ctx.accounts.arbitrator_token_account.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
+
+    // A user's x25519 public key, shared by all their trades
+    pub fn register_encryption_key(ctx: Context<RegisterEncryptionKey>, encryption_key: [u8; 32]) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.owner = ctx.accounts.owner.key();
+        profile.set_encryption_key(encryption_key)
+    }
+
+    pub fn update_encryption_key(ctx: Context<UpdateEncryptionKey>, encryption_key: [u8; 32]) -> Result<()> {
+        ctx.accounts.profile.set_encryption_key(encryption_key)
+    }
+
+    // Arbitrator registers the Pyth feed quoting the token in `currency`, and the limits
+    // create_escrow holds committed rates to when a price update for it is supplied
+    pub fn register_price_feed(
+        ctx: Context<RegisterPriceFeed>,
+        currency: [u8; 3],
+        settings: PriceFeedSettings,
+    ) -> Result<()> {
+        require!(currency.iter().all(u8::is_ascii_uppercase), EscrowError::InvalidFiatTerms);
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.currency = currency;
+        price_feed.apply(settings)
+    }
+
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, settings: PriceFeedSettings) -> Result<()> {
+        ctx.accounts.price_feed.apply(settings)
+    }
+
+    // Fee floors and rounding for escrows created from now on; existing escrows keep theirs
+    pub fn initialize_config(ctx: Context<InitializeConfig>, fee_policy: FeePolicy) -> Result<()> {
+        ctx.accounts.config.apply(fee_policy)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, fee_policy: FeePolicy) -> Result<()> {
+        ctx.accounts.config.apply(fee_policy)
+    }
+
+    // Read-only, meant to be simulated: what an escrow of `amount` created now would cost,
+    // returned through set_return_data so clients don't repeat the fee and bond arithmetic
+    pub fn quote_escrow(ctx: Context<QuoteEscrow>, amount: u64) -> Result<EscrowQuote> {
+        state_machine::check_terms(amount, false, false)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let fee_policy = read_fee_policy(&ctx.accounts.config)?.into();
+        let fee = state_machine::fee(amount, fee_policy)?;
+
+        Ok(EscrowQuote {
+            amount,
+            fee,
+            total_deposit: state_machine::vault_total(amount, fee)?,
+            dispute_bond: state_machine::dispute_bond(amount, fee_policy)?,
+            buyer_deposit: state_machine::buyer_deposit(amount, fee_policy)?,
+            cancellation_penalty: state_machine::cancellation_penalty(amount, fee, fee_policy)?,
+            deposit_deadline: state_machine::deposit_deadline(current_time)?,
+            fiat_deadline: state_machine::fiat_deadline(current_time)?,
+            response_deadline: state_machine::response_deadline(current_time)?,
+        })
+    }
+
+    // Read-only, meant to be simulated: the escrow's phase as the frontend names it, what
+    // each role may do next and how long each running deadline has left
+    pub fn escrow_status(ctx: Context<EscrowStatus>) -> Result<EscrowStatusReport> {
+        let escrow = &ctx.accounts.escrow;
+        let current_time = Clock::get()?.unix_timestamp;
+        let facts = escrow.facts();
+        let countdown = state_machine::countdown(&facts, current_time)?;
+
+        Ok(EscrowStatusReport {
+            state: escrow.state,
+            phase: state_machine::phase(&facts).into(),
+            seller_actions: state_machine::allowed(Role::Seller, &facts, current_time),
+            buyer_actions: state_machine::allowed(Role::Buyer, &facts, current_time),
+            arbitrator_actions: state_machine::allowed(Role::Arbitrator, &facts, current_time),
+            anyone_actions: state_machine::allowed(Role::Other, &facts, current_time),
+            deposit_seconds_left: countdown.deposit,
+            fiat_seconds_left: countdown.fiat,
+            response_seconds_left: countdown.response,
+            timestamp: current_time,
+        })
+    }
+}
+
+// Closes an empty bond token account, refunding its rent to `destination`
+fn close_bond_account<'info>(
+    token_program: &Program<'info, Token>,
+    bond_account: &Account<'info, token::TokenAccount>,
+    destination: AccountInfo<'info>,
+    seed_prefix: &[u8],
+    escrow_key: &Pubkey,
+    bump: u8,
+) -> Result<()> {
+    let bump_array = [bump];
+    let bond_seeds = [seed_prefix, escrow_key.as_ref(), &bump_array[..]];
+
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: bond_account.to_account_info(),
+            destination,
+            authority: bond_account.to_account_info(),
+        },
+        &[&bond_seeds[..]],
+    ))
+}
+
+// Closes an optional, unused bond account on a non-dispute settlement path. If the escrow
+// recorded the bond as initialized the account must be supplied. Stray tokens sent to it
+// are swept to the arbitrator, as sweep_surplus does for the vault, so the account always
+// closes before the escrow that records its rent payer. Returns the amount swept
+fn close_unused_bond_account<'info>(
+    token_program: &Program<'info, Token>,
+    bond_account: &Option<Account<'info, token::TokenAccount>>,
+    bump: Option<u8>,
+    rent_payer: &Option<UncheckedAccount<'info>>,
+    initialized: bool,
+    arbitrator_token_account: Option<&Account<'info, token::TokenAccount>>,
+    seed_prefix: &[u8],
+    escrow_key: &Pubkey,
+) -> Result<u64> {
+    let bond_account = match bond_account {
+        Some(bond_account) => bond_account,
+        None => {
+            require!(!initialized, EscrowError::MissingBondAccount);
+            return Ok(0);
+        }
+    };
+
+    let rent_payer = rent_payer.as_ref().ok_or(EscrowError::InvalidRentPayer)?;
+    let bump = bump.ok_or(EscrowError::BumpNotFound)?;
+
+    let stray = bond_account.amount;
+    if stray > 0 {
+        let arbitrator_token_account = arbitrator_token_account
+            .ok_or(EscrowError::MissingArbitratorTokenAccount)?;
+        let bump_array = [bump];
+        let bond_seeds = [seed_prefix, escrow_key.as_ref(), &bump_array[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: bond_account.to_account_info(),
+                    to: arbitrator_token_account.to_account_info(),
+                    authority: bond_account.to_account_info(),
+                },
+                &[&bond_seeds[..]],
+            ),
+            stray,
+        )?;
+    }
+
+    close_bond_account(
+        token_program,
+        bond_account,
+        rent_payer.to_account_info(),
+        seed_prefix,
+        escrow_key,
+        bump,
+    )?;
+    Ok(stray)
+}
+
+// Pays a posted buyer deposit out to `recipient`, which must belong to `owner`, and closes
+// its account to the rent payer. The whole balance moves so stray tokens cannot keep the
+// account open. Returns the amount paid, or None when no deposit was posted
+fn settle_buyer_deposit<'info>(
+    token_program: &Program<'info, Token>,
+    deposit_account: &Option<Account<'info, token::TokenAccount>>,
+    bump: Option<u8>,
+    rent_payer: &Option<UncheckedAccount<'info>>,
+    posted: bool,
+    recipient: Option<&Account<'info, token::TokenAccount>>,
+    owner: &Pubkey,
+    escrow_key: &Pubkey,
+) -> Result<Option<u64>> {
+    if !posted {
+        return Ok(None);
+    }
+
+    let deposit_account = deposit_account.as_ref().ok_or(EscrowError::MissingBuyerDeposit)?;
+    let recipient = recipient.ok_or(EscrowError::InvalidState)?;
+    require_keys_eq!(recipient.owner, *owner, EscrowError::Unauthorized);
+    let rent_payer = rent_payer.as_ref().ok_or(EscrowError::InvalidRentPayer)?;
+    let bump = bump.ok_or(EscrowError::BumpNotFound)?;
+
+    let amount = deposit_account.amount;
+    let bump_array = [bump];
+    let deposit_seeds = [b"buyer_deposit".as_ref(), escrow_key.as_ref(), &bump_array[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: deposit_account.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: deposit_account.to_account_info(),
+            },
+            &[&deposit_seeds[..]],
+        ),
+        amount,
+    )?;
+
+    close_bond_account(
+        token_program,
+        deposit_account,
+        rent_payer.to_account_info(),
+        b"buyer_deposit",
+        escrow_key,
+        bump,
+    )?;
+
+    Ok(Some(amount))
 }
 
+#[event_cpi]
 #[derive(Accounts)] // Applied to structs to indicate a list of accounts required by an instruction
-#[instruction(escrow_id: u64, trade_id: u64, amount: u64, sequential: bool, sequential_escrow_address: Option<Pubkey>)]
-// REQUIRED: seller, buyer, escrow (account), system_program
+#[instruction(escrow_id: u64, trade_id: u64, amount: u64, sequential: bool, sequential_escrow_address: Option<Pubkey>, fiat_terms: FiatTerms)]
+// REQUIRED: seller, buyer, escrow (account), system_program, price_feed (registered or not),
+// config (initialized or not)
+// OPTIONAL: price_update, required when price_feed is registered, for the oracle check of fiat_terms.rate
 // this is the escrow _state_ account ("rulebook")
 pub struct CreateEscrow<'info> {
     // pass in mutable account
@@ -1455,17 +2854,29 @@ pub struct CreateEscrow<'info> {
         // init used to create the account
         init,
         payer = seller,
-        // account discriminator
-        space = 8 + std::mem::size_of::<Escrow>(),
-        // space = 337,
-        seeds = [b"escrow", escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
+        // account discriminator + Borsh size of the current layout
+        space = 8 + Escrow::INIT_SPACE,
+        // seller-scoped so nobody else can occupy the address for a given id pair
+        seeds = [b"escrow", seller.key().as_ref(), escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: the currency's feed address, registered or not, read by check_committed_rate
+    #[account(seeds = [b"price_feed", fiat_terms.currency.as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: owner, discriminator and feed id are verified by read_price_update
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the config address, initialized or not, read by read_fee_policy
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(_escrow_id: u64, _trade_id: u64)]
 // this is the escrow _token_ account ("vault")
@@ -1475,7 +2886,7 @@ pub struct FundEscrow<'info> {
 
     #[account(
         mut,
-        seeds = [b"escrow", _escrow_id.to_le_bytes().as_ref(), _trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), _escrow_id.to_le_bytes().as_ref(), _trade_id.to_le_bytes().as_ref()],
         // automatic bump calculation
         bump,
         constraint = escrow.seller == seller.key()
@@ -1505,50 +2916,76 @@ pub struct FundEscrow<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct MarkFiatPaid<'info> {
+#[instruction(escrow_id: u64, trade_id: u64, amount: u64, sequential: bool, sequential_escrow_address: Option<Pubkey>, fiat_terms: FiatTerms)]
+// REQUIRED: the accounts of create_escrow followed by those of fund_escrow
+// OPTIONAL: price_update, as for create_escrow
+pub struct CreateAndFundEscrow<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
+    pub seller: Signer<'info>,
+
+    /// CHECK: Buyer account is just used for escrow parameters
+    pub buyer: AccountInfo<'info>,
 
     #[account(
-        mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
-        bump,
-        constraint = escrow.buyer == buyer.key()
+        init,
+        payer = seller,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", seller.key().as_ref(), escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
+        bump
     )]
     pub escrow: Account<'info, Escrow>,
-}
-
-#[derive(Accounts)]
-pub struct UpdateSequentialAddress<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        constraint = seller_token_account.owner == seller.key(),
+        constraint = seller_token_account.mint == token_mint.key()
+    )]
+    pub seller_token_account: Account<'info, token::TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        seeds = [b"escrow_token", escrow.key().as_ref()],
         bump,
-        constraint = escrow.buyer == buyer.key()
+        token::mint = token_mint,
+        token::authority = escrow_token_account,
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub escrow_token_account: Account<'info, token::TokenAccount>,
+
+    pub token_mint: Account<'info, token::Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: the currency's feed address, registered or not, read by check_committed_rate
+    #[account(seeds = [b"price_feed", fiat_terms.currency.as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: owner, discriminator and feed id are verified by read_price_update
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the config address, initialized or not, read by read_fee_policy
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ReleaseEscrow<'info> {
+pub struct PostBuyerDeposit<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub buyer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
-        bump,
-        close = authority,
-        constraint = escrow.seller == authority.key() || escrow.arbitrator == authority.key()
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
     )]
     pub escrow: Account<'info, Escrow>,
 
+    // The vault, so the deposit is held in the escrow's token
     #[account(
-        mut,
         seeds = [b"escrow_token", escrow.key().as_ref()],
         bump
     )]
@@ -1556,56 +2993,277 @@ pub struct ReleaseEscrow<'info> {
 
     #[account(
         mut,
-        constraint = buyer_token_account.owner == escrow.buyer,
+        constraint = buyer_token_account.owner == buyer.key(),
         constraint = buyer_token_account.mint == escrow_token_account.mint
     )]
     pub buyer_token_account: Account<'info, token::TokenAccount>,
 
     #[account(
-        mut,
-        constraint = arbitrator_token_account.owner == escrow.arbitrator,
-        constraint = arbitrator_token_account.mint == escrow_token_account.mint
+        init,
+        payer = buyer,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = buyer_deposit_account,
     )]
-    pub arbitrator_token_account: Account<'info, token::TokenAccount>,
-
-    #[account(mut)]
-    pub sequential_escrow_token_account: Option<Account<'info, token::TokenAccount>>,
+    pub buyer_deposit_account: Account<'info, token::TokenAccount>,
 
+    #[account(address = escrow_token_account.mint)]
+    pub token_mint: Account<'info, token::Mint>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CancelEscrow<'info> {
-    /// CHECK: Seller is the refund destination, verified by the close constraint
-    #[account(mut)]
-    pub seller: AccountInfo<'info>, // Refund destination
-    #[account(mut)]
-    pub authority: Signer<'info>,
+pub struct AcceptEscrow<'info> {
+    pub buyer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
-        bump,
-        close = seller,
-        constraint = escrow.seller == authority.key() || escrow.arbitrator == authority.key()
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
     )]
     pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MarkFiatPaid<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow_token", escrow.key().as_ref()],
-        bump
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.buyer == buyer.key()
     )]
-    pub escrow_token_account: Option<Account<'info, token::TokenAccount>>,
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateSequentialAddress<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.buyer == buyer.key()
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostPaymentDetails<'info> {
+    pub sender: Signer<'info>,
+
+    #[account(
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(seeds = [b"profile", sender.key().as_ref()], bump)]
+    pub sender_profile: Account<'info, UserProfile>,
+
+    #[account(seeds = [b"profile", recipient_profile.owner.as_ref()], bump)]
+    pub recipient_profile: Account<'info, UserProfile>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitChatRoot<'info> {
+    pub party: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProveChatMessage<'info> {
+    pub prover: Signer<'info>,
+
+    #[account(
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Refund destination for the escrow state rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller: AccountInfo<'info>,
+
+    /// CHECK: Refund destination for the vault rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_token_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub escrow_token_rent_payer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump,
+        close = seller,
+        constraint = escrow.seller == authority.key() || escrow.arbitrator == authority.key()
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_token", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, token::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == escrow.buyer,
+        constraint = buyer_token_account.mint == escrow_token_account.mint
+    )]
+    pub buyer_token_account: Account<'info, token::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = arbitrator_token_account.owner == escrow.arbitrator,
+        constraint = arbitrator_token_account.mint == escrow_token_account.mint
+    )]
+    pub arbitrator_token_account: Account<'info, token::TokenAccount>,
+
+    #[account(mut)]
+    pub sequential_escrow_token_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub seller_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer bond rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Refund destination for the seller bond rent, must match the recorded payer
+    #[account(mut, address = escrow.seller_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_deposit_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer deposit rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_deposit_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_deposit_rent_payer: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    /// CHECK: Refund destination for the escrow state rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller: AccountInfo<'info>, // Refund destination
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Refund destination for the vault rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_token_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub escrow_token_rent_payer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump,
+        close = seller,
+        constraint = escrow.seller == authority.key() || escrow.arbitrator == authority.key()
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_token", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Option<Account<'info, token::TokenAccount>>,
 
     #[account(mut)]
     pub seller_token_account: Option<Account<'info, token::TokenAccount>>,
 
+    #[account(
+        mut,
+        seeds = [b"buyer_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub seller_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer bond rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Refund destination for the seller bond rent, must match the recorded payer
+    #[account(mut, address = escrow.seller_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
     pub token_program: Program<'info, Token>,
+
+    // Where a posted buyer deposit is returned and a cancellation penalty paid, owner
+    // checked in the handler
+    #[account(mut)]
+    pub buyer_token_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_deposit_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer deposit rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_deposit_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_deposit_rent_payer: Option<UncheckedAccount<'info>>,
+
+    // Receives stray tokens swept out of unused bond accounts, required only when there are any
+    #[account(mut, constraint = arbitrator_token_account.owner == escrow.arbitrator)]
+    pub arbitrator_token_account: Option<Account<'info, token::TokenAccount>>,
 }
 
 // new, part of refactor to solve stack overflow
 // Split into two separate context structs
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(escrow_id: u64, trade_id: u64)]
 pub struct InitializeBuyerBondAccount<'info> {
@@ -1613,7 +3271,8 @@ pub struct InitializeBuyerBondAccount<'info> {
     pub payer: Signer<'info>,
 
     #[account(
-        seeds = [b"escrow", escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -1634,6 +3293,7 @@ pub struct InitializeBuyerBondAccount<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(escrow_id: u64, trade_id: u64)]
 pub struct InitializeSellerBondAccount<'info> {
@@ -1641,7 +3301,8 @@ pub struct InitializeSellerBondAccount<'info> {
     pub payer: Signer<'info>,
 
     #[account(
-        seeds = [b"escrow", escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"escrow", escrow.seller_seed(), escrow_id.to_le_bytes().as_ref(), trade_id.to_le_bytes().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -1663,6 +3324,7 @@ pub struct InitializeSellerBondAccount<'info> {
 }
 
 // refactored for stack overflow error
+#[event_cpi]
 #[derive(Accounts)]
 pub struct OpenDispute<'info> {
     #[account(mut)]
@@ -1670,7 +3332,7 @@ pub struct OpenDispute<'info> {
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
         bump,
         constraint = escrow.buyer == disputing_party.key() || escrow.seller == disputing_party.key()
     )]
@@ -1699,6 +3361,7 @@ pub struct OpenDispute<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RespondToDispute<'info> {
     #[account(mut)]
@@ -1706,7 +3369,7 @@ pub struct RespondToDispute<'info> {
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -1734,22 +3397,34 @@ pub struct RespondToDispute<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DefaultJudgment<'info> {
-    /// CHECK: Seller is the refund destination, verified by the close constraint
-    #[account(mut)]
+    /// CHECK: Refund destination for the escrow state rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_rent_payer() @ EscrowError::InvalidRentPayer)]
     pub seller: AccountInfo<'info>, // Refund destination
+    // Permissionless: any signer may trigger default judgment once the response deadline passes
     #[account(mut)]
-    pub arbitrator: Signer<'info>,
+    pub caller: Signer<'info>,
+
+    /// CHECK: Refund destination for the vault rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_token_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub escrow_token_rent_payer: AccountInfo<'info>,
+
+    /// CHECK: Refund destination for the buyer bond rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_bond_rent_payer: AccountInfo<'info>,
+
+    /// CHECK: Refund destination for the seller bond rent, must match the recorded payer
+    #[account(mut, address = escrow.seller_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller_bond_rent_payer: AccountInfo<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
         bump,
         // rent goes to seller
-        close = seller,
-        // but only arbitrator can call this
-        constraint = escrow.arbitrator == arbitrator.key()
+        close = seller
     )]
     pub escrow: Account<'info, Escrow>,
 
@@ -1787,20 +3462,44 @@ pub struct DefaultJudgment<'info> {
     pub seller_bond_account: Account<'info, token::TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_deposit_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer deposit rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_deposit_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_deposit_rent_payer: Option<UncheckedAccount<'info>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ResolveDispute<'info> {
     #[account(mut)]
     pub arbitrator: Signer<'info>,
     // For rent refund
-    /// CHECK: Seller is the refund destination, verified by the close constraint
-    #[account(mut)]
+    /// CHECK: Refund destination for the escrow state rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_rent_payer() @ EscrowError::InvalidRentPayer)]
     pub seller: AccountInfo<'info>,
 
+    /// CHECK: Refund destination for the vault rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_token_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub escrow_token_rent_payer: AccountInfo<'info>,
+
+    /// CHECK: Refund destination for the buyer bond rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_bond_rent_payer: AccountInfo<'info>,
+
+    /// CHECK: Refund destination for the seller bond rent, must match the recorded payer
+    #[account(mut, address = escrow.seller_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller_bond_rent_payer: AccountInfo<'info>,
+
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
         bump,
         close = seller,
         constraint = escrow.arbitrator == arbitrator.key()
@@ -1847,20 +3546,36 @@ pub struct ResolveDispute<'info> {
     pub seller_bond_account: Account<'info, token::TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_deposit_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer deposit rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_deposit_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_deposit_rent_payer: Option<UncheckedAccount<'info>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AutoCancel<'info> {
     #[account(mut)]
     pub arbitrator: Signer<'info>,
     // For rent refund
-    /// CHECK: Seller is the refund destination, verified by the close constraint
-    #[account(mut)]
+    /// CHECK: Refund destination for the escrow state rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_rent_payer() @ EscrowError::InvalidRentPayer)]
     pub seller: AccountInfo<'info>,
 
+    /// CHECK: Refund destination for the vault rent, must match the recorded payer
+    #[account(mut, address = escrow.escrow_token_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub escrow_token_rent_payer: AccountInfo<'info>,
+
     #[account(
         mut,
-        seeds = [b"escrow", escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
         bump,
         close = seller,
         constraint = escrow.arbitrator == arbitrator.key()
@@ -1877,78 +3592,876 @@ pub struct AutoCancel<'info> {
     #[account(mut)]
     pub seller_token_account: Option<Account<'info, token::TokenAccount>>,
 
+    #[account(
+        mut,
+        seeds = [b"buyer_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub seller_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer bond rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Refund destination for the seller bond rent, must match the recorded payer
+    #[account(mut, address = escrow.seller_bond_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub seller_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
     pub token_program: Program<'info, Token>,
-}
 
-// Applied to structs to create custom account types for the program
-#[account]
-// #[derive(Debug)]
-pub struct Escrow {
-    pub escrow_id: u64,
-    pub trade_id: u64,
-    pub seller: Pubkey,
-    pub buyer: Pubkey,
-    pub arbitrator: Pubkey,
-    pub amount: u64,
-    pub fee: u64,
-    pub deposit_deadline: i64,
-    pub fiat_deadline: i64,
-    pub state: EscrowState,
-    pub sequential: bool,
-    pub sequential_escrow_address: Option<Pubkey>,
-    pub fiat_paid: bool,
-    pub counter: u64,
-    // Dispute resolution fields
-    pub dispute_initiator: Option<Pubkey>,
-    pub dispute_initiated_time: Option<i64>,
-    pub dispute_evidence_hash_buyer: Option<[u8; 32]>,
-    pub dispute_evidence_hash_seller: Option<[u8; 32]>,
-    pub dispute_resolution_hash: Option<[u8; 32]>,
-    // tracked balance field for off-chain indexers
-    pub tracked_balance: u64,
-}
+    #[account(
+        mut,
+        seeds = [b"buyer_deposit", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_deposit_account: Option<Account<'info, token::TokenAccount>>,
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
-pub enum EscrowState {
-    Created,
-    Funded,
-    Released,
-    Cancelled,
-    Disputed,
-    Resolved
-}
+    /// CHECK: Refund destination for the buyer deposit rent, must match the recorded payer
+    #[account(mut, address = escrow.buyer_deposit_rent_payer() @ EscrowError::InvalidRentPayer)]
+    pub buyer_deposit_rent_payer: Option<UncheckedAccount<'info>>,
 
-// Events
-#[event]
-pub struct EscrowCreated {
-    pub object_id: Pubkey,
-    pub escrow_id: u64,
-    pub trade_id: u64,
-    pub seller: Pubkey,
-    pub buyer: Pubkey,
-    pub arbitrator: Pubkey,
-    pub amount: u64,
-    pub fee: u64,
-    pub deposit_deadline: i64,
-    pub fiat_deadline: i64,
-    pub sequential: bool,
-    pub sequential_escrow_address: Option<Pubkey>,
-    pub timestamp: i64,
+    // Receives stray tokens swept out of unused bond accounts, required only when there are any
+    #[account(mut, constraint = arbitrator_token_account.owner == escrow.arbitrator)]
+    pub arbitrator_token_account: Option<Account<'info, token::TokenAccount>>,
 }
 
-#[event]
-pub struct FundsDeposited {
-    pub object_id: Pubkey,
-    pub escrow_id: u64,
-    pub trade_id: u64,
-    pub amount: u64,
-    pub fee: u64,
-    pub counter: u64,
-    pub timestamp: i64,
-}
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimBondAccounts<'info> {
+    // Permissionless: anyone may clean up bond accounts of a finished escrow
+    pub caller: Signer<'info>,
+
+    /// CHECK: Escrow the bond accounts belong to, any layout version. Checked in the handler
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub buyer_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_bond", escrow.key().as_ref()],
+        bump
+    )]
+    pub seller_bond_account: Option<Account<'info, token::TokenAccount>>,
+
+    /// CHECK: Refund destination for the buyer bond rent, verified in the handler
+    #[account(mut)]
+    pub buyer_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Refund destination for the seller bond rent, verified in the handler
+    #[account(mut)]
+    pub seller_bond_rent_payer: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    // Permissionless: anyone may check an escrow's vault
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"escrow_token", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, token::TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepSurplus<'info> {
+    pub arbitrator: Signer<'info>,
+
+    #[account(
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.arbitrator == arbitrator.key()
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_token", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, token::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = arbitrator_token_account.owner == escrow.arbitrator,
+        constraint = arbitrator_token_account.mint == escrow_token_account.mint
+    )]
+    pub arbitrator_token_account: Account<'info, token::TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterEncryptionKey<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + UserProfile::INIT_SPACE,
+        seeds = [b"profile", owner.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEncryptionKey<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"profile", owner.key().as_ref()], bump)]
+    pub profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+#[instruction(currency: [u8; 3])]
+pub struct RegisterPriceFeed<'info> {
+    #[account(mut, address = Pubkey::new_from_array(constants::ARBITRATOR_BYTES) @ EscrowError::Unauthorized)]
+    pub arbitrator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = arbitrator,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [b"price_feed", currency.as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(address = Pubkey::new_from_array(constants::ARBITRATOR_BYTES) @ EscrowError::Unauthorized)]
+    pub arbitrator: Signer<'info>,
+
+    #[account(mut, seeds = [b"price_feed", price_feed.currency.as_ref()], bump)]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut, address = Pubkey::new_from_array(constants::ARBITRATOR_BYTES) @ EscrowError::Unauthorized)]
+    pub arbitrator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = arbitrator,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(address = Pubkey::new_from_array(constants::ARBITRATOR_BYTES) @ EscrowError::Unauthorized)]
+    pub arbitrator: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteEscrow<'info> {
+    /// CHECK: the config address, initialized or not, read by read_fee_policy
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowStatus<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.seller_seed(), escrow.escrow_id.to_le_bytes().as_ref(), escrow.trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    // Pays any extra rent needed for the larger layout
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Deserialized manually because older layouts may not decode as the current Escrow;
+    /// ownership is enforced here and the discriminator is checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Applied to structs to create custom account types for the program
+#[account]
+#[derive(InitSpace)]
+// #[derive(Debug)]
+pub struct Escrow {
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub arbitrator: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub state: EscrowState,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub fiat_paid: bool,
+    pub counter: u64,
+    // Dispute resolution fields
+    pub dispute_initiator: Option<Pubkey>,
+    pub dispute_initiated_time: Option<i64>,
+    pub dispute_evidence_hash_buyer: Option<[u8; 32]>,
+    pub dispute_evidence_hash_seller: Option<[u8; 32]>,
+    pub dispute_resolution_hash: Option<[u8; 32]>,
+    // tracked balance field for off-chain indexers
+    pub tracked_balance: u64,
+    // Layout version (see ESCROW_VERSION). Must stay directly after the v0.1.2 fields;
+    // new fields go below it
+    pub version: u8,
+    // true when the PDA was derived with the seller key (see seller_seed)
+    pub seller_scoped_seeds: bool,
+    // Who paid rent for each account owned by this escrow (None = not recorded)
+    pub escrow_rent_payer: Option<Pubkey>,
+    pub escrow_token_rent_payer: Option<Pubkey>,
+    pub buyer_bond_rent_payer: Option<Pubkey>,
+    pub seller_bond_rent_payer: Option<Pubkey>,
+    // Fiat side of the trade as committed at creation (None for escrows created before
+    // version 2)
+    pub fiat_terms: Option<FiatTerms>,
+    // Each party's latest chat transcript checkpoint (added in version 3)
+    pub chat_checkpoint_buyer: Option<ChatCheckpoint>,
+    pub chat_checkpoint_seller: Option<ChatCheckpoint>,
+    // Set at creation when funding has to wait for the buyer; accepted_at is when the
+    // buyer did (added in version 4)
+    pub requires_acceptance: bool,
+    pub accepted_at: Option<i64>,
+    // Posted by the buyer through post_buyer_deposit, 0 when none was (added in version 5)
+    pub buyer_deposit: u64,
+    pub buyer_deposit_rent_payer: Option<Pubkey>,
+    // Fee floors and rounding read from the config at creation, and the bond each party
+    // posts to a dispute, 0 until one is opened (added in version 6)
+    pub fee_policy: Option<FeePolicy>,
+    pub dispute_bond: u64,
+    // Set at creation when the buyer has to post the deposit before marking fiat paid
+    // (added in version 7)
+    pub requires_buyer_deposit: bool,
+}
+
+// Per-user settings kept across trades
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile {
+    pub owner: Pubkey,
+    pub encryption_key: [u8; 32], // x25519 public key for payment details
+    pub updated_at: i64,
+}
+
+impl UserProfile {
+    fn set_encryption_key(&mut self, encryption_key: [u8; 32]) -> Result<()> {
+        require!(encryption_key != [0u8; 32], EscrowError::InvalidEncryptionKey);
+        self.encryption_key = encryption_key;
+        self.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
+
+// Oracle feed for one fiat currency, registered by the arbitrator
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub currency: [u8; 3],
+    pub feed_id: [u8; 32],        // Pyth feed id quoting the token in `currency`
+    pub max_deviation_bps: u16,   // of a committed rate from the oracle price
+    pub max_age_seconds: u32,     // of the oracle price at create_escrow
+    pub max_confidence_bps: u16,  // of the confidence interval relative to the price
+}
+
+impl PriceFeed {
+    pub fn band(&self) -> state_machine::RateBand {
+        state_machine::RateBand {
+            max_deviation_bps: self.max_deviation_bps,
+            max_age_seconds: self.max_age_seconds,
+            max_confidence_bps: self.max_confidence_bps,
+        }
+    }
+
+    fn apply(&mut self, settings: PriceFeedSettings) -> Result<()> {
+        self.feed_id = settings.feed_id;
+        self.max_deviation_bps = settings.max_deviation_bps;
+        self.max_age_seconds = settings.max_age_seconds;
+        self.max_confidence_bps = settings.max_confidence_bps;
+        self.band().check()?;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceFeedSettings {
+    pub feed_id: [u8; 32],
+    pub max_deviation_bps: u16,
+    pub max_age_seconds: u32,
+    pub max_confidence_bps: u16,
+}
+
+// Program-wide settings, initialized and updated by the arbitrator
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub fee_policy: FeePolicy,
+}
+
+impl Config {
+    fn apply(&mut self, fee_policy: FeePolicy) -> Result<()> {
+        state_machine::FeePolicy::from(fee_policy).check()?;
+        self.fee_policy = fee_policy;
+        Ok(())
+    }
+}
+
+// Floors and rounding of fees and bonds, see state_machine::FeePolicy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct FeePolicy {
+    pub min_fee: u64,           // in base units
+    pub min_dispute_bond: u64,  // in base units
+    pub rounding: Rounding,
+}
+
+impl From<FeePolicy> for state_machine::FeePolicy {
+    fn from(policy: FeePolicy) -> Self {
+        state_machine::FeePolicy {
+            min_fee: policy.min_fee,
+            min_dispute_bond: policy.min_dispute_bond,
+            rounding: policy.rounding.into(),
+        }
+    }
+}
+
+impl From<state_machine::FeePolicy> for FeePolicy {
+    fn from(policy: state_machine::FeePolicy) -> Self {
+        FeePolicy {
+            min_fee: policy.min_fee,
+            min_dispute_bond: policy.min_dispute_bond,
+            rounding: policy.rounding.into(),
+        }
+    }
+}
+
+// The arbitrator's fee policy, or the default while the config account is uninitialized
+fn read_fee_policy(config: &UncheckedAccount) -> Result<FeePolicy> {
+    if config.owner != &crate::ID {
+        return Ok(state_machine::FeePolicy::DEFAULT.into());
+    }
+    let config = Config::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+    Ok(config.fee_policy)
+}
+
+// Pyth receiver PriceUpdateV2 account layout, after the discriminator
+#[derive(AnchorDeserialize)]
+struct PriceUpdateV2 {
+    _write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    price_message: PriceFeedMessage,
+    _posted_slot: u64,
+}
+
+#[derive(AnchorDeserialize, PartialEq)]
+enum VerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceFeedMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    _prev_publish_time: i64,
+    _ema_price: i64,
+    _ema_conf: u64,
+}
+
+// Checks a committed rate against the oracle in create_escrow and create_and_fund_escrow.
+// The seller does not get to skip it: once the arbitrator registered a feed for the
+// currency a price update is required, and without one none may be passed. Returns the
+// feed id, publish time and outcome for FiatRateChecked, or None for an unregistered currency
+fn check_committed_rate(
+    price_feed: &UncheckedAccount,
+    price_update: Option<&UncheckedAccount>,
+    rate: u64,
+    now: i64,
+) -> Result<Option<([u8; 32], i64, state_machine::RateCheck)>> {
+    if price_feed.owner != &crate::ID {
+        require!(price_update.is_none(), EscrowError::InvalidOraclePrice);
+        return Ok(None);
+    }
+    let price_feed = PriceFeed::try_deserialize(&mut &price_feed.try_borrow_data()?[..])?;
+    let price_update = price_update.ok_or(error!(EscrowError::InvalidOraclePrice))?;
+    let oracle = read_price_update(&price_feed, price_update)?;
+    let check = state_machine::check_rate(rate, oracle, price_feed.band(), now)?;
+    Ok(Some((price_feed.feed_id, oracle.publish_time, check)))
+}
+
+// Reads the oracle price from a fully verified Pyth price update of the registered feed
+fn read_price_update(price_feed: &PriceFeed, price_update: &AccountInfo) -> Result<state_machine::OraclePrice> {
+    require!(
+        *price_update.owner == Pubkey::new_from_array(constants::PYTH_RECEIVER_BYTES),
+        EscrowError::InvalidOraclePrice
+    );
+    let data = price_update.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == constants::PRICE_UPDATE_DISCRIMINATOR,
+        EscrowError::InvalidOraclePrice
+    );
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| error!(EscrowError::InvalidOraclePrice))?;
+    require!(
+        update.verification_level == VerificationLevel::Full,
+        EscrowError::InvalidOraclePrice
+    );
+    let message = update.price_message;
+    require!(message.feed_id == price_feed.feed_id, EscrowError::InvalidOraclePrice);
+    Ok(state_machine::OraclePrice {
+        price: message.price,
+        confidence: message.conf,
+        exponent: message.exponent,
+        publish_time: message.publish_time,
+    })
+}
+
+// What the buyer pays off-chain, committed by the seller in create_escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct FiatTerms {
+    pub currency: [u8; 3],              // ISO 4217 alphabetic code, e.g. b"EUR"
+    pub fiat_amount: u64,               // in the currency's minor units
+    pub rate: u64,                      // fiat major units per whole token, FIAT_RATE_DECIMALS decimals
+    pub payment_method_hash: [u8; 32],  // hash of the agreed payment method details
+}
+
+// RFC 6962 Merkle root over a party's chat messages, see state_machine::verify_inclusion
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct ChatCheckpoint {
+    pub root: [u8; 32],
+    pub message_count: u64,  // tree size the root was computed over
+    pub committed_at: i64,
+}
+
+impl Escrow {
+    // Every instruction that mutates the escrow bumps the counter exactly once, so indexers
+    // can order and de-duplicate updates
+    pub fn bump_counter(&mut self) -> Result<u64> {
+        self.counter = self.counter
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        Ok(self.counter)
+    }
+
+    // Seller component of the escrow PDA seeds. Escrows created before seller-scoped seeds
+    // were introduced use an empty seed here, which derives the same address as the
+    // legacy [b"escrow", escrow_id, trade_id] seeds, so they stay addressable.
+    pub fn seller_seed(&self) -> &[u8] {
+        if self.seller_scoped_seeds {
+            self.seller.as_ref()
+        } else {
+            &[]
+        }
+    }
+
+    // Rent refund destinations. Escrows created before payers were recorded fall back to
+    // the seller, which is where rent was refunded previously.
+    pub fn escrow_rent_payer(&self) -> Pubkey {
+        self.escrow_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn escrow_token_rent_payer(&self) -> Pubkey {
+        self.escrow_token_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn buyer_bond_rent_payer(&self) -> Pubkey {
+        self.buyer_bond_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn seller_bond_rent_payer(&self) -> Pubkey {
+        self.seller_bond_rent_payer.unwrap_or(self.seller)
+    }
+
+    pub fn buyer_deposit_rent_payer(&self) -> Pubkey {
+        self.buyer_deposit_rent_payer.unwrap_or(self.buyer)
+    }
+
+    // Fee policy the escrow was created under. Escrows created before it was recorded
+    // were charged by the defaults
+    pub fn fee_policy(&self) -> state_machine::FeePolicy {
+        self.fee_policy.map(Into::into).unwrap_or(state_machine::FeePolicy::DEFAULT)
+    }
+
+    // Bond each party posted to the dispute. Disputes opened before it was recorded
+    // posted what the initiator's bond account holds
+    pub fn posted_dispute_bond(&self, initiator_bond_balance: u64) -> u64 {
+        if self.dispute_bond > 0 { self.dispute_bond } else { initiator_bond_balance }
+    }
+
+    // What the lifecycle guards in state_machine read from this escrow
+    pub fn facts(&self) -> state_machine::Facts {
+        state_machine::Facts {
+            state: self.state.into(),
+            fiat_paid: self.fiat_paid,
+            requires_acceptance: self.requires_acceptance,
+            accepted: self.accepted_at.is_some(),
+            buyer_deposit_posted: self.buyer_deposit > 0,
+            requires_buyer_deposit: self.requires_buyer_deposit,
+            sequential: self.sequential,
+            has_sequential_address: self.sequential_escrow_address.is_some(),
+            deposit_deadline: self.deposit_deadline,
+            fiat_deadline: self.fiat_deadline,
+            dispute_initiator: self.dispute_initiator.map(|initiator| {
+                if initiator == self.buyer { Party::Buyer } else { Party::Seller }
+            }),
+            dispute_initiated_time: self.dispute_initiated_time,
+            buyer_evidence: self.dispute_evidence_hash_buyer.is_some(),
+            seller_evidence: self.dispute_evidence_hash_seller.is_some(),
+        }
+    }
+
+    // Roles `signer` holds in this escrow
+    pub fn caller(&self, signer: &Pubkey) -> state_machine::Caller {
+        state_machine::Caller::of(signer, &self.seller, &self.buyer, &self.arbitrator)
+    }
+
+    // Runs the guards for `action` signed by `signer` and returns the state to move to
+    pub fn check(&self, action: Action, signer: &Pubkey, now: i64) -> Result<EscrowState> {
+        Ok(state_machine::check(action, self.caller(signer), &self.facts(), now)?.into())
+    }
+}
+
+// The v0.1.2 Escrow layout. Borsh writes Options compactly, so the version byte that
+// follows it has no fixed offset; decoding this prefix is how we find it.
+#[derive(AnchorDeserialize)]
+struct EscrowV0 {
+    _escrow_id: u64,
+    _trade_id: u64,
+    _seller: Pubkey,
+    _buyer: Pubkey,
+    _arbitrator: Pubkey,
+    _amount: u64,
+    _fee: u64,
+    _deposit_deadline: i64,
+    _fiat_deadline: i64,
+    _state: EscrowState,
+    _sequential: bool,
+    _sequential_escrow_address: Option<Pubkey>,
+    _fiat_paid: bool,
+    _counter: u64,
+    _dispute_initiator: Option<Pubkey>,
+    _dispute_initiated_time: Option<i64>,
+    _dispute_evidence_hash_buyer: Option<[u8; 32]>,
+    _dispute_evidence_hash_seller: Option<[u8; 32]>,
+    _dispute_resolution_hash: Option<[u8; 32]>,
+    _tracked_balance: u64,
+}
+
+// Offset of the version byte in raw escrow account data (discriminator included)
+fn escrow_version_offset(data: &[u8]) -> Result<usize> {
+    let mut rest = &data[8..];
+    EscrowV0::deserialize(&mut rest).map_err(|_| error!(EscrowError::InvalidEscrowAccount))?;
+    Ok(data.len() - rest.len())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum EscrowState {
+    Created,
+    Funded,
+    Released,
+    Cancelled,
+    Disputed,
+    Resolved
+}
+
+impl From<EscrowState> for state_machine::State {
+    fn from(state: EscrowState) -> Self {
+        match state {
+            EscrowState::Created => state_machine::State::Created,
+            EscrowState::Funded => state_machine::State::Funded,
+            EscrowState::Released => state_machine::State::Released,
+            EscrowState::Cancelled => state_machine::State::Cancelled,
+            EscrowState::Disputed => state_machine::State::Disputed,
+            EscrowState::Resolved => state_machine::State::Resolved,
+        }
+    }
+}
+
+impl From<state_machine::State> for EscrowState {
+    fn from(state: state_machine::State) -> Self {
+        match state {
+            state_machine::State::Created => EscrowState::Created,
+            state_machine::State::Funded => EscrowState::Funded,
+            state_machine::State::Released => EscrowState::Released,
+            state_machine::State::Cancelled => EscrowState::Cancelled,
+            state_machine::State::Disputed => EscrowState::Disputed,
+            state_machine::State::Resolved => EscrowState::Resolved,
+        }
+    }
+}
+
+// Rounding applied to fees and bonds, reported in the events that charge them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    HalfEven,
+}
+
+impl From<state_machine::Rounding> for Rounding {
+    fn from(rounding: state_machine::Rounding) -> Self {
+        match rounding {
+            state_machine::Rounding::Floor => Rounding::Floor,
+            state_machine::Rounding::Ceil => Rounding::Ceil,
+            state_machine::Rounding::HalfEven => Rounding::HalfEven,
+        }
+    }
+}
+
+impl From<Rounding> for state_machine::Rounding {
+    fn from(rounding: Rounding) -> Self {
+        match rounding {
+            Rounding::Floor => state_machine::Rounding::Floor,
+            Rounding::Ceil => state_machine::Rounding::Ceil,
+            Rounding::HalfEven => state_machine::Rounding::HalfEven,
+        }
+    }
+}
+
+// Frontend trade states, see docs/state-ref-escrows-trades.md
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowPhase {
+    Created,
+    AwaitingFiatPayment,
+    PendingCryptoRelease,
+    Completed,
+    Cancelled,
+    Disputed,
+    Resolved,
+    Accepted, // Created and accepted by the buyer; last so the others keep their encoding
+}
+
+impl From<state_machine::Phase> for EscrowPhase {
+    fn from(phase: state_machine::Phase) -> Self {
+        match phase {
+            state_machine::Phase::Created => EscrowPhase::Created,
+            state_machine::Phase::AwaitingFiatPayment => EscrowPhase::AwaitingFiatPayment,
+            state_machine::Phase::PendingCryptoRelease => EscrowPhase::PendingCryptoRelease,
+            state_machine::Phase::Completed => EscrowPhase::Completed,
+            state_machine::Phase::Cancelled => EscrowPhase::Cancelled,
+            state_machine::Phase::Disputed => EscrowPhase::Disputed,
+            state_machine::Phase::Resolved => EscrowPhase::Resolved,
+            state_machine::Phase::Accepted => EscrowPhase::Accepted,
+        }
+    }
+}
+
+// Return data of quote_escrow. Deadlines are those of an escrow created, funded and
+// disputed at the time of the call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowQuote {
+    pub amount: u64,
+    pub fee: u64,
+    pub total_deposit: u64,     // amount + fee, what fund_escrow takes from the seller
+    pub dispute_bond: u64,      // posted by each party to a dispute
+    pub buyer_deposit: u64,     // optional, see post_buyer_deposit
+    pub cancellation_penalty: u64, // paid by a seller cancelling after the buyer accepted
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub response_deadline: i64,
+}
+
+// Return data of escrow_status. Action masks set bit i when state_machine::Action::ALL[i]
+// would pass its checks for a signer holding only that role; deadlines count down only
+// while they are running and stop at 0
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowStatusReport {
+    pub state: EscrowState,
+    pub phase: EscrowPhase,
+    pub seller_actions: u32,
+    pub buyer_actions: u32,
+    pub arbitrator_actions: u32,
+    pub anyone_actions: u32,
+    pub deposit_seconds_left: Option<i64>,
+    pub fiat_seconds_left: Option<i64>,
+    pub response_seconds_left: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BalanceChangeReason {
+    Funded,
+    Released,
+    Cancelled,
+    AutoCancelled,
+    DefaultJudgment,
+    ResolvedToBuyer,
+    ResolvedToSeller,
+}
+
+// Events
+// Delivered with emit_cpi! (self-CPI carrying the event as instruction data) so indexers can
+// read them from inner instructions instead of relying on truncatable program logs
+#[event]
+pub struct EscrowCreated {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub arbitrator: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub sequential: bool,
+    pub sequential_escrow_address: Option<Pubkey>,
+    pub timestamp: i64,
+    pub rounding: Rounding,
+    pub min_fee_applied: bool, // min_fee was charged instead of the rounded 1%
+    pub fiat_terms: Option<FiatTerms>, // always set by this version, see Escrow::fiat_terms
+    pub requires_acceptance: bool, // fund_escrow waits for accept_escrow
+    pub min_fee: u64,           // fee floor of the config the escrow was created under
+    pub requires_buyer_deposit: bool, // mark_fiat_paid waits for post_buyer_deposit
+}
+
+#[event]
+pub struct EscrowAccepted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuyerDepositPosted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub rounding: Rounding,
+    pub min_deposit_applied: bool, // MIN_BUYER_DEPOSIT was posted instead of the rounded 2%
+    pub counter: u64,
+    pub timestamp: i64,
+}
+
+// Emitted by the settlement that returns the deposit to the buyer or forfeits it to the seller
+#[event]
+pub struct BuyerDepositSettled {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub forfeited: bool,
+    pub timestamp: i64,
+}
+
+// Emitted by cancel_escrow when the seller pays the buyer for cancelling an accepted escrow
+#[event]
+pub struct CancellationPenaltyPaid {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+// Emitted by post_payment_details; ciphertext_hash is of the payment details encrypted
+// from sender_key to recipient_key
+#[event]
+pub struct PaymentDetailsPosted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub sender_key: [u8; 32],
+    pub recipient_key: [u8; 32],
+    pub ciphertext_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+// Emitted by create_escrow when the committed rate was checked against an oracle
+#[event]
+pub struct FiatRateChecked {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub currency: [u8; 3],
+    pub feed_id: [u8; 32],
+    pub committed_rate: u64,
+    pub oracle_rate: u64,      // FIAT_RATE_DECIMALS, like committed_rate
+    pub deviation_bps: u64,
+    pub publish_time: i64,     // of the oracle price
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundsDeposited {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub counter: u64,
+    pub timestamp: i64,
+}
 
 #[event]
 pub struct FiatMarkedPaid {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -1957,6 +4470,7 @@ pub struct FiatMarkedPaid {
 
 #[event]
 pub struct EscrowReleased {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -1970,6 +4484,7 @@ pub struct EscrowReleased {
 
 #[event]
 pub struct EscrowCancelled {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -1982,6 +4497,7 @@ pub struct EscrowCancelled {
 
 #[event]
 pub struct DisputeOpened {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -1989,10 +4505,14 @@ pub struct DisputeOpened {
     pub timestamp: i64,
     pub evidence_hash: [u8; 32],
     pub bond_amount: u64,
+    pub rounding: Rounding,
+    pub min_bond_applied: bool, // min_bond was posted instead of the rounded 5%
+    pub min_bond: u64,          // bond floor of the escrow's fee policy
 }
 
 #[event]
 pub struct DisputeResponseSubmitted {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -2000,10 +4520,14 @@ pub struct DisputeResponseSubmitted {
     pub timestamp: i64,
     pub evidence_hash: [u8; 32],
     pub bond_amount: u64,
+    pub rounding: Rounding,
+    pub min_bond_applied: bool, // min_bond was posted instead of the rounded 5%
+    pub min_bond: u64,          // bond floor of the escrow's fee policy
 }
 
 #[event]
 pub struct DisputeResolved {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -2018,6 +4542,7 @@ pub struct DisputeResolved {
 
 #[event]
 pub struct DisputeDefaultJudgment {
+    pub schema_version: u8,
     pub object_id: Pubkey,
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -2026,18 +4551,33 @@ pub struct DisputeDefaultJudgment {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct EscrowStateChanged {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub from: Option<EscrowState>, // None when the escrow is created
+    pub to: EscrowState,
+    pub counter: u64,
+    pub actor: Pubkey,             // signer that triggered the change
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct EscrowBalanceChanged {
+    pub schema_version: u8,
     pub object_id: Pubkey,     // the escrow account address (PDA) itself
     pub escrow_id: u64,
     pub trade_id: u64,         // makes it easier to link across systems
     pub new_balance: u64,
-    pub reason: String,        // e.g. "Escrow funded" / "Escrow released"
+    pub reason: BalanceChangeReason,
     pub timestamp: i64,        // when it happened (for audit trails)
 }
 
 #[event]
 pub struct SequentialAddressUpdated {
+    pub schema_version: u8,
     pub object_id: Pubkey,       // the escrow account PDA
     pub escrow_id: u64,
     pub trade_id: u64,
@@ -2045,3 +4585,73 @@ pub struct SequentialAddressUpdated {
     pub new_address: Pubkey,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct ChatRootCommitted {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub party: Pubkey,
+    pub root: [u8; 32],
+    pub message_count: u64,
+    pub timestamp: i64,
+}
+
+// Emitted by prove_chat_message once the message is proven part of party's checkpoint
+#[event]
+pub struct ChatMessageProven {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub party: Pubkey,          // whose transcript root the proof is against
+    pub root: [u8; 32],
+    pub message_count: u64,
+    pub leaf_index: u64,
+    pub message_hash: [u8; 32], // sha256 of the message
+    pub prover: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowMigrated {
+    pub schema_version: u8,
+    pub object_id: Pubkey,
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondAccountReclaimed {
+    pub schema_version: u8,
+    pub object_id: Pubkey,       // the escrow account PDA
+    pub bond_account: Pubkey,
+    pub rent_destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BalanceDiscrepancy {
+    pub schema_version: u8,
+    pub object_id: Pubkey,       // the escrow account PDA
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub tracked_balance: u64,
+    pub vault_balance: u64,      // actual escrow_token_account.amount
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurplusSwept {
+    pub schema_version: u8,
+    pub object_id: Pubkey,       // the escrow account PDA
+    pub escrow_id: u64,
+    pub trade_id: u64,
+    pub amount: u64,
+    pub destination: Pubkey,     // token account the surplus went to
+    pub timestamp: i64,
+}
@@ -0,0 +1,47 @@
+#!/bin/bash
+
+# Builds the escrow program and runs the LiteSVM integration tests against it,
+# including the ones that are #[ignore]d for lack of a program binary.
+#
+# Either point LOCALSOLANA_PROGRAM_SO at an already built localsolana_contracts.so,
+# or point LOCALSOLANA_CONTRACTS_DIR at the program crate of the localsolana-contracts
+# checkout (the directory holding its Cargo.toml): the program sources in this tree are
+# copied into its src/ and built with `cargo build-sbf` into target/deploy here.
+#
+# Extra arguments are passed to the test binaries, e.g. a test name filter.
+
+set -euo pipefail
+
+WORKSPACE_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+DEPLOY_DIR="$WORKSPACE_DIR/target/deploy"
+
+if [ -z "${LOCALSOLANA_PROGRAM_SO:-}" ]; then
+  if [ -z "${LOCALSOLANA_CONTRACTS_DIR:-}" ]; then
+    echo "Set LOCALSOLANA_PROGRAM_SO to a built program, or LOCALSOLANA_CONTRACTS_DIR to the" >&2
+    echo "localsolana-contracts program crate to build it from." >&2
+    exit 1
+  fi
+  if [ ! -f "$LOCALSOLANA_CONTRACTS_DIR/Cargo.toml" ]; then
+    echo "No Cargo.toml in $LOCALSOLANA_CONTRACTS_DIR" >&2
+    exit 1
+  fi
+
+  # Build what is in this tree, not whatever the checkout last had
+  for file in lib.rs state_machine.rs state_machine.md; do
+    cp "$WORKSPACE_DIR/$file" "$LOCALSOLANA_CONTRACTS_DIR/src/$file"
+  done
+
+  cargo build-sbf \
+    --manifest-path "$LOCALSOLANA_CONTRACTS_DIR/Cargo.toml" \
+    --sbf-out-dir "$DEPLOY_DIR"
+  LOCALSOLANA_PROGRAM_SO="$DEPLOY_DIR/localsolana_contracts.so"
+fi
+
+if [ ! -f "$LOCALSOLANA_PROGRAM_SO" ]; then
+  echo "No program at $LOCALSOLANA_PROGRAM_SO" >&2
+  exit 1
+fi
+export LOCALSOLANA_PROGRAM_SO
+
+cd "$WORKSPACE_DIR"
+cargo test -p localsolana-program-tests -- --include-ignored "$@"
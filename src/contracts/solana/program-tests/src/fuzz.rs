@@ -0,0 +1,484 @@
+//! Stateful fuzzing of one escrow: random sequences of the thirteen lifecycle
+//! instructions from random signers at random times, with the invariants checked after
+//! every step.
+//!
+//! Sequences come from [`steps`] and run through [`replay`]. Proptest shrinks a failing
+//! sequence and records its seed next to the test that found it, under
+//! `proptest-regressions/`; commit that file so the seed is replayed first on every
+//! run, and pin the shrunk sequence it prints as a plain `replay` test. The step types
+//! print as their constructors, so the sequence can be pasted as is.
+//!
+//! Settlement closes the escrow, so terminal states are observed as a missing account:
+//! once the escrow is gone the only instruction that may succeed is creating it again.
+
+use localsolana_contracts_client::constants::{
+    ARBITRATOR, DEPOSIT_DEADLINE_MINUTES, DISPUTE_RESPONSE_DEADLINE_HOURS, FIAT_DEADLINE_MINUTES,
+    MAX_AMOUNT, SECONDS_PER_HOUR, SECONDS_PER_MINUTE,
+};
+use localsolana_contracts_client::instructions::{
+    AutoCancel, CancelEscrow, CreateEscrow, DefaultJudgment, InitializeBuyerBondAccount,
+    InitializeSellerBondAccount, MarkFiatPaid, OpenDisputeWithBond, ReleaseEscrow,
+    ResolveDisputeWithExplanation, RespondToDisputeWithBond, UpdateSequentialAddress,
+};
+use localsolana_contracts_client::{pda, Escrow, EscrowState};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::Env;
+
+const ESCROW_ID: u64 = 1;
+const TRADE_ID: u64 = 1_001;
+
+/// Longest sequence [`steps`] generates.
+pub const MAX_STEPS: usize = 48;
+
+/// Who signs a step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Actor {
+    Seller,
+    Buyer,
+    Arbitrator,
+    /// Holds tokens but has no role in the escrow.
+    Stranger,
+}
+
+use Actor::*;
+
+impl Actor {
+    pub const ALL: [Actor; 4] = [Seller, Buyer, Arbitrator, Stranger];
+
+    fn evidence(self) -> [u8; 32] {
+        [self as u8 + 1; 32]
+    }
+}
+
+/// One fuzzer action: an instruction on the escrow, or a clock warp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    Create { amount: u64, sequential: bool },
+    Fund { by: Actor },
+    MarkFiatPaid { by: Actor },
+    UpdateSequentialAddress { by: Actor },
+    Release { by: Actor, to_sequential: bool },
+    Cancel { by: Actor },
+    InitBuyerBond { payer: Actor },
+    InitSellerBond { payer: Actor },
+    OpenDispute { by: Actor },
+    Respond { by: Actor },
+    DefaultJudgment { by: Actor },
+    Resolve { by: Actor, for_buyer: bool },
+    AutoCancel { by: Actor },
+    Warp { seconds: i64 },
+}
+
+impl Step {
+    fn signer(&self) -> Option<Actor> {
+        match *self {
+            Step::Create { .. } => Some(Seller),
+            Step::Fund { by }
+            | Step::MarkFiatPaid { by }
+            | Step::UpdateSequentialAddress { by }
+            | Step::Release { by, .. }
+            | Step::Cancel { by }
+            | Step::OpenDispute { by }
+            | Step::Respond { by }
+            | Step::DefaultJudgment { by }
+            | Step::Resolve { by, .. }
+            | Step::AutoCancel { by } => Some(by),
+            Step::InitBuyerBond { payer } | Step::InitSellerBond { payer } => Some(payer),
+            Step::Warp { .. } => None,
+        }
+    }
+
+    /// Signers the step may succeed for.
+    fn authorized(&self) -> &'static [Actor] {
+        match self {
+            Step::Create { .. } | Step::Fund { .. } => &[Seller],
+            Step::MarkFiatPaid { .. } | Step::UpdateSequentialAddress { .. } => &[Buyer],
+            Step::Release { .. } | Step::Cancel { .. } => &[Seller, Arbitrator],
+            Step::OpenDispute { .. } | Step::Respond { .. } => &[Buyer, Seller],
+            Step::Resolve { .. } | Step::AutoCancel { .. } => &[Arbitrator],
+            Step::InitBuyerBond { .. }
+            | Step::InitSellerBond { .. }
+            | Step::DefaultJudgment { .. }
+            | Step::Warp { .. } => &Actor::ALL,
+        }
+    }
+
+    /// Whether the step may succeed from `state`, `None` being no escrow at all.
+    fn legal_from(&self, state: Option<EscrowState>) -> bool {
+        use EscrowState::*;
+        match (self, state) {
+            (Step::Create { .. }, None) => true,
+            (Step::Fund { .. }, Some(Created)) => true,
+            (
+                Step::MarkFiatPaid { .. } | Step::Release { .. } | Step::OpenDispute { .. },
+                Some(Funded),
+            ) => true,
+            (Step::Cancel { .. } | Step::AutoCancel { .. }, Some(Created | Funded)) => true,
+            (
+                Step::Respond { .. } | Step::DefaultJudgment { .. } | Step::Resolve { .. },
+                Some(Disputed),
+            ) => true,
+            (
+                Step::UpdateSequentialAddress { .. }
+                | Step::InitBuyerBond { .. }
+                | Step::InitSellerBond { .. },
+                Some(state),
+            ) => !state.is_terminal(),
+            _ => false,
+        }
+    }
+
+    /// State after the step succeeded from `before`; `None` once it settled the escrow.
+    fn successor(&self, before: Option<EscrowState>) -> Option<EscrowState> {
+        match self {
+            Step::Create { .. } => Some(EscrowState::Created),
+            Step::Fund { .. } => Some(EscrowState::Funded),
+            Step::OpenDispute { .. } => Some(EscrowState::Disputed),
+            Step::Release { .. }
+            | Step::Cancel { .. }
+            | Step::DefaultJudgment { .. }
+            | Step::Resolve { .. }
+            | Step::AutoCancel { .. } => None,
+            Step::MarkFiatPaid { .. }
+            | Step::UpdateSequentialAddress { .. }
+            | Step::InitBuyerBond { .. }
+            | Step::InitSellerBond { .. }
+            | Step::Respond { .. }
+            | Step::Warp { .. } => before,
+        }
+    }
+}
+
+/// Any actor, with `usual` three times as likely so sequences get past the first
+/// authorization checks often enough to reach disputes.
+fn signer(usual: Actor) -> impl Strategy<Value = Actor> {
+    prop_oneof![3 => Just(usual), 1 => proptest::sample::select(&Actor::ALL[..])]
+}
+
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(MAX_AMOUNT),
+        Just(MAX_AMOUNT + 1),
+        1..=MAX_AMOUNT,
+    ]
+}
+
+/// Warps land on both sides of every deadline as well as at random.
+fn seconds() -> impl Strategy<Value = i64> {
+    let deposit = DEPOSIT_DEADLINE_MINUTES * SECONDS_PER_MINUTE;
+    let fiat = FIAT_DEADLINE_MINUTES * SECONDS_PER_MINUTE;
+    let response = DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR;
+    prop_oneof![
+        proptest::sample::select(vec![
+            deposit,
+            deposit + 1,
+            fiat,
+            fiat + 1,
+            response,
+            response + 1,
+        ]),
+        1..=response * 2,
+    ]
+}
+
+pub fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        (amount(), any::<bool>())
+            .prop_map(|(amount, sequential)| Step::Create { amount, sequential }),
+        signer(Seller).prop_map(|by| Step::Fund { by }),
+        signer(Buyer).prop_map(|by| Step::MarkFiatPaid { by }),
+        signer(Buyer).prop_map(|by| Step::UpdateSequentialAddress { by }),
+        (signer(Seller), any::<bool>())
+            .prop_map(|(by, to_sequential)| Step::Release { by, to_sequential }),
+        signer(Seller).prop_map(|by| Step::Cancel { by }),
+        signer(Buyer).prop_map(|payer| Step::InitBuyerBond { payer }),
+        signer(Seller).prop_map(|payer| Step::InitSellerBond { payer }),
+        signer(Buyer).prop_map(|by| Step::OpenDispute { by }),
+        signer(Seller).prop_map(|by| Step::Respond { by }),
+        signer(Stranger).prop_map(|by| Step::DefaultJudgment { by }),
+        (signer(Arbitrator), any::<bool>())
+            .prop_map(|(by, for_buyer)| Step::Resolve { by, for_buyer }),
+        signer(Arbitrator).prop_map(|by| Step::AutoCancel { by }),
+        seconds().prop_map(|seconds| Step::Warp { seconds }),
+    ]
+}
+
+pub fn steps() -> impl Strategy<Value = Vec<Step>> {
+    vec(step(), 1..=MAX_STEPS)
+}
+
+/// Runs `steps` against a fresh environment, stopping at the first broken invariant.
+pub fn replay(steps: &[Step]) -> Result<(), String> {
+    let mut fuzzer = Fuzzer::new();
+    for (index, step) in steps.iter().enumerate() {
+        fuzzer
+            .apply(step)
+            .map_err(|violation| format!("step {index} {step:?}: {violation}"))?;
+    }
+    Ok(())
+}
+
+struct Fuzzer {
+    env: Env,
+    escrow: Pubkey,
+    /// Owns the token account sequential escrows forward to.
+    next_hop: Pubkey,
+    /// Escrow as last seen on chain; builders still need it once the account is closed.
+    last: Option<Escrow>,
+    supply: u64,
+}
+
+impl Fuzzer {
+    fn new() -> Self {
+        let mut env = Env::new();
+        let next_hop = env.wallet(0);
+        let escrow = pda::find_escrow_address(&env.seller, ESCROW_ID, TRADE_ID).0;
+        let mut fuzzer = Fuzzer {
+            env,
+            escrow,
+            next_hop,
+            last: None,
+            supply: 0,
+        };
+        fuzzer.supply = fuzzer.token_total();
+        fuzzer
+    }
+
+    fn key(&self, actor: Actor) -> Pubkey {
+        match actor {
+            Seller => self.env.seller,
+            Buyer => self.env.buyer,
+            Arbitrator => ARBITRATOR,
+            Stranger => self.env.stranger,
+        }
+    }
+
+    fn tokens(&self, actor: Actor) -> Pubkey {
+        self.env.tokens_of(&self.key(actor))
+    }
+
+    fn sequential_account(&self) -> Pubkey {
+        self.env.tokens_of(&self.next_hop)
+    }
+
+    fn state(&self) -> Option<Escrow> {
+        self.env
+            .exists(&self.escrow)
+            .then(|| self.env.escrow_at(&self.escrow))
+    }
+
+    fn balance_or_zero(&self, address: &Pubkey) -> u64 {
+        if self.env.exists(address) {
+            self.env.balance(address)
+        } else {
+            0
+        }
+    }
+
+    fn escrow_accounts(&self) -> [Pubkey; 3] {
+        [
+            pda::find_escrow_token_address(&self.escrow).0,
+            pda::find_buyer_bond_address(&self.escrow).0,
+            pda::find_seller_bond_address(&self.escrow).0,
+        ]
+    }
+
+    // Every token account the fuzzer can move tokens between
+    fn token_total(&self) -> u64 {
+        let wallets = Actor::ALL.map(|actor| self.tokens(actor));
+        wallets
+            .iter()
+            .chain(&self.escrow_accounts())
+            .chain(&[self.sequential_account()])
+            .map(|address| self.balance_or_zero(address))
+            .sum()
+    }
+
+    /// The step's instruction, or `None` for warps and for steps that need an escrow
+    /// before one was ever created.
+    fn instruction(&self, step: &Step) -> Option<Instruction> {
+        if let Step::Create { amount, sequential } = *step {
+            let create = CreateEscrow {
+                seller: self.env.seller,
+                buyer: self.env.buyer,
+                escrow_id: ESCROW_ID,
+                trade_id: TRADE_ID,
+                amount,
+                sequential,
+                sequential_escrow_address: sequential.then(|| self.sequential_account()),
+                fiat_terms: crate::fiat_terms(amount),
+                requires_acceptance: false,
+                requires_buyer_deposit: false,
+                price_update: None,
+            };
+            return Some(create.instruction());
+        }
+        let escrow = self.last.as_ref()?;
+        let signer = step.signer()?;
+        let key = self.key(signer);
+        let mut instruction = match *step {
+            Step::Create { .. } | Step::Warp { .. } => return None,
+            Step::Fund { .. } => self.env.fund_escrow(escrow).instruction(),
+            Step::MarkFiatPaid { .. } => MarkFiatPaid { escrow }.instruction(),
+            Step::UpdateSequentialAddress { .. } => UpdateSequentialAddress {
+                escrow,
+                new_address: self.sequential_account(),
+            }
+            .instruction(),
+            Step::Release { to_sequential, .. } => ReleaseEscrow {
+                escrow,
+                authority: key,
+                buyer_token_account: self.tokens(Buyer),
+                arbitrator_token_account: self.tokens(Arbitrator),
+                sequential_escrow_token_account: to_sequential.then(|| self.sequential_account()),
+            }
+            .instruction(),
+            Step::Cancel { .. } => CancelEscrow {
+                escrow,
+                authority: key,
+                seller_token_account: Some(self.tokens(Seller)),
+                buyer_token_account: None,
+                arbitrator_token_account: None,
+            }
+            .instruction(),
+            Step::InitBuyerBond { .. } => InitializeBuyerBondAccount {
+                escrow,
+                payer: key,
+                token_mint: self.env.mint,
+            }
+            .instruction(),
+            Step::InitSellerBond { .. } => InitializeSellerBondAccount {
+                escrow,
+                payer: key,
+                token_mint: self.env.mint,
+            }
+            .instruction(),
+            Step::OpenDispute { .. } => OpenDisputeWithBond {
+                escrow,
+                disputing_party: key,
+                disputing_party_token_account: self.tokens(signer),
+                evidence_hash: signer.evidence(),
+            }
+            .instruction(),
+            Step::Respond { .. } => RespondToDisputeWithBond {
+                escrow,
+                responding_party: key,
+                responding_party_token_account: self.tokens(signer),
+                evidence_hash: signer.evidence(),
+            }
+            .instruction(),
+            Step::DefaultJudgment { .. } => DefaultJudgment {
+                escrow,
+                caller: key,
+                buyer_token_account: self.tokens(Buyer),
+                seller_token_account: self.tokens(Seller),
+            }
+            .instruction(),
+            Step::Resolve { for_buyer, .. } => ResolveDisputeWithExplanation {
+                escrow,
+                arbitrator: key,
+                decision: for_buyer,
+                resolution_hash: [0xa7; 32],
+                buyer_token_account: self.tokens(Buyer),
+                seller_token_account: self.tokens(Seller),
+                arbitrator_token_account: self.tokens(Arbitrator),
+            }
+            .instruction(),
+            Step::AutoCancel { .. } => AutoCancel {
+                escrow,
+                arbitrator: key,
+                seller_token_account: Some(self.tokens(Seller)),
+                arbitrator_token_account: None,
+            }
+            .instruction(),
+        };
+        // these builders take their signer from the escrow or the ids
+        if matches!(
+            step,
+            Step::Fund { .. } | Step::MarkFiatPaid { .. } | Step::UpdateSequentialAddress { .. }
+        ) {
+            instruction.accounts[0] = AccountMeta::new(key, true);
+        }
+        Some(instruction)
+    }
+
+    fn apply(&mut self, step: &Step) -> Result<(), String> {
+        if let Step::Warp { seconds } = *step {
+            self.env.warp(seconds);
+            return Ok(());
+        }
+        let Some(instruction) = self.instruction(step) else {
+            return Ok(());
+        };
+        let before = self.state();
+        let state_before = before.as_ref().map(|escrow| escrow.state);
+        let succeeded = self.env.send(&[instruction]).is_ok();
+        let after = self.state();
+        let state_after = after.as_ref().map(|escrow| escrow.state);
+
+        if succeeded {
+            let signer = step.signer().unwrap();
+            if !step.authorized().contains(&signer) {
+                return Err(format!("succeeded for unauthorized signer {signer:?}"));
+            }
+            if !step.legal_from(state_before) {
+                return Err(format!("succeeded from illegal state {state_before:?}"));
+            }
+            if let (Step::Respond { .. }, Some(escrow)) = (step, &before) {
+                if escrow.dispute_initiator == Some(self.key(signer)) {
+                    return Err("the initiator answered its own dispute".into());
+                }
+            }
+            let expected = step.successor(state_before);
+            if state_after != expected {
+                return Err(format!("moved to {state_after:?}, expected {expected:?}"));
+            }
+        } else if after != before {
+            return Err("failed but changed the escrow".into());
+        }
+        if after.is_some() {
+            self.last = after;
+        }
+        self.check_accounts()
+    }
+
+    // Invariants that hold between any two steps
+    fn check_accounts(&self) -> Result<(), String> {
+        let total = self.token_total();
+        if total != self.supply {
+            return Err(format!("token total is {total}, expected {}", self.supply));
+        }
+        let [vault, buyer_bond, seller_bond] = self.escrow_accounts();
+        match self.state() {
+            Some(escrow) => {
+                if escrow.state.is_terminal() {
+                    return Err(format!("escrow left open in {:?}", escrow.state));
+                }
+                let held = self.balance_or_zero(&vault);
+                if held != escrow.tracked_balance {
+                    return Err(format!(
+                        "vault holds {held}, tracked_balance is {}",
+                        escrow.tracked_balance
+                    ));
+                }
+            }
+            None => {
+                for account in [vault, buyer_bond, seller_bond] {
+                    if self.env.exists(&account) {
+                        return Err(format!(
+                            "{account} outlived its escrow holding {}",
+                            self.balance_or_zero(&account)
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+//! In-process test harness for the escrow program, built on LiteSVM.
+//!
+//! The program binary is built from the localsolana-contracts repository, not by this
+//! workspace. Tests load it from `LOCALSOLANA_PROGRAM_SO`, falling back to
+//! `target/deploy/localsolana_contracts.so` in this workspace, and are `#[ignore]`d so
+//! the workspace still builds and tests without it. `run.sh` in this crate builds the
+//! program from this tree's sources with `cargo build-sbf` and runs every test:
+//!
+//! ```text
+//! LOCALSOLANA_CONTRACTS_DIR=/path/to/localsolana-contracts/programs/localsolana-contracts \
+//!     program-tests/run.sh
+//! ```
+//!
+//! or, with a program that is already built:
+//!
+//! ```text
+//! LOCALSOLANA_PROGRAM_SO=/path/to/localsolana_contracts.so program-tests/run.sh
+//! ```
+//!
+//! Transactions run without signature verification so tests can act as the hardcoded
+//! arbitrator, whose key nobody holds: every signer the harness has a keypair for signs,
+//! and the arbitrator's signature is left empty.
+
+pub mod fuzz;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use litesvm::types::{FailedTransactionMetadata, TransactionMetadata};
+use litesvm::LiteSVM;
+use localsolana_contracts_client::constants::{ARBITRATOR, FIAT_RATE_DECIMALS};
+use localsolana_contracts_client::instructions::{
+    CreateEscrow, FundEscrow, InitializeBuyerBondAccount, InitializeSellerBondAccount,
+    MarkFiatPaid, OpenDisputeWithBond, RespondToDisputeWithBond,
+};
+use localsolana_contracts_client::token::{TokenAccount, TOKEN_ACCOUNT_LEN};
+use localsolana_contracts_client::{
+    pda, Escrow, EscrowError, Event, FiatTerms, ID, TOKEN_PROGRAM_ID,
+};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+/// One USDC in base units (6 decimals).
+pub const USDC: u64 = 1_000_000;
+
+/// Anchor's own error codes, returned when an account constraint fails before the
+/// handler runs.
+pub mod anchor_error {
+    pub const CONSTRAINT_RAW: u32 = 2003;
+    pub const ACCOUNT_NOT_INITIALIZED: u32 = 3012;
+}
+
+const MINT_LEN: usize = 82;
+
+// Unix time the test clock starts at
+const START_TIME: i64 = 1_700_000_000;
+
+const WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+pub type TxResult = Result<TransactionMetadata, FailedTransactionMetadata>;
+
+/// A trading party acting in a dispute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Party {
+    Buyer,
+    Seller,
+}
+
+impl Party {
+    pub fn of(self, escrow: &Escrow) -> Pubkey {
+        match self {
+            Party::Buyer => escrow.buyer,
+            Party::Seller => escrow.seller,
+        }
+    }
+
+    /// Evidence hash this party submits.
+    pub fn evidence(self) -> [u8; 32] {
+        match self {
+            Party::Buyer => [0xb0; 32],
+            Party::Seller => [0x5e; 32],
+        }
+    }
+}
+
+pub struct Env {
+    pub svm: LiteSVM,
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    /// Holds tokens but is neither a party nor the arbitrator.
+    pub stranger: Pubkey,
+    fee_payer: Keypair,
+    keys: HashMap<Pubkey, Keypair>,
+    next_id: u64,
+}
+
+fn program_path() -> PathBuf {
+    match std::env::var_os("LOCALSOLANA_PROGRAM_SO") {
+        Some(path) => path.into(),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../target/deploy/localsolana_contracts.so"),
+    }
+}
+
+impl Env {
+    /// Loads the program and sets up a mint, a seller with 100 USDC, a buyer and a
+    /// stranger with 10 USDC each, and an empty arbitrator token account.
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new().with_sigverify(false);
+        let path = program_path();
+        svm.add_program_from_file(ID, &path).unwrap_or_else(|err| {
+            panic!(
+                "cannot load {}: {err}; build it with program-tests/run.sh",
+                path.display()
+            )
+        });
+
+        let fee_payer = Keypair::new();
+        let mut env = Env {
+            svm,
+            mint: Pubkey::new_unique(),
+            seller: Pubkey::default(),
+            buyer: Pubkey::default(),
+            stranger: Pubkey::default(),
+            fee_payer,
+            keys: HashMap::new(),
+            next_id: 0,
+        };
+        env.set_lamports(&env.fee_payer.pubkey(), WALLET_LAMPORTS);
+        env.set_lamports(&ARBITRATOR, WALLET_LAMPORTS);
+        env.set_mint();
+        env.set_time(START_TIME);
+
+        env.seller = env.wallet(100 * USDC);
+        env.buyer = env.wallet(10 * USDC);
+        env.stranger = env.wallet(10 * USDC);
+        let arbitrator_tokens = env.tokens_of(&ARBITRATOR);
+        env.set_token_account(&arbitrator_tokens, &ARBITRATOR, 0);
+        env
+    }
+
+    /// A new funded keypair whose associated token account holds `tokens`.
+    pub fn wallet(&mut self, tokens: u64) -> Pubkey {
+        let keypair = Keypair::new();
+        let owner = keypair.pubkey();
+        self.keys.insert(owner, keypair);
+        self.set_lamports(&owner, WALLET_LAMPORTS);
+        let address = self.tokens_of(&owner);
+        self.set_token_account(&address, &owner, tokens);
+        owner
+    }
+
+    /// Address of `owner`'s associated token account for the test mint.
+    pub fn tokens_of(&self, owner: &Pubkey) -> Pubkey {
+        pda::find_associated_token_address(owner, &self.mint)
+    }
+
+    /// Writes an initialized token account of the test mint at `address`.
+    pub fn set_token_account(&mut self, address: &Pubkey, owner: &Pubkey, amount: u64) {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(self.mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        // AccountState::Initialized
+        data[108] = 1;
+        self.set_program_account(address, data, TOKEN_PROGRAM_ID);
+    }
+
+    fn set_mint(&mut self) {
+        let mut data = vec![0u8; MINT_LEN];
+        // supply, decimals, is_initialized; no mint or freeze authority
+        data[36..44].copy_from_slice(&u64::MAX.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+        let mint = self.mint;
+        self.set_program_account(&mint, data, TOKEN_PROGRAM_ID);
+    }
+
+    /// Writes a rent-exempt account owned by `owner`.
+    pub fn set_program_account(&mut self, address: &Pubkey, data: Vec<u8>, owner: Pubkey) {
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(*address, account).unwrap();
+    }
+
+    fn set_lamports(&mut self, address: &Pubkey, lamports: u64) {
+        let account = Account {
+            lamports,
+            ..Account::default()
+        };
+        self.svm.set_account(*address, account).unwrap();
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn set_time(&mut self, unix_timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Moves the clock `seconds` forward.
+    pub fn warp(&mut self, seconds: i64) {
+        self.set_time(self.now() + seconds);
+    }
+
+    /// Sends `instructions` in one transaction, signed by every signer the harness holds
+    /// a keypair for.
+    // Returns LiteSVM's result unchanged so failures keep their logs
+    #[allow(clippy::result_large_err)]
+    pub fn send(&mut self, instructions: &[Instruction]) -> TxResult {
+        let transaction = self.transaction(instructions);
+        let result = self.svm.send_transaction(transaction);
+        // identical retries must not be rejected as already processed
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// Simulates a read-only view and returns its return data, panicking with the
+    /// program logs if it fails.
+    #[track_caller]
+    pub fn view(&self, instruction: Instruction) -> Vec<u8> {
+        match self
+            .svm
+            .simulate_transaction(self.transaction(&[instruction]))
+        {
+            Ok(simulated) => {
+                assert_eq!(simulated.meta.return_data.program_id, ID);
+                simulated.meta.return_data.data
+            }
+            Err(failed) => panic!(
+                "simulation failed: {:?}\n{}",
+                failed.err,
+                failed.meta.pretty_logs()
+            ),
+        }
+    }
+
+    fn transaction(&self, instructions: &[Instruction]) -> Transaction {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        all.extend_from_slice(instructions);
+        let message = Message::new(&all, Some(&self.fee_payer.pubkey()));
+        let required = usize::from(message.header.num_required_signatures);
+        let mut signers = vec![&self.fee_payer];
+        signers.extend(
+            message.account_keys[..required]
+                .iter()
+                .filter_map(|key| self.keys.get(key)),
+        );
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction.partial_sign(&signers, self.svm.latest_blockhash());
+        transaction
+    }
+
+    /// Like `send`, panicking with the program logs if the transaction fails.
+    #[track_caller]
+    pub fn ok(&mut self, instructions: &[Instruction]) -> TransactionMetadata {
+        match self.send(instructions) {
+            Ok(meta) => meta,
+            Err(failed) => panic!(
+                "transaction failed: {:?}\n{}",
+                failed.err,
+                failed.meta.pretty_logs()
+            ),
+        }
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm
+            .get_account(address)
+            .is_some_and(|account| account.lamports > 0)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_balance(address).unwrap_or(0)
+    }
+
+    /// Token balance of a token account.
+    pub fn balance(&self, address: &Pubkey) -> u64 {
+        let account = self
+            .svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("no token account at {address}"));
+        TokenAccount::try_from_account_data(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    /// Token balance of `owner`'s associated token account.
+    pub fn tokens(&self, owner: &Pubkey) -> u64 {
+        self.balance(&self.tokens_of(owner))
+    }
+
+    pub fn escrow_at(&self, address: &Pubkey) -> Escrow {
+        let account = self
+            .svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("no escrow at {address}"));
+        Escrow::try_from_account_data(&account.data).unwrap()
+    }
+
+    /// Overwrites the escrow account with `escrow`, keeping its size. Sets up states the
+    /// instructions cannot reach, like accounts left behind by older program versions.
+    pub fn set_escrow(&mut self, escrow: &Escrow) {
+        let address = escrow.address();
+        let mut account = self
+            .svm
+            .get_account(&address)
+            .unwrap_or_else(|| panic!("no escrow at {address}"));
+        let len = account.data.len();
+        account.data = Escrow::discriminator().to_vec();
+        account.data.extend(borsh::to_vec(escrow).unwrap());
+        account.data.resize(len, 0);
+        self.svm.set_account(address, account).unwrap();
+    }
+
+    /// Current on-chain state of `escrow`.
+    pub fn reload(&self, escrow: &Escrow) -> Escrow {
+        self.escrow_at(&escrow.address())
+    }
+
+    /// `CreateEscrow` from the seller to the buyer with fresh ids.
+    pub fn create_escrow(&mut self, amount: u64) -> CreateEscrow {
+        self.next_id += 1;
+        CreateEscrow {
+            seller: self.seller,
+            buyer: self.buyer,
+            escrow_id: self.next_id,
+            trade_id: 1_000 + self.next_id,
+            amount,
+            sequential: false,
+            sequential_escrow_address: None,
+            fiat_terms: fiat_terms(amount),
+            requires_acceptance: false,
+            requires_buyer_deposit: false,
+            price_update: None,
+        }
+    }
+
+    pub fn fund_escrow(&self, escrow: &Escrow) -> FundEscrow {
+        FundEscrow {
+            seller: escrow.seller,
+            escrow_id: escrow.escrow_id,
+            trade_id: escrow.trade_id,
+            seller_token_account: self.tokens_of(&escrow.seller),
+            token_mint: self.mint,
+        }
+    }
+
+    #[track_caller]
+    pub fn create(&mut self, amount: u64) -> Escrow {
+        let create = self.create_escrow(amount);
+        self.ok(&[create.instruction()]);
+        self.escrow_at(&create.escrow_address())
+    }
+
+    #[track_caller]
+    pub fn fund(&mut self, escrow: &Escrow) -> Escrow {
+        let fund = self.fund_escrow(escrow);
+        self.ok(&[fund.instruction()]);
+        self.reload(escrow)
+    }
+
+    #[track_caller]
+    pub fn mark_paid(&mut self, escrow: &Escrow) -> Escrow {
+        self.ok(&[MarkFiatPaid { escrow }.instruction()]);
+        self.reload(escrow)
+    }
+
+    /// A funded escrow the buyer has marked paid.
+    #[track_caller]
+    pub fn paid(&mut self, amount: u64) -> Escrow {
+        let escrow = self.create(amount);
+        let escrow = self.fund(&escrow);
+        self.mark_paid(&escrow)
+    }
+
+    /// Initializes both bond accounts, each paid for by its party.
+    #[track_caller]
+    pub fn init_bonds(&mut self, escrow: &Escrow) -> Escrow {
+        let buyer_bond = InitializeBuyerBondAccount {
+            escrow,
+            payer: escrow.buyer,
+            token_mint: self.mint,
+        };
+        let seller_bond = InitializeSellerBondAccount {
+            escrow,
+            payer: escrow.seller,
+            token_mint: self.mint,
+        };
+        self.ok(&[buyer_bond.instruction(), seller_bond.instruction()]);
+        self.reload(escrow)
+    }
+
+    pub fn open_dispute<'a>(&self, escrow: &'a Escrow, by: Party) -> OpenDisputeWithBond<'a> {
+        let disputing_party = by.of(escrow);
+        OpenDisputeWithBond {
+            escrow,
+            disputing_party,
+            disputing_party_token_account: self.tokens_of(&disputing_party),
+            evidence_hash: by.evidence(),
+        }
+    }
+
+    pub fn respond_to_dispute<'a>(
+        &self,
+        escrow: &'a Escrow,
+        by: Party,
+    ) -> RespondToDisputeWithBond<'a> {
+        let responding_party = by.of(escrow);
+        RespondToDisputeWithBond {
+            escrow,
+            responding_party,
+            responding_party_token_account: self.tokens_of(&responding_party),
+            evidence_hash: by.evidence(),
+        }
+    }
+
+    /// A paid escrow with both bond accounts initialized and a dispute opened by `by`.
+    #[track_caller]
+    pub fn disputed(&mut self, amount: u64, by: Party) -> Escrow {
+        let escrow = self.paid(amount);
+        let escrow = self.init_bonds(&escrow);
+        let open = self.open_dispute(&escrow, by).instruction();
+        self.ok(&[open]);
+        self.reload(&escrow)
+    }
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Env::new()
+    }
+}
+
+/// USD terms at parity for `amount` USDC base units, at least one cent.
+pub fn fiat_terms(amount: u64) -> FiatTerms {
+    FiatTerms {
+        currency: *b"USD",
+        fiat_amount: (amount / 10_000).max(1),
+        rate: 10u64.pow(FIAT_RATE_DECIMALS),
+        payment_method_hash: [0xb4; 32],
+    }
+}
+
+/// Events the program emitted in a transaction, in order.
+pub fn events(meta: &TransactionMetadata) -> Vec<Event> {
+    meta.inner_instructions
+        .iter()
+        .flatten()
+        .filter_map(|inner| Event::from_cpi_instruction_data(&inner.instruction.data).ok())
+        .collect()
+}
+
+#[track_caller]
+pub fn assert_escrow_error(result: TxResult, expected: EscrowError) {
+    assert_custom_error(result, expected.code());
+}
+
+/// Asserts that the transaction failed with `InstructionError::Custom(code)`.
+#[track_caller]
+pub fn assert_custom_error(result: TxResult, code: u32) {
+    let failed = match result {
+        Ok(_) => panic!("expected error {code}, but the transaction succeeded"),
+        Err(failed) => failed,
+    };
+    match failed.err {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(
+            actual,
+            code,
+            "unexpected error code\n{}",
+            failed.meta.pretty_logs()
+        ),
+        other => panic!(
+            "expected error {code}, got {other:?}\n{}",
+            failed.meta.pretty_logs()
+        ),
+    }
+}
@@ -0,0 +1,536 @@
+//! Disputes: bond accounts, opening and answering a dispute, default judgment for either
+//! initiator, both arbitrator decisions, the minimum bond on tiny trades, the configured
+//! fee policy and chat transcript proofs.
+
+use localsolana_contracts_client::constants::{
+    ARBITRATOR, DISPUTE_RESPONSE_DEADLINE_HOURS, MIN_DISPUTE_BOND, MIN_FEE, ROUNDING,
+    SECONDS_PER_HOUR,
+};
+use localsolana_contracts_client::instructions::{
+    AutoCancel, CommitChatRoot, DefaultJudgment, InitializeConfig, ProveChatMessage,
+    ResolveDisputeWithExplanation, UpdateConfig,
+};
+use localsolana_contracts_client::{
+    chat, pda, Escrow, EscrowError, EscrowState, Event, FeePolicy, Rounding,
+};
+use localsolana_program_tests::{
+    anchor_error, assert_custom_error, assert_escrow_error, events, Env, Party, USDC,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+const AMOUNT: u64 = 10 * USDC;
+const FEE: u64 = AMOUNT / 100;
+const BOND: u64 = AMOUNT / 20;
+const RESPONSE_WINDOW: i64 = DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR;
+
+const SELLER_AFTER_FUNDING: u64 = 100 * USDC - AMOUNT - FEE;
+const BUYER_START: u64 = 10 * USDC;
+
+fn default_judgment(env: &Env, escrow: &Escrow, caller: Pubkey) -> Instruction {
+    DefaultJudgment {
+        escrow,
+        caller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        seller_token_account: env.tokens_of(&escrow.seller),
+    }
+    .instruction()
+}
+
+fn resolve(env: &Env, escrow: &Escrow, decision: bool) -> Instruction {
+    ResolveDisputeWithExplanation {
+        escrow,
+        arbitrator: ARBITRATOR,
+        decision,
+        resolution_hash: [0xa7; 32],
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        seller_token_account: env.tokens_of(&escrow.seller),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+    }
+    .instruction()
+}
+
+// Every account the escrow owns is closed once it settles
+fn assert_closed(env: &Env, escrow: &Escrow) {
+    let key = escrow.address();
+    for address in [
+        key,
+        pda::find_escrow_token_address(&key).0,
+        pda::find_buyer_bond_address(&key).0,
+        pda::find_seller_bond_address(&key).0,
+    ] {
+        assert!(!env.exists(&address), "{address} still open");
+    }
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn bond_accounts_record_who_paid_their_rent() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let escrow = env.init_bonds(&escrow);
+    let key = escrow.address();
+
+    assert_eq!(escrow.buyer_bond_rent_payer, Some(env.buyer));
+    assert_eq!(escrow.seller_bond_rent_payer, Some(env.seller));
+    assert_eq!(escrow.state, EscrowState::Funded);
+    assert_eq!(escrow.counter, 4);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+    assert_eq!(env.balance(&pda::find_buyer_bond_address(&key).0), 0);
+    assert_eq!(env.balance(&pda::find_seller_bond_address(&key).0), 0);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn open_dispute_escrows_the_initiators_bond() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let key = escrow.address();
+
+    assert_eq!(escrow.state, EscrowState::Disputed);
+    assert_eq!(escrow.dispute_initiator, Some(env.buyer));
+    assert_eq!(escrow.dispute_initiated_time, Some(env.now()));
+    assert_eq!(
+        escrow.dispute_evidence_hash_buyer,
+        Some(Party::Buyer.evidence())
+    );
+    assert_eq!(escrow.dispute_evidence_hash_seller, None);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+    assert_eq!(env.balance(&pda::find_buyer_bond_address(&key).0), BOND);
+    assert_eq!(env.tokens(&env.buyer), BUYER_START - BOND);
+
+    // only one dispute per escrow
+    let again = env.open_dispute(&escrow, Party::Seller).instruction();
+    assert_escrow_error(env.send(&[again]), EscrowError::InvalidState);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn open_dispute_requires_fiat_paid_a_party_and_the_full_bond() {
+    let mut env = Env::new();
+    let unpaid = env.create(AMOUNT);
+    let unpaid = env.fund(&unpaid);
+    let unpaid = env.init_bonds(&unpaid);
+    let open = env.open_dispute(&unpaid, Party::Seller).instruction();
+    assert_escrow_error(env.send(&[open]), EscrowError::InvalidState);
+
+    let escrow = env.paid(AMOUNT);
+    let escrow = env.init_bonds(&escrow);
+
+    let mut by_stranger = env.open_dispute(&escrow, Party::Buyer).instruction();
+    by_stranger.accounts[0] = AccountMeta::new(env.stranger, true);
+    by_stranger.accounts[2] = AccountMeta::new(env.tokens_of(&env.stranger), false);
+    assert_custom_error(env.send(&[by_stranger]), anchor_error::CONSTRAINT_RAW);
+
+    let buyer_tokens = env.tokens_of(&env.buyer);
+    let buyer = env.buyer;
+    env.set_token_account(&buyer_tokens, &buyer, BOND - 1);
+    let open = env.open_dispute(&escrow, Party::Buyer).instruction();
+    assert_escrow_error(env.send(&[open]), EscrowError::IncorrectBondAmount);
+
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Funded);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn response_is_open_to_the_other_party_until_the_deadline() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let key = escrow.address();
+
+    let own = env.respond_to_dispute(&escrow, Party::Buyer).instruction();
+    assert_escrow_error(env.send(&[own]), EscrowError::Unauthorized);
+
+    let mut by_stranger = env.respond_to_dispute(&escrow, Party::Seller).instruction();
+    by_stranger.accounts[0] = AccountMeta::new(env.stranger, true);
+    by_stranger.accounts[2] = AccountMeta::new(env.tokens_of(&env.stranger), false);
+    assert_escrow_error(env.send(&[by_stranger]), EscrowError::Unauthorized);
+
+    env.warp(RESPONSE_WINDOW);
+    let respond = env.respond_to_dispute(&escrow, Party::Seller);
+    env.ok(&[respond.instruction()]);
+
+    let responded = env.reload(&escrow);
+    assert_eq!(responded.state, EscrowState::Disputed);
+    assert_eq!(
+        responded.dispute_evidence_hash_seller,
+        Some(Party::Seller.evidence())
+    );
+    assert_eq!(responded.counter, escrow.counter + 1);
+    assert_eq!(responded.tracked_balance, AMOUNT + FEE);
+    assert_eq!(env.balance(&pda::find_seller_bond_address(&key).0), BOND);
+    assert_eq!(env.tokens(&env.seller), SELLER_AFTER_FUNDING - BOND);
+
+    assert_escrow_error(
+        env.send(&[respond.instruction()]),
+        EscrowError::DuplicateEvidence,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn response_after_the_deadline_is_rejected() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Seller);
+    env.warp(RESPONSE_WINDOW + 1);
+    let respond = env.respond_to_dispute(&escrow, Party::Buyer).instruction();
+    assert_escrow_error(env.send(&[respond]), EscrowError::ResponseDeadlineExpired);
+    assert_eq!(env.reload(&escrow).dispute_evidence_hash_buyer, None);
+    assert_eq!(env.tokens(&env.buyer), BUYER_START);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn default_judgment_for_the_buyer_when_the_seller_stays_silent() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let seller_lamports = env.lamports(&env.seller);
+
+    // the deadline itself still belongs to the respondent
+    env.warp(RESPONSE_WINDOW);
+    let judgment = default_judgment(&env, &escrow, env.stranger);
+    assert_escrow_error(env.send(&[judgment]), EscrowError::InvalidState);
+    assert_eq!(env.reload(&escrow).tracked_balance, AMOUNT + FEE);
+
+    // anyone may apply it once the deadline has passed
+    env.warp(1);
+    let judgment = default_judgment(&env, &escrow, env.stranger);
+    env.ok(&[judgment]);
+
+    assert_eq!(env.tokens(&env.buyer), BUYER_START + AMOUNT + FEE);
+    assert_eq!(env.tokens(&env.seller), SELLER_AFTER_FUNDING);
+    assert_eq!(env.tokens(&ARBITRATOR), 0);
+    assert_closed(&env, &escrow);
+    // escrow, vault and unused seller bond rent all return to the seller
+    assert!(env.lamports(&env.seller) > seller_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn default_judgment_for_the_seller_when_the_buyer_stays_silent() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Seller);
+    assert_eq!(env.tokens(&env.seller), SELLER_AFTER_FUNDING - BOND);
+
+    env.warp(RESPONSE_WINDOW + 1);
+    let judgment = default_judgment(&env, &escrow, env.seller);
+    env.ok(&[judgment]);
+
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert_eq!(env.tokens(&env.buyer), BUYER_START);
+    assert_eq!(env.tokens(&ARBITRATOR), 0);
+    assert_closed(&env, &escrow);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn default_judgment_is_unavailable_once_both_sides_answered() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let respond = env.respond_to_dispute(&escrow, Party::Seller).instruction();
+    env.ok(&[respond]);
+
+    env.warp(RESPONSE_WINDOW + 1);
+    let judgment = default_judgment(&env, &escrow, env.buyer);
+    assert_escrow_error(env.send(&[judgment]), EscrowError::InvalidState);
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Disputed);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn resolution_for_the_buyer_forfeits_the_sellers_bond() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let respond = env.respond_to_dispute(&escrow, Party::Seller).instruction();
+    env.ok(&[respond]);
+    let escrow = env.reload(&escrow);
+
+    env.ok(&[resolve(&env, &escrow, true)]);
+
+    assert_eq!(env.tokens(&env.buyer), BUYER_START + AMOUNT);
+    assert_eq!(env.tokens(&env.seller), SELLER_AFTER_FUNDING - BOND);
+    assert_eq!(env.tokens(&ARBITRATOR), FEE + BOND);
+    assert_closed(&env, &escrow);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn resolution_for_the_seller_forfeits_the_buyers_bond() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let respond = env.respond_to_dispute(&escrow, Party::Seller).instruction();
+    env.ok(&[respond]);
+    let escrow = env.reload(&escrow);
+    let buyer_lamports = env.lamports(&env.buyer);
+
+    env.ok(&[resolve(&env, &escrow, false)]);
+
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert_eq!(env.tokens(&env.buyer), BUYER_START - BOND);
+    assert_eq!(env.tokens(&ARBITRATOR), BOND);
+    assert_closed(&env, &escrow);
+    // the buyer still gets the rent of the bond account they paid for
+    assert!(env.lamports(&env.buyer) > buyer_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn resolution_needs_the_arbitrator_both_answers_and_both_bonds() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Seller);
+
+    assert_escrow_error(
+        env.send(&[resolve(&env, &escrow, true)]),
+        EscrowError::InvalidEvidenceHash,
+    );
+
+    let respond = env.respond_to_dispute(&escrow, Party::Buyer).instruction();
+    env.ok(&[respond]);
+    let escrow = env.reload(&escrow);
+
+    let mut by_seller = resolve(&env, &escrow, false);
+    by_seller.accounts[0] = AccountMeta::new(env.seller, true);
+    assert_custom_error(env.send(&[by_seller]), anchor_error::CONSTRAINT_RAW);
+
+    // a bond that no longer holds the full amount cannot be settled
+    let bond = pda::find_buyer_bond_address(&escrow.address()).0;
+    env.set_token_account(&bond, &bond, BOND - 1);
+    assert_escrow_error(
+        env.send(&[resolve(&env, &escrow, false)]),
+        EscrowError::MissingDisputeBond,
+    );
+
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Disputed);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+    assert_eq!(escrow.dispute_resolution_hash, None);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn auto_cancel_leaves_disputes_to_the_arbitration_paths() {
+    let mut env = Env::new();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    env.warp(RESPONSE_WINDOW + 1);
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(
+        env.send(&[auto_cancel.instruction()]),
+        EscrowError::InvalidState,
+    );
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Disputed);
+    assert_eq!(
+        env.balance(&pda::find_buyer_bond_address(&escrow.address()).0),
+        BOND
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn tiny_trades_pay_the_minimum_fee_and_bond() {
+    let mut env = Env::new();
+    let create = env.create_escrow(1);
+    let meta = env.ok(&[create.instruction()]);
+    let Some(Event::EscrowCreated(created)) = events(&meta).into_iter().next() else {
+        panic!("create_escrow emits EscrowCreated first");
+    };
+    assert_eq!(created.fee, MIN_FEE);
+    assert!(created.min_fee_applied);
+    assert_eq!(created.rounding, Rounding::from(ROUNDING));
+
+    let escrow = env.escrow_at(&created.object_id);
+    let escrow = env.fund(&escrow);
+    let escrow = env.mark_paid(&escrow);
+    let escrow = env.init_bonds(&escrow);
+    let meta = env.ok(&[env.open_dispute(&escrow, Party::Buyer).instruction()]);
+    let opened = events(&meta)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::DisputeOpened(opened) => Some(opened),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(opened.bond_amount, MIN_DISPUTE_BOND);
+    assert!(opened.min_bond_applied);
+    let bond = pda::find_buyer_bond_address(&escrow.address()).0;
+    assert_eq!(env.balance(&bond), MIN_DISPUTE_BOND);
+
+    // the payout returns exactly what was posted, so the bond account can close
+    let escrow = env.reload(&escrow);
+    env.ok(&[env.respond_to_dispute(&escrow, Party::Seller).instruction()]);
+    let escrow = env.reload(&escrow);
+    env.ok(&[resolve(&env, &escrow, true)]);
+    assert_eq!(env.tokens(&env.buyer), BUYER_START + 1);
+    assert_closed(&env, &escrow);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn escrows_keep_the_fee_policy_they_were_created_under() {
+    let mut env = Env::new();
+    let policy = FeePolicy {
+        min_fee: 20_000,
+        min_dispute_bond: 100_000,
+        rounding: Rounding::Ceil,
+    };
+    let initialize = InitializeConfig {
+        arbitrator: ARBITRATOR,
+        fee_policy: policy,
+    };
+    env.ok(&[initialize.instruction()]);
+
+    let create = env.create_escrow(1);
+    let meta = env.ok(&[create.instruction()]);
+    let Some(Event::EscrowCreated(created)) = events(&meta).into_iter().next() else {
+        panic!("create_escrow emits EscrowCreated first");
+    };
+    assert_eq!(created.fee, policy.min_fee);
+    assert!(created.min_fee_applied);
+    assert_eq!(created.min_fee, policy.min_fee);
+    assert_eq!(created.rounding, Rounding::Ceil);
+
+    // only the arbitrator sets the policy, and escrows already created keep theirs
+    let lower = FeePolicy {
+        min_dispute_bond: 1,
+        ..policy
+    };
+    let update = |arbitrator| {
+        UpdateConfig {
+            arbitrator,
+            fee_policy: lower,
+        }
+        .instruction()
+    };
+    assert_escrow_error(env.send(&[update(env.stranger)]), EscrowError::Unauthorized);
+    env.ok(&[update(ARBITRATOR)]);
+
+    let escrow = env.escrow_at(&created.object_id);
+    assert_eq!(escrow.fee_policy, Some(policy));
+    let escrow = env.fund(&escrow);
+    let escrow = env.mark_paid(&escrow);
+    let escrow = env.init_bonds(&escrow);
+    let meta = env.ok(&[env.open_dispute(&escrow, Party::Buyer).instruction()]);
+    let opened = events(&meta)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::DisputeOpened(opened) => Some(opened),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(opened.bond_amount, policy.min_dispute_bond);
+    assert_eq!(opened.min_bond, policy.min_dispute_bond);
+    assert!(opened.min_bond_applied);
+    assert_eq!(env.reload(&escrow).dispute_bond, policy.min_dispute_bond);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn chat_checkpoints_back_message_proofs_in_disputes() {
+    let mut env = Env::new();
+    let hashes: Vec<[u8; 32]> = (0..5u8).map(|i| chat::message_hash(&[i])).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(chat::leaf_hash).collect();
+    let escrow = env.disputed(AMOUNT, Party::Buyer);
+    let commit = |party: Pubkey, count: usize| CommitChatRoot {
+        escrow: &escrow,
+        party,
+        root: chat::root(&leaves[..count]).unwrap(),
+        message_count: count as u64,
+    };
+
+    env.ok(&[commit(escrow.seller, 3).instruction()]);
+    let meta = env.ok(&[commit(escrow.buyer, 5).instruction()]);
+    let Some(Event::ChatRootCommitted(committed)) = events(&meta).into_iter().next() else {
+        panic!("no ChatRootCommitted event");
+    };
+    assert_eq!(committed.party, escrow.buyer);
+    assert_eq!(committed.message_count, 5);
+    let reloaded = env.reload(&escrow);
+    assert_eq!(reloaded.counter, escrow.counter + 2);
+    assert_eq!(reloaded.chat_checkpoint_seller.unwrap().message_count, 3);
+
+    // checkpoints only move forward, and only parties keep them
+    assert_escrow_error(
+        env.send(&[commit(escrow.buyer, 4).instruction()]),
+        EscrowError::InvalidChatRoot,
+    );
+    let zero_root = CommitChatRoot {
+        root: [0; 32],
+        message_count: 6,
+        ..commit(escrow.buyer, 5)
+    };
+    assert_escrow_error(
+        env.send(&[zero_root.instruction()]),
+        EscrowError::InvalidChatRoot,
+    );
+    assert_escrow_error(
+        env.send(&[commit(env.stranger, 5).instruction()]),
+        EscrowError::Unauthorized,
+    );
+
+    let prove = |prover: Pubkey, party: Pubkey, index: usize, count: usize| ProveChatMessage {
+        escrow: &escrow,
+        prover,
+        party,
+        leaf_index: index as u64,
+        message_hash: hashes[index],
+        proof: chat::proof(&leaves[..count], index).unwrap(),
+    };
+    let meta = env.ok(&[prove(ARBITRATOR, escrow.buyer, 3, 5).instruction()]);
+    let Some(Event::ChatMessageProven(proven)) = events(&meta).into_iter().next() else {
+        panic!("no ChatMessageProven event");
+    };
+    assert_eq!(proven.party, escrow.buyer);
+    assert_eq!(proven.prover, ARBITRATOR);
+    assert_eq!(proven.leaf_index, 3);
+    assert_eq!(proven.message_hash, hashes[3]);
+    assert_eq!(proven.root, chat::root(&leaves).unwrap());
+    env.ok(&[prove(escrow.buyer, escrow.seller, 1, 3).instruction()]);
+
+    // a proof against the wrong checkpoint, or of a message past it
+    assert_escrow_error(
+        env.send(&[prove(ARBITRATOR, escrow.seller, 1, 5).instruction()]),
+        EscrowError::InvalidInclusionProof,
+    );
+    let past_checkpoint = ProveChatMessage {
+        leaf_index: 4,
+        message_hash: hashes[4],
+        ..prove(ARBITRATOR, escrow.seller, 1, 3)
+    };
+    assert_escrow_error(
+        env.send(&[past_checkpoint.instruction()]),
+        EscrowError::InvalidInclusionProof,
+    );
+    assert_escrow_error(
+        env.send(&[prove(env.stranger, escrow.buyer, 3, 5).instruction()]),
+        EscrowError::Unauthorized,
+    );
+
+    // proofs are for the arbitrator, so only once a dispute is open
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let commit = CommitChatRoot {
+        escrow: &escrow,
+        party: escrow.buyer,
+        root: chat::root(&leaves).unwrap(),
+        message_count: 5,
+    };
+    env.ok(&[commit.instruction()]);
+    let early = ProveChatMessage {
+        escrow: &escrow,
+        prover: escrow.buyer,
+        party: escrow.buyer,
+        leaf_index: 0,
+        message_hash: hashes[0],
+        proof: chat::proof(&leaves, 0).unwrap(),
+    };
+    assert_escrow_error(env.send(&[early.instruction()]), EscrowError::InvalidState);
+}
@@ -0,0 +1,1024 @@
+//! Escrow lifecycle outside disputes: create, fund, create-and-fund, mark paid, release, cancel, sequential
+//! escrows, buyer acceptance and seller cancellation penalties, buyer deposits, automatic
+//! cancellation after the deadlines, the read-only views and the encrypted payment details
+//! exchange.
+
+use localsolana_contracts_client::constants::{
+    ARBITRATOR, DEPOSIT_DEADLINE_MINUTES, FIAT_DEADLINE_MINUTES, MAX_AMOUNT, SECONDS_PER_MINUTE,
+};
+use localsolana_contracts_client::events::BuyerDepositSettled;
+use localsolana_contracts_client::instructions::{
+    AcceptEscrow, AutoCancel, CancelEscrow, CreateAndFundEscrow, EscrowStatus,
+    InitializeBuyerBondAccount, MarkFiatPaid, PostBuyerDeposit, PostPaymentDetails, QuoteEscrow,
+    RegisterEncryptionKey, ReleaseEscrow, UpdateEncryptionKey, UpdateSequentialAddress,
+};
+use localsolana_contracts_client::state_machine::{self, Action, FeePolicy, Role};
+use localsolana_contracts_client::views::{EscrowPhase, EscrowQuote, EscrowStatusReport};
+use localsolana_contracts_client::{
+    pda, Escrow, EscrowError, EscrowState, Event, FiatTerms, UserProfile, ID,
+};
+use localsolana_program_tests::{
+    anchor_error, assert_custom_error, assert_escrow_error, events, fiat_terms, Env, USDC,
+};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+const AMOUNT: u64 = 10 * USDC;
+const FEE: u64 = AMOUNT / 100;
+const DEPOSIT: u64 = AMOUNT / 50;
+const DEPOSIT_WINDOW: i64 = DEPOSIT_DEADLINE_MINUTES * SECONDS_PER_MINUTE;
+const FIAT_WINDOW: i64 = FIAT_DEADLINE_MINUTES * SECONDS_PER_MINUTE;
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn create_records_terms_and_deposit_deadline() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+
+    assert_eq!(escrow.state, EscrowState::Created);
+    assert_eq!(escrow.seller, env.seller);
+    assert_eq!(escrow.buyer, env.buyer);
+    assert_eq!(escrow.arbitrator, ARBITRATOR);
+    assert_eq!(escrow.amount, AMOUNT);
+    assert_eq!(escrow.fee, FEE);
+    assert_eq!(escrow.deposit_deadline, env.now() + DEPOSIT_WINDOW);
+    assert_eq!(escrow.fiat_deadline, 0);
+    assert_eq!(escrow.counter, 0);
+    assert_eq!(escrow.tracked_balance, 0);
+    assert_eq!(escrow.escrow_rent_payer, Some(env.seller));
+    assert!(escrow.seller_scoped_seeds);
+    assert_eq!(escrow.fiat_terms, Some(fiat_terms(AMOUNT)));
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn create_rejects_invalid_terms() {
+    let mut env = Env::new();
+
+    let zero = env.create_escrow(0);
+    assert_escrow_error(env.send(&[zero.instruction()]), EscrowError::InvalidAmount);
+
+    let excessive = env.create_escrow(MAX_AMOUNT + 1);
+    assert_escrow_error(
+        env.send(&[excessive.instruction()]),
+        EscrowError::ExceedsMaximum,
+    );
+
+    let mut sequential = env.create_escrow(AMOUNT);
+    sequential.sequential = true;
+    assert_escrow_error(
+        env.send(&[sequential.instruction()]),
+        EscrowError::MissingSequentialAddress,
+    );
+
+    for terms in [
+        FiatTerms {
+            currency: *b"usd",
+            ..fiat_terms(AMOUNT)
+        },
+        FiatTerms {
+            fiat_amount: 0,
+            ..fiat_terms(AMOUNT)
+        },
+        FiatTerms {
+            rate: 0,
+            ..fiat_terms(AMOUNT)
+        },
+    ] {
+        let mut create = env.create_escrow(AMOUNT);
+        create.fiat_terms = terms;
+        assert_escrow_error(
+            env.send(&[create.instruction()]),
+            EscrowError::InvalidFiatTerms,
+        );
+    }
+
+    // the maximum itself is allowed
+    env.create(MAX_AMOUNT);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn fund_moves_principal_and_fee_into_the_vault() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let vault = pda::find_escrow_token_address(&escrow.address()).0;
+
+    assert_eq!(escrow.state, EscrowState::Funded);
+    assert_eq!(escrow.fiat_deadline, env.now() + FIAT_WINDOW);
+    assert_eq!(escrow.counter, 1);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+    assert_eq!(escrow.escrow_token_rent_payer, Some(env.seller));
+    assert_eq!(env.balance(&vault), AMOUNT + FEE);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC - AMOUNT - FEE);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn fund_is_allowed_up_to_the_deposit_deadline() {
+    let mut env = Env::new();
+    let on_time = env.create(AMOUNT);
+    let late = env.create(AMOUNT);
+
+    env.warp(DEPOSIT_WINDOW);
+    env.fund(&on_time);
+
+    env.warp(1);
+    let fund = env.fund_escrow(&late).instruction();
+    assert_escrow_error(env.send(&[fund]), EscrowError::DepositDeadlineExpired);
+    let late = env.reload(&late);
+    assert_eq!(late.state, EscrowState::Created);
+    assert_eq!(late.tracked_balance, 0);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn fund_rejects_short_balance_and_other_signers() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+
+    // one unit short of principal plus fee
+    let seller_tokens = env.tokens_of(&env.seller);
+    let seller = env.seller;
+    env.set_token_account(&seller_tokens, &seller, AMOUNT + FEE - 1);
+    let fund = env.fund_escrow(&escrow).instruction();
+    assert_escrow_error(env.send(&[fund]), EscrowError::InsufficientFunds);
+
+    // a stranger funding the seller's escrow trips the seller constraint
+    let mut fund = env.fund_escrow(&escrow).instruction();
+    fund.accounts[0] = AccountMeta::new(env.stranger, true);
+    fund.accounts[2] = AccountMeta::new(env.tokens_of(&env.stranger), false);
+    assert_custom_error(env.send(&[fund]), anchor_error::CONSTRAINT_RAW);
+
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Created);
+    assert_eq!(escrow.tracked_balance, 0);
+    assert_eq!(env.tokens(&env.stranger), 10 * USDC);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn mark_fiat_paid_is_buyer_only_and_before_the_fiat_deadline() {
+    let mut env = Env::new();
+    let created = env.create(AMOUNT);
+    let mark = MarkFiatPaid { escrow: &created }.instruction();
+    assert_escrow_error(env.send(&[mark]), EscrowError::InvalidState);
+
+    let funded = env.fund(&created);
+    let mut mark = MarkFiatPaid { escrow: &funded }.instruction();
+    mark.accounts[0] = AccountMeta::new(env.stranger, true);
+    assert_custom_error(env.send(&[mark]), anchor_error::CONSTRAINT_RAW);
+
+    let late = env.create(AMOUNT);
+    let late = env.fund(&late);
+
+    env.warp(FIAT_WINDOW);
+    let paid = env.mark_paid(&funded);
+    assert!(paid.fiat_paid);
+    assert_eq!(paid.counter, funded.counter + 1);
+    assert_eq!(paid.tracked_balance, AMOUNT + FEE);
+
+    env.warp(1);
+    let mark = MarkFiatPaid { escrow: &late }.instruction();
+    assert_escrow_error(env.send(&[mark]), EscrowError::FiatDeadlineExpired);
+    assert!(!env.reload(&late).fiat_paid);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn seller_release_pays_buyer_and_fee_and_closes_accounts() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let escrow_key = escrow.address();
+    let vault = pda::find_escrow_token_address(&escrow_key).0;
+    let seller_lamports = env.lamports(&env.seller);
+
+    let release = ReleaseEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+        sequential_escrow_token_account: None,
+    };
+    env.ok(&[release.instruction()]);
+
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC + AMOUNT);
+    assert_eq!(env.tokens(&ARBITRATOR), FEE);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC - AMOUNT - FEE);
+    assert!(!env.exists(&escrow_key));
+    assert!(!env.exists(&vault));
+    // both rents come back to the seller, who paid them
+    assert!(env.lamports(&env.seller) > seller_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn release_requires_fiat_paid_and_seller_or_arbitrator() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let release = |env: &Env, escrow, authority| {
+        ReleaseEscrow {
+            escrow,
+            authority,
+            buyer_token_account: env.tokens_of(&env.buyer),
+            arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+            sequential_escrow_token_account: None,
+        }
+        .instruction()
+    };
+
+    let ix = release(&env, &escrow, env.seller);
+    assert_escrow_error(env.send(&[ix]), EscrowError::InvalidState);
+
+    let escrow = env.mark_paid(&escrow);
+    for caller in [env.buyer, env.stranger] {
+        let ix = release(&env, &escrow, caller);
+        assert_custom_error(env.send(&[ix]), anchor_error::CONSTRAINT_RAW);
+    }
+    assert_eq!(env.reload(&escrow).tracked_balance, AMOUNT + FEE);
+
+    let ix = release(&env, &escrow, ARBITRATOR);
+    env.ok(&[ix]);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC + AMOUNT);
+    assert_eq!(env.tokens(&ARBITRATOR), FEE);
+
+    // the escrow is gone, so a second release finds nothing to act on
+    let ix = release(&env, &escrow, env.seller);
+    assert_custom_error(env.send(&[ix]), anchor_error::ACCOUNT_NOT_INITIALIZED);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn sequential_release_pays_the_next_escrow() {
+    let mut env = Env::new();
+    let first_hop = Pubkey::new_unique();
+    let next_hop = Pubkey::new_unique();
+    let buyer = env.buyer;
+    env.set_token_account(&next_hop, &buyer, 0);
+
+    let mut create = env.create_escrow(AMOUNT);
+    create.sequential = true;
+    create.sequential_escrow_address = Some(first_hop);
+    env.ok(&[create.instruction()]);
+    let escrow = env.escrow_at(&create.escrow_address());
+    assert_eq!(escrow.sequential_escrow_address, Some(first_hop));
+
+    let escrow = env.fund(&escrow);
+    let update = UpdateSequentialAddress {
+        escrow: &escrow,
+        new_address: next_hop,
+    };
+    env.ok(&[update.instruction()]);
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.sequential_escrow_address, Some(next_hop));
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+    let escrow = env.mark_paid(&escrow);
+
+    let mut release = ReleaseEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+        sequential_escrow_token_account: None,
+    };
+    assert_escrow_error(
+        env.send(&[release.instruction()]),
+        EscrowError::MissingSequentialAddress,
+    );
+
+    release.sequential_escrow_token_account = Some(next_hop);
+    env.ok(&[release.instruction()]);
+    assert_eq!(env.balance(&next_hop), AMOUNT);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC);
+    assert_eq!(env.tokens(&ARBITRATOR), FEE);
+    assert!(!env.exists(&escrow.address()));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn update_sequential_address_needs_a_sequential_escrow_and_the_buyer() {
+    let mut env = Env::new();
+    let plain = env.create(AMOUNT);
+    let update = UpdateSequentialAddress {
+        escrow: &plain,
+        new_address: Pubkey::new_unique(),
+    };
+    assert_escrow_error(env.send(&[update.instruction()]), EscrowError::InvalidState);
+
+    let mut create = env.create_escrow(AMOUNT);
+    create.sequential = true;
+    create.sequential_escrow_address = Some(Pubkey::new_unique());
+    env.ok(&[create.instruction()]);
+    let sequential = env.escrow_at(&create.escrow_address());
+    let mut update = UpdateSequentialAddress {
+        escrow: &sequential,
+        new_address: Pubkey::new_unique(),
+    }
+    .instruction();
+    update.accounts[0] = AccountMeta::new(env.seller, true);
+    assert_custom_error(env.send(&[update]), anchor_error::CONSTRAINT_RAW);
+    assert_eq!(
+        env.reload(&sequential).sequential_escrow_address,
+        sequential.sequential_escrow_address
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn cancel_before_funding_only_closes_the_escrow() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: env.buyer,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_custom_error(
+        env.send(&[cancel.instruction()]),
+        anchor_error::CONSTRAINT_RAW,
+    );
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    env.ok(&[cancel.instruction()]);
+    assert!(!env.exists(&escrow.address()));
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn cancel_of_funded_escrow_refunds_the_seller() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let vault = pda::find_escrow_token_address(&escrow.address()).0;
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: ARBITRATOR,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(env.send(&[cancel.instruction()]), EscrowError::InvalidState);
+    assert_eq!(env.reload(&escrow).tracked_balance, AMOUNT + FEE);
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    env.ok(&[cancel.instruction()]);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert!(!env.exists(&vault));
+    assert!(!env.exists(&escrow.address()));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn cancel_after_fiat_paid_is_refused() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(env.send(&[cancel.instruction()]), EscrowError::InvalidState);
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.state, EscrowState::Funded);
+    assert_eq!(escrow.tracked_balance, AMOUNT + FEE);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn settlement_closes_unused_bond_accounts_and_refunds_their_payer() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let init = InitializeBuyerBondAccount {
+        escrow: &escrow,
+        payer: escrow.buyer,
+        token_mint: env.mint,
+    };
+    env.ok(&[init.instruction()]);
+    let escrow = env.reload(&escrow);
+    let bond = pda::find_buyer_bond_address(&escrow.address()).0;
+    assert_eq!(escrow.buyer_bond_rent_payer, Some(env.buyer));
+    assert_eq!(env.balance(&bond), 0);
+
+    let release = ReleaseEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+        sequential_escrow_token_account: None,
+    };
+
+    // leaving out a recorded bond account would strand its rent
+    let mut without_bond = release.instruction();
+    without_bond.accounts[8] = AccountMeta::new_readonly(ID, false);
+    without_bond.accounts[10] = AccountMeta::new_readonly(ID, false);
+    assert_escrow_error(env.send(&[without_bond]), EscrowError::MissingBondAccount);
+
+    // and its rent may only go back to whoever paid it
+    let mut wrong_payer = release.instruction();
+    wrong_payer.accounts[10] = AccountMeta::new(env.stranger, false);
+    assert_escrow_error(env.send(&[wrong_payer]), EscrowError::InvalidRentPayer);
+
+    let buyer_lamports = env.lamports(&env.buyer);
+    env.ok(&[release.instruction()]);
+    assert!(!env.exists(&bond));
+    assert!(env.lamports(&env.buyer) > buyer_lamports);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC + AMOUNT);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn auto_cancel_of_unfunded_escrow_waits_for_the_deposit_deadline() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: None,
+        arbitrator_token_account: None,
+    };
+
+    env.warp(DEPOSIT_WINDOW);
+    assert_escrow_error(
+        env.send(&[auto_cancel.instruction()]),
+        EscrowError::InvalidState,
+    );
+
+    env.warp(1);
+    let mut by_seller = auto_cancel.instruction();
+    by_seller.accounts[0] = AccountMeta::new(env.seller, true);
+    assert_custom_error(env.send(&[by_seller]), anchor_error::CONSTRAINT_RAW);
+
+    env.ok(&[auto_cancel.instruction()]);
+    assert!(!env.exists(&escrow.address()));
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn auto_cancel_of_funded_escrow_refunds_after_the_fiat_deadline() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let vault = pda::find_escrow_token_address(&escrow.address()).0;
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        arbitrator_token_account: None,
+    };
+
+    env.warp(FIAT_WINDOW);
+    assert_escrow_error(
+        env.send(&[auto_cancel.instruction()]),
+        EscrowError::InvalidState,
+    );
+    assert_eq!(env.reload(&escrow).tracked_balance, AMOUNT + FEE);
+
+    env.warp(1);
+    env.ok(&[auto_cancel.instruction()]);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert!(!env.exists(&vault));
+    assert!(!env.exists(&escrow.address()));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn auto_cancel_spares_escrows_marked_paid() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    env.warp(FIAT_WINDOW + 1);
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(
+        env.send(&[auto_cancel.instruction()]),
+        EscrowError::InvalidState,
+    );
+    assert_eq!(env.reload(&escrow).state, EscrowState::Funded);
+}
+
+/// A funded escrow with the buyer deposit posted.
+fn with_buyer_deposit(env: &mut Env) -> Escrow {
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let post = PostBuyerDeposit {
+        escrow: &escrow,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        token_mint: env.mint,
+    };
+    let meta = env.ok(&[post.instruction()]);
+    let Some(Event::BuyerDepositPosted(posted)) = events(&meta).into_iter().next() else {
+        panic!("no BuyerDepositPosted event");
+    };
+    assert_eq!(posted.amount, DEPOSIT);
+    assert!(!posted.min_deposit_applied);
+    assert_escrow_error(env.send(&[post.instruction()]), EscrowError::InvalidState);
+    env.reload(&escrow)
+}
+
+fn deposit_settled(events: Vec<Event>) -> BuyerDepositSettled {
+    events
+        .into_iter()
+        .find_map(|event| match event {
+            Event::BuyerDepositSettled(settled) => Some(settled),
+            _ => None,
+        })
+        .expect("no BuyerDepositSettled event")
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn buyer_deposit_comes_back_on_release_and_seller_cancel() {
+    let mut env = Env::new();
+    let escrow = with_buyer_deposit(&mut env);
+    let deposit_account = pda::find_buyer_deposit_address(&escrow.address()).0;
+    assert_eq!(escrow.buyer_deposit, DEPOSIT);
+    assert_eq!(escrow.buyer_deposit_rent_payer, Some(env.buyer));
+    assert_eq!(env.balance(&deposit_account), DEPOSIT);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC - DEPOSIT);
+
+    let escrow = env.mark_paid(&escrow);
+    let release = ReleaseEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+        sequential_escrow_token_account: None,
+    };
+    let settled = deposit_settled(events(&env.ok(&[release.instruction()])));
+    assert_eq!((settled.recipient, settled.amount), (env.buyer, DEPOSIT));
+    assert!(!settled.forfeited);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC + AMOUNT);
+    assert!(!env.exists(&deposit_account));
+
+    let mut env = Env::new();
+    let escrow = with_buyer_deposit(&mut env);
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(env.send(&[cancel.instruction()]), EscrowError::InvalidState);
+
+    let cancel = CancelEscrow {
+        buyer_token_account: Some(env.tokens_of(&escrow.buyer)),
+        ..cancel
+    };
+    let settled = deposit_settled(events(&env.ok(&[cancel.instruction()])));
+    assert!(!settled.forfeited);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert!(!env.exists(&escrow.address()));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn buyer_deposit_goes_to_the_seller_when_fiat_expires_unpaid() {
+    let mut env = Env::new();
+    let escrow = with_buyer_deposit(&mut env);
+    let deposit_account = pda::find_buyer_deposit_address(&escrow.address()).0;
+    let buyer_lamports = env.lamports(&env.buyer);
+
+    env.warp(FIAT_WINDOW + 1);
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        arbitrator_token_account: None,
+    };
+    let settled = deposit_settled(events(&env.ok(&[auto_cancel.instruction()])));
+    assert_eq!((settled.recipient, settled.amount), (env.seller, DEPOSIT));
+    assert!(settled.forfeited);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC + DEPOSIT);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC - DEPOSIT);
+    // the deposit account's rent still goes back to the buyer, who paid it
+    assert!(!env.exists(&deposit_account));
+    assert!(env.lamports(&env.buyer) > buyer_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn a_required_deposit_must_be_posted_before_paying_and_is_lost_by_walking_away() {
+    let mut env = Env::new();
+    let required = |env: &mut Env| {
+        let mut create = env.create_escrow(AMOUNT);
+        create.requires_buyer_deposit = true;
+        let meta = env.ok(&[create.instruction()]);
+        let Some(Event::EscrowCreated(created)) = events(&meta).into_iter().next() else {
+            panic!("create_escrow emits EscrowCreated first");
+        };
+        assert!(created.requires_buyer_deposit);
+        let escrow = env.escrow_at(&create.escrow_address());
+        assert!(escrow.requires_buyer_deposit);
+        let escrow = env.fund(&escrow);
+        assert_escrow_error(
+            env.send(&[MarkFiatPaid { escrow: &escrow }.instruction()]),
+            EscrowError::BuyerDepositRequired,
+        );
+        let post = PostBuyerDeposit {
+            escrow: &escrow,
+            buyer_token_account: env.tokens_of(&escrow.buyer),
+            token_mint: env.mint,
+        };
+        env.ok(&[post.instruction()]);
+        env.reload(&escrow)
+    };
+
+    // posting unlocks mark_fiat_paid
+    let escrow = required(&mut env);
+    assert!(env.mark_paid(&escrow).fiat_paid);
+
+    // and a buyer who posts and then walks away forfeits it
+    let escrow = required(&mut env);
+    env.warp(FIAT_WINDOW + 1);
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        arbitrator_token_account: None,
+    };
+    let settled = deposit_settled(events(&env.ok(&[auto_cancel.instruction()])));
+    assert_eq!((settled.recipient, settled.amount), (env.seller, DEPOSIT));
+    assert!(settled.forfeited);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn views_quote_terms_and_report_status() {
+    let mut env = Env::new();
+
+    let quote =
+        EscrowQuote::try_from_return_data(&env.view(QuoteEscrow { amount: AMOUNT }.instruction()))
+            .unwrap();
+    assert_eq!(quote.fee, FEE);
+    assert_eq!(quote.total_deposit, AMOUNT + FEE);
+    assert_eq!(
+        quote.dispute_bond,
+        state_machine::dispute_bond(AMOUNT, FeePolicy::DEFAULT).unwrap()
+    );
+    assert_eq!(
+        quote.buyer_deposit,
+        state_machine::buyer_deposit(AMOUNT, FeePolicy::DEFAULT).unwrap()
+    );
+    assert_eq!(quote.cancellation_penalty, FEE / 2);
+    assert_eq!(quote.deposit_deadline, env.now() + DEPOSIT_WINDOW);
+    assert_eq!(quote.fiat_deadline, env.now() + FIAT_WINDOW);
+    assert_escrow_error(
+        env.send(&[QuoteEscrow { amount: 0 }.instruction()]),
+        EscrowError::InvalidAmount,
+    );
+
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    env.warp(60);
+    let status = EscrowStatus {
+        escrow: escrow.address(),
+    };
+    let report = EscrowStatusReport::try_from_return_data(&env.view(status.instruction())).unwrap();
+    assert_eq!(report.state, EscrowState::Funded);
+    assert_eq!(report.phase, EscrowPhase::AwaitingFiatPayment);
+    assert_eq!(report.fiat_seconds_left, Some(FIAT_WINDOW - 60));
+    assert_eq!(report.deposit_seconds_left, None);
+    assert!(report.actions(Role::Buyer).contains(&Action::MarkFiatPaid));
+    assert!(report.actions(Role::Seller).contains(&Action::Cancel));
+    assert!(report.actions(Role::Other).is_empty());
+
+    let escrow = env.mark_paid(&escrow);
+    let report = EscrowStatusReport::try_from_return_data(&env.view(status.instruction())).unwrap();
+    assert_eq!(report.phase, EscrowPhase::PendingCryptoRelease);
+    assert_eq!(report.fiat_seconds_left, None);
+    assert!(report.actions(Role::Seller).contains(&Action::Release));
+    assert!(!report.actions(Role::Seller).contains(&Action::Cancel));
+    assert_eq!(env.reload(&escrow).state, EscrowState::Funded);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn parties_post_payment_details_encrypted_to_registered_keys() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let post = PostPaymentDetails {
+        escrow: &escrow,
+        sender: escrow.seller,
+        ciphertext_hash: [0xc1; 32],
+    };
+    // the buyer has no profile yet
+    let register_seller = RegisterEncryptionKey {
+        owner: escrow.seller,
+        encryption_key: [0x5e; 32],
+    };
+    env.ok(&[register_seller.instruction()]);
+    assert_custom_error(
+        env.send(&[post.instruction()]),
+        anchor_error::ACCOUNT_NOT_INITIALIZED,
+    );
+
+    let zero_key = RegisterEncryptionKey {
+        owner: escrow.buyer,
+        encryption_key: [0; 32],
+    };
+    assert_escrow_error(
+        env.send(&[zero_key.instruction()]),
+        EscrowError::InvalidEncryptionKey,
+    );
+    let register_buyer = RegisterEncryptionKey {
+        owner: escrow.buyer,
+        encryption_key: [0xb0; 32],
+    };
+    env.ok(&[register_buyer.instruction()]);
+
+    let meta = env.ok(&[post.instruction()]);
+    let Some(Event::PaymentDetailsPosted(posted)) = events(&meta).into_iter().next() else {
+        panic!("no PaymentDetailsPosted event");
+    };
+    assert_eq!(posted.object_id, escrow.address());
+    assert_eq!(posted.sender, escrow.seller);
+    assert_eq!(posted.recipient, escrow.buyer);
+    assert_eq!(posted.sender_key, [0x5e; 32]);
+    assert_eq!(posted.recipient_key, [0xb0; 32]);
+    assert_eq!(posted.ciphertext_hash, [0xc1; 32]);
+    // posting changes nothing on the escrow
+    assert_eq!(env.reload(&escrow), escrow);
+
+    // a rotated key is the one recorded from then on
+    let rotate = UpdateEncryptionKey {
+        owner: escrow.buyer,
+        encryption_key: [0xb1; 32],
+    };
+    env.ok(&[rotate.instruction()]);
+    let profile = env
+        .svm
+        .get_account(&pda::find_profile_address(&escrow.buyer).0)
+        .unwrap();
+    let profile = UserProfile::try_from_account_data(&profile.data).unwrap();
+    assert_eq!(profile.encryption_key, [0xb1; 32]);
+    let escrow = env.fund(&escrow);
+    let reply = PostPaymentDetails {
+        escrow: &escrow,
+        sender: escrow.buyer,
+        ciphertext_hash: [0xc2; 32],
+    };
+    let meta = env.ok(&[reply.instruction()]);
+    let Some(Event::PaymentDetailsPosted(posted)) = events(&meta).into_iter().next() else {
+        panic!("no PaymentDetailsPosted event");
+    };
+    assert_eq!(posted.sender_key, [0xb1; 32]);
+    assert_eq!(posted.recipient, escrow.seller);
+
+    // only the parties, only with a hash, and only to the other party's profile
+    let stranger = PostPaymentDetails {
+        escrow: &escrow,
+        sender: env.stranger,
+        ciphertext_hash: [0xc3; 32],
+    };
+    let register_stranger = RegisterEncryptionKey {
+        owner: env.stranger,
+        encryption_key: [0x57; 32],
+    };
+    env.ok(&[register_stranger.instruction()]);
+    assert_escrow_error(
+        env.send(&[stranger.instruction()]),
+        EscrowError::Unauthorized,
+    );
+    let empty = PostPaymentDetails {
+        ciphertext_hash: [0; 32],
+        ..reply.clone()
+    };
+    assert_escrow_error(
+        env.send(&[empty.instruction()]),
+        EscrowError::InvalidEvidenceHash,
+    );
+    let mut misdirected = reply.instruction();
+    misdirected.accounts[3].pubkey = pda::find_profile_address(&env.stranger).0;
+    assert_escrow_error(
+        env.send(&[misdirected]),
+        EscrowError::RecipientProfileMismatch,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn funding_waits_for_the_buyer_when_acceptance_is_required() {
+    let mut env = Env::new();
+    let mut create = env.create_escrow(AMOUNT);
+    create.requires_acceptance = true;
+    env.ok(&[create.instruction()]);
+    let escrow = env.escrow_at(&create.escrow_address());
+    assert!(escrow.requires_acceptance);
+    assert_eq!(escrow.accepted_at, None);
+    assert_escrow_error(
+        env.send(&[env.fund_escrow(&escrow).instruction()]),
+        EscrowError::AcceptanceRequired,
+    );
+
+    // only the named buyer can accept
+    let accept = AcceptEscrow { escrow: &escrow };
+    let mut by_seller = accept.instruction();
+    by_seller.accounts[0].pubkey = escrow.seller;
+    assert_escrow_error(env.send(&[by_seller]), EscrowError::Unauthorized);
+
+    let meta = env.ok(&[accept.instruction()]);
+    let Some(Event::EscrowAccepted(accepted)) = events(&meta).into_iter().next() else {
+        panic!("no EscrowAccepted event");
+    };
+    assert_eq!(accepted.object_id, escrow.address());
+    assert_eq!(accepted.buyer, escrow.buyer);
+    assert_eq!(accepted.timestamp, env.now());
+    let reloaded = env.reload(&escrow);
+    assert_eq!(reloaded.state, EscrowState::Created);
+    assert_eq!(reloaded.accepted_at, Some(env.now()));
+    assert_eq!(reloaded.counter, escrow.counter + 1);
+    let status = EscrowStatus {
+        escrow: escrow.address(),
+    };
+    let report = EscrowStatusReport::try_from_return_data(&env.view(status.instruction())).unwrap();
+    assert_eq!(report.phase, EscrowPhase::Accepted);
+    assert!(report.actions(Role::Seller).contains(&Action::Fund));
+    assert_escrow_error(env.send(&[accept.instruction()]), EscrowError::InvalidState);
+
+    let funded = env.fund(&escrow);
+    assert_eq!(funded.state, EscrowState::Funded);
+
+    // escrows that do not ask for it cannot be accepted, and fund as before
+    let open = env.create(AMOUNT);
+    assert_escrow_error(
+        env.send(&[AcceptEscrow { escrow: &open }.instruction()]),
+        EscrowError::InvalidState,
+    );
+    env.fund(&open);
+
+    // nor can an escrow past its deposit deadline
+    let mut create = env.create_escrow(AMOUNT);
+    create.requires_acceptance = true;
+    env.ok(&[create.instruction()]);
+    let late = env.escrow_at(&create.escrow_address());
+    env.warp(DEPOSIT_WINDOW + 1);
+    assert_escrow_error(
+        env.send(&[AcceptEscrow { escrow: &late }.instruction()]),
+        EscrowError::DepositDeadlineExpired,
+    );
+}
+
+/// A funded escrow the buyer accepted before funding.
+fn accepted_and_funded(env: &mut Env) -> Escrow {
+    let mut create = env.create_escrow(AMOUNT);
+    create.requires_acceptance = true;
+    env.ok(&[create.instruction()]);
+    let escrow = env.escrow_at(&create.escrow_address());
+    env.ok(&[AcceptEscrow { escrow: &escrow }.instruction()]);
+    env.fund(&escrow)
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn seller_cancelling_an_accepted_escrow_pays_the_buyer() {
+    let mut env = Env::new();
+    let escrow = accepted_and_funded(&mut env);
+    let penalty = state_machine::cancellation_penalty(AMOUNT, FEE, FeePolicy::DEFAULT).unwrap();
+    assert_eq!(penalty, FEE / 2);
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(env.send(&[cancel.instruction()]), EscrowError::InvalidState);
+
+    let cancel = CancelEscrow {
+        buyer_token_account: Some(env.tokens_of(&escrow.buyer)),
+        ..cancel
+    };
+    let meta = env.ok(&[cancel.instruction()]);
+    let paid = events(&meta)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::CancellationPenaltyPaid(paid) => Some(paid),
+            _ => None,
+        })
+        .expect("no CancellationPenaltyPaid event");
+    assert_eq!((paid.seller, paid.buyer), (env.seller, env.buyer));
+    assert_eq!(paid.amount, penalty);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC + penalty);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC - penalty);
+    assert!(!env.exists(&escrow.address()));
+
+    // the arbitrator cancels without penalty, and so does a seller the buyer never accepted
+    let mut env = Env::new();
+    let escrow = accepted_and_funded(&mut env);
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: ARBITRATOR,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    env.ok(&[cancel.instruction()]);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+    assert_eq!(env.tokens(&env.buyer), 10 * USDC);
+
+    let escrow = env.create(AMOUNT);
+    let escrow = env.fund(&escrow);
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: Some(env.tokens_of(&escrow.seller)),
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    env.ok(&[cancel.instruction()]);
+    assert_eq!(env.tokens(&env.seller), 100 * USDC);
+}
+
+fn create_and_fund(env: &mut Env) -> CreateAndFundEscrow {
+    let create = env.create_escrow(AMOUNT);
+    CreateAndFundEscrow {
+        seller: create.seller,
+        buyer: create.buyer,
+        escrow_id: create.escrow_id,
+        trade_id: create.trade_id,
+        amount: create.amount,
+        sequential: create.sequential,
+        sequential_escrow_address: create.sequential_escrow_address,
+        fiat_terms: create.fiat_terms,
+        requires_buyer_deposit: false,
+        price_update: None,
+        seller_token_account: env.tokens_of(&create.seller),
+        token_mint: env.mint,
+    }
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn create_and_fund_matches_the_two_step_flow() {
+    let mut env = Env::new();
+    let create = env.create_escrow(AMOUNT);
+    let mut two_step = events(&env.ok(&[create.instruction()]));
+    let escrow = env.escrow_at(&create.escrow_address());
+    two_step.extend(events(&env.ok(&[env.fund_escrow(&escrow).instruction()])));
+    let expected = env.reload(&escrow);
+
+    let atomic = create_and_fund(&mut env);
+    let meta = env.ok(&[atomic.instruction()]);
+    let names: Vec<_> = events(&meta).iter().map(Event::name).collect();
+    assert_eq!(names, two_step.iter().map(Event::name).collect::<Vec<_>>());
+
+    let funded = env.escrow_at(&atomic.escrow_address());
+    assert_eq!(funded.state, EscrowState::Funded);
+    assert_eq!(funded.counter, expected.counter);
+    assert_eq!(funded.tracked_balance, AMOUNT + FEE);
+    assert_eq!(funded.fiat_deadline, env.now() + FIAT_WINDOW);
+    assert_eq!(funded.escrow_token_rent_payer, Some(env.seller));
+    assert!(!funded.requires_acceptance);
+    let vault = pda::find_escrow_token_address(&funded.address()).0;
+    assert_eq!(env.balance(&vault), AMOUNT + FEE);
+    // both escrows were paid for out of the seller's tokens
+    assert_eq!(env.tokens(&env.seller), 100 * USDC - 2 * (AMOUNT + FEE));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn failed_create_and_fund_leaves_no_escrow() {
+    let mut env = Env::new();
+    let seller_tokens = env.tokens_of(&env.seller);
+    let seller = env.seller;
+    env.set_token_account(&seller_tokens, &seller, AMOUNT + FEE - 1);
+    let atomic = create_and_fund(&mut env);
+    assert_escrow_error(
+        env.send(&[atomic.instruction()]),
+        EscrowError::InsufficientFunds,
+    );
+    assert!(!env.exists(&atomic.escrow_address()));
+
+    let mut over = create_and_fund(&mut env);
+    over.amount = MAX_AMOUNT + 1;
+    assert_escrow_error(env.send(&[over.instruction()]), EscrowError::ExceedsMaximum);
+}
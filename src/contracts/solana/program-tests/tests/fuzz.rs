@@ -0,0 +1,38 @@
+//! Random instruction sequences against one escrow, see `localsolana_program_tests::fuzz`.
+//!
+//! Runs 256 sequences by default; set `PROPTEST_CASES` for longer sessions.
+
+use localsolana_contracts_client::constants::MAX_AMOUNT;
+use localsolana_program_tests::fuzz::{replay, steps, Actor::*, Step::*};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+proptest! {
+    #[test]
+    #[ignore = "needs the built program, see the crate docs"]
+    fn invariants_hold_for_random_sequences(steps in steps()) {
+        replay(&steps).map_err(TestCaseError::fail)?;
+    }
+}
+
+// Sequences that broke an invariant, kept once fixed
+
+// auto_cancel accepted disputed escrows and closed them while the vault and the
+// initiator's bond still held funds
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn auto_cancel_of_a_disputed_escrow() {
+    replay(&[
+        Create {
+            amount: MAX_AMOUNT / 10,
+            sequential: false,
+        },
+        Fund { by: Seller },
+        MarkFiatPaid { by: Buyer },
+        InitBuyerBond { payer: Buyer },
+        InitSellerBond { payer: Seller },
+        OpenDispute { by: Buyer },
+        AutoCancel { by: Arbitrator },
+    ])
+    .unwrap();
+}
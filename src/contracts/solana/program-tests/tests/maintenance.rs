@@ -0,0 +1,386 @@
+//! Account maintenance and states only older program versions leave behind: layout
+//! migration, bond account reclaiming, vault reconciliation, escrows kept open in a
+//! terminal state, disputes opened before bonds were recorded and arithmetic limits.
+//!
+//! `ArbitrationDeadlineExpired`, `InvalidResolutionExplanation` and `BumpNotFound` are
+//! never returned by the current program and have no test.
+
+use localsolana_contracts_client::constants::{ARBITRATOR, ESCROW_VERSION};
+use localsolana_contracts_client::instructions::{
+    AutoCancel, CancelEscrow, MarkFiatPaid, MigrateEscrow, ReclaimBondAccounts, Reconcile,
+    ReleaseEscrow, ResolveDisputeWithExplanation, SweepSurplus, UpdateSequentialAddress,
+};
+use localsolana_contracts_client::{pda, Escrow, EscrowError, EscrowState, Event, ID};
+use localsolana_program_tests::{
+    anchor_error, assert_custom_error, assert_escrow_error, events, Env, Party, USDC,
+};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+const AMOUNT: u64 = 10 * USDC;
+
+// Account size of v0.1.2 escrows: discriminator plus the 337-byte v0.1.2 layout
+const LEGACY_ESCROW_LEN: usize = 8 + 337;
+
+// A Created escrow as v0.1.2 wrote it, at its legacy address
+fn set_legacy_escrow(env: &mut Env, escrow_id: u64, trade_id: u64) -> Escrow {
+    let escrow = Escrow {
+        escrow_id,
+        trade_id,
+        seller: env.seller,
+        buyer: env.buyer,
+        arbitrator: ARBITRATOR,
+        amount: AMOUNT,
+        fee: AMOUNT / 100,
+        deposit_deadline: env.now() + 900,
+        fiat_deadline: 0,
+        state: EscrowState::Created,
+        sequential: false,
+        sequential_escrow_address: None,
+        fiat_paid: false,
+        counter: 0,
+        dispute_initiator: None,
+        dispute_initiated_time: None,
+        dispute_evidence_hash_buyer: None,
+        dispute_evidence_hash_seller: None,
+        dispute_resolution_hash: None,
+        tracked_balance: 0,
+        version: 0,
+        seller_scoped_seeds: false,
+        escrow_rent_payer: None,
+        escrow_token_rent_payer: None,
+        buyer_bond_rent_payer: None,
+        seller_bond_rent_payer: None,
+        fiat_terms: None,
+        chat_checkpoint_buyer: None,
+        chat_checkpoint_seller: None,
+        requires_acceptance: false,
+        accepted_at: None,
+        buyer_deposit: 0,
+        buyer_deposit_rent_payer: None,
+        fee_policy: None,
+        dispute_bond: 0,
+        requires_buyer_deposit: false,
+    };
+    let mut data = Escrow::discriminator().to_vec();
+    data.extend(borsh::to_vec(&escrow).unwrap());
+    data.resize(LEGACY_ESCROW_LEN, 0);
+    env.set_program_account(&escrow.address(), data, ID);
+    escrow
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn migration_upgrades_a_legacy_escrow_in_place() {
+    let mut env = Env::new();
+    let legacy = set_legacy_escrow(&mut env, 7, 70);
+    let address = legacy.address();
+    assert_eq!(address, pda::find_legacy_escrow_address(7, 70).0);
+
+    let migrate = MigrateEscrow {
+        escrow: address,
+        payer: env.stranger,
+    };
+    env.ok(&[migrate.instruction()]);
+
+    let migrated = env.escrow_at(&address);
+    assert_eq!(migrated.version, ESCROW_VERSION);
+    assert_eq!(migrated.counter, 1);
+    assert!(!migrated.seller_scoped_seeds);
+    assert_eq!(migrated.amount, AMOUNT);
+    assert_eq!(migrated.fiat_terms, None);
+    assert_eq!(migrated.fee_policy, None);
+    assert_eq!(
+        env.svm.get_account(&address).unwrap().data.len(),
+        8 + Escrow::SPACE
+    );
+
+    assert_escrow_error(
+        env.send(&[migrate.instruction()]),
+        EscrowError::AlreadyMigrated,
+    );
+
+    // the upgraded escrow keeps working at its legacy address
+    let cancel = CancelEscrow {
+        escrow: &migrated,
+        authority: env.seller,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    env.ok(&[cancel.instruction()]);
+    assert!(!env.exists(&address));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn disputes_opened_before_bonds_were_recorded_settle_the_posted_bond() {
+    let mut env = Env::new();
+    let mut escrow = env.disputed(AMOUNT, Party::Buyer);
+
+    // as an older program left it: no recorded policy or bond, and a bond smaller than
+    // today's 5%
+    let posted = AMOUNT / 20 - 1;
+    escrow.fee_policy = None;
+    escrow.dispute_bond = 0;
+    env.set_escrow(&escrow);
+    let buyer_bond = pda::find_buyer_bond_address(&escrow.address()).0;
+    let mut account = env.svm.get_account(&buyer_bond).unwrap();
+    account.data[64..72].copy_from_slice(&posted.to_le_bytes());
+    env.svm.set_account(buyer_bond, account).unwrap();
+
+    // the respondent matches the bond actually posted
+    env.ok(&[env.respond_to_dispute(&escrow, Party::Seller).instruction()]);
+    let escrow = env.reload(&escrow);
+    assert_eq!(escrow.dispute_bond, posted);
+    assert_eq!(
+        env.balance(&pda::find_seller_bond_address(&escrow.address()).0),
+        posted
+    );
+
+    // and the resolution pays out exactly that, so both bond accounts close
+    let resolve = ResolveDisputeWithExplanation {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        decision: true,
+        resolution_hash: [0xa7; 32],
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        seller_token_account: env.tokens_of(&escrow.seller),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+    };
+    env.ok(&[resolve.instruction()]);
+    assert_eq!(env.tokens(&ARBITRATOR), AMOUNT / 100 + posted);
+    assert!(!env.exists(&buyer_bond));
+    assert!(!env.exists(&pda::find_seller_bond_address(&escrow.address()).0));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn migration_rejects_accounts_that_are_not_escrows() {
+    let mut env = Env::new();
+    let address = Pubkey::new_unique();
+    env.set_program_account(&address, vec![1; LEGACY_ESCROW_LEN], ID);
+    let migrate = MigrateEscrow {
+        escrow: address,
+        payer: env.stranger,
+    };
+    assert_escrow_error(
+        env.send(&[migrate.instruction()]),
+        EscrowError::InvalidEscrowAccount,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn reclaim_refunds_bonds_of_terminal_escrows_to_their_payer() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let escrow = env.init_bonds(&escrow);
+    let key = escrow.address();
+    let reclaim = ReclaimBondAccounts {
+        caller: env.stranger,
+        escrow: &escrow,
+        buyer_bond: true,
+        seller_bond: false,
+    };
+
+    assert_escrow_error(
+        env.send(&[reclaim.instruction()]),
+        EscrowError::NotTerminalState,
+    );
+
+    // released by a version that did not close accounts on settlement
+    let mut released = escrow.clone();
+    released.state = EscrowState::Released;
+    env.set_escrow(&released);
+
+    let mut to_stranger = reclaim.instruction();
+    to_stranger.accounts[4] = AccountMeta::new(env.stranger, false);
+    assert_escrow_error(env.send(&[to_stranger]), EscrowError::InvalidRentPayer);
+
+    let buyer_lamports = env.lamports(&env.buyer);
+    env.ok(&[reclaim.instruction()]);
+    assert!(!env.exists(&pda::find_buyer_bond_address(&key).0));
+    assert!(env.exists(&pda::find_seller_bond_address(&key).0));
+    assert!(env.lamports(&env.buyer) > buyer_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn bonds_of_closed_escrows_cannot_be_reclaimed() {
+    let mut env = Env::new();
+    // an escrow whose account is gone, leaving the rent payer record with it
+    let mut closed = env.paid(AMOUNT);
+    closed.escrow_id += 1_000;
+    let bond = pda::find_seller_bond_address(&closed.address()).0;
+    env.set_token_account(&bond, &bond, 0);
+
+    let reclaim = ReclaimBondAccounts {
+        caller: env.stranger,
+        escrow: &closed,
+        buyer_bond: false,
+        seller_bond: true,
+    };
+    assert_escrow_error(
+        env.send(&[reclaim.instruction()]),
+        EscrowError::InvalidEscrowAccount,
+    );
+    assert!(env.exists(&bond));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn stray_vault_tokens_are_reported_and_swept_so_the_vault_can_close() {
+    let mut env = Env::new();
+    let escrow = env.paid(AMOUNT);
+    let vault = pda::find_escrow_token_address(&escrow.address()).0;
+    let funded = escrow.tracked_balance;
+    let reconcile = Reconcile {
+        escrow: &escrow,
+        caller: env.stranger,
+    };
+    let meta = env.ok(&[reconcile.instruction()]);
+    assert!(events(&meta).is_empty());
+
+    // tokens sent straight to the vault
+    env.set_token_account(&vault, &vault, funded + USDC);
+    let meta = env.ok(&[reconcile.instruction()]);
+    match events(&meta).as_slice() {
+        [Event::BalanceDiscrepancy(e)] => {
+            assert_eq!(
+                (e.tracked_balance, e.vault_balance),
+                (funded, funded + USDC)
+            );
+        }
+        other => panic!("expected one BalanceDiscrepancy, got {other:?}"),
+    }
+
+    let release = ReleaseEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        buyer_token_account: env.tokens_of(&escrow.buyer),
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+        sequential_escrow_token_account: None,
+    };
+    assert!(env.send(&[release.instruction()]).is_err());
+
+    let sweep = SweepSurplus {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        arbitrator_token_account: env.tokens_of(&ARBITRATOR),
+    };
+    let mut by_seller = sweep.clone();
+    by_seller.arbitrator = escrow.seller;
+    assert_custom_error(
+        env.send(&[by_seller.instruction()]),
+        anchor_error::CONSTRAINT_RAW,
+    );
+
+    env.ok(&[sweep.instruction()]);
+    assert_eq!(env.tokens(&ARBITRATOR), USDC);
+    assert_eq!(env.balance(&vault), funded);
+    assert_escrow_error(env.send(&[sweep.instruction()]), EscrowError::NoSurplus);
+
+    env.ok(&[release.instruction()]);
+    assert!(!env.exists(&vault));
+    assert_eq!(env.tokens(&ARBITRATOR), USDC + escrow.fee);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn stray_bond_tokens_are_swept_so_bonds_close_with_the_escrow() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let escrow = env.init_bonds(&escrow);
+    // tokens sent straight to an unused bond account
+    let bond = pda::find_seller_bond_address(&escrow.address()).0;
+    env.set_token_account(&bond, &bond, USDC);
+
+    let mut cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(
+        env.send(&[cancel.instruction()]),
+        EscrowError::MissingArbitratorTokenAccount,
+    );
+
+    cancel.arbitrator_token_account = Some(env.tokens_of(&ARBITRATOR));
+    let seller_lamports = env.lamports(&escrow.seller);
+    let meta = env.ok(&[cancel.instruction()]);
+    assert!(events(&meta).iter().any(|event| matches!(
+        event,
+        Event::SurplusSwept(e) if e.amount == USDC
+    )));
+    assert_eq!(env.tokens(&ARBITRATOR), USDC);
+    assert!(!env.exists(&bond));
+    assert!(!env.exists(&pda::find_buyer_bond_address(&escrow.address()).0));
+    assert!(env.lamports(&escrow.seller) > seller_lamports);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn terminal_escrows_accept_no_further_transitions() {
+    let mut env = Env::new();
+    let mut create = env.create_escrow(AMOUNT);
+    create.sequential = true;
+    create.sequential_escrow_address = Some(Pubkey::new_unique());
+    env.ok(&[create.instruction()]);
+    let mut escrow = env.escrow_at(&create.escrow_address());
+    escrow.state = EscrowState::Cancelled;
+    env.set_escrow(&escrow);
+
+    let update = UpdateSequentialAddress {
+        escrow: &escrow,
+        new_address: Pubkey::new_unique(),
+    };
+    assert_escrow_error(
+        env.send(&[update.instruction()]),
+        EscrowError::TerminalState,
+    );
+
+    let auto_cancel = AutoCancel {
+        escrow: &escrow,
+        arbitrator: ARBITRATOR,
+        seller_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(
+        env.send(&[auto_cancel.instruction()]),
+        EscrowError::TerminalState,
+    );
+
+    let cancel = CancelEscrow {
+        escrow: &escrow,
+        authority: escrow.seller,
+        seller_token_account: None,
+        buyer_token_account: None,
+        arbitrator_token_account: None,
+    };
+    assert_escrow_error(env.send(&[cancel.instruction()]), EscrowError::InvalidState);
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn arithmetic_overflow_is_reported_not_wrapped() {
+    let mut env = Env::new();
+    let escrow = env.create(AMOUNT);
+    let mut escrow = env.fund(&escrow);
+    escrow.counter = u64::MAX;
+    env.set_escrow(&escrow);
+    let mark = MarkFiatPaid { escrow: &escrow };
+    assert_escrow_error(
+        env.send(&[mark.instruction()]),
+        EscrowError::CounterOverflow,
+    );
+
+    let escrow = env.paid(AMOUNT);
+    let mut escrow = env.init_bonds(&escrow);
+    escrow.amount = u64::MAX;
+    env.set_escrow(&escrow);
+    let open = env.open_dispute(&escrow, Party::Buyer).instruction();
+    assert_escrow_error(env.send(&[open]), EscrowError::FeeCalculationError);
+}
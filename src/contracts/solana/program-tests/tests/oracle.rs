@@ -0,0 +1,233 @@
+//! The price feed registry and the oracle check of the committed exchange rate in
+//! `create_escrow`, mandatory once the currency has a feed, against Pyth price updates
+//! written straight into the SVM.
+
+use localsolana_contracts_client::constants::{ARBITRATOR, PYTH_RECEIVER_PROGRAM_ID};
+use localsolana_contracts_client::events::FiatRateChecked;
+use localsolana_contracts_client::instructions::{
+    PriceFeedSettings, RegisterPriceFeed, UpdatePriceFeed,
+};
+use localsolana_contracts_client::oracle::{PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+use localsolana_contracts_client::{pda, EscrowError, Event, PriceFeed};
+use localsolana_program_tests::{assert_escrow_error, events, Env, USDC};
+use solana_sdk::pubkey::Pubkey;
+
+const AMOUNT: u64 = 10 * USDC;
+const FEED_ID: [u8; 32] = [0xfe; 32];
+// One USD per token with Pyth's usual exponent
+const PARITY: i64 = 100_000_000;
+
+fn settings() -> PriceFeedSettings {
+    PriceFeedSettings {
+        feed_id: FEED_ID,
+        max_deviation_bps: 200,
+        max_age_seconds: 60,
+        max_confidence_bps: 50,
+    }
+}
+
+fn register(env: &mut Env) {
+    let register = RegisterPriceFeed {
+        arbitrator: ARBITRATOR,
+        currency: *b"USD",
+        settings: settings(),
+    };
+    env.ok(&[register.instruction()]);
+}
+
+// A fully verified update of `feed_id` published `age` seconds ago
+fn set_price_update(env: &mut Env, feed_id: [u8; 32], price: i64, conf: u64, age: i64) -> Pubkey {
+    let publish_time = env.now() - age;
+    let update = PriceUpdateV2 {
+        write_authority: Pubkey::new_unique(),
+        verification_level: VerificationLevel::Full,
+        price_message: PriceFeedMessage {
+            feed_id,
+            price,
+            conf,
+            exponent: -8,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            ema_price: price,
+            ema_conf: conf,
+        },
+        posted_slot: 1,
+    };
+    let address = Pubkey::new_unique();
+    env.set_program_account(&address, update.to_account_data(), PYTH_RECEIVER_PROGRAM_ID);
+    address
+}
+
+fn price_feed(env: &Env) -> PriceFeed {
+    let account = env
+        .svm
+        .get_account(&pda::find_price_feed_address(b"USD").0)
+        .expect("price feed registered");
+    PriceFeed::try_from_account_data(&account.data).unwrap()
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn arbitrator_registers_and_updates_feeds() {
+    let mut env = Env::new();
+    register(&mut env);
+    let feed = price_feed(&env);
+    assert_eq!(feed.currency, *b"USD");
+    assert_eq!(feed.feed_id, FEED_ID);
+    assert_eq!(feed.max_deviation_bps, 200);
+
+    let update = UpdatePriceFeed {
+        arbitrator: ARBITRATOR,
+        currency: *b"USD",
+        settings: PriceFeedSettings {
+            max_deviation_bps: 500,
+            ..settings()
+        },
+    };
+    env.ok(&[update.instruction()]);
+    assert_eq!(price_feed(&env).max_deviation_bps, 500);
+
+    let zero_age = UpdatePriceFeed {
+        arbitrator: ARBITRATOR,
+        currency: *b"USD",
+        settings: PriceFeedSettings {
+            max_age_seconds: 0,
+            ..settings()
+        },
+    };
+    assert_escrow_error(
+        env.send(&[zero_age.instruction()]),
+        EscrowError::InvalidRateBand,
+    );
+
+    let stranger = RegisterPriceFeed {
+        arbitrator: env.stranger,
+        currency: *b"EUR",
+        settings: settings(),
+    };
+    assert_escrow_error(
+        env.send(&[stranger.instruction()]),
+        EscrowError::Unauthorized,
+    );
+
+    let lowercase = RegisterPriceFeed {
+        arbitrator: ARBITRATOR,
+        currency: *b"eur",
+        settings: settings(),
+    };
+    assert_escrow_error(
+        env.send(&[lowercase.instruction()]),
+        EscrowError::InvalidFiatTerms,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn rate_within_band_of_a_fresh_price_is_accepted_and_logged() {
+    let mut env = Env::new();
+    register(&mut env);
+    // oracle 1% above the committed parity rate
+    let update = set_price_update(&mut env, FEED_ID, PARITY + PARITY / 100, 10_000, 5);
+    let mut create = env.create_escrow(AMOUNT);
+    create.price_update = Some(update);
+    let meta = env.ok(&[create.instruction()]);
+
+    let checked = events(&meta)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::FiatRateChecked(e) => Some(e),
+            _ => None,
+        })
+        .expect("FiatRateChecked emitted");
+    assert_eq!(
+        checked,
+        FiatRateChecked {
+            schema_version: checked.schema_version,
+            object_id: create.escrow_address(),
+            escrow_id: create.escrow_id,
+            trade_id: create.trade_id,
+            currency: *b"USD",
+            feed_id: FEED_ID,
+            committed_rate: create.fiat_terms.rate,
+            oracle_rate: 101_000_000,
+            deviation_bps: 99,
+            publish_time: env.now() - 5,
+            timestamp: env.now(),
+        }
+    );
+
+    // the seller cannot skip the check by leaving the price update out
+    let create = env.create_escrow(AMOUNT);
+    assert_escrow_error(
+        env.send(&[create.instruction()]),
+        EscrowError::InvalidOraclePrice,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn unregistered_currencies_skip_the_check() {
+    let mut env = Env::new();
+    let create = env.create_escrow(AMOUNT);
+    let meta = env.ok(&[create.instruction()]);
+    assert!(!events(&meta)
+        .iter()
+        .any(|event| matches!(event, Event::FiatRateChecked(_))));
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn rates_against_bad_or_distant_prices_are_rejected() {
+    let mut env = Env::new();
+    register(&mut env);
+    let cases = [
+        (FEED_ID, 2 * PARITY, 10_000, 5, EscrowError::RateOutOfBand),
+        (FEED_ID, PARITY, 10_000, 61, EscrowError::StaleOraclePrice),
+        (
+            FEED_ID,
+            PARITY,
+            PARITY as u64 / 100,
+            5,
+            EscrowError::OraclePriceUncertain,
+        ),
+        (FEED_ID, 0, 0, 5, EscrowError::InvalidOraclePrice),
+        (
+            [0x01; 32],
+            PARITY,
+            10_000,
+            5,
+            EscrowError::InvalidOraclePrice,
+        ),
+    ];
+    for (feed_id, price, conf, age, expected) in cases {
+        let update = set_price_update(&mut env, feed_id, price, conf, age);
+        let mut create = env.create_escrow(AMOUNT);
+        create.price_update = Some(update);
+        assert_escrow_error(env.send(&[create.instruction()]), expected);
+    }
+
+    // an account the Pyth receiver does not own
+    let update = set_price_update(&mut env, FEED_ID, PARITY, 10_000, 5);
+    let account = env.svm.get_account(&update).unwrap();
+    env.set_program_account(&update, account.data, Pubkey::new_unique());
+    let mut create = env.create_escrow(AMOUNT);
+    create.price_update = Some(update);
+    assert_escrow_error(
+        env.send(&[create.instruction()]),
+        EscrowError::InvalidOraclePrice,
+    );
+}
+
+#[test]
+#[ignore = "needs the built program, see the crate docs"]
+fn price_update_without_the_feed_is_rejected() {
+    let mut env = Env::new();
+    // no feed registered for the currency
+    let update = set_price_update(&mut env, FEED_ID, PARITY, 10_000, 5);
+    let mut create = env.create_escrow(AMOUNT);
+    create.price_update = Some(update);
+    assert_escrow_error(
+        env.send(&[create.instruction()]),
+        EscrowError::InvalidOraclePrice,
+    );
+}
@@ -0,0 +1,1951 @@
+//! Escrow lifecycle rules with no dependency on Anchor or Solana: the transition table,
+//! the guards every instruction checks before it moves funds, and the payout arithmetic
+//! of each settlement.
+//!
+//! The program's handlers call into this module and the client crate compiles the same
+//! file, so off-chain code predicts outcomes with the rules the chain enforces.
+//! `state_machine.md` is generated from [`TRANSITIONS`] by [`mermaid`].
+//!
+//! Checks that depend on which accounts a transaction supplies (token balances, bond
+//! accounts, rent payers) stay in the handlers.
+
+use std::fmt::Write as _;
+
+/// Largest principal an escrow may hold (100 USDC, 6 decimals).
+pub const MAX_AMOUNT: u64 = 100_000_000;
+
+/// Fee charged on top of the principal (1%).
+pub const FEE_BASIS_POINTS: u64 = 100;
+
+/// Bond each party posts to take part in a dispute (5% of the principal).
+pub const DISPUTE_BOND_BASIS_POINTS: u64 = 500;
+
+/// Default smallest fee charged, in base units (0.01 USDC, the fee of a 1 USDC trade).
+pub const MIN_FEE: u64 = 10_000;
+
+/// Default smallest dispute bond, in base units (0.05 USDC, the bond of a 1 USDC trade).
+pub const MIN_DISPUTE_BOND: u64 = 50_000;
+
+/// Deposit a buyer may post against walking away from a funded trade (2% of the principal).
+pub const BUYER_DEPOSIT_BASIS_POINTS: u64 = 200;
+
+/// Smallest buyer deposit, in base units (0.02 USDC, the deposit of a 1 USDC trade).
+pub const MIN_BUYER_DEPOSIT: u64 = 20_000;
+
+/// Paid to the buyer out of the escrowed fee when the seller cancels a funded escrow the
+/// buyer accepted (0.5% of the principal, at most the fee).
+pub const SELLER_CANCEL_PENALTY_BASIS_POINTS: u64 = 50;
+
+/// Default rounding of fees, bonds, deposits and penalties to whole base units.
+pub const ROUNDING: Rounding = Rounding::Floor;
+
+/// Decimal places of a committed exchange rate, quoted as fiat major units per whole token.
+pub const FIAT_RATE_DECIMALS: u32 = 8;
+
+pub const DEPOSIT_DEADLINE_MINUTES: i64 = 15; // from creation
+pub const FIAT_DEADLINE_MINUTES: i64 = 30; // from funding
+
+// Shorten deadlines for localnet testing
+// pub const DEPOSIT_DEADLINE_MINUTES: i64 = 1;
+// pub const FIAT_DEADLINE_MINUTES: i64 = 1;
+
+pub const DISPUTE_RESPONSE_DEADLINE_HOURS: i64 = 72; // from opening the dispute
+
+pub const SECONDS_PER_MINUTE: i64 = 60;
+pub const SECONDS_PER_HOUR: i64 = 3600;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    Created,
+    Funded,
+    Released,
+    Cancelled,
+    Disputed,
+    Resolved,
+}
+
+impl State {
+    pub const ALL: [State; 6] = [
+        State::Created,
+        State::Funded,
+        State::Released,
+        State::Cancelled,
+        State::Disputed,
+        State::Resolved,
+    ];
+
+    pub fn is_terminal(self) -> bool {
+        matches!(self, State::Released | State::Cancelled | State::Resolved)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            State::Created => "Created",
+            State::Funded => "Funded",
+            State::Released => "Released",
+            State::Cancelled => "Cancelled",
+            State::Disputed => "Disputed",
+            State::Resolved => "Resolved",
+        }
+    }
+}
+
+/// Role a signer can hold in an escrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Seller,
+    Buyer,
+    Arbitrator,
+    /// Anyone else.
+    Other,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Seller, Role::Buyer, Role::Arbitrator, Role::Other];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Seller => "seller",
+            Role::Buyer => "buyer",
+            Role::Arbitrator => "arbitrator",
+            Role::Other => "anyone",
+        }
+    }
+}
+
+/// A trading party.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Party {
+    Buyer,
+    Seller,
+}
+
+impl Party {
+    pub fn other(self) -> Party {
+        match self {
+            Party::Buyer => Party::Seller,
+            Party::Seller => Party::Buyer,
+        }
+    }
+}
+
+/// Roles the signer of an instruction holds. Nothing stops a seller from trading with
+/// themselves, so more than one can be true.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Caller {
+    pub seller: bool,
+    pub buyer: bool,
+    pub arbitrator: bool,
+}
+
+impl Caller {
+    /// Roles of `signer` in an escrow between `seller` and `buyer`.
+    pub fn of<K: PartialEq>(signer: &K, seller: &K, buyer: &K, arbitrator: &K) -> Caller {
+        Caller {
+            seller: signer == seller,
+            buyer: signer == buyer,
+            arbitrator: signer == arbitrator,
+        }
+    }
+
+    /// A signer holding only `role`; [`Role::Other`] holds none.
+    pub fn acting_as(role: Role) -> Caller {
+        Caller {
+            seller: role == Role::Seller,
+            buyer: role == Role::Buyer,
+            arbitrator: role == Role::Arbitrator,
+        }
+    }
+
+    /// Whether the signer holds `role`; everyone counts as [`Role::Other`].
+    pub fn is(self, role: Role) -> bool {
+        match role {
+            Role::Seller => self.seller,
+            Role::Buyer => self.buyer,
+            Role::Arbitrator => self.arbitrator,
+            Role::Other => true,
+        }
+    }
+
+    fn is_party(self) -> bool {
+        self.seller || self.buyer
+    }
+}
+
+/// The lifecycle instructions. Migration and bond reclaiming only touch accounts older
+/// program versions left behind and are not part of the lifecycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Create,
+    Fund,
+    MarkFiatPaid,
+    UpdateSequentialAddress,
+    Release,
+    Cancel,
+    InitializeBuyerBond,
+    InitializeSellerBond,
+    OpenDispute,
+    RespondToDispute,
+    DefaultJudgment,
+    ResolveDispute,
+    AutoCancel,
+    PostPaymentDetails,
+    CommitChatRoot,
+    ProveChatMessage,
+    Accept,
+    PostBuyerDeposit,
+}
+
+impl Action {
+    pub const ALL: [Action; 18] = [
+        Action::Create,
+        Action::Fund,
+        Action::MarkFiatPaid,
+        Action::UpdateSequentialAddress,
+        Action::Release,
+        Action::Cancel,
+        Action::InitializeBuyerBond,
+        Action::InitializeSellerBond,
+        Action::OpenDispute,
+        Action::RespondToDispute,
+        Action::DefaultJudgment,
+        Action::ResolveDispute,
+        Action::AutoCancel,
+        Action::PostPaymentDetails,
+        Action::CommitChatRoot,
+        Action::ProveChatMessage,
+        Action::Accept,
+        Action::PostBuyerDeposit,
+    ];
+
+    /// Name of the program instruction.
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Action::Create => "create_escrow",
+            Action::Fund => "fund_escrow",
+            Action::MarkFiatPaid => "mark_fiat_paid",
+            Action::UpdateSequentialAddress => "update_sequential_address",
+            Action::Release => "release_escrow",
+            Action::Cancel => "cancel_escrow",
+            Action::InitializeBuyerBond => "initialize_buyer_bond_account",
+            Action::InitializeSellerBond => "initialize_seller_bond_account",
+            Action::OpenDispute => "open_dispute_with_bond",
+            Action::RespondToDispute => "respond_to_dispute_with_bond",
+            Action::DefaultJudgment => "default_judgment",
+            Action::ResolveDispute => "resolve_dispute_with_explanation",
+            Action::AutoCancel => "auto_cancel",
+            Action::PostPaymentDetails => "post_payment_details",
+            Action::CommitChatRoot => "commit_chat_root",
+            Action::ProveChatMessage => "prove_chat_message",
+            Action::Accept => "accept_escrow",
+            Action::PostBuyerDeposit => "post_buyer_deposit",
+        }
+    }
+
+    /// Bit of this action in an [`allowed`] mask: its position in [`Action::ALL`].
+    pub fn bit(self) -> u32 {
+        let index = Action::ALL.iter().position(|action| *action == self);
+        1 << index.expect("every action is listed in ALL")
+    }
+
+    /// The actions set in `mask`, in [`Action::ALL`] order.
+    pub fn from_mask(mask: u32) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| mask & action.bit() != 0)
+            .collect()
+    }
+}
+
+/// One row of the transition table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub action: Action,
+    /// `None` for the instruction that creates the escrow.
+    pub from: Option<State>,
+    pub to: State,
+    /// Signers allowed to take the transition.
+    pub by: &'static [Role],
+    /// Conditions beyond state and signer, for people; [`check`] is what enforces them.
+    pub guard: &'static str,
+}
+
+const SELLER: &[Role] = &[Role::Seller];
+const BUYER: &[Role] = &[Role::Buyer];
+const PARTIES: &[Role] = &[Role::Buyer, Role::Seller];
+const SELLER_OR_ARBITRATOR: &[Role] = &[Role::Seller, Role::Arbitrator];
+const PARTIES_OR_ARBITRATOR: &[Role] = &[Role::Buyer, Role::Seller, Role::Arbitrator];
+const ARBITRATOR: &[Role] = &[Role::Arbitrator];
+const ANYONE: &[Role] = &[Role::Other];
+
+const fn row(
+    action: Action,
+    from: Option<State>,
+    to: State,
+    by: &'static [Role],
+    guard: &'static str,
+) -> Transition {
+    Transition {
+        action,
+        from,
+        to,
+        by,
+        guard,
+    }
+}
+
+/// Every transition the program allows. Settlement closes the escrow account, so
+/// nothing leaves a terminal state.
+#[rustfmt::skip]
+pub const TRANSITIONS: &[Transition] = {
+    use Action::*;
+    use State::*;
+    &[
+        row(Create, None, Created, SELLER, "amount between 1 and MAX_AMOUNT"),
+        row(Accept, Some(Created), Created, BUYER,
+            "acceptance required and not yet given, by the deposit deadline"),
+        row(Fund, Some(Created), Funded, SELLER, "by the deposit deadline, accepted if required"),
+        row(MarkFiatPaid, Some(Funded), Funded, BUYER,
+            "by the fiat deadline, deposit posted if required"),
+        row(PostBuyerDeposit, Some(Funded), Funded, BUYER,
+            "fiat not paid, none posted yet, by the fiat deadline"),
+        row(UpdateSequentialAddress, Some(Created), Created, BUYER, "sequential"),
+        row(UpdateSequentialAddress, Some(Funded), Funded, BUYER, "sequential"),
+        row(UpdateSequentialAddress, Some(Disputed), Disputed, BUYER, "sequential"),
+        row(Release, Some(Funded), Released, SELLER_OR_ARBITRATOR, "fiat paid"),
+        row(Cancel, Some(Created), Cancelled, SELLER_OR_ARBITRATOR, ""),
+        row(Cancel, Some(Funded), Cancelled, SELLER_OR_ARBITRATOR, "fiat not paid"),
+        row(InitializeBuyerBond, Some(Created), Created, ANYONE, ""),
+        row(InitializeBuyerBond, Some(Funded), Funded, ANYONE, ""),
+        row(InitializeBuyerBond, Some(Disputed), Disputed, ANYONE, ""),
+        row(InitializeSellerBond, Some(Created), Created, ANYONE, ""),
+        row(InitializeSellerBond, Some(Funded), Funded, ANYONE, ""),
+        row(InitializeSellerBond, Some(Disputed), Disputed, ANYONE, ""),
+        row(OpenDispute, Some(Funded), Disputed, PARTIES, "fiat paid"),
+        row(RespondToDispute, Some(Disputed), Disputed, PARTIES,
+            "the other party, by the response deadline"),
+        row(DefaultJudgment, Some(Disputed), Resolved, ANYONE,
+            "response deadline passed without a response"),
+        row(ResolveDispute, Some(Disputed), Resolved, ARBITRATOR, "both parties answered"),
+        row(AutoCancel, Some(Created), Cancelled, ARBITRATOR, "deposit deadline passed"),
+        row(AutoCancel, Some(Funded), Cancelled, ARBITRATOR,
+            "fiat deadline passed, fiat not paid"),
+        row(PostPaymentDetails, Some(Created), Created, PARTIES, "both encryption keys registered"),
+        row(PostPaymentDetails, Some(Funded), Funded, PARTIES, "both encryption keys registered"),
+        row(CommitChatRoot, Some(Created), Created, PARTIES, "more messages than the last checkpoint"),
+        row(CommitChatRoot, Some(Funded), Funded, PARTIES, "more messages than the last checkpoint"),
+        row(CommitChatRoot, Some(Disputed), Disputed, PARTIES, "more messages than the last checkpoint"),
+        row(ProveChatMessage, Some(Disputed), Disputed, PARTIES_OR_ARBITRATOR,
+            "proof against a committed root"),
+    ]
+};
+
+/// The row for taking `action` from `from`, if the table has one.
+pub fn transition(action: Action, from: Option<State>) -> Option<&'static Transition> {
+    TRANSITIONS
+        .iter()
+        .find(|row| row.action == action && row.from == from)
+}
+
+/// Why an instruction was refused. Each variant has an `EscrowError` of the same name
+/// except `Overflow`, which the program reports as `FeeCalculationError`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    InvalidAmount,
+    ExceedsMaximum,
+    Unauthorized,
+    DepositDeadlineExpired,
+    FiatDeadlineExpired,
+    InvalidState,
+    MissingSequentialAddress,
+    TerminalState,
+    ResponseDeadlineExpired,
+    InvalidEvidenceHash,
+    DuplicateEvidence,
+    InvalidFiatTerms,
+    InvalidOraclePrice,
+    StaleOraclePrice,
+    OraclePriceUncertain,
+    RateOutOfBand,
+    InvalidRateBand,
+    InvalidChatRoot,
+    InvalidInclusionProof,
+    AcceptanceRequired,
+    InvalidFeePolicy,
+    BuyerDepositRequired,
+    Overflow,
+}
+
+/// What the guards read from an escrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Facts {
+    pub state: State,
+    pub fiat_paid: bool,
+    /// The buyer has to accept before the seller can fund.
+    pub requires_acceptance: bool,
+    pub accepted: bool,
+    pub buyer_deposit_posted: bool,
+    /// The buyer has to post the deposit before marking fiat paid.
+    pub requires_buyer_deposit: bool,
+    pub sequential: bool,
+    pub has_sequential_address: bool,
+    pub deposit_deadline: i64,
+    pub fiat_deadline: i64,
+    pub dispute_initiator: Option<Party>,
+    pub dispute_initiated_time: Option<i64>,
+    pub buyer_evidence: bool,
+    pub seller_evidence: bool,
+}
+
+/// Validates the terms `create_escrow` is called with.
+pub fn check_terms(
+    amount: u64,
+    sequential: bool,
+    has_sequential_address: bool,
+) -> Result<(), Violation> {
+    require(amount > 0, Violation::InvalidAmount)?;
+    require(amount <= MAX_AMOUNT, Violation::ExceedsMaximum)?;
+    if sequential {
+        require(has_sequential_address, Violation::MissingSequentialAddress)?;
+    }
+    Ok(())
+}
+
+/// Validates the fiat side `create_escrow` commits to: an uppercase ISO 4217 alphabetic
+/// currency code and a non-zero fiat amount and rate.
+pub fn check_fiat_terms(currency: [u8; 3], fiat_amount: u64, rate: u64) -> Result<(), Violation> {
+    require(
+        currency.iter().all(u8::is_ascii_uppercase),
+        Violation::InvalidFiatTerms,
+    )?;
+    require(fiat_amount > 0 && rate > 0, Violation::InvalidFiatTerms)
+}
+
+/// An oracle price: `price * 10^exponent` fiat major units per whole token, give or take
+/// `confidence` in the same units, as of `publish_time`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub confidence: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+/// How far a committed rate may be from the oracle, and how fresh and precise the oracle
+/// price has to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateBand {
+    pub max_deviation_bps: u16,
+    pub max_age_seconds: u32,
+    pub max_confidence_bps: u16,
+}
+
+impl RateBand {
+    pub fn check(&self) -> Result<(), Violation> {
+        require(
+            self.max_deviation_bps > 0
+                && self.max_age_seconds > 0
+                && self.max_confidence_bps > 0
+                && self.max_confidence_bps <= 10_000,
+            Violation::InvalidRateBand,
+        )
+    }
+}
+
+/// Outcome of a passed [`check_rate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateCheck {
+    /// The oracle price with [`FIAT_RATE_DECIMALS`] decimals.
+    pub oracle_rate: u64,
+    pub deviation_bps: u64,
+}
+
+/// Checks a committed `rate` ([`FIAT_RATE_DECIMALS`] decimals) against `oracle` at `now`.
+///
+/// The price must be positive, at most `max_age_seconds` old, and its confidence
+/// interval at most `max_confidence_bps` of the price; the rate may then deviate from it
+/// by at most `max_deviation_bps`.
+pub fn check_rate(
+    rate: u64,
+    oracle: OraclePrice,
+    band: RateBand,
+    now: i64,
+) -> Result<RateCheck, Violation> {
+    require(oracle.price > 0, Violation::InvalidOraclePrice)?;
+    let age = now.saturating_sub(oracle.publish_time);
+    require(
+        age <= i64::from(band.max_age_seconds),
+        Violation::StaleOraclePrice,
+    )?;
+
+    let price = oracle.price as u128;
+    require(
+        u128::from(oracle.confidence) * 10_000 <= u128::from(band.max_confidence_bps) * price,
+        Violation::OraclePriceUncertain,
+    )?;
+
+    let shift = FIAT_RATE_DECIMALS as i32 + oracle.exponent;
+    let scale = 10u128
+        .checked_pow(shift.unsigned_abs())
+        .ok_or(Violation::InvalidOraclePrice)?;
+    let oracle_rate = if shift >= 0 {
+        price
+            .checked_mul(scale)
+            .ok_or(Violation::InvalidOraclePrice)?
+    } else {
+        price / scale
+    };
+    require(oracle_rate > 0, Violation::InvalidOraclePrice)?;
+    let oracle_rate = u64::try_from(oracle_rate).map_err(|_| Violation::InvalidOraclePrice)?;
+
+    let difference = u128::from(rate.abs_diff(oracle_rate));
+    let deviation_bps = difference * 10_000 / u128::from(oracle_rate);
+    require(
+        deviation_bps <= u128::from(band.max_deviation_bps),
+        Violation::RateOutOfBand,
+    )?;
+    Ok(RateCheck {
+        oracle_rate,
+        deviation_bps: deviation_bps as u64,
+    })
+}
+
+/// Domain separation of chat transcript Merkle trees, as in RFC 6962: a leaf hashes
+/// `CHAT_LEAF_PREFIX || sha256(message)`, an inner node `CHAT_NODE_PREFIX || left || right`.
+pub const CHAT_LEAF_PREFIX: u8 = 0;
+pub const CHAT_NODE_PREFIX: u8 = 1;
+
+/// Validates a chat transcript checkpoint: a non-zero root over more messages than the
+/// party's previous checkpoint (`0` if none). Whether the earlier messages are unchanged
+/// cannot be checked on-chain; both roots stay in the event log for the arbitrator.
+pub fn check_chat_checkpoint(
+    root: [u8; 32],
+    message_count: u64,
+    previous_count: u64,
+) -> Result<(), Violation> {
+    require(root != [0; 32], Violation::InvalidChatRoot)?;
+    require(message_count > previous_count, Violation::InvalidChatRoot)
+}
+
+/// Checks an RFC 6962 audit path: that `leaf` is leaf `leaf_index` of the tree of
+/// `tree_size` leaves with root `root`. `node` hashes two children with
+/// [`CHAT_NODE_PREFIX`]; it is passed in because this module has no hash function.
+pub fn verify_inclusion(
+    leaf_index: u64,
+    tree_size: u64,
+    leaf: [u8; 32],
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+    node: impl Fn(&[u8; 32], &[u8; 32]) -> [u8; 32],
+) -> Result<(), Violation> {
+    require(leaf_index < tree_size, Violation::InvalidInclusionProof)?;
+    let mut index = leaf_index;
+    let mut last = tree_size - 1;
+    let mut hash = leaf;
+    for sibling in proof {
+        require(last > 0, Violation::InvalidInclusionProof)?;
+        if index & 1 == 1 || index == last {
+            hash = node(sibling, &hash);
+            // a right-most node without a sibling on this level moves up unchanged
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node(&hash, sibling);
+        }
+        index >>= 1;
+        last >>= 1;
+    }
+    require(last == 0 && hash == *root, Violation::InvalidInclusionProof)
+}
+
+fn require(condition: bool, violation: Violation) -> Result<(), Violation> {
+    if condition {
+        Ok(())
+    } else {
+        Err(violation)
+    }
+}
+
+fn after(start: i64, seconds: i64) -> Result<i64, Violation> {
+    start.checked_add(seconds).ok_or(Violation::Overflow)
+}
+
+/// Deposit deadline of an escrow created at `now`.
+pub fn deposit_deadline(now: i64) -> Result<i64, Violation> {
+    after(now, DEPOSIT_DEADLINE_MINUTES * SECONDS_PER_MINUTE)
+}
+
+/// Fiat deadline of an escrow funded at `now`.
+pub fn fiat_deadline(now: i64) -> Result<i64, Violation> {
+    after(now, FIAT_DEADLINE_MINUTES * SECONDS_PER_MINUTE)
+}
+
+/// Last second the other party may answer a dispute opened at `opened`.
+pub fn response_deadline(opened: i64) -> Result<i64, Violation> {
+    after(opened, DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR)
+}
+
+/// Checks whether `caller` may take `action` on `escrow` at `now`, and returns the
+/// state the escrow ends in. Guards run in the order the program has always checked
+/// them, so a call breaking several rules reports the same error it used to; the
+/// handlers' account checks come after them.
+///
+/// `Create` is always refused: it has no escrow to check, see [`check_terms`].
+pub fn check(action: Action, caller: Caller, escrow: &Facts, now: i64) -> Result<State, Violation> {
+    let state = escrow.state;
+    match action {
+        Action::Create => return Err(Violation::InvalidState),
+        Action::Fund => {
+            require(state == State::Created, Violation::InvalidState)?;
+            require(
+                now <= escrow.deposit_deadline,
+                Violation::DepositDeadlineExpired,
+            )?;
+            require(caller.seller, Violation::Unauthorized)?;
+            require(
+                !escrow.requires_acceptance || escrow.accepted,
+                Violation::AcceptanceRequired,
+            )?;
+        }
+        Action::Accept => {
+            require(state == State::Created, Violation::InvalidState)?;
+            require(caller.buyer, Violation::Unauthorized)?;
+            require(
+                escrow.requires_acceptance && !escrow.accepted,
+                Violation::InvalidState,
+            )?;
+            require(
+                now <= escrow.deposit_deadline,
+                Violation::DepositDeadlineExpired,
+            )?;
+        }
+        Action::MarkFiatPaid => {
+            require(state == State::Funded, Violation::InvalidState)?;
+            require(caller.buyer, Violation::Unauthorized)?;
+            require(now <= escrow.fiat_deadline, Violation::FiatDeadlineExpired)?;
+            require(
+                !escrow.requires_buyer_deposit || escrow.buyer_deposit_posted,
+                Violation::BuyerDepositRequired,
+            )?;
+        }
+        // Only once funded, so the deposit is in the vault's mint
+        Action::PostBuyerDeposit => {
+            require(state == State::Funded, Violation::InvalidState)?;
+            require(caller.buyer, Violation::Unauthorized)?;
+            require(
+                !escrow.fiat_paid && !escrow.buyer_deposit_posted,
+                Violation::InvalidState,
+            )?;
+            require(now <= escrow.fiat_deadline, Violation::FiatDeadlineExpired)?;
+        }
+        Action::UpdateSequentialAddress => {
+            require(escrow.sequential, Violation::InvalidState)?;
+            require(caller.buyer, Violation::Unauthorized)?;
+            require(!state.is_terminal(), Violation::TerminalState)?;
+        }
+        Action::Release => {
+            require(state == State::Funded, Violation::InvalidState)?;
+            require(caller.seller || caller.arbitrator, Violation::Unauthorized)?;
+            require(escrow.fiat_paid, Violation::InvalidState)?;
+            if escrow.sequential {
+                require(
+                    escrow.has_sequential_address,
+                    Violation::MissingSequentialAddress,
+                )?;
+            }
+        }
+        Action::Cancel => {
+            require(
+                state == State::Created || state == State::Funded,
+                Violation::InvalidState,
+            )?;
+            require(caller.seller || caller.arbitrator, Violation::Unauthorized)?;
+            require(!escrow.fiat_paid, Violation::InvalidState)?;
+        }
+        // Initializing a bond account only needs the escrow to exist
+        Action::InitializeBuyerBond | Action::InitializeSellerBond => {
+            require(!state.is_terminal(), Violation::TerminalState)?;
+        }
+        Action::OpenDispute => {
+            require(state == State::Funded, Violation::InvalidState)?;
+            require(escrow.fiat_paid, Violation::InvalidState)?;
+            require(caller.is_party(), Violation::Unauthorized)?;
+        }
+        Action::RespondToDispute => {
+            require(state == State::Disputed, Violation::InvalidState)?;
+            let initiator = escrow.dispute_initiator.ok_or(Violation::InvalidState)?;
+            let responder = match initiator {
+                Party::Buyer => caller.seller && !caller.buyer,
+                Party::Seller => caller.buyer && !caller.seller,
+            };
+            require(responder, Violation::Unauthorized)?;
+            let opened = escrow
+                .dispute_initiated_time
+                .ok_or(Violation::InvalidState)?;
+            require(
+                now <= response_deadline(opened)?,
+                Violation::ResponseDeadlineExpired,
+            )?;
+            let answered = match initiator.other() {
+                Party::Buyer => escrow.buyer_evidence,
+                Party::Seller => escrow.seller_evidence,
+            };
+            require(!answered, Violation::DuplicateEvidence)?;
+        }
+        Action::DefaultJudgment => {
+            require(state == State::Disputed, Violation::InvalidState)?;
+            default_winner(escrow, now)?;
+        }
+        Action::ResolveDispute => {
+            require(state == State::Disputed, Violation::InvalidState)?;
+            require(caller.arbitrator, Violation::Unauthorized)?;
+            require(
+                escrow.buyer_evidence && escrow.seller_evidence,
+                Violation::InvalidEvidenceHash,
+            )?;
+        }
+        Action::AutoCancel => {
+            require(caller.arbitrator, Violation::Unauthorized)?;
+            require(!state.is_terminal(), Violation::TerminalState)?;
+            // Disputes settle through resolution or default judgment; cancelling one
+            // would close the escrow with the vault and the posted bonds still holding
+            // funds
+            require(
+                state == State::Created || state == State::Funded,
+                Violation::InvalidState,
+            )?;
+            if state == State::Created {
+                require(now > escrow.deposit_deadline, Violation::InvalidState)?;
+            } else {
+                require(
+                    now > escrow.fiat_deadline && !escrow.fiat_paid,
+                    Violation::InvalidState,
+                )?;
+            }
+        }
+        // The checkpoint and the proof are checked by check_chat_checkpoint and
+        // verify_inclusion
+        Action::CommitChatRoot => {
+            require(!state.is_terminal(), Violation::TerminalState)?;
+            require(caller.is_party(), Violation::Unauthorized)?;
+        }
+        Action::ProveChatMessage => {
+            require(state == State::Disputed, Violation::InvalidState)?;
+            require(
+                caller.is_party() || caller.arbitrator,
+                Violation::Unauthorized,
+            )?;
+        }
+        // The registered keys are account checks, left to the handler
+        Action::PostPaymentDetails => {
+            require(
+                state == State::Created || state == State::Funded,
+                Violation::InvalidState,
+            )?;
+            require(caller.is_party(), Violation::Unauthorized)?;
+        }
+    }
+    let to = transition(action, Some(state))
+        .map(|row| row.to)
+        .ok_or(Violation::InvalidState)?;
+    Ok(to)
+}
+
+/// Party a default judgment rules for: the initiator, once the response deadline has
+/// passed without the other party answering.
+pub fn default_winner(escrow: &Facts, now: i64) -> Result<Party, Violation> {
+    let initiator = escrow.dispute_initiator.ok_or(Violation::InvalidState)?;
+    let opened = escrow
+        .dispute_initiated_time
+        .ok_or(Violation::InvalidState)?;
+    require(now > response_deadline(opened)?, Violation::InvalidState)?;
+    let answered = match initiator.other() {
+        Party::Buyer => escrow.buyer_evidence,
+        Party::Seller => escrow.seller_evidence,
+    };
+    require(!answered, Violation::InvalidState)?;
+    Ok(initiator)
+}
+
+/// Rounding of a basis-point share to whole base units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// Round half to even (banker's rounding).
+    HalfEven,
+}
+
+/// `amount * basis_points / 10_000`, rounded.
+pub fn basis_points(amount: u64, basis_points: u64, rounding: Rounding) -> Result<u64, Violation> {
+    let scaled = amount
+        .checked_mul(basis_points)
+        .ok_or(Violation::Overflow)?;
+    let (quotient, remainder) = (scaled / 10_000, scaled % 10_000);
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => remainder > 0,
+        Rounding::HalfEven => remainder > 5_000 || (remainder == 5_000 && quotient % 2 == 1),
+    };
+    Ok(quotient + u64::from(round_up))
+}
+
+/// A fee or bond and how it was arrived at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Charge {
+    pub value: u64,
+    pub rounding: Rounding,
+    /// The rounded share fell below the minimum, which was charged instead.
+    pub minimum_applied: bool,
+}
+
+/// Floors and rounding of fees and bonds. The arbitrator sets them in the program's
+/// config account, and each escrow keeps the policy it was created under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Smallest fee charged, in base units.
+    pub min_fee: u64,
+    /// Smallest dispute bond, in base units.
+    pub min_dispute_bond: u64,
+    pub rounding: Rounding,
+}
+
+impl FeePolicy {
+    /// Applies until the arbitrator configures a policy, and to escrows created before
+    /// the policy was recorded on them.
+    pub const DEFAULT: FeePolicy = FeePolicy {
+        min_fee: MIN_FEE,
+        min_dispute_bond: MIN_DISPUTE_BOND,
+        rounding: ROUNDING,
+    };
+
+    /// Neither floor may exceed the largest principal.
+    pub fn check(&self) -> Result<(), Violation> {
+        require(
+            self.min_fee <= MAX_AMOUNT && self.min_dispute_bond <= MAX_AMOUNT,
+            Violation::InvalidFeePolicy,
+        )
+    }
+}
+
+fn charge(amount: u64, rate: u64, minimum: u64, rounding: Rounding) -> Result<Charge, Violation> {
+    let share = basis_points(amount, rate, rounding)?;
+    Ok(Charge {
+        value: share.max(minimum),
+        rounding,
+        minimum_applied: share < minimum,
+    })
+}
+
+/// Fee charged on top of `amount` under `policy`, with its rounding.
+pub fn fee_charge(amount: u64, policy: FeePolicy) -> Result<Charge, Violation> {
+    charge(amount, FEE_BASIS_POINTS, policy.min_fee, policy.rounding)
+}
+
+/// Bond each party posts to dispute an escrow of `amount` under `policy`, with its rounding.
+pub fn dispute_bond_charge(amount: u64, policy: FeePolicy) -> Result<Charge, Violation> {
+    charge(
+        amount,
+        DISPUTE_BOND_BASIS_POINTS,
+        policy.min_dispute_bond,
+        policy.rounding,
+    )
+}
+
+/// Fee charged on top of `amount` under `policy`.
+pub fn fee(amount: u64, policy: FeePolicy) -> Result<u64, Violation> {
+    fee_charge(amount, policy).map(|charge| charge.value)
+}
+
+/// Bond each party posts to dispute an escrow of `amount` under `policy`.
+pub fn dispute_bond(amount: u64, policy: FeePolicy) -> Result<u64, Violation> {
+    dispute_bond_charge(amount, policy).map(|charge| charge.value)
+}
+
+/// Deposit a buyer posts on an escrow of `amount`, rounded by `policy`, with its rounding.
+pub fn buyer_deposit_charge(amount: u64, policy: FeePolicy) -> Result<Charge, Violation> {
+    charge(
+        amount,
+        BUYER_DEPOSIT_BASIS_POINTS,
+        MIN_BUYER_DEPOSIT,
+        policy.rounding,
+    )
+}
+
+/// Deposit a buyer posts on an escrow of `amount`, rounded by `policy`.
+pub fn buyer_deposit(amount: u64, policy: FeePolicy) -> Result<u64, Violation> {
+    buyer_deposit_charge(amount, policy).map(|charge| charge.value)
+}
+
+/// Penalty a seller pays the buyer for cancelling a committed escrow of `amount` and `fee`,
+/// rounded by `policy`.
+pub fn cancellation_penalty(amount: u64, fee: u64, policy: FeePolicy) -> Result<u64, Violation> {
+    Ok(basis_points(amount, SELLER_CANCEL_PENALTY_BASIS_POINTS, policy.rounding)?.min(fee))
+}
+
+/// What a funded escrow's vault holds: principal plus fee.
+pub fn vault_total(amount: u64, fee: u64) -> Result<u64, Violation> {
+    amount.checked_add(fee).ok_or(Violation::Overflow)
+}
+
+/// How a settlement ends an escrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Settlement {
+    /// Release after fiat was paid.
+    Release,
+    /// Cancellation or auto-cancellation of a funded escrow.
+    Refund,
+    /// The seller cancelling a funded escrow the buyer accepted, see [`cancellation`].
+    SellerCancel,
+    DefaultJudgment {
+        winner: Party,
+    },
+    Resolution {
+        for_buyer: bool,
+    },
+}
+
+impl Settlement {
+    /// Whether a posted buyer deposit goes to the seller rather than back to the buyer:
+    /// only when the buyer let the fiat deadline pass unpaid (`expired`, a refund by
+    /// `auto_cancel`). Every other settlement, disputes included, returns it.
+    pub fn forfeits_buyer_deposit(self, expired: bool) -> bool {
+        match self {
+            Settlement::Refund => expired,
+            Settlement::Release
+            | Settlement::SellerCancel
+            | Settlement::DefaultJudgment { .. }
+            | Settlement::Resolution { .. } => false,
+        }
+    }
+}
+
+/// How `cancel_escrow` signed by `caller` settles a funded escrow. A seller backing out
+/// after the buyer committed by accepting pays the cancellation penalty; arbitrator
+/// cancellations never do.
+pub fn cancellation(escrow: &Facts, caller: Caller) -> Settlement {
+    if escrow.state == State::Funded && escrow.accepted && caller.seller && !caller.arbitrator {
+        Settlement::SellerCancel
+    } else {
+        Settlement::Refund
+    }
+}
+
+/// Token movements of a settlement, in base units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Payout {
+    /// Out of the vault to the arbitrator.
+    pub to_arbitrator: u64,
+    /// Out of the vault to the buyer, or to the next escrow of a sequential release.
+    pub to_buyer: u64,
+    /// Out of the vault to the seller.
+    pub to_seller: u64,
+    /// Bond returned to the party who posted it.
+    pub bond_returned: u64,
+    /// Bond forfeited to the arbitrator.
+    pub bond_forfeited: u64,
+}
+
+impl Payout {
+    /// Everything paid out of the vault, which empties it.
+    pub fn from_vault(&self) -> u64 {
+        self.to_arbitrator + self.to_buyer + self.to_seller
+    }
+}
+
+/// Splits a settlement of an escrow with `amount` principal and `fee`, created under
+/// `policy`. `bond` is what each party posted to the dispute, which the bond settlements
+/// pay out whatever the policy would charge today.
+pub fn payout(
+    settlement: Settlement,
+    amount: u64,
+    fee: u64,
+    bond: u64,
+    policy: FeePolicy,
+) -> Result<Payout, Violation> {
+    let total = vault_total(amount, fee)?;
+    let payout = match settlement {
+        Settlement::Release => Payout {
+            to_arbitrator: fee,
+            to_buyer: amount,
+            ..Payout::default()
+        },
+        Settlement::Refund => Payout {
+            to_seller: total,
+            ..Payout::default()
+        },
+        Settlement::SellerCancel => {
+            let penalty = cancellation_penalty(amount, fee, policy)?;
+            Payout {
+                to_buyer: penalty,
+                to_seller: total - penalty,
+                ..Payout::default()
+            }
+        }
+        Settlement::DefaultJudgment {
+            winner: Party::Buyer,
+        } => Payout {
+            to_buyer: total,
+            bond_returned: bond,
+            ..Payout::default()
+        },
+        Settlement::DefaultJudgment {
+            winner: Party::Seller,
+        } => Payout {
+            to_seller: total,
+            bond_returned: bond,
+            ..Payout::default()
+        },
+        Settlement::Resolution { for_buyer: true } => Payout {
+            to_arbitrator: fee,
+            to_buyer: amount,
+            bond_returned: bond,
+            bond_forfeited: bond,
+            ..Payout::default()
+        },
+        Settlement::Resolution { for_buyer: false } => Payout {
+            to_seller: total,
+            bond_returned: bond,
+            bond_forfeited: bond,
+            ..Payout::default()
+        },
+    };
+    Ok(payout)
+}
+
+/// Where an escrow stands, named after the frontend trade states in
+/// `docs/state-ref-escrows-trades.md`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Created,
+    AwaitingFiatPayment,
+    PendingCryptoRelease,
+    Completed,
+    Cancelled,
+    Disputed,
+    Resolved,
+    Accepted,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 8] = [
+        Phase::Created,
+        Phase::AwaitingFiatPayment,
+        Phase::PendingCryptoRelease,
+        Phase::Completed,
+        Phase::Cancelled,
+        Phase::Disputed,
+        Phase::Resolved,
+        Phase::Accepted,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Created => "CREATED",
+            Phase::AwaitingFiatPayment => "AWAITING_FIAT_PAYMENT",
+            Phase::PendingCryptoRelease => "PENDING_CRYPTO_RELEASE",
+            Phase::Completed => "COMPLETED",
+            Phase::Cancelled => "CANCELLED",
+            Phase::Disputed => "DISPUTED",
+            Phase::Resolved => "RESOLVED",
+            Phase::Accepted => "ACCEPTED",
+        }
+    }
+}
+
+pub fn phase(escrow: &Facts) -> Phase {
+    match escrow.state {
+        State::Created if escrow.accepted => Phase::Accepted,
+        State::Created => Phase::Created,
+        State::Funded if escrow.fiat_paid => Phase::PendingCryptoRelease,
+        State::Funded => Phase::AwaitingFiatPayment,
+        State::Released => Phase::Completed,
+        State::Cancelled => Phase::Cancelled,
+        State::Disputed => Phase::Disputed,
+        State::Resolved => Phase::Resolved,
+    }
+}
+
+/// Mask of the actions a signer holding only `role` may take at `now`, one
+/// [`Action::bit`] each. Bond account setup is left out: it is open to anyone in every
+/// non-terminal state and moves nothing.
+pub fn allowed(role: Role, escrow: &Facts, now: i64) -> u32 {
+    Action::ALL
+        .into_iter()
+        .filter(|action| {
+            !matches!(
+                action,
+                Action::InitializeBuyerBond | Action::InitializeSellerBond
+            )
+        })
+        .filter(|action| check(*action, Caller::acting_as(role), escrow, now).is_ok())
+        .fold(0, |mask, action| mask | action.bit())
+}
+
+/// Seconds left until each deadline that is running, `0` once it has passed. A deadline
+/// runs while the party it binds has yet to act: the seller's deposit while the escrow
+/// is `Created`, the buyer's fiat payment while it is funded and unpaid, and the
+/// response to a dispute until the other party answers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Countdown {
+    pub deposit: Option<i64>,
+    pub fiat: Option<i64>,
+    pub response: Option<i64>,
+}
+
+pub fn countdown(escrow: &Facts, now: i64) -> Result<Countdown, Violation> {
+    let left = |deadline: i64| deadline.saturating_sub(now).max(0);
+    let mut countdown = Countdown::default();
+    match escrow.state {
+        State::Created => countdown.deposit = Some(left(escrow.deposit_deadline)),
+        State::Funded if !escrow.fiat_paid => countdown.fiat = Some(left(escrow.fiat_deadline)),
+        State::Disputed => {
+            let answered = match escrow.dispute_initiator.map(Party::other) {
+                Some(Party::Buyer) => escrow.buyer_evidence,
+                Some(Party::Seller) => escrow.seller_evidence,
+                None => true,
+            };
+            if let (false, Some(opened)) = (answered, escrow.dispute_initiated_time) {
+                countdown.response = Some(left(response_deadline(opened)?));
+            }
+        }
+        _ => {}
+    }
+    Ok(countdown)
+}
+
+/// Mermaid state diagram of [`TRANSITIONS`], one labelled edge per state pair.
+pub fn mermaid() -> String {
+    let mut edges: Vec<(Option<State>, State, Vec<String>)> = Vec::new();
+    for row in TRANSITIONS {
+        let by: Vec<&str> = row.by.iter().map(|role| role.name()).collect();
+        let mut label = format!("{} by {}", row.action.instruction(), by.join(" or "));
+        if !row.guard.is_empty() {
+            let _ = write!(label, ", {}", row.guard);
+        }
+        match edges
+            .iter_mut()
+            .find(|(from, to, _)| *from == row.from && *to == row.to)
+        {
+            Some((_, _, labels)) => labels.push(label),
+            None => edges.push((row.from, row.to, vec![label])),
+        }
+    }
+
+    let mut out = String::from("stateDiagram-v2\n");
+    for (from, to, labels) in edges {
+        let from = from.map_or("[*]", State::name);
+        let _ = writeln!(
+            out,
+            "    {from} --> {}: {}",
+            to.name(),
+            labels.join("<br/>")
+        );
+    }
+    for state in State::ALL.iter().filter(|state| state.is_terminal()) {
+        let _ = writeln!(out, "    {} --> [*]", state.name());
+    }
+    out
+}
+
+/// The committed `state_machine.md`: the diagram wrapped in a short page.
+pub fn diagram_page() -> String {
+    format!(
+        "# Escrow state machine\n\n\
+         Generated from `TRANSITIONS` in `state_machine.rs`; do not edit by hand.\n\
+         Regenerate with `cargo run -p localsolana-contracts-client --example state_diagram`.\n\n\
+         Settling an escrow closes its account, so terminal states are final.\n\n\
+         ```mermaid\n{}```\n",
+        mermaid()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const RESPONSE_WINDOW: i64 = DISPUTE_RESPONSE_DEADLINE_HOURS * SECONDS_PER_HOUR;
+
+    fn facts(state: State) -> Facts {
+        Facts {
+            state,
+            fiat_paid: false,
+            requires_acceptance: false,
+            accepted: false,
+            buyer_deposit_posted: false,
+            requires_buyer_deposit: false,
+            sequential: false,
+            has_sequential_address: false,
+            deposit_deadline: NOW + 900,
+            fiat_deadline: NOW + 1_800,
+            dispute_initiator: None,
+            dispute_initiated_time: None,
+            buyer_evidence: false,
+            seller_evidence: false,
+        }
+    }
+
+    // Escrows that satisfy every non-state guard of some action, so enumeration reaches
+    // each row of the table
+    fn favourable(state: State) -> Vec<(Facts, i64)> {
+        let mut escrows = Vec::new();
+        for (fiat_paid, accepted) in [(false, false), (true, true)] {
+            for initiator in [Party::Buyer, Party::Seller] {
+                for answered in [false, true] {
+                    let escrow = Facts {
+                        fiat_paid,
+                        requires_acceptance: true,
+                        accepted,
+                        sequential: true,
+                        has_sequential_address: true,
+                        dispute_initiator: Some(initiator),
+                        dispute_initiated_time: Some(NOW),
+                        buyer_evidence: initiator == Party::Buyer || answered,
+                        seller_evidence: initiator == Party::Seller || answered,
+                        ..facts(state)
+                    };
+                    for now in [NOW, NOW + RESPONSE_WINDOW + 1] {
+                        escrows.push((escrow, now));
+                    }
+                }
+            }
+        }
+        escrows
+    }
+
+    #[test]
+    fn every_accepted_call_matches_a_row_of_the_table() {
+        let mut reached = Vec::new();
+        for action in Action::ALL {
+            for state in State::ALL {
+                for role in Role::ALL {
+                    for (escrow, now) in favourable(state) {
+                        if let Ok(to) = check(action, Caller::acting_as(role), &escrow, now) {
+                            let row = transition(action, Some(state)).unwrap();
+                            assert_eq!(row.to, to);
+                            assert!(row.by.contains(&role) || row.by == ANYONE, "{row:?}");
+                            reached.push(row);
+                        }
+                    }
+                }
+            }
+        }
+        for row in TRANSITIONS.iter().filter(|row| row.from.is_some()) {
+            assert!(reached.contains(&row), "unreachable row {row:?}");
+        }
+    }
+
+    #[test]
+    fn terminal_states_have_no_way_out() {
+        assert!(TRANSITIONS
+            .iter()
+            .all(|row| row.from.is_none_or(|from| !from.is_terminal())));
+        for action in Action::ALL {
+            for state in State::ALL.into_iter().filter(|state| state.is_terminal()) {
+                for (escrow, now) in favourable(state) {
+                    let caller = Caller {
+                        seller: true,
+                        buyer: true,
+                        arbitrator: true,
+                    };
+                    assert!(check(action, caller, &escrow, now).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn table_has_one_row_per_action_and_state() {
+        for (index, row) in TRANSITIONS.iter().enumerate() {
+            assert!(TRANSITIONS[..index]
+                .iter()
+                .all(|earlier| (earlier.action, earlier.from) != (row.action, row.from)));
+        }
+    }
+
+    #[test]
+    fn guards_keep_the_programs_error_order() {
+        let stranger = Caller::default();
+        let created = facts(State::Created);
+        assert_eq!(
+            check(Action::Fund, stranger, &created, NOW + 901),
+            Err(Violation::DepositDeadlineExpired)
+        );
+        assert_eq!(
+            check(Action::Fund, stranger, &created, NOW),
+            Err(Violation::Unauthorized)
+        );
+
+        let funded = facts(State::Funded);
+        assert_eq!(
+            check(Action::Release, stranger, &funded, NOW),
+            Err(Violation::Unauthorized)
+        );
+        assert_eq!(
+            check(
+                Action::Release,
+                Caller::acting_as(Role::Seller),
+                &funded,
+                NOW
+            ),
+            Err(Violation::InvalidState)
+        );
+        let sequential = Facts {
+            fiat_paid: true,
+            sequential: true,
+            ..funded
+        };
+        assert_eq!(
+            check(
+                Action::Release,
+                Caller::acting_as(Role::Seller),
+                &sequential,
+                NOW
+            ),
+            Err(Violation::MissingSequentialAddress)
+        );
+        assert_eq!(
+            check(Action::UpdateSequentialAddress, stranger, &funded, NOW),
+            Err(Violation::InvalidState)
+        );
+        assert_eq!(
+            check(Action::AutoCancel, stranger, &facts(State::Released), NOW),
+            Err(Violation::Unauthorized)
+        );
+        assert_eq!(
+            check(
+                Action::AutoCancel,
+                Caller::acting_as(Role::Arbitrator),
+                &facts(State::Released),
+                NOW
+            ),
+            Err(Violation::TerminalState)
+        );
+    }
+
+    #[test]
+    fn deadlines_are_inclusive_for_the_actor_and_exclusive_for_the_cleanup() {
+        let created = facts(State::Created);
+        let seller = Caller::acting_as(Role::Seller);
+        let arbitrator = Caller::acting_as(Role::Arbitrator);
+        assert!(check(Action::Fund, seller, &created, NOW + 900).is_ok());
+        assert!(check(Action::AutoCancel, arbitrator, &created, NOW + 900).is_err());
+        assert!(check(Action::AutoCancel, arbitrator, &created, NOW + 901).is_ok());
+
+        let funded = facts(State::Funded);
+        let buyer = Caller::acting_as(Role::Buyer);
+        assert!(check(Action::MarkFiatPaid, buyer, &funded, NOW + 1_800).is_ok());
+        assert_eq!(
+            check(Action::MarkFiatPaid, buyer, &funded, NOW + 1_801),
+            Err(Violation::FiatDeadlineExpired)
+        );
+        assert!(check(Action::AutoCancel, arbitrator, &funded, NOW + 1_801).is_ok());
+        let paid = Facts {
+            fiat_paid: true,
+            ..funded
+        };
+        assert!(check(Action::AutoCancel, arbitrator, &paid, NOW + 1_801).is_err());
+    }
+
+    #[test]
+    fn funding_waits_for_the_buyer_only_when_acceptance_is_required() {
+        let seller = Caller::acting_as(Role::Seller);
+        let buyer = Caller::acting_as(Role::Buyer);
+        let open = facts(State::Created);
+        assert_eq!(check(Action::Fund, seller, &open, NOW), Ok(State::Funded));
+        assert_eq!(
+            check(Action::Accept, buyer, &open, NOW),
+            Err(Violation::InvalidState)
+        );
+
+        let pending = Facts {
+            requires_acceptance: true,
+            ..open
+        };
+        assert_eq!(
+            check(Action::Fund, seller, &pending, NOW),
+            Err(Violation::AcceptanceRequired)
+        );
+        assert_eq!(
+            check(Action::Accept, seller, &pending, NOW),
+            Err(Violation::Unauthorized)
+        );
+        assert_eq!(
+            check(Action::Accept, buyer, &pending, NOW + 901),
+            Err(Violation::DepositDeadlineExpired)
+        );
+        assert_eq!(
+            check(Action::Accept, buyer, &pending, NOW),
+            Ok(State::Created)
+        );
+        assert_eq!(phase(&pending), Phase::Created);
+
+        let accepted = Facts {
+            accepted: true,
+            ..pending
+        };
+        assert_eq!(
+            check(Action::Fund, seller, &accepted, NOW),
+            Ok(State::Funded)
+        );
+        assert_eq!(
+            check(Action::Accept, buyer, &accepted, NOW),
+            Err(Violation::InvalidState)
+        );
+        assert_eq!(phase(&accepted).name(), "ACCEPTED");
+        assert_eq!(
+            Action::from_mask(allowed(Role::Buyer, &pending, NOW)),
+            [
+                Action::PostPaymentDetails,
+                Action::CommitChatRoot,
+                Action::Accept
+            ]
+        );
+    }
+
+    #[test]
+    fn buyer_deposit_is_posted_once_before_paying_and_lost_only_by_default() {
+        let buyer = Caller::acting_as(Role::Buyer);
+        let funded = facts(State::Funded);
+        assert_eq!(
+            check(Action::PostBuyerDeposit, buyer, &facts(State::Created), NOW),
+            Err(Violation::InvalidState)
+        );
+        assert_eq!(
+            check(
+                Action::PostBuyerDeposit,
+                Caller::acting_as(Role::Seller),
+                &funded,
+                NOW
+            ),
+            Err(Violation::Unauthorized)
+        );
+        assert_eq!(
+            check(Action::PostBuyerDeposit, buyer, &funded, NOW + 1_801),
+            Err(Violation::FiatDeadlineExpired)
+        );
+        assert_eq!(
+            check(Action::PostBuyerDeposit, buyer, &funded, NOW),
+            Ok(State::Funded)
+        );
+        for escrow in [
+            Facts {
+                fiat_paid: true,
+                ..funded
+            },
+            Facts {
+                buyer_deposit_posted: true,
+                ..funded
+            },
+        ] {
+            assert_eq!(
+                check(Action::PostBuyerDeposit, buyer, &escrow, NOW),
+                Err(Violation::InvalidState)
+            );
+        }
+
+        let policy = FeePolicy::DEFAULT;
+        assert_eq!(buyer_deposit(10_000_000, policy), Ok(200_000));
+        assert_eq!(buyer_deposit(1, policy), Ok(MIN_BUYER_DEPOSIT));
+        assert!(!buyer_deposit_charge(1_000_000, policy).unwrap().minimum_applied);
+
+        // only an unpaid expiry forfeits it; disputes return it whoever wins
+        assert!(!Settlement::Release.forfeits_buyer_deposit(false));
+        assert!(!Settlement::Refund.forfeits_buyer_deposit(false));
+        assert!(Settlement::Refund.forfeits_buyer_deposit(true));
+        assert!(!Settlement::SellerCancel.forfeits_buyer_deposit(false));
+        let seller_wins = Settlement::DefaultJudgment {
+            winner: Party::Seller,
+        };
+        assert!(!seller_wins.forfeits_buyer_deposit(false));
+        assert!(!Settlement::Resolution { for_buyer: true }.forfeits_buyer_deposit(false));
+        assert!(!Settlement::Resolution { for_buyer: false }.forfeits_buyer_deposit(false));
+    }
+
+    #[test]
+    fn paying_waits_for_the_deposit_only_when_the_seller_requires_it() {
+        let buyer = Caller::acting_as(Role::Buyer);
+        let arbitrator = Caller::acting_as(Role::Arbitrator);
+        let required = Facts {
+            requires_buyer_deposit: true,
+            ..facts(State::Funded)
+        };
+        assert_eq!(
+            check(Action::MarkFiatPaid, buyer, &required, NOW),
+            Err(Violation::BuyerDepositRequired)
+        );
+        assert_eq!(
+            Action::from_mask(allowed(Role::Buyer, &required, NOW)),
+            [
+                Action::PostPaymentDetails,
+                Action::CommitChatRoot,
+                Action::PostBuyerDeposit
+            ]
+        );
+
+        let posted = Facts {
+            buyer_deposit_posted: true,
+            ..required
+        };
+        assert_eq!(
+            check(Action::MarkFiatPaid, buyer, &posted, NOW),
+            Ok(State::Funded)
+        );
+        // a buyer who posts and then walks away loses it to auto_cancel
+        assert!(check(Action::AutoCancel, arbitrator, &posted, NOW + 1_801).is_ok());
+        assert!(Settlement::Refund.forfeits_buyer_deposit(true));
+    }
+
+    #[test]
+    fn sellers_pay_for_cancelling_only_after_the_buyer_accepted() {
+        let seller = Caller::acting_as(Role::Seller);
+        let arbitrator = Caller::acting_as(Role::Arbitrator);
+        let accepted = Facts {
+            requires_acceptance: true,
+            accepted: true,
+            ..facts(State::Funded)
+        };
+        assert_eq!(cancellation(&accepted, seller), Settlement::SellerCancel);
+        assert_eq!(cancellation(&accepted, arbitrator), Settlement::Refund);
+        assert_eq!(
+            cancellation(&facts(State::Funded), seller),
+            Settlement::Refund
+        );
+        let unfunded = Facts {
+            state: State::Created,
+            ..accepted
+        };
+        assert_eq!(cancellation(&unfunded, seller), Settlement::Refund);
+
+        // half the fee of a 10 USDC trade, and never more than the fee
+        let policy = FeePolicy::DEFAULT;
+        let penalized = payout(Settlement::SellerCancel, 10_000_000, 100_000, 0, policy).unwrap();
+        assert_eq!(
+            (penalized.to_buyer, penalized.to_seller),
+            (50_000, 10_050_000)
+        );
+        assert_eq!(
+            cancellation_penalty(1_000_000, fee(1_000_000, policy).unwrap(), policy),
+            Ok(5_000)
+        );
+        assert_eq!(cancellation_penalty(10_000_000, 1, policy), Ok(1));
+    }
+
+    #[test]
+    fn disputes_need_the_other_party_or_the_deadline() {
+        let disputed = Facts {
+            dispute_initiator: Some(Party::Buyer),
+            dispute_initiated_time: Some(NOW),
+            buyer_evidence: true,
+            ..facts(State::Disputed)
+        };
+        let deadline = NOW + RESPONSE_WINDOW;
+        let seller = Caller::acting_as(Role::Seller);
+        assert_eq!(
+            check(
+                Action::RespondToDispute,
+                Caller::acting_as(Role::Buyer),
+                &disputed,
+                NOW
+            ),
+            Err(Violation::Unauthorized)
+        );
+        assert!(check(Action::RespondToDispute, seller, &disputed, deadline).is_ok());
+        assert_eq!(
+            check(Action::RespondToDispute, seller, &disputed, deadline + 1),
+            Err(Violation::ResponseDeadlineExpired)
+        );
+
+        assert_eq!(
+            default_winner(&disputed, deadline),
+            Err(Violation::InvalidState)
+        );
+        assert_eq!(default_winner(&disputed, deadline + 1), Ok(Party::Buyer));
+        assert_eq!(
+            check(
+                Action::ResolveDispute,
+                Caller::acting_as(Role::Arbitrator),
+                &disputed,
+                NOW
+            ),
+            Err(Violation::InvalidEvidenceHash)
+        );
+
+        let answered = Facts {
+            seller_evidence: true,
+            ..disputed
+        };
+        assert_eq!(
+            check(Action::RespondToDispute, seller, &answered, NOW),
+            Err(Violation::DuplicateEvidence)
+        );
+        assert_eq!(
+            default_winner(&answered, deadline + 1),
+            Err(Violation::InvalidState)
+        );
+        assert_eq!(
+            check(
+                Action::ResolveDispute,
+                Caller::acting_as(Role::Arbitrator),
+                &answered,
+                NOW
+            ),
+            Ok(State::Resolved)
+        );
+    }
+
+    #[test]
+    fn terms_are_validated_in_order() {
+        assert_eq!(check_terms(0, true, false), Err(Violation::InvalidAmount));
+        assert_eq!(
+            check_terms(MAX_AMOUNT + 1, false, false),
+            Err(Violation::ExceedsMaximum)
+        );
+        assert_eq!(
+            check_terms(MAX_AMOUNT, true, false),
+            Err(Violation::MissingSequentialAddress)
+        );
+        assert_eq!(check_terms(1, false, false), Ok(()));
+    }
+
+    #[test]
+    fn rates_are_checked_against_fresh_precise_oracle_prices() {
+        let band = RateBand {
+            max_deviation_bps: 300,
+            max_age_seconds: 60,
+            max_confidence_bps: 100,
+        };
+        // 0.92 EUR per USDC with Pyth's usual exponent
+        let oracle = OraclePrice {
+            price: 92_000_000,
+            confidence: 50_000,
+            exponent: -8,
+            publish_time: NOW - 10,
+        };
+        assert_eq!(
+            check_rate(92_000_000, oracle, band, NOW),
+            Ok(RateCheck {
+                oracle_rate: 92_000_000,
+                deviation_bps: 0
+            })
+        );
+        // 3% either side is accepted, more is not
+        assert_eq!(
+            check_rate(94_760_000, oracle, band, NOW).map(|c| c.deviation_bps),
+            Ok(300)
+        );
+        assert_eq!(
+            check_rate(89_240_000, oracle, band, NOW).map(|c| c.deviation_bps),
+            Ok(300)
+        );
+        assert_eq!(
+            check_rate(94_770_000, oracle, band, NOW),
+            Err(Violation::RateOutOfBand)
+        );
+        // other exponents are rescaled to FIAT_RATE_DECIMALS
+        let coarse = OraclePrice {
+            price: 92,
+            confidence: 0,
+            exponent: -2,
+            ..oracle
+        };
+        assert_eq!(
+            check_rate(92_000_000, coarse, band, NOW).map(|c| c.oracle_rate),
+            Ok(92_000_000)
+        );
+
+        let stale = OraclePrice {
+            publish_time: NOW - 61,
+            ..oracle
+        };
+        assert_eq!(
+            check_rate(92_000_000, stale, band, NOW),
+            Err(Violation::StaleOraclePrice)
+        );
+        let uncertain = OraclePrice {
+            confidence: 920_001,
+            ..oracle
+        };
+        assert_eq!(
+            check_rate(92_000_000, uncertain, band, NOW),
+            Err(Violation::OraclePriceUncertain)
+        );
+        for broken in [
+            OraclePrice { price: 0, ..oracle },
+            OraclePrice {
+                price: -1,
+                ..oracle
+            },
+            OraclePrice {
+                exponent: -20,
+                ..oracle
+            },
+            OraclePrice {
+                exponent: 40,
+                ..oracle
+            },
+        ] {
+            assert_eq!(
+                check_rate(92_000_000, broken, band, NOW),
+                Err(Violation::InvalidOraclePrice)
+            );
+        }
+        assert_eq!(band.check(), Ok(()));
+        assert_eq!(
+            RateBand {
+                max_deviation_bps: 0,
+                ..band
+            }
+            .check(),
+            Err(Violation::InvalidRateBand)
+        );
+    }
+
+    #[test]
+    fn fiat_terms_need_a_currency_code_amount_and_rate() {
+        assert_eq!(check_fiat_terms(*b"EUR", 1_000, 92_000_000), Ok(()));
+        for (currency, fiat_amount, rate) in [
+            (*b"eur", 1_000, 92_000_000),
+            (*b"EU1", 1_000, 92_000_000),
+            ([0; 3], 1_000, 92_000_000),
+            (*b"EUR", 0, 92_000_000),
+            (*b"EUR", 1_000, 0),
+        ] {
+            assert_eq!(
+                check_fiat_terms(currency, fiat_amount, rate),
+                Err(Violation::InvalidFiatTerms)
+            );
+        }
+    }
+
+    #[test]
+    fn chat_checkpoints_grow_and_proofs_follow_rfc_6962() {
+        assert_eq!(check_chat_checkpoint([1; 32], 3, 0), Ok(()));
+        assert_eq!(check_chat_checkpoint([1; 32], 4, 3), Ok(()));
+        for (root, count, previous) in [([0; 32], 3, 0), ([1; 32], 0, 0), ([1; 32], 3, 3)] {
+            assert_eq!(
+                check_chat_checkpoint(root, count, previous),
+                Err(Violation::InvalidChatRoot)
+            );
+        }
+
+        // any deterministic, order-sensitive combination will do for the tree shape
+        let node = |left: &[u8; 32], right: &[u8; 32]| {
+            let mut out = [0u8; 32];
+            for i in 0..32 {
+                out[i] = left[i].wrapping_mul(3) ^ right[(i + 1) % 32].wrapping_add(7);
+            }
+            out
+        };
+        // three leaves: root = node(node(a, b), c)
+        let (a, b, c) = ([0xa; 32], [0xb; 32], [0xc; 32]);
+        let ab = node(&a, &b);
+        let root = node(&ab, &c);
+        assert_eq!(verify_inclusion(0, 3, a, &[b, c], &root, node), Ok(()));
+        assert_eq!(verify_inclusion(1, 3, b, &[a, c], &root, node), Ok(()));
+        assert_eq!(verify_inclusion(2, 3, c, &[ab], &root, node), Ok(()));
+        for (index, size, leaf, proof) in [
+            (2, 3, c, vec![ab, ab]),
+            (1, 3, a, vec![b, c]),
+            (3, 3, c, vec![ab]),
+            (0, 3, a, vec![b]),
+        ] {
+            assert_eq!(
+                verify_inclusion(index, size, leaf, &proof, &root, node),
+                Err(Violation::InvalidInclusionProof)
+            );
+        }
+        assert_eq!(verify_inclusion(0, 1, a, &[], &a, node), Ok(()));
+    }
+
+    #[test]
+    fn payouts_empty_the_vault_and_the_bonds() {
+        let amount = 10_000_000;
+        let policy = FeePolicy::DEFAULT;
+        let charged = fee(amount, policy).unwrap();
+        let bond = dispute_bond(amount, policy).unwrap();
+        assert_eq!((charged, bond), (100_000, 500_000));
+
+        let settlements = [
+            (Settlement::Release, 0),
+            (Settlement::Refund, 0),
+            (Settlement::SellerCancel, 0),
+            (
+                Settlement::DefaultJudgment {
+                    winner: Party::Buyer,
+                },
+                bond,
+            ),
+            (
+                Settlement::DefaultJudgment {
+                    winner: Party::Seller,
+                },
+                bond,
+            ),
+            (Settlement::Resolution { for_buyer: true }, 2 * bond),
+            (Settlement::Resolution { for_buyer: false }, 2 * bond),
+        ];
+        for (settlement, bonds) in settlements {
+            let payout = payout(settlement, amount, charged, bond, policy).unwrap();
+            assert_eq!(payout.from_vault(), amount + charged, "{settlement:?}");
+            assert_eq!(payout.bond_returned + payout.bond_forfeited, bonds);
+        }
+
+        let for_buyer = payout(
+            Settlement::Resolution { for_buyer: true },
+            amount,
+            charged,
+            bond,
+            policy,
+        );
+        assert_eq!(for_buyer.unwrap().to_arbitrator, charged);
+        assert_eq!(fee(u64::MAX, policy), Err(Violation::Overflow));
+        assert_eq!(vault_total(u64::MAX, 1), Err(Violation::Overflow));
+    }
+
+    #[test]
+    fn small_trades_pay_the_minimums_and_rounding_is_explicit() {
+        // 1% of 150 is 1.5 and 1% of 250 is 2.5
+        assert_eq!(basis_points(150, 100, Rounding::Floor), Ok(1));
+        assert_eq!(basis_points(150, 100, Rounding::Ceil), Ok(2));
+        assert_eq!(basis_points(150, 100, Rounding::HalfEven), Ok(2));
+        assert_eq!(basis_points(250, 100, Rounding::HalfEven), Ok(2));
+        assert_eq!(basis_points(251, 100, Rounding::HalfEven), Ok(3));
+        assert_eq!(basis_points(200, 100, Rounding::Ceil), Ok(2));
+
+        let policy = FeePolicy::DEFAULT;
+        let tiny = fee_charge(99, policy).unwrap();
+        assert_eq!(tiny.value, MIN_FEE);
+        assert!(tiny.minimum_applied);
+        assert_eq!(tiny.rounding, ROUNDING);
+        assert_eq!(dispute_bond(1, policy), Ok(MIN_DISPUTE_BOND));
+        assert!(dispute_bond_charge(1, policy).unwrap().minimum_applied);
+
+        // the minimums are the fee and bond of a 1 USDC trade, so they stop applying there
+        assert_eq!(fee_charge(1_000_000, policy).unwrap().value, MIN_FEE);
+        assert!(!fee_charge(1_000_000, policy).unwrap().minimum_applied);
+        assert!(!dispute_bond_charge(1_000_000, policy).unwrap().minimum_applied);
+
+        // payouts move the bond that was posted, even when today's floor is higher
+        let payout = payout(
+            Settlement::Resolution { for_buyer: true },
+            1,
+            fee(1, policy).unwrap(),
+            0,
+            policy,
+        );
+        let payout = payout.unwrap();
+        assert_eq!((payout.bond_returned, payout.bond_forfeited), (0, 0));
+    }
+
+    #[test]
+    fn the_configured_policy_sets_floors_and_rounding() {
+        let policy = FeePolicy {
+            min_fee: 0,
+            min_dispute_bond: 70_000,
+            rounding: Rounding::Ceil,
+        };
+        assert_eq!(policy.check(), Ok(()));
+
+        // 1% of 150 rounds up to 2, with no fee floor left to apply
+        let charged = fee_charge(150, policy).unwrap();
+        assert_eq!((charged.value, charged.rounding), (2, Rounding::Ceil));
+        assert!(!charged.minimum_applied);
+        assert_eq!(dispute_bond(1_000_000, policy), Ok(70_000));
+        assert_eq!(buyer_deposit(1_000_001, policy), Ok(20_001));
+        assert_eq!(cancellation_penalty(1_000_001, 10_000, policy), Ok(5_001));
+
+        let too_high = FeePolicy {
+            min_fee: MAX_AMOUNT + 1,
+            ..FeePolicy::DEFAULT
+        };
+        assert_eq!(too_high.check(), Err(Violation::InvalidFeePolicy));
+        assert_eq!(FeePolicy::DEFAULT.check(), Ok(()));
+    }
+
+    #[test]
+    fn status_names_the_phase_and_what_each_role_can_do_next() {
+        let funded = facts(State::Funded);
+        let paid = Facts {
+            fiat_paid: true,
+            ..funded
+        };
+        assert_eq!(phase(&funded).name(), "AWAITING_FIAT_PAYMENT");
+        assert_eq!(phase(&paid).name(), "PENDING_CRYPTO_RELEASE");
+        assert_eq!(phase(&facts(State::Released)), Phase::Completed);
+
+        assert_eq!(
+            Action::from_mask(allowed(Role::Buyer, &funded, NOW)),
+            [
+                Action::MarkFiatPaid,
+                Action::PostPaymentDetails,
+                Action::CommitChatRoot,
+                Action::PostBuyerDeposit
+            ]
+        );
+        assert_eq!(
+            Action::from_mask(allowed(Role::Seller, &paid, NOW)),
+            [
+                Action::Release,
+                Action::OpenDispute,
+                Action::PostPaymentDetails,
+                Action::CommitChatRoot
+            ]
+        );
+        assert_eq!(
+            Action::from_mask(allowed(Role::Arbitrator, &funded, NOW + 1_801)),
+            [Action::Cancel, Action::AutoCancel]
+        );
+        assert_eq!(allowed(Role::Other, &funded, NOW), 0);
+        for state in State::ALL.into_iter().filter(|state| state.is_terminal()) {
+            for role in Role::ALL {
+                assert_eq!(allowed(role, &facts(state), NOW), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn countdown_runs_only_for_the_party_that_has_to_act() {
+        let created = countdown(&facts(State::Created), NOW + 100).unwrap();
+        assert_eq!(
+            created,
+            Countdown {
+                deposit: Some(800),
+                ..Countdown::default()
+            }
+        );
+        let funded = countdown(&facts(State::Funded), NOW + 2_000).unwrap();
+        assert_eq!(funded.fiat, Some(0));
+        let paid = Facts {
+            fiat_paid: true,
+            ..facts(State::Funded)
+        };
+        assert_eq!(countdown(&paid, NOW).unwrap(), Countdown::default());
+
+        let disputed = Facts {
+            dispute_initiator: Some(Party::Seller),
+            dispute_initiated_time: Some(NOW),
+            seller_evidence: true,
+            ..facts(State::Disputed)
+        };
+        let open = countdown(&disputed, NOW + 60).unwrap();
+        assert_eq!(open.response, Some(RESPONSE_WINDOW - 60));
+        let answered = Facts {
+            buyer_evidence: true,
+            ..disputed
+        };
+        assert_eq!(countdown(&answered, NOW).unwrap(), Countdown::default());
+    }
+
+    #[test]
+    fn committed_diagram_is_current() {
+        assert_eq!(
+            include_str!("state_machine.md"),
+            diagram_page(),
+            "state_machine.md is stale; regenerate it"
+        );
+    }
+}
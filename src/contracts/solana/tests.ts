@@ -148,6 +148,21 @@ const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
 
 const generateRandomId = () => new BN(Math.floor(Math.random() * 1_000_000_000));
 
+// Fiat side committed on create_escrow: 1 USDC for 1.00 USD. No price feed is registered
+// for USD on the test validator, so no price update account is passed.
+const fiatTerms = {
+  currency: Array.from(Buffer.from("USD")),
+  fiatAmount: new BN(100),
+  rate: new BN(100_000_000),
+  paymentMethodHash: Array.from(Buffer.alloc(32, 1)),
+};
+
+// Must match EVENT_SCHEMA_VERSION in lib.rs
+const EVENT_SCHEMA_VERSION = 6;
+
+// Prefix of the self-CPI instruction data emit_cpi! uses to carry an event (anchor's EVENT_IX_TAG)
+const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex");
+
 describe("Localsolana Contracts Tests", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -155,10 +170,15 @@ describe("Localsolana Contracts Tests", () => {
   const expectedProgramId = new PublicKey("4PonUp1nPEzDPnRMPjTqufLT3f37QuBJGk1CVnsTXx7x");
 
   // Helper Functions
-  const deriveEscrowPDA = (escrowId: BN, tradeId: BN): [PublicKey, number] =>
+  const deriveEscrowPDA = (
+    escrowId: BN,
+    tradeId: BN,
+    escrowSeller: PublicKey = seller.publicKey
+  ): [PublicKey, number] =>
     PublicKey.findProgramAddressSync(
       [
         Buffer.from("escrow"),
+        escrowSeller.toBuffer(),
         escrowId.toArrayLike(Buffer, "le", 8),
         tradeId.toArrayLike(Buffer, "le", 8),
       ],
@@ -205,6 +225,7 @@ describe("Localsolana Contracts Tests", () => {
           .accounts({
             seller: seller.publicKey,
             authority: seller.publicKey,
+            escrowTokenRentPayer: seller.publicKey,
             escrow: escrowPDA,
             escrowTokenAccount: escrowTokenPDA,
             sellerTokenAccount: sellerTokenAccount,
@@ -221,6 +242,7 @@ describe("Localsolana Contracts Tests", () => {
           .accounts({
             seller: seller.publicKey,
             authority: seller.publicKey,
+            escrowTokenRentPayer: seller.publicKey,
             escrow: escrowPDA,
             escrowTokenAccount: null,
             sellerTokenAccount: null,
@@ -236,6 +258,8 @@ describe("Localsolana Contracts Tests", () => {
           .releaseEscrow()
           .accounts({
             authority: seller.publicKey,
+            seller: seller.publicKey,
+            escrowTokenRentPayer: seller.publicKey,
             escrow: escrowPDA,
             escrowTokenAccount: escrowTokenPDA,
             buyerTokenAccount: sequentialTokenAccount || buyerTokenAccount,
@@ -291,19 +315,52 @@ describe("Localsolana Contracts Tests", () => {
     }
   }
 
+  // Helper function to read the events a transaction emitted. They are delivered with
+  // emit_cpi!, so they are decoded from the program's self-CPIs rather than from the logs
+  async function fetchCpiEvents(signature: string): Promise<{ name: string; data: any }[]> {
+    const txDetails = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    assert.isNotNull(txDetails, "Transaction not found");
+
+    const accountKeys = txDetails.transaction.message.getAccountKeys({
+      accountKeysFromLookups: txDetails.meta.loadedAddresses,
+    });
+    const events: { name: string; data: any }[] = [];
+    for (const inner of txDetails.meta.innerInstructions ?? []) {
+      for (const ix of inner.instructions) {
+        if (!accountKeys.get(ix.programIdIndex)?.equals(program.programId)) continue;
+        const data = Buffer.from(anchor.utils.bytes.bs58.decode(ix.data));
+        if (!data.subarray(0, 8).equals(EVENT_IX_TAG)) continue;
+        const event = program.coder.events.decode(data.subarray(8).toString("base64"));
+        if (event) events.push(event);
+      }
+    }
+    return events;
+  }
+
+  // Helper function to find the single event of a kind a transaction emitted
+  async function fetchCpiEvent(signature: string, name: string): Promise<any> {
+    const events = (await fetchCpiEvents(signature)).filter((event) => event.name === name);
+    assert.equal(events.length, 1, `Expected one ${name} event`);
+    return events[0].data;
+  }
+
   // Helper function to validate EscrowBalanceChanged events
   function validateEscrowBalanceChangedEvent(
     event: any,
     expectedBalance: BN,
-    expectedReason: string,
+    expectedReason: Record<string, object>, // e.g. { funded: {} }
     escrowId: BN,
     tradeId: BN
   ): void {
+    assert.equal(event.schemaVersion, EVENT_SCHEMA_VERSION, "Schema version mismatch");
     assert.equal(event.objectId.toBase58(), event.objectId.toBase58(), "Object ID mismatch");
     assert.equal(event.escrowId.toString(), escrowId.toString(), "Escrow ID mismatch");
     assert.equal(event.tradeId.toString(), tradeId.toString(), "Trade ID mismatch");
     assert.equal(event.newBalance.toString(), expectedBalance.toString(), "New balance mismatch");
-    assert.equal(event.reason, expectedReason, "Reason mismatch");
+    assert.deepEqual(event.reason, expectedReason, "Reason mismatch");
     assert(event.timestamp > 0, "Timestamp should be positive");
   }
 
@@ -315,6 +372,7 @@ describe("Localsolana Contracts Tests", () => {
     escrowId: BN,
     tradeId: BN
   ): void {
+    assert.equal(event.schemaVersion, EVENT_SCHEMA_VERSION, "Schema version mismatch");
     assert.equal(event.objectId.toBase58(), event.objectId.toBase58(), "Object ID mismatch");
     assert.equal(event.escrowId.toString(), escrowId.toString(), "Escrow ID mismatch");
     assert.equal(event.tradeId.toString(), tradeId.toString(), "Trade ID mismatch");
@@ -561,12 +619,13 @@ describe("Localsolana Contracts Tests", () => {
       console.log(`Seller balance before: ${sellerBalanceBefore} lamports`);
 
       const tx = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -606,6 +665,11 @@ describe("Localsolana Contracts Tests", () => {
       assert.isNull(escrowAccount.disputeEvidenceHashSeller, "Seller evidence hash should be null");
       assert.isNull(escrowAccount.disputeResolutionHash, "Resolution hash should be null");
       assert.equal(escrowAccount.trackedBalance.toString(), "0", "Tracked balance should be 0 for new escrow");
+      assert.deepEqual(escrowAccount.fiatTerms.currency, fiatTerms.currency, "Fiat currency mismatch");
+      assert.equal(escrowAccount.fiatTerms.fiatAmount.toString(), fiatTerms.fiatAmount.toString(), "Fiat amount mismatch");
+      assert.equal(escrowAccount.fiatTerms.rate.toString(), fiatTerms.rate.toString(), "Fiat rate mismatch");
+      assert.isFalse(escrowAccount.requiresAcceptance, "Acceptance should not be required");
+      assert.isFalse(escrowAccount.requiresBuyerDeposit, "Buyer deposit should not be required");
 
     // Cleanup: Cancel escrow to free PDA
     const cancelTx = await program.methods
@@ -613,6 +677,7 @@ describe("Localsolana Contracts Tests", () => {
       .accounts({
         seller: seller.publicKey,
         authority: seller.publicKey,
+        escrowTokenRentPayer: seller.publicKey,
         escrow: escrowPDA,
         escrowTokenAccount: null,
         sellerTokenAccount: null,
@@ -635,12 +700,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Funding ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -713,6 +779,7 @@ describe("Localsolana Contracts Tests", () => {
       .accounts({
         seller: seller.publicKey,
         authority: seller.publicKey,
+        escrowTokenRentPayer: seller.publicKey,
         escrow: escrowPDA,
         escrowTokenAccount: escrowTokenPDA,
         sellerTokenAccount: sellerTokenAccount,
@@ -744,12 +811,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Funding and Marking Paid ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -800,6 +868,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -824,12 +894,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Full Flow ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -888,6 +959,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -959,12 +1032,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Creation ===");
       const tx = await program.methods
-        .createEscrow(escrowId, tradeId, amount, true, sequentialAddress)
+        .createEscrow(escrowId, tradeId, amount, true, sequentialAddress, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1001,12 +1075,13 @@ describe("Localsolana Contracts Tests", () => {
       console.log("=== Escrow Creation ===");
       try {
         await program.methods
-          .createEscrow(escrowId, tradeId, new BN(0), false, null)
+          .createEscrow(escrowId, tradeId, new BN(0), false, null, fiatTerms, false, false)
           .accounts({
             seller: seller.publicKey,
             buyer: buyer.publicKey,
             escrow: escrowPDA,
             system_program: anchor.web3.SystemProgram.programId,
+            priceUpdate: null,
           })
           .signers([seller])
           .rpc();
@@ -1026,12 +1101,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Sequential Escrow Update ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, true, initialSequentialAddress)
+        .createEscrow(escrowId, tradeId, amount, true, initialSequentialAddress, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1058,19 +1134,14 @@ describe("Localsolana Contracts Tests", () => {
 
       await sleep(1000);
 
-      // Get transaction details to check for SequentialAddressUpdated event
-      const txDetails = await provider.connection.getTransaction(tx2, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0,
-      });
-
-      // For localnet testing, we'll verify the state change instead of the event
-      // Events in localnet can be tricky to capture, so we'll validate the escrow state
-      console.log("Transaction completed, verifying escrow state update...");
-
-      // Alternative: Check if we can get the event from the transaction
-      // Note: Events in Solana are not automatically included in logMessages
-      // They need to be explicitly requested or parsed from the transaction
+      // Check the SequentialAddressUpdated event
+      validateSequentialAddressUpdatedEvent(
+        await fetchCpiEvent(tx2, "SequentialAddressUpdated"),
+        initialSequentialAddress,
+        newSequentialAddress,
+        escrowId,
+        tradeId
+      );
 
       const escrowAccount = await program.account.escrow.fetch(escrowPDA);
       assert.equal(
@@ -1096,12 +1167,13 @@ describe("Localsolana Contracts Tests", () => {
       await sleep(1000);
 
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, true, sequentialAddress)
+        .createEscrow(escrowId, tradeId, amount, true, sequentialAddress, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1151,6 +1223,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -1217,12 +1291,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Cancellation Before Funding ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           systemProgram: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1244,6 +1319,7 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           seller: seller.publicKey,
           authority: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: null,
           sellerTokenAccount: null,
@@ -1275,12 +1351,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Cancellation After Funding ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           systemProgram: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1329,6 +1406,7 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           seller: seller.publicKey,
           authority: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           sellerTokenAccount: sellerTokenAccount,
@@ -1377,12 +1455,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Escrow Cancellation After Fiat Paid ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           systemProgram: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1440,6 +1519,7 @@ describe("Localsolana Contracts Tests", () => {
           .accounts({
             seller: seller.publicKey,
             authority: seller.publicKey,
+            escrowTokenRentPayer: seller.publicKey,
             escrow: escrowPDA,
             escrowTokenAccount: escrowTokenPDA,
             sellerTokenAccount: sellerTokenAccount,
@@ -1475,12 +1555,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Bond Account Initialization ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1570,12 +1651,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Dispute Opening ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1687,6 +1769,9 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           arbitrator: arbitrator.publicKey,
           seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
+          buyerBondRentPayer: buyer.publicKey,
+          sellerBondRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -1718,12 +1803,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Dispute Response ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -1849,6 +1935,9 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           arbitrator: arbitrator.publicKey,
           seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
+          buyerBondRentPayer: buyer.publicKey,
+          sellerBondRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -1881,12 +1970,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Dispute Resolution (Buyer Wins) ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2010,6 +2100,9 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           arbitrator: arbitrator.publicKey,
           seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
+          buyerBondRentPayer: buyer.publicKey,
+          sellerBondRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -2095,12 +2188,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Dispute Resolution (Seller Wins) ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           systemProgram: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2224,6 +2318,9 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           arbitrator: arbitrator.publicKey,
           seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
+          buyerBondRentPayer: buyer.publicKey,
+          sellerBondRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -2280,12 +2377,13 @@ describe("Localsolana Contracts Tests", () => {
       console.log("=== Exceeds Maximum Amount ===");
       try {
         await program.methods
-          .createEscrow(escrowId, tradeId, excessiveAmount, false, null)
+          .createEscrow(escrowId, tradeId, excessiveAmount, false, null, fiatTerms, false, false)
           .accounts({
             seller: seller.publicKey,
             buyer: buyer.publicKey,
             escrow: escrowPDA,
             system_program: anchor.web3.SystemProgram.programId,
+            priceUpdate: null,
           })
           .signers([seller])
           .rpc();
@@ -2304,12 +2402,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Unauthorized Actions ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2359,12 +2458,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Insufficient Funds ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2434,12 +2534,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Reinitialization Prevention ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2532,6 +2633,7 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           seller: seller.publicKey,
           authority: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           sellerTokenAccount: sellerTokenAccount,
@@ -2556,12 +2658,13 @@ describe("Localsolana Contracts Tests", () => {
 
       // Step 1: Create escrow - tracked_balance should be 0
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2638,6 +2741,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -2683,12 +2788,13 @@ describe("Localsolana Contracts Tests", () => {
 
       // Step 1: Create sequential escrow
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, true, initialSequentialAddress)
+        .createEscrow(escrowId, tradeId, amount, true, initialSequentialAddress, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2723,19 +2829,14 @@ describe("Localsolana Contracts Tests", () => {
 
       await sleep(1000);
 
-      // Step 3: Get transaction details to validate SequentialAddressUpdated event
-      const txDetails = await provider.connection.getTransaction(tx2, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0,
-      });
-
-      // For localnet testing, we'll verify the state change instead of the event
-      // Events in localnet can be tricky to capture, so we'll validate the escrow state
-      console.log("Transaction completed, verifying escrow state update...");
-
-      // Note: Events in Solana are not automatically included in logMessages
-      // They need to be explicitly requested or parsed from the transaction
-      // For testing purposes, we'll verify the state change which proves the function executed
+      // Step 3: Validate the SequentialAddressUpdated event
+      validateSequentialAddressUpdatedEvent(
+        await fetchCpiEvent(tx2, "SequentialAddressUpdated"),
+        initialSequentialAddress,
+        newSequentialAddress,
+        escrowId,
+        tradeId
+      );
 
       // Step 4: Verify escrow state was updated
       const escrowAccount = await program.account.escrow.fetch(escrowPDA);
@@ -2760,12 +2861,13 @@ describe("Localsolana Contracts Tests", () => {
 
       // Step 1: Create escrow - should emit EscrowCreated event
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2807,6 +2909,15 @@ describe("Localsolana Contracts Tests", () => {
 
       await sleep(1000);
 
+      // The vault holds the amount plus the 1% fee
+      validateEscrowBalanceChangedEvent(
+        await fetchCpiEvent(tx2, "EscrowBalanceChanged"),
+        amount.add(amount.div(new BN(100))),
+        { funded: {} },
+        escrowId,
+        tradeId
+      );
+
       // Step 3: Mark fiat paid - should emit FiatMarkedPaid event
       const tx3 = await program.methods
         .markFiatPaid()
@@ -2832,6 +2943,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -2860,9 +2973,13 @@ describe("Localsolana Contracts Tests", () => {
 
       await sleep(1000);
 
-      // Verify all state changes occurred (which means events were emitted)
-      // Note: In localnet, we validate state changes rather than parsing events directly
-      console.log("All escrow lifecycle events validated through state changes");
+      validateEscrowBalanceChangedEvent(
+        await fetchCpiEvent(tx4, "EscrowBalanceChanged"),
+        new BN(0),
+        { released: {} },
+        escrowId,
+        tradeId
+      );
 
       // Verify accounts were closed (which means final events were emitted)
       assert.isNull(await provider.connection.getAccountInfo(escrowPDA), "Escrow state account should be closed after release");
@@ -2880,12 +2997,13 @@ describe("Localsolana Contracts Tests", () => {
 
       // Step 1: Create escrow
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -2928,7 +3046,14 @@ describe("Localsolana Contracts Tests", () => {
 
       await sleep(1000);
 
-      // Step 3: Verify funding occurred (which means FundsDeposited event was emitted)
+      // Step 3: Verify the FundsDeposited event and the funding itself
+      const fundsDeposited = await fetchCpiEvent(tx2, "FundsDeposited");
+      assert.equal(fundsDeposited.schemaVersion, EVENT_SCHEMA_VERSION, "Schema version mismatch");
+      assert.equal(fundsDeposited.objectId.toBase58(), escrowPDA.toBase58(), "Object ID mismatch");
+      assert.equal(fundsDeposited.amount.toString(), amount.toString(), "Amount mismatch");
+      assert.equal(fundsDeposited.fee.toString(), amount.div(new BN(100)).toString(), "Fee mismatch");
+      assert.equal(fundsDeposited.counter.toString(), "1", "Event counter mismatch");
+
       const escrowAccount = await program.account.escrow.fetch(escrowPDA);
       const escrowBalance = (await provider.connection.getTokenAccountBalance(escrowTokenPDA)).value.amount;
 
@@ -2943,6 +3068,7 @@ describe("Localsolana Contracts Tests", () => {
         .accounts({
           seller: seller.publicKey,
           authority: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           sellerTokenAccount: sellerTokenAccount,
@@ -2967,12 +3093,13 @@ describe("Localsolana Contracts Tests", () => {
 
       // Step 1: Create escrow
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
@@ -3026,7 +3153,12 @@ describe("Localsolana Contracts Tests", () => {
       await provider.connection.confirmTransaction(tx3, "confirmed");
       await sleep(1000);
 
-      // Step 4: Verify fiat was marked as paid (which means FiatMarkedPaid event was emitted)
+      // Step 4: Verify the FiatMarkedPaid event and the escrow flag
+      const fiatMarkedPaid = await fetchCpiEvent(tx3, "FiatMarkedPaid");
+      assert.equal(fiatMarkedPaid.schemaVersion, EVENT_SCHEMA_VERSION, "Schema version mismatch");
+      assert.equal(fiatMarkedPaid.objectId.toBase58(), escrowPDA.toBase58(), "Object ID mismatch");
+      assert.equal(fiatMarkedPaid.escrowId.toString(), escrowId.toString(), "Escrow ID mismatch");
+
       const escrowAccount = await program.account.escrow.fetch(escrowPDA);
       assert.isTrue(escrowAccount.fiatPaid, "Fiat paid should be true");
 
@@ -3035,6 +3167,8 @@ describe("Localsolana Contracts Tests", () => {
         .releaseEscrow()
         .accounts({
           authority: seller.publicKey,
+          seller: seller.publicKey,
+          escrowTokenRentPayer: seller.publicKey,
           escrow: escrowPDA,
           escrowTokenAccount: escrowTokenPDA,
           buyerTokenAccount: buyerTokenAccount,
@@ -3073,12 +3207,13 @@ describe("Localsolana Contracts Tests", () => {
 
       console.log("=== Insufficient Funds Test (Running Last) ===");
       const tx1 = await program.methods
-        .createEscrow(escrowId, tradeId, amount, false, null)
+        .createEscrow(escrowId, tradeId, amount, false, null, fiatTerms, false, false)
         .accounts({
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           escrow: escrowPDA,
           system_program: anchor.web3.SystemProgram.programId,
+          priceUpdate: null,
         })
         .signers([seller])
         .rpc();
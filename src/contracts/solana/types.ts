@@ -14,18 +14,13 @@ export type LocalsolanaContracts = {
   };
   instructions: [
     {
-      name: 'autoCancel';
-      discriminator: [83, 161, 99, 154, 167, 3, 133, 159];
+      name: 'acceptEscrow';
+      discriminator: [193, 2, 224, 245, 36, 116, 65, 154];
       accounts: [
         {
-          name: 'arbitrator';
-          writable: true;
+          name: 'buyer';
           signer: true;
         },
-        {
-          name: 'seller';
-          writable: true;
-        },
         {
           name: 'escrow';
           writable: true;
@@ -35,6 +30,10 @@ export type LocalsolanaContracts = {
                 kind: 'const';
                 value: [101, 115, 99, 114, 111, 119];
               },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
               {
                 kind: 'account';
                 path: 'escrow.escrow_id';
@@ -49,46 +48,56 @@ export type LocalsolanaContracts = {
           };
         },
         {
-          name: 'escrowTokenAccount';
-          writable: true;
-          optional: true;
+          name: 'eventAuthority';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
-              },
-              {
-                kind: 'account';
-                path: 'escrow';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
               }
             ];
           };
         },
         {
-          name: 'sellerTokenAccount';
-          writable: true;
-          optional: true;
-        },
-        {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+          name: 'program';
         }
       ];
       args: [];
     },
     {
-      name: 'cancelEscrow';
-      discriminator: [156, 203, 54, 179, 38, 72, 33, 21];
+      name: 'autoCancel';
+      discriminator: [83, 161, 99, 154, 167, 3, 133, 159];
       accounts: [
+        {
+          name: 'arbitrator';
+          writable: true;
+          signer: true;
+        },
         {
           name: 'seller';
           writable: true;
         },
         {
-          name: 'authority';
+          name: 'escrowTokenRentPayer';
           writable: true;
-          signer: true;
         },
         {
           name: 'escrow';
@@ -99,6 +108,10 @@ export type LocalsolanaContracts = {
                 kind: 'const';
                 value: [101, 115, 99, 114, 111, 119];
               },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
               {
                 kind: 'account';
                 path: 'escrow.escrow_id';
@@ -135,87 +148,132 @@ export type LocalsolanaContracts = {
           optional: true;
         },
         {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
-        }
-      ];
-      args: [];
-    },
-    {
-      name: 'createEscrow';
-      discriminator: [253, 215, 165, 116, 36, 108, 68, 80];
-      accounts: [
-        {
-          name: 'seller';
+          name: 'buyerBondAccount';
           writable: true;
-          signer: true;
-        },
-        {
-          name: 'buyer';
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
         },
         {
-          name: 'escrow';
+          name: 'sellerBondAccount';
           writable: true;
+          optional: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119];
-              },
-              {
-                kind: 'arg';
-                path: 'escrowId';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
               },
               {
-                kind: 'arg';
-                path: 'tradeId';
+                kind: 'account';
+                path: 'escrow';
               }
             ];
           };
         },
         {
-          name: 'systemProgram';
-          address: '11111111111111111111111111111111';
-        }
-      ];
-      args: [
+          name: 'buyerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
         {
-          name: 'escrowId';
-          type: 'u64';
+          name: 'sellerBondRentPayer';
+          writable: true;
+          optional: true;
         },
         {
-          name: 'tradeId';
-          type: 'u64';
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
         },
         {
-          name: 'amount';
-          type: 'u64';
+          name: 'buyerDepositAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
         },
         {
-          name: 'sequential';
-          type: 'bool';
+          name: 'buyerDepositRentPayer';
+          writable: true;
+          optional: true;
         },
         {
-          name: 'sequentialEscrowAddress';
-          type: {
-            option: 'pubkey';
+          name: 'arbitratorTokenAccount';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
           };
+        },
+        {
+          name: 'program';
         }
       ];
+      args: [];
     },
     {
-      name: 'defaultJudgment';
-      discriminator: [103, 255, 235, 98, 158, 165, 125, 75];
+      name: 'cancelEscrow';
+      discriminator: [156, 203, 54, 179, 38, 72, 33, 21];
       accounts: [
         {
           name: 'seller';
           writable: true;
         },
         {
-          name: 'arbitrator';
+          name: 'authority';
           writable: true;
           signer: true;
         },
+        {
+          name: 'escrowTokenRentPayer';
+          writable: true;
+        },
         {
           name: 'escrow';
           writable: true;
@@ -225,6 +283,10 @@ export type LocalsolanaContracts = {
                 kind: 'const';
                 value: [101, 115, 99, 114, 111, 119];
               },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
               {
                 kind: 'account';
                 path: 'escrow.escrow_id';
@@ -241,6 +303,7 @@ export type LocalsolanaContracts = {
         {
           name: 'escrowTokenAccount';
           writable: true;
+          optional: true;
           pda: {
             seeds: [
               {
@@ -254,17 +317,15 @@ export type LocalsolanaContracts = {
             ];
           };
         },
-        {
-          name: 'buyerTokenAccount';
-          writable: true;
-        },
         {
           name: 'sellerTokenAccount';
           writable: true;
+          optional: true;
         },
         {
           name: 'buyerBondAccount';
           writable: true;
+          optional: true;
           pda: {
             seeds: [
               {
@@ -281,6 +342,7 @@ export type LocalsolanaContracts = {
         {
           name: 'sellerBondAccount';
           writable: true;
+          optional: true;
           pda: {
             seeds: [
               {
@@ -294,100 +356,98 @@ export type LocalsolanaContracts = {
             ];
           };
         },
+        {
+          name: 'buyerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'sellerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
         {
           name: 'tokenProgram';
           address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
-        }
-      ];
-      args: [];
-    },
-    {
-      name: 'fundEscrow';
-      discriminator: [155, 18, 218, 141, 182, 213, 69, 201];
-      accounts: [
+        },
         {
-          name: 'seller';
+          name: 'buyerTokenAccount';
           writable: true;
-          signer: true;
+          optional: true;
         },
         {
-          name: 'escrow';
+          name: 'buyerDepositAccount';
           writable: true;
+          optional: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119];
-              },
-              {
-                kind: 'arg';
-                path: 'escrowId';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
               },
               {
-                kind: 'arg';
-                path: 'tradeId';
+                kind: 'account';
+                path: 'escrow';
               }
             ];
           };
         },
         {
-          name: 'sellerTokenAccount';
+          name: 'buyerDepositRentPayer';
           writable: true;
+          optional: true;
         },
         {
-          name: 'escrowTokenAccount';
+          name: 'arbitratorTokenAccount';
           writable: true;
+          optional: true;
+        },
+        {
+          name: 'eventAuthority';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
-              },
-              {
-                kind: 'account';
-                path: 'escrow';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
               }
             ];
           };
         },
         {
-          name: 'tokenMint';
-        },
-        {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
-        },
-        {
-          name: 'systemProgram';
-          address: '11111111111111111111111111111111';
-        },
-        {
-          name: 'rent';
-          address: 'SysvarRent111111111111111111111111111111111';
-        }
-      ];
-      args: [
-        {
-          name: 'escrowId';
-          type: 'u64';
-        },
-        {
-          name: 'tradeId';
-          type: 'u64';
+          name: 'program';
         }
       ];
+      args: [];
     },
     {
-      name: 'initializeBuyerBondAccount';
-      discriminator: [33, 161, 142, 133, 182, 160, 195, 90];
+      name: 'commitChatRoot';
+      discriminator: [56, 122, 199, 51, 156, 2, 137, 90];
       accounts: [
         {
-          name: 'payer';
-          writable: true;
+          name: 'party';
           signer: true;
         },
         {
           name: 'escrow';
+          writable: true;
           pda: {
             seeds: [
               {
@@ -395,76 +455,93 @@ export type LocalsolanaContracts = {
                 value: [101, 115, 99, 114, 111, 119];
               },
               {
-                kind: 'arg';
-                path: 'escrowId';
+                kind: 'account';
+                path: 'escrow';
               },
               {
-                kind: 'arg';
-                path: 'tradeId';
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
               }
             ];
           };
         },
         {
-          name: 'buyerBondAccount';
-          writable: true;
+          name: 'eventAuthority';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
-              },
-              {
-                kind: 'account';
-                path: 'escrow';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
               }
             ];
           };
         },
         {
-          name: 'tokenMint';
-        },
-        {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
-        },
-        {
-          name: 'systemProgram';
-          address: '11111111111111111111111111111111';
-        },
-        {
-          name: 'rent';
-          address: 'SysvarRent111111111111111111111111111111111';
+          name: 'program';
         }
       ];
       args: [
         {
-          name: 'escrowId';
-          type: 'u64';
+          name: 'root';
+          type: {
+            array: ['u8', 32];
+          };
         },
         {
-          name: 'tradeId';
+          name: 'messageCount';
           type: 'u64';
         }
       ];
     },
     {
-      name: 'initializeSellerBondAccount';
-      discriminator: [164, 116, 173, 16, 54, 62, 16, 194];
+      name: 'createAndFundEscrow';
+      discriminator: [143, 254, 15, 181, 252, 27, 188, 229];
       accounts: [
         {
-          name: 'payer';
+          name: 'seller';
           writable: true;
           signer: true;
         },
+        {
+          name: 'buyer';
+        },
         {
           name: 'escrow';
+          writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
                 value: [101, 115, 99, 114, 111, 119];
               },
+              {
+                kind: 'account';
+                path: 'seller';
+              },
               {
                 kind: 'arg';
                 path: 'escrowId';
@@ -477,13 +554,17 @@ export type LocalsolanaContracts = {
           };
         },
         {
-          name: 'sellerBondAccount';
+          name: 'sellerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'escrowTokenAccount';
           writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
               },
               {
                 kind: 'account';
@@ -506,6 +587,68 @@ export type LocalsolanaContracts = {
         {
           name: 'rent';
           address: 'SysvarRent111111111111111111111111111111111';
+        },
+        {
+          name: 'priceFeed';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 105, 99, 101, 95, 102, 101, 101, 100];
+              },
+              {
+                kind: 'arg';
+                path: 'fiat_terms.currency';
+              }
+            ];
+          };
+        },
+        {
+          name: 'priceUpdate';
+          optional: true;
+        },
+        {
+          name: 'config';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
         }
       ];
       args: [
@@ -516,18 +659,47 @@ export type LocalsolanaContracts = {
         {
           name: 'tradeId';
           type: 'u64';
+        },
+        {
+          name: 'amount';
+          type: 'u64';
+        },
+        {
+          name: 'sequential';
+          type: 'bool';
+        },
+        {
+          name: 'sequentialEscrowAddress';
+          type: {
+            option: 'pubkey';
+          };
+        },
+        {
+          name: 'fiatTerms';
+          type: {
+            defined: {
+              name: 'fiatTerms';
+            };
+          };
+        },
+        {
+          name: 'requiresBuyerDeposit';
+          type: 'bool';
         }
       ];
     },
     {
-      name: 'markFiatPaid';
-      discriminator: [147, 155, 78, 133, 158, 150, 89, 70];
+      name: 'createEscrow';
+      discriminator: [253, 215, 165, 116, 36, 108, 68, 80];
       accounts: [
         {
-          name: 'buyer';
+          name: 'seller';
           writable: true;
           signer: true;
         },
+        {
+          name: 'buyer';
+        },
         {
           name: 'escrow';
           writable: true;
@@ -539,110 +711,152 @@ export type LocalsolanaContracts = {
               },
               {
                 kind: 'account';
-                path: 'escrow.escrow_id';
-                account: 'Escrow';
+                path: 'seller';
               },
               {
-                kind: 'account';
-                path: 'escrow.trade_id';
-                account: 'Escrow';
+                kind: 'arg';
+                path: 'escrowId';
+              },
+              {
+                kind: 'arg';
+                path: 'tradeId';
               }
             ];
           };
-        }
-      ];
-      args: [];
-    },
-    {
-      name: 'openDisputeWithBond';
-      discriminator: [107, 47, 12, 245, 112, 23, 5, 85];
-      accounts: [
+        },
         {
-          name: 'disputingParty';
-          writable: true;
-          signer: true;
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
         },
         {
-          name: 'escrow';
-          writable: true;
+          name: 'priceFeed';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119];
-              },
-              {
-                kind: 'account';
-                path: 'escrow.escrow_id';
-                account: 'Escrow';
+                value: [112, 114, 105, 99, 101, 95, 102, 101, 101, 100];
               },
               {
-                kind: 'account';
-                path: 'escrow.trade_id';
-                account: 'Escrow';
+                kind: 'arg';
+                path: 'fiat_terms.currency';
               }
             ];
           };
         },
         {
-          name: 'disputingPartyTokenAccount';
-          writable: true;
+          name: 'priceUpdate';
+          optional: true;
         },
         {
-          name: 'buyerBondAccount';
-          writable: true;
+          name: 'config';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
-              },
-              {
-                kind: 'account';
-                path: 'escrow';
+                value: [99, 111, 110, 102, 105, 103];
               }
             ];
           };
         },
         {
-          name: 'sellerBondAccount';
-          writable: true;
+          name: 'eventAuthority';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
-              },
-              {
-                kind: 'account';
-                path: 'escrow';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
               }
             ];
           };
         },
         {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+          name: 'program';
         }
       ];
       args: [
         {
-          name: 'evidenceHash';
+          name: 'escrowId';
+          type: 'u64';
+        },
+        {
+          name: 'tradeId';
+          type: 'u64';
+        },
+        {
+          name: 'amount';
+          type: 'u64';
+        },
+        {
+          name: 'sequential';
+          type: 'bool';
+        },
+        {
+          name: 'sequentialEscrowAddress';
           type: {
-            array: ['u8', 32];
+            option: 'pubkey';
+          };
+        },
+        {
+          name: 'fiatTerms';
+          type: {
+            defined: {
+              name: 'fiatTerms';
+            };
           };
+        },
+        {
+          name: 'requiresAcceptance';
+          type: 'bool';
+        },
+        {
+          name: 'requiresBuyerDeposit';
+          type: 'bool';
         }
       ];
     },
     {
-      name: 'releaseEscrow';
-      discriminator: [146, 253, 129, 233, 20, 145, 181, 206];
+      name: 'defaultJudgment';
+      discriminator: [103, 255, 235, 98, 158, 165, 125, 75];
       accounts: [
         {
-          name: 'authority';
+          name: 'seller';
+          writable: true;
+        },
+        {
+          name: 'caller';
           writable: true;
           signer: true;
         },
+        {
+          name: 'escrowTokenRentPayer';
+          writable: true;
+        },
+        {
+          name: 'buyerBondRentPayer';
+          writable: true;
+        },
+        {
+          name: 'sellerBondRentPayer';
+          writable: true;
+        },
         {
           name: 'escrow';
           writable: true;
@@ -652,6 +866,10 @@ export type LocalsolanaContracts = {
                 kind: 'const';
                 value: [101, 115, 99, 114, 111, 119];
               },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
               {
                 kind: 'account';
                 path: 'escrow.escrow_id';
@@ -686,64 +904,33 @@ export type LocalsolanaContracts = {
           writable: true;
         },
         {
-          name: 'arbitratorTokenAccount';
-          writable: true;
-        },
-        {
-          name: 'sequentialEscrowTokenAccount';
-          writable: true;
-          optional: true;
-        },
-        {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
-        }
-      ];
-      args: [];
-    },
-    {
-      name: 'resolveDisputeWithExplanation';
-      discriminator: [189, 74, 181, 226, 179, 199, 201, 192];
-      accounts: [
-        {
-          name: 'arbitrator';
-          writable: true;
-          signer: true;
-        },
-        {
-          name: 'seller';
+          name: 'sellerTokenAccount';
           writable: true;
         },
         {
-          name: 'escrow';
+          name: 'buyerBondAccount';
           writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119];
-              },
-              {
-                kind: 'account';
-                path: 'escrow.escrow_id';
-                account: 'Escrow';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
               },
               {
                 kind: 'account';
-                path: 'escrow.trade_id';
-                account: 'Escrow';
+                path: 'escrow';
               }
             ];
           };
         },
         {
-          name: 'escrowTokenAccount';
+          name: 'sellerBondAccount';
           writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
               },
               {
                 kind: 'account';
@@ -753,41 +940,148 @@ export type LocalsolanaContracts = {
           };
         },
         {
-          name: 'buyerTokenAccount';
-          writable: true;
-        },
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
         {
-          name: 'sellerTokenAccount';
+          name: 'buyerDepositAccount';
           writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
         },
         {
-          name: 'arbitratorTokenAccount';
+          name: 'buyerDepositRentPayer';
           writable: true;
+          optional: true;
         },
         {
-          name: 'buyerBondAccount';
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
+    },
+    {
+      name: 'escrowStatus';
+      discriminator: [74, 95, 113, 165, 85, 182, 210, 201];
+      accounts: [
+        {
+          name: 'escrow';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        }
+      ];
+      args: [];
+      returns: {
+        defined: {
+          name: 'escrowStatusReport';
+        };
+      };
+    },
+    {
+      name: 'fundEscrow';
+      discriminator: [155, 18, 218, 141, 182, 213, 69, 201];
+      accounts: [
+        {
+          name: 'seller';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
           writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+                value: [101, 115, 99, 114, 111, 119];
               },
               {
                 kind: 'account';
                 path: 'escrow';
+              },
+              {
+                kind: 'arg';
+                path: 'escrowId';
+              },
+              {
+                kind: 'arg';
+                path: 'tradeId';
               }
             ];
           };
         },
         {
-          name: 'sellerBondAccount';
+          name: 'sellerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'escrowTokenAccount';
           writable: true;
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
               },
               {
                 kind: 'account';
@@ -796,30 +1090,71 @@ export type LocalsolanaContracts = {
             ];
           };
         },
+        {
+          name: 'tokenMint';
+        },
         {
           name: 'tokenProgram';
           address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        },
+        {
+          name: 'rent';
+          address: 'SysvarRent111111111111111111111111111111111';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
         }
       ];
       args: [
         {
-          name: 'decision';
-          type: 'bool';
+          name: 'escrowId';
+          type: 'u64';
         },
         {
-          name: 'resolutionHash';
-          type: {
-            array: ['u8', 32];
-          };
+          name: 'tradeId';
+          type: 'u64';
         }
       ];
     },
     {
-      name: 'respondToDisputeWithBond';
-      discriminator: [228, 27, 214, 143, 75, 31, 29, 212];
+      name: 'initializeBuyerBondAccount';
+      discriminator: [33, 161, 142, 133, 182, 160, 195, 90];
       accounts: [
         {
-          name: 'respondingParty';
+          name: 'payer';
           writable: true;
           signer: true;
         },
@@ -834,21 +1169,19 @@ export type LocalsolanaContracts = {
               },
               {
                 kind: 'account';
-                path: 'escrow.escrow_id';
-                account: 'Escrow';
+                path: 'escrow';
               },
               {
-                kind: 'account';
-                path: 'escrow.trade_id';
-                account: 'Escrow';
+                kind: 'arg';
+                path: 'escrowId';
+              },
+              {
+                kind: 'arg';
+                path: 'tradeId';
               }
             ];
           };
         },
-        {
-          name: 'respondingPartyTokenAccount';
-          writable: true;
-        },
         {
           name: 'buyerBondAccount';
           writable: true;
@@ -866,41 +1199,107 @@ export type LocalsolanaContracts = {
           };
         },
         {
-          name: 'sellerBondAccount';
-          writable: true;
+          name: 'tokenMint';
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        },
+        {
+          name: 'rent';
+          address: 'SysvarRent111111111111111111111111111111111';
+        },
+        {
+          name: 'eventAuthority';
           pda: {
             seeds: [
               {
                 kind: 'const';
-                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
-              },
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'escrowId';
+          type: 'u64';
+        },
+        {
+          name: 'tradeId';
+          type: 'u64';
+        }
+      ];
+    },
+    {
+      name: 'initializeConfig';
+      discriminator: [208, 127, 21, 1, 194, 190, 196, 70];
+      accounts: [
+        {
+          name: 'arbitrator';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'config';
+          writable: true;
+          pda: {
+            seeds: [
               {
-                kind: 'account';
-                path: 'escrow';
+                kind: 'const';
+                value: [99, 111, 110, 102, 105, 103];
               }
             ];
           };
         },
         {
-          name: 'tokenProgram';
-          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
         }
       ];
       args: [
         {
-          name: 'evidenceHash';
+          name: 'feePolicy';
           type: {
-            array: ['u8', 32];
+            defined: {
+              name: 'feePolicy';
+            };
           };
         }
       ];
     },
     {
-      name: 'updateSequentialAddress';
-      discriminator: [11, 3, 89, 53, 111, 178, 25, 158];
+      name: 'initializeSellerBondAccount';
+      discriminator: [164, 116, 173, 16, 54, 62, 16, 194];
       accounts: [
         {
-          name: 'buyer';
+          name: 'payer';
           writable: true;
           signer: true;
         },
@@ -915,176 +1314,2827 @@ export type LocalsolanaContracts = {
               },
               {
                 kind: 'account';
-                path: 'escrow.escrow_id';
-                account: 'Escrow';
+                path: 'escrow';
+              },
+              {
+                kind: 'arg';
+                path: 'escrowId';
+              },
+              {
+                kind: 'arg';
+                path: 'tradeId';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
               },
               {
                 kind: 'account';
-                path: 'escrow.trade_id';
-                account: 'Escrow';
+                path: 'escrow';
               }
             ];
           };
-        }
-      ];
-      args: [
+        },
         {
-          name: 'newAddress';
-          type: 'pubkey';
-        }
-      ];
-    }
-  ];
-  accounts: [
-    {
-      name: 'escrow';
-      discriminator: [31, 213, 123, 187, 186, 22, 218, 155];
-    }
-  ];
-  events: [
-    {
-      name: 'disputeDefaultJudgment';
-      discriminator: [194, 12, 130, 224, 60, 204, 39, 194];
-    },
-    {
-      name: 'disputeOpened';
-      discriminator: [239, 222, 102, 235, 193, 85, 1, 214];
-    },
-    {
-      name: 'disputeResolved';
-      discriminator: [121, 64, 249, 153, 139, 128, 236, 187];
-    },
-    {
-      name: 'disputeResponseSubmitted';
-      discriminator: [22, 179, 0, 219, 181, 109, 45, 5];
-    },
-    {
-      name: 'escrowBalanceChanged';
-      discriminator: [169, 241, 33, 44, 253, 206, 89, 168];
-    },
-    {
-      name: 'escrowCancelled';
-      discriminator: [98, 241, 195, 122, 213, 0, 162, 161];
-    },
-    {
-      name: 'escrowCreated';
-      discriminator: [70, 127, 105, 102, 92, 97, 7, 173];
-    },
-    {
-      name: 'escrowReleased';
-      discriminator: [131, 7, 138, 104, 166, 190, 113, 112];
-    },
-    {
-      name: 'fiatMarkedPaid';
-      discriminator: [38, 159, 7, 17, 32, 79, 143, 184];
-    },
-    {
-      name: 'fundsDeposited';
-      discriminator: [157, 209, 100, 95, 59, 100, 3, 68];
+          name: 'tokenMint';
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        },
+        {
+          name: 'rent';
+          address: 'SysvarRent111111111111111111111111111111111';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'escrowId';
+          type: 'u64';
+        },
+        {
+          name: 'tradeId';
+          type: 'u64';
+        }
+      ];
     },
     {
-      name: 'sequentialAddressUpdated';
-      discriminator: [205, 6, 123, 144, 102, 253, 81, 133];
-    }
-  ];
-  errors: [
-    {
-      code: 6000;
-      name: 'invalidAmount';
-      msg: 'Invalid amount: Zero or negative';
+      name: 'markFiatPaid';
+      discriminator: [147, 155, 78, 133, 158, 150, 89, 70];
+      accounts: [
+        {
+          name: 'buyer';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
     },
     {
-      code: 6001;
-      name: 'exceedsMaximum';
-      msg: 'Amount exceeds maximum (100 USDC)';
+      name: 'migrateEscrow';
+      discriminator: [65, 111, 186, 119, 58, 11, 81, 209];
+      accounts: [
+        {
+          name: 'payer';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          docs: ['ownership is enforced here and the discriminator is checked in the handler'];
+          writable: true;
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
     },
     {
-      code: 6002;
-      name: 'unauthorized';
-      msg: 'Unauthorized caller';
+      name: 'openDisputeWithBond';
+      discriminator: [107, 47, 12, 245, 112, 23, 5, 85];
+      accounts: [
+        {
+          name: 'disputingParty';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'disputingPartyTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'buyerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'evidenceHash';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
     },
     {
-      code: 6003;
-      name: 'depositDeadlineExpired';
-      msg: 'Deposit deadline expired';
+      name: 'postBuyerDeposit';
+      discriminator: [105, 160, 106, 120, 153, 127, 35, 89];
+      accounts: [
+        {
+          name: 'buyer';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'escrowTokenAccount';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'buyerDepositAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'tokenMint';
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        },
+        {
+          name: 'rent';
+          address: 'SysvarRent111111111111111111111111111111111';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
     },
     {
-      code: 6004;
-      name: 'fiatDeadlineExpired';
-      msg: 'Fiat payment deadline expired';
+      name: 'postPaymentDetails';
+      discriminator: [95, 123, 114, 22, 127, 239, 29, 139];
+      accounts: [
+        {
+          name: 'sender';
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'senderProfile';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 111, 102, 105, 108, 101];
+              },
+              {
+                kind: 'account';
+                path: 'sender';
+              }
+            ];
+          };
+        },
+        {
+          name: 'recipientProfile';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 111, 102, 105, 108, 101];
+              },
+              {
+                kind: 'account';
+                path: 'recipient_profile.owner';
+                account: 'UserProfile';
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'ciphertextHash';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
     },
     {
-      code: 6005;
-      name: 'invalidState';
-      msg: 'Invalid state transition';
+      name: 'proveChatMessage';
+      discriminator: [27, 22, 29, 28, 110, 17, 231, 201];
+      accounts: [
+        {
+          name: 'prover';
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'party';
+          type: 'pubkey';
+        },
+        {
+          name: 'leafIndex';
+          type: 'u64';
+        },
+        {
+          name: 'messageHash';
+          type: {
+            array: ['u8', 32];
+          };
+        },
+        {
+          name: 'proof';
+          type: {
+            vec: {
+              array: ['u8', 32];
+            };
+          };
+        }
+      ];
     },
     {
-      code: 6006;
-      name: 'missingSequentialAddress';
-      msg: 'Missing sequential escrow address';
+      name: 'quoteEscrow';
+      discriminator: [95, 59, 242, 121, 168, 125, 14, 101];
+      accounts: [
+        {
+          name: 'config';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        }
+      ];
+      args: [
+        {
+          name: 'amount';
+          type: 'u64';
+        }
+      ];
+      returns: {
+        defined: {
+          name: 'escrowQuote';
+        };
+      };
     },
     {
-      code: 6007;
-      name: 'terminalState';
-      msg: 'Already in terminal state';
-    },
-    {
-      code: 6008;
-      name: 'feeCalculationError';
-      msg: 'Fee calculation error';
-    },
-    {
-      code: 6009;
-      name: 'insufficientFunds';
-      msg: 'Insufficient funds to cover principal and fee';
-    },
-    {
-      code: 6010;
-      name: 'incorrectBondAmount';
-      msg: 'Dispute bond amount incorrect';
-    },
-    {
-      code: 6011;
-      name: 'responseDeadlineExpired';
-      msg: 'Dispute response deadline expired';
-    },
-    {
-      code: 6012;
-      name: 'invalidEvidenceHash';
-      msg: 'Evidence hash missing or invalid';
-    },
-    {
-      code: 6013;
-      name: 'duplicateEvidence';
-      msg: 'Duplicate evidence submission';
+      name: 'reclaimBondAccounts';
+      discriminator: [215, 115, 237, 156, 68, 175, 129, 165];
+      accounts: [
+        {
+          name: 'caller';
+          signer: true;
+        },
+        {
+          name: 'escrow';
+        },
+        {
+          name: 'buyerBondAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'sellerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
     },
     {
-      code: 6014;
-      name: 'arbitrationDeadlineExpired';
-      msg: 'Arbitration deadline expired';
+      name: 'reconcile';
+      discriminator: [61, 24, 197, 180, 195, 169, 138, 105];
+      accounts: [
+        {
+          name: 'caller';
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'escrowTokenAccount';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
     },
     {
-      code: 6015;
-      name: 'missingDisputeBond';
-      msg: 'Missing dispute bond';
+      name: 'registerEncryptionKey';
+      discriminator: [52, 17, 28, 66, 141, 254, 167, 183];
+      accounts: [
+        {
+          name: 'owner';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'profile';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 111, 102, 105, 108, 101];
+              },
+              {
+                kind: 'account';
+                path: 'owner';
+              }
+            ];
+          };
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        }
+      ];
+      args: [
+        {
+          name: 'encryptionKey';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
     },
     {
-      code: 6016;
-      name: 'invalidResolutionExplanation';
-      msg: 'Invalid resolution explanation';
+      name: 'registerPriceFeed';
+      discriminator: [18, 130, 99, 48, 173, 153, 230, 220];
+      accounts: [
+        {
+          name: 'arbitrator';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'priceFeed';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 105, 99, 101, 95, 102, 101, 101, 100];
+              },
+              {
+                kind: 'arg';
+                path: 'currency';
+              }
+            ];
+          };
+        },
+        {
+          name: 'systemProgram';
+          address: '11111111111111111111111111111111';
+        }
+      ];
+      args: [
+        {
+          name: 'currency';
+          type: {
+            array: ['u8', 3];
+          };
+        },
+        {
+          name: 'settings';
+          type: {
+            defined: {
+              name: 'priceFeedSettings';
+            };
+          };
+        }
+      ];
     },
     {
-      code: 6017;
-      name: 'bumpNotFound';
-      msg: 'Required bump seed not found';
-    }
-  ];
-  types: [
+      name: 'releaseEscrow';
+      discriminator: [146, 253, 129, 233, 20, 145, 181, 206];
+      accounts: [
+        {
+          name: 'authority';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'seller';
+          writable: true;
+        },
+        {
+          name: 'escrowTokenRentPayer';
+          writable: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'escrowTokenAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'arbitratorTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'sequentialEscrowTokenAccount';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'buyerBondAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'sellerBondRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'buyerDepositAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerDepositRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
+    },
+    {
+      name: 'resolveDisputeWithExplanation';
+      discriminator: [189, 74, 181, 226, 179, 199, 201, 192];
+      accounts: [
+        {
+          name: 'arbitrator';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'seller';
+          writable: true;
+        },
+        {
+          name: 'escrowTokenRentPayer';
+          writable: true;
+        },
+        {
+          name: 'buyerBondRentPayer';
+          writable: true;
+        },
+        {
+          name: 'sellerBondRentPayer';
+          writable: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'escrowTokenAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'sellerTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'arbitratorTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'buyerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'buyerDepositAccount';
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 100, 101, 112, 111, 115, 105, 116];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'buyerDepositRentPayer';
+          writable: true;
+          optional: true;
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'decision';
+          type: 'bool';
+        },
+        {
+          name: 'resolutionHash';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
+    },
+    {
+      name: 'respondToDisputeWithBond';
+      discriminator: [228, 27, 214, 143, 75, 31, 29, 212];
+      accounts: [
+        {
+          name: 'respondingParty';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'respondingPartyTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'buyerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [98, 117, 121, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'sellerBondAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [115, 101, 108, 108, 101, 114, 95, 98, 111, 110, 100];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'evidenceHash';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
+    },
+    {
+      name: 'sweepSurplus';
+      discriminator: [144, 67, 197, 177, 218, 200, 50, 24];
+      accounts: [
+        {
+          name: 'arbitrator';
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'escrowTokenAccount';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119, 95, 116, 111, 107, 101, 110];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'arbitratorTokenAccount';
+          writable: true;
+        },
+        {
+          name: 'tokenProgram';
+          address: 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [];
+    },
+    {
+      name: 'updateConfig';
+      discriminator: [29, 158, 252, 191, 10, 83, 219, 99];
+      accounts: [
+        {
+          name: 'arbitrator';
+          signer: true;
+        },
+        {
+          name: 'config';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        }
+      ];
+      args: [
+        {
+          name: 'feePolicy';
+          type: {
+            defined: {
+              name: 'feePolicy';
+            };
+          };
+        }
+      ];
+    },
+    {
+      name: 'updateEncryptionKey';
+      discriminator: [92, 233, 29, 101, 152, 97, 110, 235];
+      accounts: [
+        {
+          name: 'owner';
+          signer: true;
+        },
+        {
+          name: 'profile';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 111, 102, 105, 108, 101];
+              },
+              {
+                kind: 'account';
+                path: 'owner';
+              }
+            ];
+          };
+        }
+      ];
+      args: [
+        {
+          name: 'encryptionKey';
+          type: {
+            array: ['u8', 32];
+          };
+        }
+      ];
+    },
+    {
+      name: 'updatePriceFeed';
+      discriminator: [28, 9, 93, 150, 86, 153, 188, 115];
+      accounts: [
+        {
+          name: 'arbitrator';
+          signer: true;
+        },
+        {
+          name: 'priceFeed';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [112, 114, 105, 99, 101, 95, 102, 101, 101, 100];
+              },
+              {
+                kind: 'account';
+                path: 'price_feed.currency';
+                account: 'PriceFeed';
+              }
+            ];
+          };
+        }
+      ];
+      args: [
+        {
+          name: 'settings';
+          type: {
+            defined: {
+              name: 'priceFeedSettings';
+            };
+          };
+        }
+      ];
+    },
+    {
+      name: 'updateSequentialAddress';
+      discriminator: [11, 3, 89, 53, 111, 178, 25, 158];
+      accounts: [
+        {
+          name: 'buyer';
+          writable: true;
+          signer: true;
+        },
+        {
+          name: 'escrow';
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [101, 115, 99, 114, 111, 119];
+              },
+              {
+                kind: 'account';
+                path: 'escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.escrow_id';
+                account: 'Escrow';
+              },
+              {
+                kind: 'account';
+                path: 'escrow.trade_id';
+                account: 'Escrow';
+              }
+            ];
+          };
+        },
+        {
+          name: 'eventAuthority';
+          pda: {
+            seeds: [
+              {
+                kind: 'const';
+                value: [
+                  95,
+                  95,
+                  101,
+                  118,
+                  101,
+                  110,
+                  116,
+                  95,
+                  97,
+                  117,
+                  116,
+                  104,
+                  111,
+                  114,
+                  105,
+                  116,
+                  121
+                ];
+              }
+            ];
+          };
+        },
+        {
+          name: 'program';
+        }
+      ];
+      args: [
+        {
+          name: 'newAddress';
+          type: 'pubkey';
+        }
+      ];
+    }
+  ];
+  accounts: [
+    {
+      name: 'config';
+      discriminator: [155, 12, 170, 224, 30, 250, 204, 130];
+    },
+    {
+      name: 'escrow';
+      discriminator: [31, 213, 123, 187, 186, 22, 218, 155];
+    },
+    {
+      name: 'priceFeed';
+      discriminator: [189, 103, 252, 23, 152, 35, 243, 156];
+    },
+    {
+      name: 'userProfile';
+      discriminator: [32, 37, 119, 205, 179, 180, 13, 194];
+    }
+  ];
+  events: [
+    {
+      name: 'balanceDiscrepancy';
+      discriminator: [88, 126, 137, 21, 236, 146, 103, 95];
+    },
+    {
+      name: 'bondAccountReclaimed';
+      discriminator: [77, 218, 188, 158, 177, 21, 176, 45];
+    },
+    {
+      name: 'buyerDepositPosted';
+      discriminator: [227, 113, 176, 226, 196, 250, 192, 125];
+    },
+    {
+      name: 'buyerDepositSettled';
+      discriminator: [105, 32, 183, 152, 119, 240, 121, 67];
+    },
+    {
+      name: 'cancellationPenaltyPaid';
+      discriminator: [55, 64, 175, 16, 122, 10, 181, 78];
+    },
+    {
+      name: 'chatMessageProven';
+      discriminator: [104, 33, 186, 200, 225, 51, 167, 188];
+    },
+    {
+      name: 'chatRootCommitted';
+      discriminator: [46, 129, 221, 217, 121, 177, 30, 219];
+    },
+    {
+      name: 'disputeDefaultJudgment';
+      discriminator: [194, 12, 130, 224, 60, 204, 39, 194];
+    },
+    {
+      name: 'disputeOpened';
+      discriminator: [239, 222, 102, 235, 193, 85, 1, 214];
+    },
+    {
+      name: 'disputeResolved';
+      discriminator: [121, 64, 249, 153, 139, 128, 236, 187];
+    },
+    {
+      name: 'disputeResponseSubmitted';
+      discriminator: [22, 179, 0, 219, 181, 109, 45, 5];
+    },
+    {
+      name: 'escrowAccepted';
+      discriminator: [129, 122, 76, 235, 127, 11, 32, 165];
+    },
+    {
+      name: 'escrowBalanceChanged';
+      discriminator: [169, 241, 33, 44, 253, 206, 89, 168];
+    },
+    {
+      name: 'escrowCancelled';
+      discriminator: [98, 241, 195, 122, 213, 0, 162, 161];
+    },
+    {
+      name: 'escrowCreated';
+      discriminator: [70, 127, 105, 102, 92, 97, 7, 173];
+    },
+    {
+      name: 'escrowMigrated';
+      discriminator: [169, 120, 241, 205, 106, 10, 45, 112];
+    },
+    {
+      name: 'escrowReleased';
+      discriminator: [131, 7, 138, 104, 166, 190, 113, 112];
+    },
+    {
+      name: 'escrowStateChanged';
+      discriminator: [117, 80, 243, 27, 8, 62, 222, 99];
+    },
+    {
+      name: 'fiatMarkedPaid';
+      discriminator: [38, 159, 7, 17, 32, 79, 143, 184];
+    },
+    {
+      name: 'fiatRateChecked';
+      discriminator: [244, 132, 242, 198, 124, 245, 83, 136];
+    },
+    {
+      name: 'fundsDeposited';
+      discriminator: [157, 209, 100, 95, 59, 100, 3, 68];
+    },
+    {
+      name: 'paymentDetailsPosted';
+      discriminator: [89, 88, 205, 61, 234, 74, 160, 232];
+    },
+    {
+      name: 'sequentialAddressUpdated';
+      discriminator: [205, 6, 123, 144, 102, 253, 81, 133];
+    },
+    {
+      name: 'surplusSwept';
+      discriminator: [10, 228, 130, 83, 221, 240, 210, 32];
+    }
+  ];
+  errors: [
+    {
+      code: 6000;
+      name: 'invalidAmount';
+      msg: 'Invalid amount: Zero or negative';
+    },
+    {
+      code: 6001;
+      name: 'exceedsMaximum';
+      msg: 'Amount exceeds maximum (100 USDC)';
+    },
+    {
+      code: 6002;
+      name: 'unauthorized';
+      msg: 'Unauthorized caller';
+    },
+    {
+      code: 6003;
+      name: 'depositDeadlineExpired';
+      msg: 'Deposit deadline expired';
+    },
+    {
+      code: 6004;
+      name: 'fiatDeadlineExpired';
+      msg: 'Fiat payment deadline expired';
+    },
+    {
+      code: 6005;
+      name: 'invalidState';
+      msg: 'Invalid state transition';
+    },
+    {
+      code: 6006;
+      name: 'missingSequentialAddress';
+      msg: 'Missing sequential escrow address';
+    },
+    {
+      code: 6007;
+      name: 'terminalState';
+      msg: 'Already in terminal state';
+    },
+    {
+      code: 6008;
+      name: 'feeCalculationError';
+      msg: 'Fee calculation error';
+    },
+    {
+      code: 6009;
+      name: 'insufficientFunds';
+      msg: 'Insufficient funds to cover principal and fee';
+    },
+    {
+      code: 6010;
+      name: 'incorrectBondAmount';
+      msg: 'Dispute bond amount incorrect';
+    },
+    {
+      code: 6011;
+      name: 'responseDeadlineExpired';
+      msg: 'Dispute response deadline expired';
+    },
+    {
+      code: 6012;
+      name: 'invalidEvidenceHash';
+      msg: 'Evidence hash missing or invalid';
+    },
+    {
+      code: 6013;
+      name: 'duplicateEvidence';
+      msg: 'Duplicate evidence submission';
+    },
+    {
+      code: 6014;
+      name: 'arbitrationDeadlineExpired';
+      msg: 'Arbitration deadline expired';
+    },
+    {
+      code: 6015;
+      name: 'missingDisputeBond';
+      msg: 'Missing dispute bond';
+    },
+    {
+      code: 6016;
+      name: 'invalidResolutionExplanation';
+      msg: 'Invalid resolution explanation';
+    },
+    {
+      code: 6017;
+      name: 'bumpNotFound';
+      msg: 'Required bump seed not found';
+    },
+    {
+      code: 6018;
+      name: 'invalidEscrowAccount';
+      msg: 'Escrow account data is not a valid Escrow';
+    },
+    {
+      code: 6019;
+      name: 'alreadyMigrated';
+      msg: 'Escrow already at current layout version';
+    },
+    {
+      code: 6020;
+      name: 'invalidRentPayer';
+      msg: 'Rent refund account does not match the recorded payer';
+    },
+    {
+      code: 6021;
+      name: 'missingBondAccount';
+      msg: 'Initialized bond account must be supplied so it can be closed';
+    },
+    {
+      code: 6022;
+      name: 'notTerminalState';
+      msg: 'Escrow is not in a terminal state';
+    },
+    {
+      code: 6023;
+      name: 'counterOverflow';
+      msg: 'Escrow update counter overflow';
+    },
+    {
+      code: 6024;
+      name: 'noSurplus';
+      msg: 'Vault holds no surplus over the tracked balance';
+    },
+    {
+      code: 6025;
+      name: 'invalidFiatTerms';
+      msg: 'Fiat terms need an ISO 4217 currency code and a non-zero amount and rate';
+    },
+    {
+      code: 6026;
+      name: 'invalidOraclePrice';
+      msg: 'Not a fully verified Pyth price update for the registered feed, or a non-positive price';
+    },
+    {
+      code: 6027;
+      name: 'staleOraclePrice';
+      msg: 'Oracle price is older than the feed allows';
+    },
+    {
+      code: 6028;
+      name: 'oraclePriceUncertain';
+      msg: 'Oracle price confidence interval is wider than the feed allows';
+    },
+    {
+      code: 6029;
+      name: 'rateOutOfBand';
+      msg: 'Committed exchange rate deviates from the oracle price by more than the feed allows';
+    },
+    {
+      code: 6030;
+      name: 'invalidRateBand';
+      msg: 'Price feed limits must be non-zero and the confidence limit at most 100%';
+    },
+    {
+      code: 6031;
+      name: 'invalidEncryptionKey';
+      msg: 'Encryption key must be a non-zero x25519 public key';
+    },
+    {
+      code: 6032;
+      name: 'recipientProfileMismatch';
+      msg: 'Recipient profile does not belong to the other party';
+    },
+    {
+      code: 6033;
+      name: 'invalidChatRoot';
+      msg: 'Chat root must be non-zero and cover more messages than the last checkpoint';
+    },
+    {
+      code: 6034;
+      name: 'invalidInclusionProof';
+      msg: "Message is not included in the party's committed chat root";
+    },
+    {
+      code: 6035;
+      name: 'acceptanceRequired';
+      msg: 'Escrow must be accepted by the buyer before it is funded';
+    },
+    {
+      code: 6036;
+      name: 'missingBuyerDeposit';
+      msg: 'Posted buyer deposit account must be supplied so it can be settled';
+    },
+    {
+      code: 6037;
+      name: 'missingArbitratorTokenAccount';
+      msg: 'Arbitrator token account must be supplied to sweep stray bond tokens';
+    },
+    {
+      code: 6038;
+      name: 'invalidFeePolicy';
+      msg: 'Fee and bond floors may not exceed the maximum trade amount';
+    },
+    {
+      code: 6039;
+      name: 'buyerDepositRequired';
+      msg: 'Buyer deposit must be posted before fiat is marked paid';
+    }
+  ];
+  types: [
+    {
+      name: 'balanceChangeReason';
+      type: {
+        kind: 'enum';
+        variants: [
+          {
+            name: 'funded';
+          },
+          {
+            name: 'released';
+          },
+          {
+            name: 'cancelled';
+          },
+          {
+            name: 'autoCancelled';
+          },
+          {
+            name: 'defaultJudgment';
+          },
+          {
+            name: 'resolvedToBuyer';
+          },
+          {
+            name: 'resolvedToSeller';
+          }
+        ];
+      };
+    },
+    {
+      name: 'balanceDiscrepancy';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'trackedBalance';
+            type: 'u64';
+          },
+          {
+            name: 'vaultBalance';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'bondAccountReclaimed';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'bondAccount';
+            type: 'pubkey';
+          },
+          {
+            name: 'rentDestination';
+            type: 'pubkey';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'buyerDepositPosted';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'buyer';
+            type: 'pubkey';
+          },
+          {
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'rounding';
+            type: {
+              defined: {
+                name: 'rounding';
+              };
+            };
+          },
+          {
+            name: 'minDepositApplied';
+            type: 'bool';
+          },
+          {
+            name: 'counter';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'buyerDepositSettled';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'recipient';
+            type: 'pubkey';
+          },
+          {
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'forfeited';
+            type: 'bool';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'cancellationPenaltyPaid';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'seller';
+            type: 'pubkey';
+          },
+          {
+            name: 'buyer';
+            type: 'pubkey';
+          },
+          {
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'chatCheckpoint';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'root';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'messageCount';
+            type: 'u64';
+          },
+          {
+            name: 'committedAt';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'chatMessageProven';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'party';
+            type: 'pubkey';
+          },
+          {
+            name: 'root';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'messageCount';
+            type: 'u64';
+          },
+          {
+            name: 'leafIndex';
+            type: 'u64';
+          },
+          {
+            name: 'messageHash';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'prover';
+            type: 'pubkey';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'chatRootCommitted';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'party';
+            type: 'pubkey';
+          },
+          {
+            name: 'root';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'messageCount';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'config';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'feePolicy';
+            type: {
+              defined: {
+                name: 'feePolicy';
+              };
+            };
+          }
+        ];
+      };
+    },
+    {
+      name: 'disputeDefaultJudgment';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'defaultingParty';
+            type: 'pubkey';
+          },
+          {
+            name: 'decision';
+            type: 'bool';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'disputeOpened';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'disputingParty';
+            type: 'pubkey';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          },
+          {
+            name: 'evidenceHash';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'bondAmount';
+            type: 'u64';
+          },
+          {
+            name: 'rounding';
+            type: {
+              defined: {
+                name: 'rounding';
+              };
+            };
+          },
+          {
+            name: 'minBondApplied';
+            type: 'bool';
+          },
+          {
+            name: 'minBond';
+            type: 'u64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'disputeResolved';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'decision';
+            type: 'bool';
+          },
+          {
+            name: 'fee';
+            type: 'u64';
+          },
+          {
+            name: 'counter';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          },
+          {
+            name: 'resolutionHash';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'winner';
+            type: 'pubkey';
+          },
+          {
+            name: 'explanationReference';
+            type: 'string';
+          }
+        ];
+      };
+    },
+    {
+      name: 'disputeResponseSubmitted';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'respondingParty';
+            type: 'pubkey';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          },
+          {
+            name: 'evidenceHash';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'bondAmount';
+            type: 'u64';
+          },
+          {
+            name: 'rounding';
+            type: {
+              defined: {
+                name: 'rounding';
+              };
+            };
+          },
+          {
+            name: 'minBondApplied';
+            type: 'bool';
+          },
+          {
+            name: 'minBond';
+            type: 'u64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrow';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'seller';
+            type: 'pubkey';
+          },
+          {
+            name: 'buyer';
+            type: 'pubkey';
+          },
+          {
+            name: 'arbitrator';
+            type: 'pubkey';
+          },
+          {
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'fee';
+            type: 'u64';
+          },
+          {
+            name: 'depositDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'fiatDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'state';
+            type: {
+              defined: {
+                name: 'escrowState';
+              };
+            };
+          },
+          {
+            name: 'sequential';
+            type: 'bool';
+          },
+          {
+            name: 'sequentialEscrowAddress';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'fiatPaid';
+            type: 'bool';
+          },
+          {
+            name: 'counter';
+            type: 'u64';
+          },
+          {
+            name: 'disputeInitiator';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'disputeInitiatedTime';
+            type: {
+              option: 'i64';
+            };
+          },
+          {
+            name: 'disputeEvidenceHashBuyer';
+            type: {
+              option: {
+                array: ['u8', 32];
+              };
+            };
+          },
+          {
+            name: 'disputeEvidenceHashSeller';
+            type: {
+              option: {
+                array: ['u8', 32];
+              };
+            };
+          },
+          {
+            name: 'disputeResolutionHash';
+            type: {
+              option: {
+                array: ['u8', 32];
+              };
+            };
+          },
+          {
+            name: 'trackedBalance';
+            type: 'u64';
+          },
+          {
+            name: 'version';
+            type: 'u8';
+          },
+          {
+            name: 'sellerScopedSeeds';
+            type: 'bool';
+          },
+          {
+            name: 'escrowRentPayer';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'escrowTokenRentPayer';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'buyerBondRentPayer';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'sellerBondRentPayer';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'fiatTerms';
+            type: {
+              option: {
+                defined: {
+                  name: 'fiatTerms';
+                };
+              };
+            };
+          },
+          {
+            name: 'chatCheckpointBuyer';
+            type: {
+              option: {
+                defined: {
+                  name: 'chatCheckpoint';
+                };
+              };
+            };
+          },
+          {
+            name: 'chatCheckpointSeller';
+            type: {
+              option: {
+                defined: {
+                  name: 'chatCheckpoint';
+                };
+              };
+            };
+          },
+          {
+            name: 'requiresAcceptance';
+            type: 'bool';
+          },
+          {
+            name: 'acceptedAt';
+            type: {
+              option: 'i64';
+            };
+          },
+          {
+            name: 'buyerDeposit';
+            type: 'u64';
+          },
+          {
+            name: 'buyerDepositRentPayer';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'feePolicy';
+            type: {
+              option: {
+                defined: {
+                  name: 'feePolicy';
+                };
+              };
+            };
+          },
+          {
+            name: 'disputeBond';
+            type: 'u64';
+          },
+          {
+            name: 'requiresBuyerDeposit';
+            type: 'bool';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowAccepted';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
+          {
+            name: 'escrowId';
+            type: 'u64';
+          },
+          {
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'buyer';
+            type: 'pubkey';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
     {
-      name: 'disputeDefaultJudgment';
+      name: 'escrowBalanceChanged';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1098,12 +4148,16 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'defaultingParty';
-            type: 'pubkey';
+            name: 'newBalance';
+            type: 'u64';
           },
           {
-            name: 'decision';
-            type: 'bool';
+            name: 'reason';
+            type: {
+              defined: {
+                name: 'balanceChangeReason';
+              };
+            };
           },
           {
             name: 'timestamp';
@@ -1113,10 +4167,14 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'disputeOpened';
+      name: 'escrowCancelled';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1130,31 +4188,37 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'disputingParty';
+            name: 'seller';
             type: 'pubkey';
           },
           {
-            name: 'timestamp';
-            type: 'i64';
+            name: 'amount';
+            type: 'u64';
           },
           {
-            name: 'evidenceHash';
-            type: {
-              array: ['u8', 32];
-            };
+            name: 'fee';
+            type: 'u64';
           },
           {
-            name: 'bondAmount';
+            name: 'counter';
             type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
           }
         ];
       };
     },
     {
-      name: 'disputeResolved';
+      name: 'escrowCreated';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1168,43 +4232,93 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'decision';
-            type: 'bool';
+            name: 'seller';
+            type: 'pubkey';
           },
           {
-            name: 'fee';
+            name: 'buyer';
+            type: 'pubkey';
+          },
+          {
+            name: 'arbitrator';
+            type: 'pubkey';
+          },
+          {
+            name: 'amount';
             type: 'u64';
           },
           {
-            name: 'counter';
+            name: 'fee';
             type: 'u64';
           },
+          {
+            name: 'depositDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'fiatDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'sequential';
+            type: 'bool';
+          },
+          {
+            name: 'sequentialEscrowAddress';
+            type: {
+              option: 'pubkey';
+            };
+          },
           {
             name: 'timestamp';
             type: 'i64';
           },
           {
-            name: 'resolutionHash';
+            name: 'rounding';
             type: {
-              array: ['u8', 32];
+              defined: {
+                name: 'rounding';
+              };
             };
           },
           {
-            name: 'winner';
-            type: 'pubkey';
+            name: 'minFeeApplied';
+            type: 'bool';
           },
           {
-            name: 'explanationReference';
-            type: 'string';
+            name: 'fiatTerms';
+            type: {
+              option: {
+                defined: {
+                  name: 'fiatTerms';
+                };
+              };
+            };
+          },
+          {
+            name: 'requiresAcceptance';
+            type: 'bool';
+          },
+          {
+            name: 'minFee';
+            type: 'u64';
+          },
+          {
+            name: 'requiresBuyerDeposit';
+            type: 'bool';
           }
         ];
       };
     },
     {
-      name: 'disputeResponseSubmitted';
+      name: 'escrowMigrated';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1218,31 +4332,109 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'respondingParty';
-            type: 'pubkey';
+            name: 'fromVersion';
+            type: 'u8';
+          },
+          {
+            name: 'toVersion';
+            type: 'u8';
           },
           {
             name: 'timestamp';
             type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowPhase';
+      type: {
+        kind: 'enum';
+        variants: [
+          {
+            name: 'created';
           },
           {
-            name: 'evidenceHash';
-            type: {
-              array: ['u8', 32];
-            };
+            name: 'awaitingFiatPayment';
           },
           {
-            name: 'bondAmount';
+            name: 'pendingCryptoRelease';
+          },
+          {
+            name: 'completed';
+          },
+          {
+            name: 'cancelled';
+          },
+          {
+            name: 'disputed';
+          },
+          {
+            name: 'resolved';
+          },
+          {
+            name: 'accepted';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowQuote';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'fee';
+            type: 'u64';
+          },
+          {
+            name: 'totalDeposit';
+            type: 'u64';
+          },
+          {
+            name: 'disputeBond';
+            type: 'u64';
+          },
+          {
+            name: 'buyerDeposit';
             type: 'u64';
+          },
+          {
+            name: 'cancellationPenalty';
+            type: 'u64';
+          },
+          {
+            name: 'depositDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'fiatDeadline';
+            type: 'i64';
+          },
+          {
+            name: 'responseDeadline';
+            type: 'i64';
           }
         ];
       };
     },
     {
-      name: 'escrow';
+      name: 'escrowReleased';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
+            type: 'pubkey';
+          },
           {
             name: 'escrowId';
             type: 'u64';
@@ -1252,33 +4444,117 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'seller';
+            name: 'buyer';
             type: 'pubkey';
           },
           {
-            name: 'buyer';
+            name: 'amount';
+            type: 'u64';
+          },
+          {
+            name: 'fee';
+            type: 'u64';
+          },
+          {
+            name: 'counter';
+            type: 'u64';
+          },
+          {
+            name: 'timestamp';
+            type: 'i64';
+          },
+          {
+            name: 'destination';
             type: 'pubkey';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowState';
+      type: {
+        kind: 'enum';
+        variants: [
+          {
+            name: 'created';
           },
           {
-            name: 'arbitrator';
+            name: 'funded';
+          },
+          {
+            name: 'released';
+          },
+          {
+            name: 'cancelled';
+          },
+          {
+            name: 'disputed';
+          },
+          {
+            name: 'resolved';
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowStateChanged';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
+          {
+            name: 'objectId';
             type: 'pubkey';
           },
           {
-            name: 'amount';
+            name: 'escrowId';
             type: 'u64';
           },
           {
-            name: 'fee';
+            name: 'tradeId';
+            type: 'u64';
+          },
+          {
+            name: 'from';
+            type: {
+              option: {
+                defined: {
+                  name: 'escrowState';
+                };
+              };
+            };
+          },
+          {
+            name: 'to';
+            type: {
+              defined: {
+                name: 'escrowState';
+              };
+            };
+          },
+          {
+            name: 'counter';
             type: 'u64';
           },
           {
-            name: 'depositDeadline';
-            type: 'i64';
+            name: 'actor';
+            type: 'pubkey';
           },
           {
-            name: 'fiatDeadline';
+            name: 'timestamp';
             type: 'i64';
-          },
+          }
+        ];
+      };
+    },
+    {
+      name: 'escrowStatusReport';
+      type: {
+        kind: 'struct';
+        fields: [
           {
             name: 'state';
             type: {
@@ -1288,71 +4564,87 @@ export type LocalsolanaContracts = {
             };
           },
           {
-            name: 'sequential';
-            type: 'bool';
-          },
-          {
-            name: 'sequentialEscrowAddress';
+            name: 'phase';
             type: {
-              option: 'pubkey';
+              defined: {
+                name: 'escrowPhase';
+              };
             };
           },
           {
-            name: 'fiatPaid';
-            type: 'bool';
+            name: 'sellerActions';
+            type: 'u32';
           },
           {
-            name: 'counter';
-            type: 'u64';
+            name: 'buyerActions';
+            type: 'u32';
           },
           {
-            name: 'disputeInitiator';
-            type: {
-              option: 'pubkey';
-            };
+            name: 'arbitratorActions';
+            type: 'u32';
           },
           {
-            name: 'disputeInitiatedTime';
+            name: 'anyoneActions';
+            type: 'u32';
+          },
+          {
+            name: 'depositSecondsLeft';
             type: {
               option: 'i64';
             };
           },
           {
-            name: 'disputeEvidenceHashBuyer';
+            name: 'fiatSecondsLeft';
             type: {
-              option: {
-                array: ['u8', 32];
-              };
+              option: 'i64';
             };
           },
           {
-            name: 'disputeEvidenceHashSeller';
+            name: 'responseSecondsLeft';
             type: {
-              option: {
-                array: ['u8', 32];
-              };
+              option: 'i64';
             };
           },
           {
-            name: 'disputeResolutionHash';
-            type: {
-              option: {
-                array: ['u8', 32];
-              };
-            };
+            name: 'timestamp';
+            type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'feePolicy';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'minFee';
+            type: 'u64';
           },
           {
-            name: 'trackedBalance';
+            name: 'minDisputeBond';
             type: 'u64';
+          },
+          {
+            name: 'rounding';
+            type: {
+              defined: {
+                name: 'rounding';
+              };
+            };
           }
         ];
       };
     },
     {
-      name: 'escrowBalanceChanged';
+      name: 'fiatMarkedPaid';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1365,14 +4657,6 @@ export type LocalsolanaContracts = {
             name: 'tradeId';
             type: 'u64';
           },
-          {
-            name: 'newBalance';
-            type: 'u64';
-          },
-          {
-            name: 'reason';
-            type: 'string';
-          },
           {
             name: 'timestamp';
             type: 'i64';
@@ -1381,10 +4665,14 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'escrowCancelled';
+      name: 'fiatRateChecked';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1398,21 +4686,33 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'seller';
-            type: 'pubkey';
+            name: 'currency';
+            type: {
+              array: ['u8', 3];
+            };
           },
           {
-            name: 'amount';
+            name: 'feedId';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'committedRate';
             type: 'u64';
           },
           {
-            name: 'fee';
+            name: 'oracleRate';
             type: 'u64';
           },
           {
-            name: 'counter';
+            name: 'deviationBps';
             type: 'u64';
           },
+          {
+            name: 'publishTime';
+            type: 'i64';
+          },
           {
             name: 'timestamp';
             type: 'i64';
@@ -1421,59 +4721,65 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'escrowCreated';
+      name: 'fiatTerms';
       type: {
         kind: 'struct';
         fields: [
           {
-            name: 'objectId';
-            type: 'pubkey';
+            name: 'currency';
+            type: {
+              array: ['u8', 3];
+            };
           },
           {
-            name: 'escrowId';
+            name: 'fiatAmount';
             type: 'u64';
           },
           {
-            name: 'tradeId';
+            name: 'rate';
             type: 'u64';
           },
           {
-            name: 'seller';
-            type: 'pubkey';
-          },
+            name: 'paymentMethodHash';
+            type: {
+              array: ['u8', 32];
+            };
+          }
+        ];
+      };
+    },
+    {
+      name: 'fundsDeposited';
+      type: {
+        kind: 'struct';
+        fields: [
           {
-            name: 'buyer';
-            type: 'pubkey';
+            name: 'schemaVersion';
+            type: 'u8';
           },
           {
-            name: 'arbitrator';
+            name: 'objectId';
             type: 'pubkey';
           },
           {
-            name: 'amount';
+            name: 'escrowId';
             type: 'u64';
           },
           {
-            name: 'fee';
+            name: 'tradeId';
             type: 'u64';
           },
           {
-            name: 'depositDeadline';
-            type: 'i64';
-          },
-          {
-            name: 'fiatDeadline';
-            type: 'i64';
+            name: 'amount';
+            type: 'u64';
           },
           {
-            name: 'sequential';
-            type: 'bool';
+            name: 'fee';
+            type: 'u64';
           },
           {
-            name: 'sequentialEscrowAddress';
-            type: {
-              option: 'pubkey';
-            };
+            name: 'counter';
+            type: 'u64';
           },
           {
             name: 'timestamp';
@@ -1483,10 +4789,14 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'escrowReleased';
+      name: 'paymentDetailsPosted';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1500,63 +4810,122 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'buyer';
+            name: 'sender';
             type: 'pubkey';
           },
           {
-            name: 'amount';
-            type: 'u64';
+            name: 'recipient';
+            type: 'pubkey';
           },
           {
-            name: 'fee';
-            type: 'u64';
+            name: 'senderKey';
+            type: {
+              array: ['u8', 32];
+            };
           },
           {
-            name: 'counter';
-            type: 'u64';
+            name: 'recipientKey';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'ciphertextHash';
+            type: {
+              array: ['u8', 32];
+            };
           },
           {
             name: 'timestamp';
             type: 'i64';
+          }
+        ];
+      };
+    },
+    {
+      name: 'priceFeed';
+      type: {
+        kind: 'struct';
+        fields: [
+          {
+            name: 'currency';
+            type: {
+              array: ['u8', 3];
+            };
           },
           {
-            name: 'destination';
-            type: 'pubkey';
+            name: 'feedId';
+            type: {
+              array: ['u8', 32];
+            };
+          },
+          {
+            name: 'maxDeviationBps';
+            type: 'u16';
+          },
+          {
+            name: 'maxAgeSeconds';
+            type: 'u32';
+          },
+          {
+            name: 'maxConfidenceBps';
+            type: 'u16';
           }
         ];
       };
     },
     {
-      name: 'escrowState';
+      name: 'priceFeedSettings';
       type: {
-        kind: 'enum';
-        variants: [
+        kind: 'struct';
+        fields: [
           {
-            name: 'created';
+            name: 'feedId';
+            type: {
+              array: ['u8', 32];
+            };
           },
           {
-            name: 'funded';
+            name: 'maxDeviationBps';
+            type: 'u16';
           },
           {
-            name: 'released';
+            name: 'maxAgeSeconds';
+            type: 'u32';
           },
           {
-            name: 'cancelled';
+            name: 'maxConfidenceBps';
+            type: 'u16';
+          }
+        ];
+      };
+    },
+    {
+      name: 'rounding';
+      type: {
+        kind: 'enum';
+        variants: [
+          {
+            name: 'floor';
           },
           {
-            name: 'disputed';
+            name: 'ceil';
           },
           {
-            name: 'resolved';
+            name: 'halfEven';
           }
         ];
       };
     },
     {
-      name: 'fiatMarkedPaid';
+      name: 'sequentialAddressUpdated';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1569,6 +4938,16 @@ export type LocalsolanaContracts = {
             name: 'tradeId';
             type: 'u64';
           },
+          {
+            name: 'oldAddress';
+            type: {
+              option: 'pubkey';
+            };
+          },
+          {
+            name: 'newAddress';
+            type: 'pubkey';
+          },
           {
             name: 'timestamp';
             type: 'i64';
@@ -1577,10 +4956,14 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'fundsDeposited';
+      name: 'surplusSwept';
       type: {
         kind: 'struct';
         fields: [
+          {
+            name: 'schemaVersion';
+            type: 'u8';
+          },
           {
             name: 'objectId';
             type: 'pubkey';
@@ -1598,12 +4981,8 @@ export type LocalsolanaContracts = {
             type: 'u64';
           },
           {
-            name: 'fee';
-            type: 'u64';
-          },
-          {
-            name: 'counter';
-            type: 'u64';
+            name: 'destination';
+            type: 'pubkey';
           },
           {
             name: 'timestamp';
@@ -1613,34 +4992,22 @@ export type LocalsolanaContracts = {
       };
     },
     {
-      name: 'sequentialAddressUpdated';
+      name: 'userProfile';
       type: {
         kind: 'struct';
         fields: [
           {
-            name: 'objectId';
+            name: 'owner';
             type: 'pubkey';
           },
           {
-            name: 'escrowId';
-            type: 'u64';
-          },
-          {
-            name: 'tradeId';
-            type: 'u64';
-          },
-          {
-            name: 'oldAddress';
+            name: 'encryptionKey';
             type: {
-              option: 'pubkey';
+              array: ['u8', 32];
             };
           },
           {
-            name: 'newAddress';
-            type: 'pubkey';
-          },
-          {
-            name: 'timestamp';
+            name: 'updatedAt';
             type: 'i64';
           }
         ];
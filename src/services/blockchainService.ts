@@ -43,10 +43,11 @@ export interface BlockchainService {
   resolveDispute(params: ResolveDisputeParams): Promise<TransactionResult>;
 
   // State queries
-  getEscrowState(escrowId: number, tradeId: number): Promise<EscrowState>;
-  getEscrowBalance(escrowId: number, tradeId: number): Promise<number>;
+  getEscrowState(sellerAddress: string, escrowId: number, tradeId: number): Promise<EscrowState>;
+  getEscrowBalance(sellerAddress: string, escrowId: number, tradeId: number): Promise<number>;
   getEscrowStateByAddress(escrowAddress: string): Promise<EscrowState>;
   getEscrowBalanceByAddress(escrowAddress: string): Promise<number>;
+  getPriceUpdateAddress(currency: string): Promise<string | undefined>;
 
   // Event monitoring
   subscribeToEscrowEvents(
@@ -224,20 +225,24 @@ export class UnifiedBlockchainService implements BlockchainService {
   }
 
   // State Queries (Solana devnet only for now)
-  async getEscrowState(escrowId: number, tradeId: number): Promise<EscrowState> {
+  async getEscrowState(
+    sellerAddress: string,
+    escrowId: number,
+    tradeId: number
+  ): Promise<EscrowState> {
     if (!this.solanaProgram) {
       throw new Error('Solana program not initialized');
     }
 
-    return this.solanaProgram.getEscrowState(escrowId, tradeId);
+    return this.solanaProgram.getEscrowState(sellerAddress, escrowId, tradeId);
   }
 
-  async getEscrowBalance(escrowId: number, tradeId: number): Promise<number> {
+  async getEscrowBalance(sellerAddress: string, escrowId: number, tradeId: number): Promise<number> {
     if (!this.solanaProgram) {
       throw new Error('Solana program not initialized');
     }
 
-    return this.solanaProgram.getEscrowBalance(escrowId, tradeId);
+    return this.solanaProgram.getEscrowBalance(sellerAddress, escrowId, tradeId);
   }
 
   async getEscrowStateByAddress(escrowAddress: string): Promise<EscrowState> {
@@ -256,6 +261,14 @@ export class UnifiedBlockchainService implements BlockchainService {
     return this.solanaProgram.getEscrowBalanceByAddress(escrowAddress);
   }
 
+  async getPriceUpdateAddress(currency: string): Promise<string | undefined> {
+    if (!this.solanaProgram) {
+      throw new Error('Solana program not initialized');
+    }
+
+    return this.solanaProgram.getPriceUpdateAddress(currency);
+  }
+
   // Event Monitoring (Solana devnet only for now)
   subscribeToEscrowEvents(
     escrowId: number,
@@ -2,6 +2,7 @@ import { blockchainService } from './blockchainService.js';
 import { BN } from '@coral-xyz/anchor';
 import { getAssociatedTokenAddress } from '@solana/spl-token';
 import { PublicKey } from '@solana/web3.js';
+import type { FiatTerms } from '../blockchain/types/index.js';
 
 // Helper function to convert escrow state string to numeric value
 const escrowStateToNumber = (state: string | number): number => {
@@ -64,6 +65,9 @@ export const createEscrowTransaction = async (
     sequential?: boolean;
     sequentialEscrowAddress?: string;
     arbitrator?: string; // Optional parameter for arbitrator address
+    fiatTerms: FiatTerms;
+    requiresAcceptance?: boolean;
+    requiresBuyerDeposit?: boolean;
   }
 ) => {
   try {
@@ -79,6 +83,11 @@ export const createEscrowTransaction = async (
     // Convert amount to BN (assuming 6 decimals for USDC)
     const amountBN = new BN(params.amount * 1_000_000); // Convert to smallest unit
 
+    // Required by the program once the arbitrator registered a price feed for the currency
+    const priceUpdateAddress = await blockchainService.getPriceUpdateAddress(
+      params.fiatTerms.currency
+    );
+
     // Create escrow using UnifiedBlockchainService
     const result = await blockchainService.createEscrow({
       escrowId: params.escrowId, // Use the pre-generated escrow ID
@@ -91,6 +100,10 @@ export const createEscrowTransaction = async (
       fiatDeadline: Math.floor(Date.now() / 1000) + 14 * 24 * 60 * 60, // 14 days from now
       sequential: params.sequential || false,
       sequentialEscrowAddress: params.sequentialEscrowAddress,
+      fiatTerms: params.fiatTerms,
+      requiresAcceptance: params.requiresAcceptance || false,
+      requiresBuyerDeposit: params.requiresBuyerDeposit || false,
+      priceUpdateAddress,
     });
 
     console.log('[DEBUG] Solana escrow created:', result);
@@ -245,6 +258,7 @@ export const markFiatPaidTransaction = async (
     const markFiatPaidParams = {
       escrowId: escrowState.id,
       tradeId: escrowState.tradeId,
+      sellerAddress: escrowState.sellerAddress,
       buyerAddress: wallet.address,
     };
 
@@ -337,6 +351,7 @@ export const releaseEscrowTransaction = async (
     const releaseEscrowParams = {
       escrowId: escrowState.id,
       tradeId: escrowState.tradeId,
+      sellerAddress: escrowState.sellerAddress,
       authorityAddress: wallet.address,
       buyerTokenAccount: buyerTokenAccount.toString(),
       arbitratorTokenAccount: arbitratorTokenAccount.toString(),
@@ -9,6 +9,7 @@ import {
   getAccountById,
 } from '../api';
 import { buildTransactionData } from '../utils/transactionUtils.js';
+import type { FiatTerms } from '../blockchain/types/index.js';
 
 // Custom interface for create trade request that matches API expectations
 interface CreateTradeRequest {
@@ -38,7 +39,7 @@ function generateEscrowId(tradeId: number, sequence: number = 0): number {
 /**
  * Derives Solana-specific addresses for escrow recording
  */
-function deriveSolanaAddresses(tradeId: number, escrowId: number) {
+function deriveSolanaAddresses(tradeId: number, escrowId: number, sellerAddress: string) {
   try {
     // Get program ID from config
     const programIdString = config.networks.solanaDevnet.programId;
@@ -53,6 +54,7 @@ function deriveSolanaAddresses(tradeId: number, escrowId: number) {
     console.log('  programId:', programIdString);
     console.log('  escrowId:', escrowId, '(type:', typeof escrowId, ')');
     console.log('  tradeId:', tradeId, '(type:', typeof tradeId, ')');
+    console.log('  seller:', sellerAddress);
 
     // Debug: Show the actual seeds being used
     const escrowIdBuffer = Buffer.alloc(8);
@@ -62,11 +64,17 @@ function deriveSolanaAddresses(tradeId: number, escrowId: number) {
 
     console.log('[DEBUG] Seed buffers:');
     console.log('  "escrow" buffer:', Buffer.from('escrow').toString('hex'));
+    console.log('  seller buffer:', new PublicKey(sellerAddress).toBuffer().toString('hex'));
     console.log('  escrowId buffer:', escrowIdBuffer.toString('hex'));
     console.log('  tradeId buffer:', tradeIdBuffer.toString('hex'));
 
     // Derive escrow PDA
-    const [escrowPda] = PDADerivation.deriveEscrowPDA(programId, escrowId, tradeId);
+    const [escrowPda] = PDADerivation.deriveEscrowPDA(
+      programId,
+      new PublicKey(sellerAddress),
+      escrowId,
+      tradeId
+    );
 
     // Derive escrow token account PDA
     const [escrowTokenAccount] = PDADerivation.deriveEscrowTokenPDA(programId, escrowPda);
@@ -93,6 +101,46 @@ function deriveSolanaAddresses(tradeId: number, escrowId: number) {
     };
   }
 }
+
+/**
+ * Builds the fiat terms the seller commits to on-chain when creating the escrow
+ */
+async function buildFiatTerms(trade: Trade): Promise<FiatTerms> {
+  const currency = trade.leg1_fiat_currency || trade.from_fiat_currency;
+  const fiatAmount = parseFloat(trade.leg1_fiat_amount || '0');
+  const cryptoAmount = parseFloat(trade.leg1_crypto_amount || '0');
+
+  // The rate divides by the crypto amount, so Infinity or NaN would reach the BN encoding
+  if (!Number.isFinite(cryptoAmount) || cryptoAmount <= 0) {
+    throw new Error(`Trade ${trade.id} has an invalid crypto amount: ${trade.leg1_crypto_amount}`);
+  }
+  if (!Number.isFinite(fiatAmount) || fiatAmount <= 0) {
+    throw new Error(`Trade ${trade.id} has an invalid fiat amount: ${trade.leg1_fiat_amount}`);
+  }
+  // The program stores three uppercase ASCII letters, and Intl.NumberFormat throws a
+  // RangeError for anything that is not a well-formed ISO 4217 code
+  if (!currency || !/^[A-Z]{3}$/.test(currency)) {
+    throw new Error(`Trade ${trade.id} has an invalid fiat currency: ${currency || '(none)'}`);
+  }
+
+  // The program takes the fiat amount in minor units and the rate with 8 decimals
+  const { maximumFractionDigits } = new Intl.NumberFormat('en', {
+    style: 'currency',
+    currency,
+  }).resolvedOptions();
+
+  const paymentMethodDigest = await crypto.subtle.digest(
+    'SHA-256',
+    new TextEncoder().encode(trade.from_bank || '')
+  );
+
+  return {
+    currency,
+    fiatAmount: Math.round(fiatAmount * 10 ** (maximumFractionDigits ?? 2)).toString(),
+    rate: Math.round((fiatAmount / cryptoAmount) * 1e8).toString(),
+    paymentMethodHash: Buffer.from(paymentMethodDigest).toString('hex'),
+  };
+}
 import { formatNumber } from '../lib/utils';
 import { handleApiError } from '../utils/errorHandling';
 import { toast } from 'sonner';
@@ -218,6 +266,7 @@ export const createTradeEscrow = async ({
       sequential: false,
       sequentialEscrowAddress: undefined,
       arbitrator: undefined, // Solana program handles arbitrator internally
+      fiatTerms: await buildFiatTerms(trade),
     });
 
     console.log('[DEBUG] Solana transaction result:', txResult);
@@ -235,7 +284,7 @@ export const createTradeEscrow = async ({
       sequential_escrow_address: '11111111111111111111111111111111', // System Program address for non-sequential escrows
       // Add Solana-specific fields - derive actual addresses using config and PDA utilities
       // Use the same escrow ID and trade ID for consistency
-      ...deriveSolanaAddresses(trade.id, escrowId),
+      ...deriveSolanaAddresses(trade.id, escrowId, sellerAddress),
     };
 
     console.log('[DEBUG] recordEscrow data being sent:', recordEscrowData);
@@ -329,7 +378,11 @@ export const createAndFundTradeEscrow = async ({
     if (txResult && 'txHash' in txResult && txResult.txHash) {
       try {
         // Derive the escrow PDA to use as to_address
-        const solanaAddresses = deriveSolanaAddresses(trade.id, parseInt(escrowResult.escrowId));
+        const solanaAddresses = deriveSolanaAddresses(
+          trade.id,
+          parseInt(escrowResult.escrowId),
+          sellerAddress
+        );
 
         const transactionData = buildTransactionData({
           trade_id: trade.id,
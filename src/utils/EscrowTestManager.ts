@@ -106,10 +106,15 @@ export class EscrowTestManager {
     return Math.floor(Math.random() * 1000000) + 1;
   }
 
-  private deriveEscrowAddress(escrowId: number, tradeId: number): string {
+  private deriveEscrowAddress(sellerAddress: string, escrowId: number, tradeId: number): string {
     // Use the actual program ID from the transaction analysis
     const programId = new PublicKey('4PonUp1nPEzDPnRMPjTqufLT3f37QuBJGk1CVnsTXx7x');
-    const [escrowPDA] = PDADerivation.deriveEscrowPDA(programId, escrowId, tradeId);
+    const [escrowPDA] = PDADerivation.deriveEscrowPDA(
+      programId,
+      new PublicKey(sellerAddress),
+      escrowId,
+      tradeId
+    );
     return escrowPDA.toString();
   }
 
@@ -174,6 +179,7 @@ export class EscrowTestManager {
     try {
       console.log('🚀 [DEBUG] Calling blockchainService.getEscrowState...');
       const state = await this.blockchainService.getEscrowState(
+        this.testState.testData.sellerWallet.publicKey.toString(),
         this.testState.escrowId,
         this.testState.tradeId
       );
@@ -188,6 +194,7 @@ export class EscrowTestManager {
       if (state.state === 'FUNDED' || expectedState === 'FUNDED') {
         console.log('🚀 [DEBUG] Calling blockchainService.getEscrowBalance...');
         const balance = await this.blockchainService.getEscrowBalance(
+          this.testState.testData.sellerWallet.publicKey.toString(),
           this.testState.escrowId,
           this.testState.tradeId
         );
@@ -301,6 +308,14 @@ export class EscrowTestManager {
       sequentialEscrowAddress: undefined,
       depositDeadline: Math.floor(Date.now() / 1000) + 3600, // 1 hour from now
       fiatDeadline: Math.floor(Date.now() / 1000) + 7200, // 2 hours from now
+      fiatTerms: {
+        currency: 'USD',
+        fiatAmount: '100', // 1.00 USD
+        rate: '100000000', // 1 USD per USDC
+        paymentMethodHash: this.generateEvidenceHash('Test payment method'),
+      },
+      requiresAcceptance: false,
+      requiresBuyerDeposit: false,
     };
 
     try {
@@ -319,7 +334,7 @@ export class EscrowTestManager {
         console.log('  - Transaction Signature:', result.signature || result.transactionHash);
 
         // Derive the escrow address using PDA derivation
-        const escrowAddress = this.deriveEscrowAddress(escrowId, tradeId);
+        const escrowAddress = this.deriveEscrowAddress(params.sellerAddress, escrowId, tradeId);
         console.log('  - Derived Escrow Address:', escrowAddress);
 
         this.updateState({
@@ -420,6 +435,7 @@ export class EscrowTestManager {
     const params: MarkFiatPaidParams = {
       escrowId: this.testState.escrowId,
       tradeId: this.testState.tradeId,
+      sellerAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       buyerAddress: this.testState.testData.buyerWallet.publicKey.toString(),
     };
 
@@ -452,6 +468,7 @@ export class EscrowTestManager {
     const params: ReleaseEscrowParams = {
       escrowId: this.testState.escrowId,
       tradeId: this.testState.tradeId,
+      sellerAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       authorityAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       buyerTokenAccount: this.testState.testData.buyerTokenAccount,
       arbitratorTokenAccount: this.testState.testData.arbitratorTokenAccount,
@@ -489,6 +506,7 @@ export class EscrowTestManager {
     const params: OpenDisputeParams = {
       escrowId: this.testState.escrowId,
       tradeId: this.testState.tradeId,
+      sellerAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       disputingPartyAddress: this.testState.testData.buyerWallet.publicKey.toString(),
       disputingPartyTokenAccount: this.testState.testData.buyerTokenAccount,
       evidenceHash,
@@ -526,6 +544,7 @@ export class EscrowTestManager {
     const params: RespondToDisputeParams = {
       escrowId: this.testState.escrowId,
       tradeId: this.testState.tradeId,
+      sellerAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       respondingPartyAddress: this.testState.testData.sellerWallet.publicKey.toString(),
       respondingPartyTokenAccount: this.testState.testData.sellerTokenAccount,
       evidenceHash,